@@ -0,0 +1,145 @@
+use crate::cache;
+use crate::remote::{self, NetworkOptions};
+use crate::uniprot;
+use argh::FromArgs;
+use flate2::read::MultiGzDecoder;
+use fst::{Set, SetBuilder};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use strum::{Display, EnumString};
+
+/// Index of the accessions of a database, loaded with [`load`] and shared by
+/// the copies of the options.
+#[derive(Clone)]
+pub struct Accessions(Arc<Set<Vec<u8>>>);
+
+impl fmt::Debug for Accessions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Accessions({} entries)", self.0.len())
+    }
+}
+
+impl Accessions {
+    /// Whether a Uniprot accession exists.
+    pub fn uniprot_exists(&self, accession: &str) -> bool {
+        // isoforms and versions share the accession of the entry
+        self.0.contains(uniprot::entry(accession))
+    }
+}
+
+/// Snapshot of accessions that can be downloaded with `fetch-db`.
+#[derive(Debug, Clone, Copy, EnumString, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum AccessionDb {
+    /// reviewed UniProtKB entries
+    UniprotSwissprot,
+}
+
+impl AccessionDb {
+    /// Gzipped list of accessions, one per line.
+    fn url(self) -> &'static str {
+        match self {
+            AccessionDb::UniprotSwissprot => {
+                "https://rest.uniprot.org/uniprotkb/stream?compressed=true&format=list&query=reviewed%3Atrue"
+            }
+        }
+    }
+
+    /// Location of the index in the cache directory.
+    pub fn path(self) -> io::Result<PathBuf> {
+        Ok(cache::dir()?.join("db").join(format!("{}.fst", self)))
+    }
+}
+
+#[derive(Debug, FromArgs)]
+/// Download and index a database of accessions, to check identifiers offline.
+#[argh(subcommand, name = "fetch-db")]
+pub struct FetchDbArgs {
+    /// database to download. Currently supported: {{uniprot-swissprot}}
+    #[argh(positional)]
+    db: AccessionDb,
+}
+
+fn fst_error(e: fst::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+pub fn run(args: FetchDbArgs, network: &NetworkOptions) -> Result<(), io::Error> {
+    let db = args.db;
+    let response = remote::request(network, "GET", db.url())
+        .map_err(|e| io::Error::other(format!("{}: {}", db, e)))?;
+    let accessions = read_accessions(MultiGzDecoder::new(response.into_reader()))?;
+    let path = db.path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // an interrupted download must not replace the previous index
+    let tmp = path.with_extension("fst.tmp");
+    write_index(&accessions, File::create(&tmp)?)?;
+    std::fs::rename(&tmp, &path)?;
+    cache::evict(&path)?;
+    eprintln!(
+        "{} accessions of {} indexed in {}",
+        accessions.len(),
+        db,
+        path.display()
+    );
+    Ok(())
+}
+
+/// Accessions listed one per line, sorted and deduplicated as required by the index.
+fn read_accessions<R: Read>(input: R) -> io::Result<Vec<String>> {
+    let mut accessions = Vec::new();
+    for line in BufReader::new(input).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            accessions.push(line.trim().to_string());
+        }
+    }
+    accessions.sort_unstable();
+    accessions.dedup();
+    Ok(accessions)
+}
+
+fn write_index<W: Write>(accessions: &[String], writer: W) -> io::Result<()> {
+    let mut builder = SetBuilder::new(BufWriter::new(writer)).map_err(fst_error)?;
+    builder.extend_iter(accessions).map_err(fst_error)?;
+    builder.finish().map_err(fst_error)
+}
+
+/// Load the index of `db`, downloaded with `fetch-db`.
+pub fn load(db: AccessionDb) -> io::Result<Accessions> {
+    let path = db.path()?;
+    let bytes = std::fs::read(&path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "{} was not downloaded, run `omics_valid fetch-db {}`",
+                db, db
+            ),
+        ),
+        _ => e,
+    })?;
+    let set = Set::new(bytes).map_err(fst_error)?;
+    Ok(Accessions(Arc::new(set)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_accessions_are_indexed() {
+        let accessions = read_accessions("Q7B2Q4\nQ00496\n\nQ00496\n".as_bytes()).unwrap();
+        assert_eq!(accessions, ["Q00496", "Q7B2Q4"]);
+        let mut index = Vec::new();
+        write_index(&accessions, &mut index).unwrap();
+        let index = Accessions(Arc::new(Set::new(index).unwrap()));
+        assert!(index.uniprot_exists("Q00496"));
+        assert!(index.uniprot_exists("Q00496-2"));
+        assert!(!index.uniprot_exists("E0X9C7"));
+    }
+}
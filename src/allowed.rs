@@ -0,0 +1,130 @@
+use crate::validators::{IssueCode, ValidationIssue};
+use csv::StringRecord;
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use tracing::info;
+
+/// Lists loaded with [`load`], by column.
+#[derive(Debug, Default, Clone)]
+pub struct AllowedLists(BTreeMap<String, List>);
+
+#[derive(Debug, Clone)]
+struct List {
+    /// file name of the list, for the reports
+    name: String,
+    values: HashSet<String>,
+}
+
+/// `COLUMN=FILE`, the values of `COLUMN` must be lines of `FILE`.
+#[derive(Debug, Clone)]
+pub struct AllowedList {
+    pub column: String,
+    pub path: PathBuf,
+}
+
+impl FromStr for AllowedList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((column, path)) if !column.is_empty() && !path.is_empty() => Ok(AllowedList {
+                column: column.to_string(),
+                path: PathBuf::from(path),
+            }),
+            _ => Err(format!("expected COLUMN=FILE, found {}", s)),
+        }
+    }
+}
+
+/// A value per line, with blank lines and `#` comments skipped.
+fn parse(text: &str) -> HashSet<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+/// Load the lists of the columns; a column given twice accepts the values of
+/// both lists.
+pub fn load(lists: &[AllowedList]) -> io::Result<AllowedLists> {
+    let mut loaded: BTreeMap<String, List> = BTreeMap::new();
+    for list in lists {
+        let text = std::fs::read_to_string(&list.path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", list.path.display(), e)))?;
+        let values = parse(&text);
+        info!(column = %list.column, values = values.len(), "loaded allowed values");
+        let name = list.path.file_name().map_or_else(
+            || list.path.display().to_string(),
+            |n| n.to_string_lossy().into(),
+        );
+        match loaded.get_mut(&list.column) {
+            Some(existing) => {
+                existing.name = format!("{} or {}", existing.name, name);
+                existing.values.extend(values);
+            }
+            None => {
+                loaded.insert(list.column.clone(), List { name, values });
+            }
+        }
+    }
+    Ok(AllowedLists(loaded))
+}
+
+impl AllowedLists {
+    /// Position and name of the columns of the header with an allowed list.
+    pub fn columns(&self, headers: Option<&StringRecord>) -> Vec<(usize, String)> {
+        if self.0.is_empty() {
+            return Vec::new();
+        }
+        headers
+            .map(|headers| {
+                headers
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, h)| self.0.contains_key(*h))
+                    .map(|(i, h)| (i, h.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Issues of the values of a record that are not in the list of their
+    /// column, whose columns are given by [`AllowedLists::columns`]. Empty
+    /// values are not checked.
+    pub fn issues(
+        &self,
+        record: &StringRecord,
+        columns: &[(usize, String)],
+    ) -> Vec<ValidationIssue> {
+        columns
+            .iter()
+            .filter_map(|(i, column)| {
+                let value = record.get(*i).filter(|v| !v.is_empty())?;
+                let list = self.0.get(column)?;
+                (!list.values.contains(value)).then(|| {
+                    ValidationIssue::new(IssueCode::NotAllowed)
+                        .field(column)
+                        .value(value)
+                        .detail(format!("not in {}", list.name))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_values_are_checked_against_the_lists() {
+        assert!("sample=samples.txt".parse::<AllowedList>().is_ok());
+        assert!("samples.txt".parse::<AllowedList>().is_err());
+        let values = parse("# strains of the 2021 runs\nMG1655\n\n BW25113 \n");
+        assert_eq!(values.len(), 2);
+        assert!(values.contains("BW25113"));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::runner::InputFormat;
+use flate2::read::GzDecoder;
+use glob::Pattern;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// Recognize an archive by the extension of its name.
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/// `PATTERN=FORMAT`, validating the archive members whose name matches the
+/// glob pattern with the format.
+#[derive(Debug)]
+pub struct MemberMapping {
+    pub pattern: Pattern,
+    pub format: InputFormat,
+}
+
+impl FromStr for MemberMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (pattern, format) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("expected PATTERN=FORMAT, found {}", s))?;
+        Ok(MemberMapping {
+            pattern: Pattern::new(pattern).map_err(|e| e.to_string())?,
+            format: format
+                .parse()
+                .map_err(|_| format!("unknown format {}", format))?,
+        })
+    }
+}
+
+/// Call `f` with the name and content of every regular file of the archive.
+pub fn for_each_member<F>(path: &Path, kind: ArchiveKind, mut f: F) -> io::Result<()>
+where
+    F: FnMut(&str, &mut dyn Read) -> io::Result<()>,
+{
+    let file = File::open(path)?;
+    match kind {
+        ArchiveKind::Zip => {
+            let mut archive = zip::ZipArchive::new(file)?;
+            for i in 0..archive.len() {
+                let mut member = archive.by_index(i)?;
+                if member.is_file() {
+                    let name = member.name().to_string();
+                    f(&name, &mut member)?;
+                }
+            }
+        }
+        ArchiveKind::TarGz => {
+            let mut archive = tar::Archive::new(GzDecoder::new(file));
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if entry.header().entry_type().is_file() {
+                    let name = entry.path()?.display().to_string();
+                    f(&name, &mut entry)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_members_of_archives_are_listed() {
+        for file in ["tests/bundle.zip", "tests/bundle.tar.gz"] {
+            let path = Path::new(file);
+            let mut names = Vec::new();
+            for_each_member(path, ArchiveKind::detect(path).unwrap(), |name, content| {
+                io::copy(content, &mut io::sink())?;
+                names.push(name.to_string());
+                Ok(())
+            })
+            .unwrap();
+            assert_eq!(names, ["README.txt", "data/uni_tidy.csv"]);
+        }
+    }
+
+    #[test]
+    fn test_member_mapping_is_parsed() {
+        let mapping: MemberMapping = "data/*.csv=tidy_prot".parse().unwrap();
+        assert!(mapping.pattern.matches("data/uni_tidy.csv"));
+        assert!(!mapping.pattern.matches("README.txt"));
+        assert!("*.csv".parse::<MemberMapping>().is_err());
+    }
+}
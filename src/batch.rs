@@ -0,0 +1,286 @@
+use crate::archive::ArchiveKind;
+use crate::bom;
+use crate::bundle::{self, Bundle};
+use crate::compression;
+use crate::models::Models;
+use crate::report::{Overrides, Part};
+use crate::rewrite;
+use crate::runner::{check_content, count_records, validate_input, InputFormat};
+use crate::validators::{IssueCode, LineError, ReadOptions, Severity, ValidationIssue};
+use glob::glob;
+use std::borrow::Cow;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// How the files of a batch are run.
+pub struct BatchOptions {
+    /// number of files validated at the same time
+    pub jobs: usize,
+    /// validate every file even after one has failed
+    pub keep_going: bool,
+    /// with `--report-dir`, where the report of each file is written
+    pub bundle: Option<Bundle>,
+}
+
+/// Result of a validated file of the batch.
+struct Outcome {
+    records: Option<usize>,
+    issues: Vec<LineError>,
+}
+
+/// Files matching the glob patterns (`**` matches the subdirectories) and
+/// listed in the manifest, one path per line relative to the manifest, with
+/// blank lines and `#` comments ignored. The files are in the order given,
+/// without duplicates.
+pub fn files(patterns: &[String], manifest: Option<&Path>) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for pattern in patterns {
+        let paths = glob(pattern).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid --files pattern {}: {}", pattern, e),
+            )
+        })?;
+        let mut matched = 0;
+        for path in paths {
+            let path = path.map_err(io::Error::from)?;
+            if path.is_file() {
+                files.push(path);
+                matched += 1;
+            }
+        }
+        if matched == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no file matches {}", pattern),
+            ));
+        }
+    }
+    if let Some(manifest) = manifest {
+        let dir = manifest.parent().unwrap_or_else(|| Path::new(""));
+        let text = std::fs::read_to_string(manifest)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", manifest.display(), e)))?;
+        files.extend(
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| dir.join(line)),
+        );
+    }
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|path| seen.insert(path.clone()));
+    Ok(files)
+}
+
+/// Check that no two files would write their report to the same directory
+/// of the `--report-dir`, e.g. `a/uni.csv` and `b/uni.csv.gz`.
+pub fn check_names(files: &[PathBuf], format: &InputFormat) -> io::Result<()> {
+    let mut seen = std::collections::HashMap::new();
+    for path in files {
+        let (name, _) = bundle::names(Some(path), format);
+        if let Some(other) = seen.insert(name.clone(), path) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "{} and {} would both write their report to {} of --report-dir",
+                    other.display(),
+                    path.display(),
+                    name
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Validate a (possibly compressed) file with the checks of a single file
+/// that do not write anything, returning its decompressed content.
+fn validate_file(
+    path: &Path,
+    format: &InputFormat,
+    models: &Models,
+    options: &ReadOptions,
+) -> io::Result<(Vec<u8>, Vec<LineError>)> {
+    if ArchiveKind::detect(path).is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "archives are validated one at a time, without --files and --manifest",
+        ));
+    }
+    let (mut input, has_bom) = bom::skip(compression::open(path)?)?;
+    if has_bom {
+        options.stream.write(&bom::warning());
+    }
+    let mut content = Vec::new();
+    input.read_to_end(&mut content)?;
+    // the rewrites are only logged, the summary is the table of the files
+    let (rewritten, _) = rewrite::apply(&content, format, options.rewrite.as_ref());
+    if let Cow::Owned(rewritten) = rewritten {
+        content = rewritten;
+    }
+    let mut error_vec = validate_input(content.as_slice(), format, models, options)?;
+    check_content(&content, format, options, &mut error_vec);
+    if has_bom {
+        error_vec.insert(0, bom::warning());
+    }
+    Ok((content, error_vec))
+}
+
+/// Validate the files on `batch.jobs` threads, a part of the report per file.
+/// Without `keep_going`, no file is started once one has errors and the
+/// others are reported as skipped. The summary starts with a table of the
+/// files (records, lines with errors, status).
+pub fn run(
+    files: &[PathBuf],
+    format: &InputFormat,
+    models: &Models,
+    options: &ReadOptions,
+    overrides: &Overrides,
+    batch: &BatchOptions,
+) -> (Vec<Part>, Vec<String>) {
+    let jobs = batch.jobs.max(1);
+    let run_bench = &options.bench;
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let outcomes: Mutex<Vec<Option<Outcome>>> = Mutex::new(files.iter().map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(files.len()) {
+            scope.spawn(|| loop {
+                if failed.load(Ordering::SeqCst) && !batch.keep_going {
+                    break;
+                }
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let path = match files.get(i) {
+                    Some(path) => path,
+                    None => break,
+                };
+                let mut options = options.part(format!("file {}", path.display()));
+                // each file is measured on its own, then added to the run
+                if options.bench.enabled() {
+                    options.to_mut().bench = options.bench.fresh();
+                }
+                let validated =
+                    validate_file(path, format, models, &options).and_then(|(content, issues)| {
+                        let issues = overrides.apply(issues);
+                        if let Some(bundle) = &batch.bundle {
+                            bundle.write(Some(path), format, &content, &issues, &[])?;
+                        }
+                        Ok(Outcome {
+                            records: Some(count_records(&content, format, options.rows)),
+                            issues,
+                        })
+                    });
+                let outcome = match validated {
+                    Ok(outcome) => outcome,
+                    Err(e) => {
+                        let error = LineError::error(
+                            0,
+                            ValidationIssue::new(IssueCode::Parse).detail(e.to_string()),
+                        );
+                        options.stream.write(&error);
+                        Outcome {
+                            records: None,
+                            issues: vec![error],
+                        }
+                    }
+                };
+                run_bench.add(&options.bench);
+                if outcome.issues.iter().any(|e| e.severity == Severity::Error) {
+                    failed.store(true, Ordering::SeqCst);
+                }
+                outcomes.lock().unwrap()[i] = Some(outcome);
+            });
+        }
+    });
+    let outcomes = outcomes.into_inner().unwrap();
+    let table = table(files, &outcomes);
+    let parts = files
+        .iter()
+        .zip(outcomes)
+        .filter_map(|(path, outcome)| {
+            Some(Part {
+                name: Some(format!("file {}", path.display())),
+                issues: outcome?.issues,
+            })
+        })
+        .collect();
+    (parts, table)
+}
+
+/// Lines of the table of the files, aligned on the longest path.
+fn table(files: &[PathBuf], outcomes: &[Option<Outcome>]) -> Vec<String> {
+    let names: Vec<String> = files.iter().map(|p| p.display().to_string()).collect();
+    let width = names.iter().map(String::len).max().unwrap_or(0).max(4);
+    let mut lines = vec![format!(
+        "{:width$}  {:>8}  {:>6}  status",
+        "file",
+        "records",
+        "errors",
+        width = width
+    )];
+    for (name, outcome) in names.iter().zip(outcomes) {
+        let (records, errors, status) = match outcome {
+            Some(outcome) => {
+                let errors = outcome
+                    .issues
+                    .iter()
+                    .filter(|e| e.severity == Severity::Error)
+                    .count();
+                (
+                    outcome
+                        .records
+                        .map_or_else(|| "-".to_string(), |n| n.to_string()),
+                    errors.to_string(),
+                    if errors > 0 { "fail" } else { "pass" },
+                )
+            }
+            None => ("-".to_string(), "-".to_string(), "skipped"),
+        };
+        lines.push(format!(
+            "{:width$}  {:>8}  {:>6}  {}",
+            name,
+            records,
+            errors,
+            status,
+            width = width
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_files_are_validated_with_a_table() {
+        let files = vec![
+            PathBuf::from("tests/uni_tidy.csv"),
+            PathBuf::from("tests/uni_tidy_notes.csv"),
+            PathBuf::from("tests/absent.csv"),
+        ];
+        let (parts, table) = run(
+            &files,
+            &InputFormat::TidyProt,
+            &Models::load(&[], &[]).unwrap(),
+            &ReadOptions::default(),
+            &Overrides::default(),
+            &BatchOptions {
+                jobs: 2,
+                keep_going: true,
+                bundle: None,
+            },
+        );
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].issues.is_empty());
+        assert_eq!(table.len(), 4);
+        assert!(table[0].starts_with("file"));
+        assert!(table[1].ends_with("pass"));
+        assert!(table[2].ends_with("fail"));
+        // an unreadable file has no records
+        assert!(table[3].contains(" -  "));
+        assert!(table[3].ends_with("fail"));
+    }
+}
@@ -0,0 +1,209 @@
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Phase {
+    /// reading and deserializing the records
+    Parsing,
+    /// validating the records, including the model lookups
+    Validation,
+    /// looking up identifiers in the models
+    ModelLookup,
+}
+
+/// Counters of a validation with `--bench`, shared by its threads. Nothing
+/// is measured if disabled, the default.
+#[derive(Debug, Clone, Default)]
+pub struct Bench(Option<Arc<Counters>>);
+
+#[derive(Debug, Default)]
+struct Counters {
+    records: AtomicU64,
+    bytes: AtomicU64,
+    /// nanoseconds spent in each [`Phase`]
+    phases: [AtomicU64; 3],
+}
+
+impl Bench {
+    /// Enabled counters, starting from zero.
+    pub fn new() -> Self {
+        Bench(Some(Arc::default()))
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Counters of a part measured on its own, enabled if these are, to be
+    /// summed with [`Bench::add`].
+    pub fn fresh(&self) -> Self {
+        match self.0 {
+            Some(_) => Bench::new(),
+            None => Bench::default(),
+        }
+    }
+
+    /// Add the measurements of `other` to these.
+    pub fn add(&self, other: &Bench) {
+        if let (Some(counters), Some(other)) = (&self.0, &other.0) {
+            let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+            counters
+                .records
+                .fetch_add(load(&other.records), Ordering::Relaxed);
+            counters
+                .bytes
+                .fetch_add(load(&other.bytes), Ordering::Relaxed);
+            for (phase, other) in counters.phases.iter().zip(&other.phases) {
+                phase.fetch_add(load(other), Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Run `f`, accounting the time it takes to `phase`.
+    pub fn time<T>(&self, phase: Phase, f: impl FnOnce() -> T) -> T {
+        let counters = match &self.0 {
+            Some(counters) => counters,
+            None => return f(),
+        };
+        let start = Instant::now();
+        let result = f();
+        counters.phases[phase as usize]
+            .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        result
+    }
+
+    /// Iterator over records, timing their parsing and counting them.
+    pub fn records<I: Iterator>(&self, iter: I) -> Records<I> {
+        Records(iter, self.clone())
+    }
+
+    /// Reader counting the bytes read through it.
+    pub fn counted<R: Read>(&self, inner: R) -> Counted<R> {
+        Counted(inner, self.clone())
+    }
+
+    /// Measurements of the run, which took `elapsed`, `None` if disabled.
+    pub fn measurements(&self, elapsed: Duration) -> Option<Measurements> {
+        let counters = self.0.as_ref()?;
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        Some(Measurements {
+            records: load(&counters.records),
+            bytes: load(&counters.bytes),
+            phases: counters
+                .phases
+                .each_ref()
+                .map(|phase| Duration::from_nanos(load(phase))),
+            elapsed,
+        })
+    }
+}
+
+pub struct Records<I>(I, Bench);
+
+impl<I: Iterator> Iterator for Records<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.1.time(Phase::Parsing, || self.0.next());
+        if let (Some(_), Some(counters)) = (&next, &self.1 .0) {
+            counters.records.fetch_add(1, Ordering::Relaxed);
+        }
+        next
+    }
+}
+
+pub struct Counted<R>(R, Bench);
+
+impl<R: Read> Read for Counted<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.0.read(buf)?;
+        if let Some(counters) = &self.1 .0 {
+            counters.bytes.fetch_add(n as u64, Ordering::Relaxed);
+        }
+        Ok(n)
+    }
+}
+
+/// Measurements of a run.
+#[derive(Debug)]
+pub struct Measurements {
+    records: u64,
+    bytes: u64,
+    phases: [Duration; 3],
+    elapsed: Duration,
+}
+
+impl Measurements {
+    /// Throughput and breakdown of the time by phase.
+    pub fn report(&self) -> Vec<String> {
+        let secs = self.elapsed.as_secs_f64().max(f64::EPSILON);
+        let [parsing, validation, lookup] = self.phases;
+        vec![
+            format!(
+                "bench: {} records, {:.1} MB in {:.3} s ({:.0} records/s, {:.1} MB/s)",
+                self.records,
+                self.bytes as f64 / 1e6,
+                self.elapsed.as_secs_f64(),
+                self.records as f64 / secs,
+                self.bytes as f64 / 1e6 / secs
+            ),
+            format!(
+                "bench: parsing {:.3} s, validation {:.3} s (model lookup {:.3} s), other {:.3} s",
+                parsing.as_secs_f64(),
+                validation.as_secs_f64(),
+                lookup.as_secs_f64(),
+                self.elapsed
+                    .saturating_sub(parsing + validation)
+                    .as_secs_f64()
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_measurements_are_reported() {
+        let measurements = Measurements {
+            records: 2000,
+            bytes: 4_000_000,
+            phases: [
+                Duration::from_millis(500),
+                Duration::from_millis(400),
+                Duration::from_millis(100),
+            ],
+            elapsed: Duration::from_secs(1),
+        };
+        assert_eq!(
+            measurements.report(),
+            [
+                "bench: 2000 records, 4.0 MB in 1.000 s (2000 records/s, 4.0 MB/s)",
+                "bench: parsing 0.500 s, validation 0.400 s (model lookup 0.100 s), other 0.100 s"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parts_are_summed_into_the_run() {
+        let run = Bench::new();
+        for part in [&b"a\nb\n"[..], b"c\n"] {
+            let part_bench = run.fresh();
+            let mut read = Vec::new();
+            part_bench.counted(part).read_to_end(&mut read).unwrap();
+            part_bench
+                .records(read.split(|&b| b == b'\n'))
+                .for_each(drop);
+            run.add(&part_bench);
+        }
+        let measurements = run.measurements(Duration::from_secs(1)).unwrap();
+        assert_eq!((measurements.records, measurements.bytes), (5, 6));
+        assert!(Bench::default()
+            .fresh()
+            .measurements(Duration::ZERO)
+            .is_none());
+    }
+}
@@ -0,0 +1,47 @@
+use crate::validators::{IssueCode, LineError, ValidationIssue};
+use std::io::{self, BufRead, BufReader, Read};
+
+/// UTF-8 byte order mark, written at the start of CSV files by Excel and some
+/// editors. Left in place, it becomes part of the first column name.
+const BOM: &[u8] = b"\xef\xbb\xbf";
+
+/// Skip the byte order mark at the start of `reader`, returning whether there
+/// was one.
+pub fn skip<R: Read>(reader: R) -> io::Result<(BufReader<R>, bool)> {
+    let mut reader = BufReader::new(reader);
+    let found = reader.fill_buf()?.starts_with(BOM);
+    if found {
+        reader.consume(BOM.len());
+    }
+    Ok((reader, found))
+}
+
+/// The content without its byte order mark, and whether there was one.
+pub fn strip(content: &[u8]) -> (&[u8], bool) {
+    match content.strip_prefix(BOM) {
+        Some(content) => (content, true),
+        None => (content, false),
+    }
+}
+
+/// Single warning reported for a skipped byte order mark, instead of the
+/// errors it would have caused on the header and every row.
+pub fn warning() -> LineError {
+    LineError::warning(1, ValidationIssue::new(IssueCode::ByteOrderMark))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_byte_order_mark_is_skipped() {
+        let input = b"\xef\xbb\xbfuniprot,sample,value\n";
+        let (mut reader, found) = skip(&input[..]).unwrap();
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert!(found);
+        assert_eq!(content, "uniprot,sample,value\n");
+        assert_eq!(strip(b"uniprot\n"), (&b"uniprot\n"[..], false));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::compression::Compression;
+use crate::jsonl::{self, Record};
+use crate::report;
+use crate::rewrite;
+use crate::runner::InputFormat;
+use crate::validators::{LineError, Severity};
+use csv::ReaderBuilder;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Where the report of each input is written with `--report-dir`.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub dir: PathBuf,
+    /// also write the valid and invalid rows to their own files, with `--split`
+    pub split: bool,
+}
+
+/// `report.json` of a bundle.
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    input: Option<String>,
+    format: String,
+    /// lines with errors
+    errors: usize,
+    warnings: usize,
+    /// lines with issues of the suppression file
+    suppressed: usize,
+    issues: Vec<Record<'a>>,
+    summary: &'a [String],
+}
+
+/// Name of the directory of an input and extension of its split files: the
+/// file name without its compression and data extensions, and the data
+/// extension or, for stdin and files without one, the default of the format.
+pub fn names(input: Option<&Path>, format: &InputFormat) -> (String, String) {
+    let default = if format.delimiter() == b'\t' {
+        "tsv"
+    } else {
+        "csv"
+    };
+    let path = match input {
+        Some(path) => path,
+        None => return ("stdin".to_string(), default.to_string()),
+    };
+    let path = match Compression::detect(path) {
+        Compression::None => path.to_path_buf(),
+        _ => path.with_extension(""),
+    };
+    let stem = path
+        .file_stem()
+        .map_or("stdin".into(), |stem| stem.to_string_lossy());
+    let extension = path
+        .extension()
+        .map_or(default.into(), |ext| ext.to_string_lossy());
+    (stem.into_owned(), extension.into_owned())
+}
+
+impl Bundle {
+    /// Write the report of an input in `<dir>/<name>/`: `report.json`, the
+    /// issues and summaries as JSON, `summary.txt`, the text report with the
+    /// number of issues of each code, and with `split`, `valid.<ext>` and
+    /// `invalid.<ext>`, the header and the rows without and with errors.
+    pub fn write(
+        &self,
+        input: Option<&Path>,
+        format: &InputFormat,
+        content: &[u8],
+        issues: &[LineError],
+        summary: &[String],
+    ) -> io::Result<()> {
+        let (name, extension) = names(input, format);
+        let dir = self.dir.join(name);
+        fs::create_dir_all(&dir)?;
+        let count = |severity: Severity| issues.iter().filter(|e| e.severity == severity).count();
+        let json = Report {
+            input: input.map(|path| path.display().to_string()),
+            format: format.to_string(),
+            errors: count(Severity::Error),
+            warnings: count(Severity::Warning),
+            suppressed: count(Severity::Suppressed),
+            issues: issues
+                .iter()
+                .flat_map(|e| jsonl::records(None, e))
+                .collect(),
+            summary,
+        };
+        let mut text = serde_json::to_string_pretty(&json).expect("issues are serializable");
+        text.push('\n');
+        fs::write(dir.join("report.json"), text)?;
+        let mut lines = report::format_errors(issues);
+        lines.push(format!(
+            "{} lines with errors, {} with warnings, {} with suppressed issues",
+            json.errors, json.warnings, json.suppressed
+        ));
+        for ((code, severity), n) in report::counts(issues) {
+            lines.push(report::count_line(code, severity, n));
+        }
+        lines.extend(summary.iter().cloned());
+        lines.push(String::new());
+        fs::write(dir.join("summary.txt"), lines.join("\n"))?;
+        if self.split {
+            let (valid, invalid) = split(content, format, issues)?;
+            fs::write(dir.join(format!("valid.{}", extension)), valid)?;
+            fs::write(dir.join(format!("invalid.{}", extension)), invalid)?;
+        }
+        Ok(())
+    }
+}
+
+/// The content split into the rows without errors and those with errors,
+/// both starting with the header. The issues concerning the whole file
+/// (before the first row) do not make any row invalid.
+fn split(
+    content: &[u8],
+    format: &InputFormat,
+    issues: &[LineError],
+) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let first_row = if format.has_headers() { 2 } else { 1 };
+    let invalid_lines: HashSet<usize> = issues
+        .iter()
+        .filter(|e| e.severity == Severity::Error && e.line >= first_row)
+        .map(|e| e.line)
+        .collect();
+    // the rows are numbered as the validation numbers them, by record
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(format.delimiter())
+        .flexible(true)
+        .from_reader(content);
+    let mut starts = Vec::new();
+    for record in rdr.byte_records() {
+        let record = record.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let position = record.position().map_or(0, |p| p.byte() as usize);
+        starts.push(rewrite::row_start(content, position));
+    }
+    let (mut valid, mut invalid) = (Vec::new(), Vec::new());
+    for (i, &start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(content.len());
+        let row = &content[start..end];
+        let line = i + 1;
+        if line < first_row {
+            valid.extend_from_slice(row);
+            invalid.extend_from_slice(row);
+        } else if invalid_lines.contains(&line) {
+            invalid.extend_from_slice(row);
+        } else {
+            valid.extend_from_slice(row);
+        }
+    }
+    Ok((valid, invalid))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::validators::{IssueCode, ValidationIssue};
+
+    #[test]
+    fn test_rows_are_split_by_validity() {
+        let content =
+            b"uniprot,sample,value\nQ00496,S1,1\nnot an id,S1,\"2\n\"\nE0X97,S1,x\nP0A6F5,S1,3\n";
+        let issues = vec![
+            LineError::warning(0, ValidationIssue::new(IssueCode::EmptyField)),
+            LineError::error(3, ValidationIssue::new(IssueCode::EmptyField)),
+            LineError::warning(4, ValidationIssue::new(IssueCode::EmptyField)),
+        ];
+        let (valid, invalid) = split(content, &InputFormat::TidyProt, &issues).unwrap();
+        assert_eq!(
+            valid,
+            b"uniprot,sample,value\nQ00496,S1,1\nE0X97,S1,x\nP0A6F5,S1,3\n"
+        );
+        assert_eq!(invalid, b"uniprot,sample,value\nnot an id,S1,\"2\n\"\n");
+        let content = b"uniprot,sample,value\r\nQ00496,S1,1\r\nnot an id,S1,2\r\n";
+        let (valid, invalid) = split(content, &InputFormat::TidyProt, &issues).unwrap();
+        assert_eq!(valid, b"uniprot,sample,value\r\nQ00496,S1,1\r\n");
+        assert_eq!(invalid, b"uniprot,sample,value\r\nnot an id,S1,2\r\n");
+        assert_eq!(
+            names(
+                Some(Path::new("data/uni_tidy.csv.gz")),
+                &InputFormat::TidyProt
+            ),
+            ("uni_tidy".to_string(), "csv".to_string())
+        );
+        assert_eq!(
+            names(None, &InputFormat::Counts),
+            ("stdin".to_string(), "tsv".to_string())
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use argh::FromArgs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::info;
+
+/// Limit of the cache size in MiB, overridden by this environment variable.
+const LIMIT_VAR: &str = "OMICS_VALID_CACHE_LIMIT";
+const DEFAULT_LIMIT_MIB: u64 = 2048;
+
+#[derive(Debug, FromArgs)]
+/// Manage the downloaded models and databases.
+#[argh(subcommand, name = "cache")]
+pub struct CacheArgs {
+    #[argh(subcommand)]
+    command: CacheCommand,
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand)]
+enum CacheCommand {
+    Dir(DirArgs),
+    Ls(LsArgs),
+    Clear(ClearArgs),
+}
+
+#[derive(Debug, FromArgs)]
+/// Print the cache directory.
+#[argh(subcommand, name = "dir")]
+struct DirArgs {}
+
+#[derive(Debug, FromArgs)]
+/// List the cached files with their size.
+#[argh(subcommand, name = "ls")]
+struct LsArgs {}
+
+#[derive(Debug, FromArgs)]
+/// Remove every cached file.
+#[argh(subcommand, name = "clear")]
+struct ClearArgs {}
+
+/// Directory of the downloaded files and indexes, `omics_valid` under the
+/// platform cache directory (`$XDG_CACHE_HOME` or `~/.cache` on Linux).
+pub fn dir() -> io::Result<PathBuf> {
+    dirs::cache_dir()
+        .map(|dir| dir.join("omics_valid"))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no cache directory found"))
+}
+
+/// A cached file.
+#[derive(Debug)]
+struct Entry {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Files under `dir`, recursively.
+fn entries(dir: &Path) -> io::Result<Vec<Entry>> {
+    let mut found = Vec::new();
+    let read_dir = match std::fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(found),
+        Err(e) => return Err(e),
+    };
+    for entry in read_dir {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_dir() {
+            found.extend(entries(&entry.path())?);
+        } else {
+            found.push(Entry {
+                path: entry.path(),
+                size: metadata.len(),
+                modified: metadata.modified()?,
+            });
+        }
+    }
+    Ok(found)
+}
+
+fn limit() -> u64 {
+    std::env::var(LIMIT_VAR)
+        .ok()
+        .and_then(|mib| mib.parse().ok())
+        .unwrap_or(DEFAULT_LIMIT_MIB)
+        * 1024
+        * 1024
+}
+
+/// Remove the least recently downloaded files until the cache fits in its
+/// limit, always keeping `keep`, which was just added.
+pub fn evict(keep: &Path) -> io::Result<()> {
+    evict_from(&dir()?, keep, limit())
+}
+
+fn evict_from(dir: &Path, keep: &Path, limit: u64) -> io::Result<()> {
+    let mut entries = entries(dir)?;
+    let mut total: u64 = entries.iter().map(|e| e.size).sum();
+    entries.sort_by_key(|e| e.modified);
+    for entry in entries {
+        if total <= limit {
+            break;
+        }
+        if entry.path != keep {
+            info!(path = %entry.path.display(), "evicting from the cache");
+            std::fs::remove_file(&entry.path)?;
+            total -= entry.size;
+        }
+    }
+    Ok(())
+}
+
+pub fn run(args: CacheArgs) -> Result<(), io::Error> {
+    let dir = dir()?;
+    match args.command {
+        CacheCommand::Dir(_) => println!("{}", dir.display()),
+        CacheCommand::Ls(_) => {
+            let mut entries = entries(&dir)?;
+            entries.sort_by(|a, b| a.path.cmp(&b.path));
+            for entry in &entries {
+                let path = entry.path.strip_prefix(&dir).unwrap_or(&entry.path);
+                println!("{}\t{:.1} MiB", path.display(), mib(entry.size));
+            }
+            let total = entries.iter().map(|e| e.size).sum();
+            println!(
+                "total\t{:.1} MiB (limit {:.0} MiB)",
+                mib(total),
+                mib(limit())
+            );
+        }
+        CacheCommand::Clear(_) => {
+            if let Err(e) = std::fs::remove_dir_all(&dir) {
+                // an empty cache has no directory
+                if e.kind() != io::ErrorKind::NotFound {
+                    return Err(e);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn mib(bytes: u64) -> f64 {
+    bytes as f64 / 1024.0 / 1024.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_oldest_files_are_evicted() {
+        let dir = std::env::temp_dir().join(format!("omics_valid_cache_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("models")).unwrap();
+        for name in ["models/old.xml", "models/new.xml", "kept.fst"] {
+            std::fs::write(dir.join(name), [0; 100]).unwrap();
+            // distinct modification times
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        evict_from(&dir, &dir.join("kept.fst"), 200).unwrap();
+        let mut left: Vec<_> = entries(&dir).unwrap().into_iter().map(|e| e.path).collect();
+        left.sort();
+        assert_eq!(left, [dir.join("kept.fst"), dir.join("models/new.xml")]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
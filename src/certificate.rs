@@ -0,0 +1,121 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Reader computing the SHA-256 of everything read through it, so that stdin
+/// can be hashed while it is validated.
+pub struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> HashingReader<R> {
+    pub fn new(inner: R) -> Self {
+        HashingReader {
+            inner,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// Consume the rest of the input and return its SHA-256 as hex.
+    pub fn finish(mut self) -> io::Result<String> {
+        io::copy(&mut self, &mut io::sink())?;
+        Ok(format!("{:x}", self.hasher.finalize()))
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// Provenance record of a successful validation, stored by the data catalog.
+#[derive(Debug, Serialize)]
+pub struct Certificate {
+    /// `None` if the input was read from stdin
+    file: Option<PathBuf>,
+    file_sha256: String,
+    format: String,
+    tool_version: &'static str,
+    models: Vec<ModelHash>,
+    /// RFC 3339, UTC
+    timestamp: String,
+    errors: usize,
+    warnings: usize,
+    /// SHA-256 of the certificate serialized without this field, to detect edits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    certificate_sha256: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ModelHash {
+    path: PathBuf,
+    sha256: String,
+}
+
+fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+impl Certificate {
+    pub fn new(
+        file: Option<&Path>,
+        file_sha256: String,
+        format: String,
+        models: &[PathBuf],
+        warnings: usize,
+    ) -> io::Result<Self> {
+        let mut certificate = Certificate {
+            file: file.map(Path::to_path_buf),
+            file_sha256,
+            format,
+            tool_version: TOOL_VERSION,
+            models: models
+                .iter()
+                .map(|path| {
+                    Ok(ModelHash {
+                        path: path.clone(),
+                        sha256: sha256_file(path)?,
+                    })
+                })
+                .collect::<io::Result<_>>()?,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            errors: 0,
+            warnings,
+            certificate_sha256: None,
+        };
+        let unsigned = serde_json::to_vec(&certificate)?;
+        certificate.certificate_sha256 = Some(format!("{:x}", Sha256::digest(&unsigned)));
+        Ok(certificate)
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(&mut file, self)?;
+        writeln!(file)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hashing_reader_hashes_whole_input() {
+        let mut reader = HashingReader::new("uniprot,sample,value\n".as_bytes());
+        let mut first = [0; 7];
+        reader.read_exact(&mut first).unwrap();
+        assert_eq!(
+            reader.finish().unwrap(),
+            "43c0086d19ed59edec01166303f0c4767f3b0a00acb574b165db246f1873aa4f"
+        );
+    }
+}
@@ -0,0 +1,342 @@
+/// Mass of an electron, lost by positive ions, in Da.
+const ELECTRON_MASS: f64 = 0.000_548_579_909;
+
+/// Monoisotopic mass of the most abundant isotope of the elements of
+/// metabolites, in Da.
+const MONOISOTOPIC_MASSES: [(&str, f64); 26] = [
+    ("H", 1.007_825_032_07),
+    ("B", 11.009_305_4),
+    ("C", 12.0),
+    ("N", 14.003_074_004_8),
+    ("O", 15.994_914_619_56),
+    ("F", 18.998_403_22),
+    ("Na", 22.989_769_280_9),
+    ("Mg", 23.985_041_7),
+    ("Si", 27.976_926_532_5),
+    ("P", 30.973_761_63),
+    ("S", 31.972_071),
+    ("Cl", 34.968_852_68),
+    ("K", 38.963_706_68),
+    ("Ca", 39.962_590_98),
+    ("Mn", 54.938_045_1),
+    ("Fe", 55.934_937_5),
+    ("Co", 58.933_195),
+    ("Ni", 57.935_342_9),
+    ("Cu", 62.929_597_5),
+    ("Zn", 63.929_142_2),
+    ("Se", 79.916_521_3),
+    ("Br", 78.918_337_1),
+    ("Mo", 97.905_408_2),
+    ("Ag", 106.905_097),
+    ("I", 126.904_473),
+    ("Li", 7.016_004_55),
+];
+
+/// Formulas of the abbreviations used in adducts.
+const ABBREVIATIONS: [(&str, &str); 6] = [
+    ("ACN", "C2H3N"),
+    ("FA", "CH2O2"),
+    ("MeOH", "CH4O"),
+    ("DMSO", "C2H6OS"),
+    ("IsoProp", "C3H8O"),
+    ("TFA", "C2HF3O2"),
+];
+
+/// Symbols of the elements of the periodic table.
+const ELEMENTS: [&str; 118] = [
+    "H", "He", "Li", "Be", "B", "C", "N", "O", "F", "Ne", "Na", "Mg", "Al", "Si", "P", "S", "Cl",
+    "Ar", "K", "Ca", "Sc", "Ti", "V", "Cr", "Mn", "Fe", "Co", "Ni", "Cu", "Zn", "Ga", "Ge", "As",
+    "Se", "Br", "Kr", "Rb", "Sr", "Y", "Zr", "Nb", "Mo", "Tc", "Ru", "Rh", "Pd", "Ag", "Cd", "In",
+    "Sn", "Sb", "Te", "I", "Xe", "Cs", "Ba", "La", "Ce", "Pr", "Nd", "Pm", "Sm", "Eu", "Gd", "Tb",
+    "Dy", "Ho", "Er", "Tm", "Yb", "Lu", "Hf", "Ta", "W", "Re", "Os", "Ir", "Pt", "Au", "Hg", "Tl",
+    "Pb", "Bi", "Po", "At", "Rn", "Fr", "Ra", "Ac", "Th", "Pa", "U", "Np", "Pu", "Am", "Cm", "Bk",
+    "Cf", "Es", "Fm", "Md", "No", "Lr", "Rf", "Db", "Sg", "Bh", "Hs", "Mt", "Ds", "Rg", "Cn", "Nh",
+    "Fl", "Mc", "Lv", "Ts", "Og",
+];
+
+/// Molecular formula, e.g. `C6H12O6` or `C6H11O7-`, as the count of each
+/// element and the charge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Formula {
+    pub elements: Vec<(String, u32)>,
+    pub charge: i32,
+}
+
+impl Formula {
+    /// Parse a formula written as element symbols of the periodic table
+    /// followed by their positive counts, and an optional charge such as `-`,
+    /// `+2`. Spreadsheet artifacts (`C6H12O6.`, `c6h12o6`) are rejected.
+    pub fn parse(s: &str) -> Result<Formula, String> {
+        let (s, charge) = match s.find(&['+', '-'][..]) {
+            Some(i) => {
+                let sign = if &s[i..=i] == "+" { 1 } else { -1 };
+                let magnitude = match &s[i + 1..] {
+                    "" => 1,
+                    magnitude => parse_count(magnitude)
+                        .ok_or_else(|| format!("invalid charge {}", &s[i..]))?,
+                };
+                (&s[..i], sign * magnitude as i32)
+            }
+            None => (s, 0),
+        };
+        let mut elements = Vec::new();
+        let mut chars = s.char_indices().peekable();
+        while let Some((start, c)) = chars.next() {
+            if c.is_ascii_lowercase() {
+                return Err(format!(
+                    "element symbols start with an uppercase letter at {}",
+                    &s[start..]
+                ));
+            } else if !c.is_ascii_uppercase() {
+                return Err(format!("unexpected character {:?} at {}", c, &s[start..]));
+            }
+            let mut end = start + 1;
+            if let Some((i, c)) = chars.next_if(|(_, c)| c.is_ascii_lowercase()) {
+                end = i + c.len_utf8();
+            }
+            let symbol = &s[start..end];
+            if !ELEMENTS.contains(&symbol) {
+                return Err(format!("unknown element {}", symbol));
+            }
+            let mut count_end = end;
+            while let Some((i, _)) = chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                count_end = i + 1;
+            }
+            let count = match &s[end..count_end] {
+                "" => 1,
+                count => parse_count(count)
+                    .ok_or_else(|| format!("invalid count {}{}", symbol, count))?,
+            };
+            elements.push((symbol.to_string(), count));
+        }
+        if elements.is_empty() {
+            return Err("empty formula".into());
+        }
+        Ok(Formula { elements, charge })
+    }
+
+    /// Monoisotopic mass of the atoms in Da, `None` if an element has no known
+    /// mass.
+    pub fn monoisotopic_mass(&self) -> Option<f64> {
+        self.elements
+            .iter()
+            .try_fold(0.0, |total, (symbol, count)| {
+                let (_, mass) = MONOISOTOPIC_MASSES.iter().find(|(s, _)| s == symbol)?;
+                Some(total + mass * *count as f64)
+            })
+    }
+}
+
+/// Mass difference in parts per million.
+pub fn ppm(measured: f64, expected: f64) -> f64 {
+    (measured - expected) / expected * 1e6
+}
+
+/// Charge of the ions found in adducts; the groups missing from this list and
+/// from [`NEUTRAL_GROUPS`] make the charge of an adduct unknown.
+const ION_CHARGES: [(&str, i32); 14] = [
+    ("H", 1),
+    ("Li", 1),
+    ("Na", 1),
+    ("K", 1),
+    ("NH4", 1),
+    ("Ag", 1),
+    ("Mg", 2),
+    ("Ca", 2),
+    ("Fe", 2),
+    ("F", -1),
+    ("Cl", -1),
+    ("Br", -1),
+    ("HCOO", -1),
+    ("CH3COO", -1),
+];
+
+/// Neutral losses and solvent molecules, including the usual abbreviations.
+const NEUTRAL_GROUPS: [&str; 13] = [
+    "H2O", "NH3", "CO2", "CO", "HCOOH", "CH3OH", "CH3CN", "ACN", "FA", "MeOH", "DMSO", "IsoProp",
+    "TFA",
+];
+
+/// A term of an adduct, e.g. `+2Na` or `-H2O`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdductTerm {
+    /// 1 if the group is added, -1 if it is lost
+    pub sign: i32,
+    pub count: u32,
+    pub group: String,
+}
+
+/// Adduct in the `[nM+X-Y]z+` notation, e.g. `[M+H]+`, `[M-H]-`, `[2M+Na]+`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Adduct {
+    /// number of molecules, 2 in `[2M+Na]+`
+    pub multimer: u32,
+    pub terms: Vec<AdductTerm>,
+    /// signed charge state, never 0
+    pub charge: i32,
+}
+
+impl Adduct {
+    /// Parse an adduct, explaining what is malformed otherwise.
+    pub fn parse(s: &str) -> Result<Adduct, String> {
+        let (inner, charge) = s
+            .strip_prefix('[')
+            .and_then(|s| s.rsplit_once(']'))
+            .ok_or("expected [M...] followed by the charge")?;
+        let multimer_end = inner.find('M').ok_or("no molecule M")?;
+        let multimer = match &inner[..multimer_end] {
+            "" => 1,
+            multimer => {
+                parse_count(multimer).ok_or_else(|| format!("invalid multimer {}M", multimer))?
+            }
+        };
+        let mut terms = Vec::new();
+        let mut rest = &inner[multimer_end + 1..];
+        while !rest.is_empty() {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return Err(format!("expected + or - before {}", rest)),
+            };
+            rest = &rest[1..];
+            let end = rest.find(&['+', '-'][..]).unwrap_or(rest.len());
+            let (term, next) = rest.split_at(end);
+            rest = next;
+            let digits = term.bytes().take_while(u8::is_ascii_digit).count();
+            let count = match &term[..digits] {
+                "" => 1,
+                count => parse_count(count).ok_or_else(|| format!("invalid count in {}", term))?,
+            };
+            let group = &term[digits..];
+            if !is_group(group) {
+                return Err(format!("invalid group {}", term));
+            }
+            terms.push(AdductTerm {
+                sign,
+                count,
+                group: group.to_string(),
+            });
+        }
+        let (magnitude, sign) = match charge.char_indices().last() {
+            Some((i, '+')) => (&charge[..i], 1),
+            Some((i, '-')) => (&charge[..i], -1),
+            _ => return Err("the charge must end with + or -".into()),
+        };
+        let magnitude = match magnitude {
+            "" => 1,
+            magnitude => {
+                parse_count(magnitude).ok_or_else(|| format!("invalid charge {}", charge))? as i32
+            }
+        };
+        let adduct = Adduct {
+            multimer,
+            terms,
+            charge: sign * magnitude,
+        };
+        // without terms, the molecule is charged itself
+        match adduct.ion_charge() {
+            Some(ions) if !adduct.terms.is_empty() && ions != adduct.charge => Err(format!(
+                "charge {} does not match the ions ({:+})",
+                charge, ions
+            )),
+            _ => Ok(adduct),
+        }
+    }
+
+    /// m/z of the ion of a molecule of mass `neutral_mass`, `None` if the mass
+    /// of a group is unknown.
+    pub fn mz(&self, neutral_mass: f64) -> Option<f64> {
+        let mass =
+            self.terms
+                .iter()
+                .try_fold(self.multimer as f64 * neutral_mass, |total, term| {
+                    let formula = ABBREVIATIONS
+                        .iter()
+                        .find(|(abbreviation, _)| *abbreviation == term.group)
+                        .map_or(term.group.as_str(), |(_, formula)| formula);
+                    let group_mass = Formula::parse(formula).ok()?.monoisotopic_mass()?;
+                    Some(total + (term.sign * term.count as i32) as f64 * group_mass)
+                })?;
+        Some((mass - self.charge as f64 * ELECTRON_MASS) / self.charge.abs() as f64)
+    }
+
+    /// Charge brought by the terms, `None` if a group is not a known ion or
+    /// neutral molecule.
+    fn ion_charge(&self) -> Option<i32> {
+        self.terms.iter().try_fold(0, |total, term| {
+            let charge = if NEUTRAL_GROUPS.contains(&term.group.as_str()) {
+                0
+            } else {
+                ION_CHARGES
+                    .iter()
+                    .find(|(ion, _)| *ion == term.group)
+                    .map(|(_, charge)| *charge)?
+            };
+            Some(total + term.sign * term.count as i32 * charge)
+        })
+    }
+}
+
+/// Positive integer without leading zeros.
+fn parse_count(s: &str) -> Option<u32> {
+    s.parse()
+        .ok()
+        .filter(|n| *n > 0 && !s.starts_with('0') && !s.starts_with('+'))
+}
+
+/// Whether `group` is a known abbreviation or looks like a formula (`Na`,
+/// `NH4`, `CH3COO`).
+fn is_group(group: &str) -> bool {
+    NEUTRAL_GROUPS.contains(&group)
+        || group.starts_with(|c: char| c.is_ascii_uppercase())
+            && group.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_adducts_are_parsed() {
+        for adduct in [
+            "[M+H]+",
+            "[M-H]-",
+            "[2M+Na]+",
+            "[M+2H]2+",
+            "[M+H-H2O]+",
+            "[M+Cl]-",
+            "[M+ACN+H]+",
+            "[M]+",
+        ] {
+            assert!(Adduct::parse(adduct).is_ok(), "{}", adduct);
+        }
+        assert_eq!(
+            Adduct::parse("[M+2H]2+").unwrap().terms,
+            [AdductTerm {
+                sign: 1,
+                count: 2,
+                group: "H".to_string()
+            }]
+        );
+        let gluconate = Formula::parse("C6H11O7-").unwrap();
+        assert_eq!(gluconate.charge, -1);
+        assert_eq!(Formula::parse("Fe+2").unwrap().charge, 2);
+        for formula in ["C6H12O6.", "c6h12o6", "C0H2", "Xx2", "C6 H12", "", "H2O+0"] {
+            assert!(Formula::parse(formula).is_err(), "{}", formula);
+        }
+        let glucose = Formula::parse("C6H12O6")
+            .unwrap()
+            .monoisotopic_mass()
+            .unwrap();
+        assert!((glucose - 180.063_388).abs() < 1e-5);
+        let mz = Adduct::parse("[M+Na]+").unwrap().mz(glucose).unwrap();
+        assert!((mz - 203.052_609).abs() < 1e-5);
+        let mz = Adduct::parse("[M-H]-").unwrap().mz(glucose).unwrap();
+        assert!((mz - 179.056_112).abs() < 1e-5);
+        for adduct in [
+            "M+H", "[M+H]", "[M+H]0+", "[M+h]+", "[0M+H]+", "[M+H]2+", "[M++H]+", "[X+H]+",
+        ] {
+            assert!(Adduct::parse(adduct).is_err(), "{}", adduct);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::report::{format_issue, ValidationReport};
+use crate::validators::Severity;
+use md5::{Digest, Md5};
+use serde::Serialize;
+
+/// An issue of the [Code Quality](https://docs.gitlab.com/ee/ci/testing/code_quality.html)
+/// report of GitLab, a subset of the Code Climate format.
+#[derive(Debug, Serialize)]
+pub struct Issue {
+    description: String,
+    check_name: &'static str,
+    /// identifies the issue across pipelines, to tell new issues from fixed ones
+    fingerprint: String,
+    severity: &'static str,
+    location: Location,
+}
+
+#[derive(Debug, Serialize)]
+struct Location {
+    path: String,
+    lines: Lines,
+}
+
+#[derive(Debug, Serialize)]
+struct Lines {
+    begin: usize,
+}
+
+/// Issues of the report, located in `path`. The issues of the whole file
+/// (line 0) are put on the first line, and the part (archive member or
+/// resource) is part of the fingerprint along with the path, line and code.
+pub fn issues(report: &ValidationReport, path: &str) -> Vec<Issue> {
+    report
+        .parts
+        .iter()
+        .flat_map(|part| {
+            part.issues.iter().flat_map(move |e| {
+                e.issues.iter().map(move |issue| {
+                    let fingerprint = Md5::new()
+                        .chain_update(path)
+                        .chain_update([0u8])
+                        .chain_update(part.name.as_deref().unwrap_or_default())
+                        .chain_update([0u8])
+                        .chain_update(e.line.to_string())
+                        .chain_update([0u8])
+                        .chain_update(issue.code.id())
+                        .chain_update([0u8])
+                        .chain_update(issue.field.as_deref().unwrap_or_default())
+                        .finalize();
+                    let description = match &part.name {
+                        Some(name) => format!("{}: {}", name, format_issue(issue)),
+                        None => format_issue(issue),
+                    };
+                    Issue {
+                        description,
+                        check_name: issue.code.id(),
+                        fingerprint: format!("{:x}", fingerprint),
+                        severity: match e.severity {
+                            Severity::Error => "major",
+                            Severity::Warning => "minor",
+                            Severity::Suppressed => "info",
+                        },
+                        location: Location {
+                            path: path.to_string(),
+                            lines: Lines {
+                                begin: e.line.max(1),
+                            },
+                        },
+                    }
+                })
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::report::Part;
+    use crate::validators::{IssueCode, LineError, ValidationIssue};
+
+    #[test]
+    fn test_fingerprints_are_stable() {
+        let report = ValidationReport {
+            parts: vec![Part {
+                name: None,
+                issues: vec![
+                    LineError::error(0, ValidationIssue::new(IssueCode::MissingColumn)),
+                    LineError::error(3, ValidationIssue::new(IssueCode::EmptySample)),
+                ],
+            }],
+            ..Default::default()
+        };
+        let issues = issues(&report, "data/uni_tidy.csv");
+        assert_eq!(issues[0].location.lines.begin, 1);
+        assert_ne!(issues[0].fingerprint, issues[1].fingerprint);
+        assert_eq!(
+            issues[1].fingerprint,
+            super::issues(&report, "data/uni_tidy.csv")[1].fingerprint
+        );
+        let json = serde_json::to_value(&issues[1]).unwrap();
+        assert_eq!(json["severity"], "major");
+        assert_eq!(json["location"]["path"], "data/uni_tidy.csv");
+    }
+}
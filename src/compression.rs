@@ -0,0 +1,64 @@
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Compression of a file, recognized by its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Bzip2,
+}
+
+impl Compression {
+    pub fn detect(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gz") => Compression::Gzip,
+            Some("zst") => Compression::Zstd,
+            Some("bz2") => Compression::Bzip2,
+            _ => Compression::None,
+        }
+    }
+
+    /// Wrap `reader` so that the decompressed content is read.
+    pub fn decoder<'a, R: Read + 'a>(self, reader: R) -> io::Result<Box<dyn Read + 'a>> {
+        Ok(match self {
+            Compression::None => Box::new(reader),
+            // FASTQ files are often concatenations of gzip members
+            Compression::Gzip => Box::new(MultiGzDecoder::new(reader)),
+            Compression::Zstd => Box::new(zstd::Decoder::new(reader)?),
+            Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(reader)),
+        })
+    }
+}
+
+/// Open a file, decompressing it according to its extension.
+pub fn open(path: &Path) -> io::Result<Box<dyn Read>> {
+    Compression::detect(path).decoder(File::open(path)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compressed_files_are_decompressed() {
+        let expected = std::fs::read_to_string("tests/uni_tidy.csv").unwrap();
+        for file in ["tests/uni_tidy.csv.gz", "tests/uni_tidy.csv.bz2"] {
+            let mut content = String::new();
+            open(Path::new(file))
+                .unwrap()
+                .read_to_string(&mut content)
+                .unwrap();
+            assert_eq!(content, expected);
+        }
+        let mut zstd_content = Vec::new();
+        open(Path::new("tests/valid.fastq.zst"))
+            .unwrap()
+            .read_to_end(&mut zstd_content)
+            .unwrap();
+        assert_eq!(zstd_content, std::fs::read("tests/valid.fastq").unwrap());
+    }
+}
@@ -0,0 +1,120 @@
+use crate::naming::Naming;
+use crate::rewrite::IdRules;
+use crate::scale::Scale;
+use crate::unique::UniqueKeys;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Configuration file read from the working directory if `--config` is not given.
+pub const DEFAULT_CONFIG: &str = "omics_valid.toml";
+
+/// Settings that are tedious to pass on every invocation, e.g.
+///
+/// ```toml
+/// ignore = ["OV020"]
+/// unique = ["met_id", "sample", "time"]
+///
+/// [promote]
+/// platform-mismatch = "error"
+///
+/// [rename]
+/// protein_id = "uniprot"
+/// metabolite = "met_id"
+///
+/// [ontology]
+/// organism_part = "uberon"
+///
+/// [naming]
+/// pattern = "{strain}_{condition}_{replicate}"
+///
+/// [max_missing]
+/// intensity = 0.2
+///
+/// [ids]
+/// strip_prefixes = ["M_"]
+///
+/// [scale]
+/// tidy_prot = "linear"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// issue codes that are not reported
+    pub ignore: Vec<String>,
+    /// issue code -> severity it is reported with
+    pub promote: BTreeMap<String, String>,
+    /// alternative header name -> expected field name
+    pub rename: BTreeMap<String, String>,
+    /// column name -> ontology loaded with `--obo` its terms must belong to
+    pub ontology: BTreeMap<String, String>,
+    /// naming convention of the sample names
+    pub naming: Option<Naming>,
+    /// column -> highest fraction of missing values
+    pub max_missing: BTreeMap<String, f64>,
+    /// rewriting of the identifiers before the validation
+    pub ids: Option<IdRules>,
+    /// columns whose values must be unique across the rows, or a list of them
+    pub unique: Option<UniqueKeys>,
+    /// format name -> scale its values are expected on
+    pub scale: BTreeMap<String, Scale>,
+}
+
+impl Config {
+    /// Load `path` or, if not given, the [`DEFAULT_CONFIG`] if it exists.
+    pub fn load(path: Option<&Path>) -> io::Result<Self> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(DEFAULT_CONFIG).exists() => Path::new(DEFAULT_CONFIG),
+            None => return Ok(Config::default()),
+        };
+        toml::from_str(&std::fs::read_to_string(path)?).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        })
+    }
+}
+
+/// `FROM=TO`, reading the column `FROM` as the field `TO`.
+#[derive(Debug)]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+}
+
+impl FromStr for Rename {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((from, to)) if !from.is_empty() && !to.is_empty() => Ok(Rename {
+                from: from.to_string(),
+                to: to.to_string(),
+            }),
+            _ => Err(format!("expected FROM=TO, found {}", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_config_is_parsed() {
+        let config: Config =
+            toml::from_str("[rename]\nprotein_id = \"uniprot\"\nmetabolite = \"met_id\"\n")
+                .unwrap();
+        assert_eq!(config.rename["protein_id"], "uniprot");
+        assert!(toml::from_str::<Config>("unknown = 1").is_err());
+        let config: Config =
+            toml::from_str("unique = [[\"met_id\", \"sample\"], [\"id\"]]").unwrap();
+        assert_eq!(config.unique.unwrap().keys().len(), 2);
+        assert!("protein_id=uniprot".parse::<Rename>().is_ok());
+        assert!("protein_id".parse::<Rename>().is_err());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::missing;
+use crate::replicates::{self, CONDITION_COLUMN, SAMPLE_COLUMNS};
+use crate::validators::{IssueCode, LineError, ValidationIssue};
+use csv::ReaderBuilder;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Columns telling the batch a sample was processed in.
+const BATCH_COLUMNS: [&str; 2] = ["batch", "run_date"];
+
+/// Check the batches of a metadata sheet (or any file with a sample column):
+/// report the samples without a batch, on their first line, and the batches
+/// holding a single condition, at line 0: their effect cannot be told from
+/// the one of the condition. A study of a single condition is not confounded.
+/// Without a condition column, the conditions are found in the sample names
+/// by `pattern`.
+pub fn check(content: &[u8], delimiter: u8, pattern: Option<&Regex>) -> Vec<LineError> {
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content);
+    let headers = match rdr.headers() {
+        Ok(headers) => headers.clone(),
+        Err(_) => return Vec::new(),
+    };
+    let position = |name: &str| headers.iter().position(|h| h == name);
+    let sample_column = match SAMPLE_COLUMNS.iter().find_map(|c| position(c)) {
+        Some(column) => column,
+        None => return Vec::new(),
+    };
+    let batch_columns: Vec<(&str, usize)> = BATCH_COLUMNS
+        .iter()
+        .filter_map(|c| Some((*c, position(c)?)))
+        .collect();
+    if batch_columns.is_empty() {
+        return Vec::new();
+    }
+    let condition_column = position(CONDITION_COLUMN);
+    let mut error_vec = Vec::new();
+    // samples of each condition of each batch, by batch column
+    let mut batches: Vec<BTreeMap<String, BTreeMap<String, BTreeSet<String>>>> =
+        vec![BTreeMap::new(); batch_columns.len()];
+    let mut conditions = BTreeSet::new();
+    let mut reported: BTreeSet<(usize, String)> = BTreeSet::new();
+    // unreadable records are reported by the validation of the format
+    for (i, record) in rdr.records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        let sample = record.get(sample_column).unwrap_or_default();
+        if sample.is_empty() {
+            continue;
+        }
+        let condition = replicates::condition(
+            sample,
+            condition_column.map(|c| record.get(c).unwrap_or_default()),
+            pattern,
+        );
+        conditions.insert(condition.to_string());
+        for (j, (name, column)) in batch_columns.iter().enumerate() {
+            let batch = record.get(*column).unwrap_or_default().trim();
+            if missing::is_missing(batch) {
+                // once per sample, the rows of a tidy file repeat it
+                if reported.insert((j, sample.to_string())) {
+                    error_vec.push(LineError::error(
+                        i + 2,
+                        ValidationIssue::new(IssueCode::MissingBatch)
+                            .field(*name)
+                            .value(sample),
+                    ));
+                }
+                continue;
+            }
+            batches[j]
+                .entry(batch.to_string())
+                .or_default()
+                .entry(condition.to_string())
+                .or_default()
+                .insert(sample.to_string());
+        }
+    }
+    if conditions.len() < 2 {
+        return error_vec;
+    }
+    for ((name, _), batches) in batch_columns.iter().zip(batches) {
+        for (batch, samples) in batches {
+            if samples.len() != 1 {
+                continue;
+            }
+            let (condition, samples) = samples.into_iter().next().unwrap_or_default();
+            error_vec.push(LineError::warning(
+                0,
+                ValidationIssue::new(IssueCode::ConfoundedBatch)
+                    .field(*name)
+                    .value(batch)
+                    .detail(format!(
+                        "only condition {}: {}",
+                        condition,
+                        samples.into_iter().collect::<Vec<_>>().join(", ")
+                    )),
+            ));
+        }
+    }
+    error_vec
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_batches_are_checked() {
+        let pattern = Regex::new(replicates::DEFAULT_PATTERN).unwrap();
+        let input = "Experiment,condition,batch\nS1,glc,b1\nS2,ac,b1\nS3,glc,b2\nS4,glc,b2\n\
+                     S5,ac,NA\n";
+        let error_vec = check(input.as_bytes(), b',', Some(&pattern));
+        assert_eq!(error_vec.len(), 2);
+        assert_eq!(error_vec[0].line, 6);
+        assert_eq!(error_vec[0].issues[0].code, IssueCode::MissingBatch);
+        assert_eq!(error_vec[1].line, 0);
+        assert_eq!(error_vec[1].issues[0].value.as_deref(), Some("b2"));
+        assert_eq!(
+            error_vec[1].issues[0].detail.as_deref(),
+            Some("only condition glc: S3, S4")
+        );
+        // the condition of the sample names, and the rows of a tidy file
+        let input = "uniprot,sample,value,run_date\nQ00496,glc_1,1,2021-03-01\n\
+                     Q7B2Q4,glc_1,2,2021-03-01\nQ00496,ac_1,3,2021-03-01\nQ00496,ac_2,4,\n\
+                     Q7B2Q4,ac_2,5,\n";
+        let error_vec = check(input.as_bytes(), b',', Some(&pattern));
+        assert_eq!(error_vec.len(), 1);
+        assert_eq!(error_vec[0].issues[0].value.as_deref(), Some("ac_2"));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::bom;
+use crate::compression::Compression;
+use crate::paths;
+use crate::remote::NetworkOptions;
+use crate::report::{print_errors, Diagnostics};
+use crate::runner::from_file_or_stdin;
+use crate::validators::*;
+use argh::FromArgs;
+use csv::ReaderBuilder;
+use serde::Serialize;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use strum::EnumString;
+
+#[derive(Debug, EnumString)]
+#[strum(serialize_all = "kebab-case")]
+pub enum ConvertTarget {
+    NfcoreRnaseq,
+}
+
+#[derive(Debug, Clone, Copy, EnumString, Serialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Strandedness {
+    Auto,
+    Forward,
+    Reverse,
+    Unstranded,
+}
+
+#[derive(Debug, FromArgs)]
+/// Convert a valid omics file into the input of other tools.
+#[argh(subcommand, name = "convert")]
+pub struct ConvertArgs {
+    /// input omics file.
+    #[argh(positional)]
+    file: Option<PathBuf>,
+
+    /// target format. Currently supported: {{nfcore-rnaseq}}
+    #[argh(option)]
+    to: ConvertTarget,
+
+    /// strandedness written to nf-core samplesheets: {{auto, forward, reverse, unstranded}}
+    #[argh(option, default = "Strandedness::Auto")]
+    strandedness: Strandedness,
+}
+
+/// A row of the [nf-core/rnaseq](https://nf-co.re/rnaseq/usage#samplesheet-input) samplesheet.
+#[derive(Debug, Serialize)]
+struct NfcoreRnaseqRecord<'a> {
+    sample: &'a str,
+    fastq_1: &'a Path,
+    fastq_2: Option<&'a Path>,
+    strandedness: Strandedness,
+}
+
+pub fn run(
+    args: ConvertArgs,
+    network: &NetworkOptions,
+    diagnostics: Diagnostics,
+) -> Result<(), std::io::Error> {
+    // the input is read twice: once for validation and once for the conversion
+    let mut content = Vec::new();
+    let compression = args
+        .file
+        .as_deref()
+        .map_or(Compression::None, Compression::detect);
+    let (mut input, _) = bom::skip(compression.decoder(from_file_or_stdin(args.file, network)?)?)?;
+    input.read_to_end(&mut content)?;
+    match args.to {
+        ConvertTarget::NfcoreRnaseq => to_nfcore_rnaseq(&content, args.strandedness, diagnostics),
+    }
+}
+
+/// Transform an iModulon RNA TSV into an nf-core/rnaseq samplesheet, written to stdout.
+fn to_nfcore_rnaseq(
+    content: &[u8],
+    strandedness: Strandedness,
+    diagnostics: Diagnostics,
+) -> Result<(), std::io::Error> {
+    let error_vec: Vec<LineError> = RnaRecord::validate_omics(content)
+        .into_iter()
+        .filter(|e| e.severity == Severity::Error)
+        .collect();
+    if !error_vec.is_empty() {
+        print_errors(diagnostics, &error_vec);
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "refusing to convert: the input has validation errors",
+        ));
+    }
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(RnaRecord::delimiter())
+        .from_reader(content);
+    let mut wtr = csv::Writer::from_writer(std::io::stdout());
+    for (i, result) in rdr.deserialize().enumerate() {
+        let record: RnaRecord = result?;
+        let fastq_1 = record.r1.as_deref().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "line {}: {} has no local R1 file, public SRA data must be downloaded first",
+                    i + 2,
+                    record.experiment
+                ),
+            )
+        })?;
+        // the samplesheet is run on this system, with its separators
+        let fastq_2 = record.r2.as_deref().map(paths::local);
+        wtr.serialize(NfcoreRnaseqRecord {
+            sample: &record.experiment,
+            fastq_1: &paths::local(fastq_1),
+            fastq_2: fastq_2.as_deref(),
+            strandedness,
+        })?;
+    }
+    wtr.flush()
+}
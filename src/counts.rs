@@ -0,0 +1,237 @@
+use crate::excel;
+use crate::models::Models;
+use crate::validators::{
+    parse_issue, IssueCode, LineError, ReadOptions, RowSelection, Severity, ValidationIssue,
+};
+use csv::ReaderBuilder;
+use std::io::Read;
+use strum::{Display, EnumString};
+
+/// Columns separated by tabs, as written by featureCounts or DESeq2.
+pub const DELIMITER: u8 = b'\t';
+
+/// Options of the counts checks, from the command line.
+#[derive(Debug, Clone)]
+pub struct CountsOptions {
+    /// accept values this close to an integer, e.g. `12.0`; `None` requires
+    /// integer literals
+    pub tolerance: Option<f64>,
+    /// the values are normalized instead of raw counts
+    pub normalization: Option<Normalization>,
+    /// relative difference allowed between the sum of a sample and the
+    /// expected sum of the normalization
+    pub sum_tolerance: f64,
+}
+
+impl Default for CountsOptions {
+    fn default() -> Self {
+        CountsOptions {
+            tolerance: None,
+            normalization: None,
+            sum_tolerance: 0.01,
+        }
+    }
+}
+
+/// Normalization of a matrix, whose samples must add up to a known total.
+#[derive(Debug, Clone, Copy, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Normalization {
+    /// transcripts per million
+    Tpm,
+    /// relative abundances in percent
+    Percent,
+}
+
+impl Normalization {
+    fn expected_sum(self) -> f64 {
+        match self {
+            Normalization::Tpm => 1e6,
+            Normalization::Percent => 100.0,
+        }
+    }
+}
+
+/// Check that a value is a raw count: fractional or negative values are a sign
+/// that normalized data (e.g. TPM) was exported instead. Normalized values
+/// only need to be non-negative. The value is returned if it is a number.
+fn check_count(value: &str, options: &CountsOptions) -> Result<f64, IssueCode> {
+    let number = match value.parse::<f64>() {
+        Ok(number) if number.is_finite() => number,
+        _ => return Err(IssueCode::InvalidValue),
+    };
+    if number < 0.0 {
+        return Err(IssueCode::NegativeCount);
+    }
+    let integral = match (options.normalization, options.tolerance) {
+        (Some(_), _) => true,
+        (None, Some(tolerance)) => (number - number.round()).abs() <= tolerance,
+        (None, None) => value.parse::<u64>().is_ok(),
+    };
+    if integral {
+        Ok(number)
+    } else {
+        Err(IssueCode::FractionalCount)
+    }
+}
+
+/// Validate a gene-by-sample counts matrix:
+///
+/// ```tsv
+/// gene_id SAMPLE_1    SAMPLE_2    ...
+/// GENE_ID NUMBER_VALUE    NUMBER_VALUE    ...
+/// ```
+///
+/// Gene IDs must not be empty and, with `--annotation`, they must be in the
+/// genome annotation. Every sample value must be a non-negative integer, see
+/// [`CountsOptions`].
+pub fn validate<R: Read>(input: R, models: &Models, options: &ReadOptions) -> Vec<LineError> {
+    let counts_options = &options.counts;
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(DELIMITER)
+        .flexible(true)
+        .from_reader(input);
+    options.rename_headers(&mut rdr);
+    let samples: Vec<String> = match rdr.headers() {
+        Ok(headers) => headers.iter().skip(1).map(String::from).collect(),
+        Err(e) => return vec![LineError::error(1, parse_issue(&e))],
+    };
+    let mut error_vec = Vec::new();
+    if samples.is_empty() {
+        error_vec.push(LineError::error(
+            1,
+            ValidationIssue::new(IssueCode::MissingColumn).value("sample"),
+        ));
+    }
+    let mut sums = vec![0.0; samples.len()];
+    for (i, result) in options.rows.select(rdr.records().enumerate()) {
+        let line = i + 2;
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                error_vec.push(LineError::error(line, parse_issue(&e)));
+                continue;
+            }
+        };
+        let mut issues = Vec::new();
+        let gene_id = record.get(0).unwrap_or_default();
+        if gene_id.is_empty() {
+            issues.push(ValidationIssue::new(IssueCode::EmptyField).field("gene_id"));
+        } else if let Some(artifact) = excel::check("gene_id", gene_id) {
+            issues.push(artifact);
+        } else if models.contains_gene(gene_id) == Some(false) {
+            issues.push(
+                ValidationIssue::new(IssueCode::NotInAnnotation)
+                    .field("gene_id")
+                    .value(gene_id),
+            );
+        }
+        for ((sample, value), sum) in samples.iter().zip(record.iter().skip(1)).zip(&mut sums) {
+            match check_count(value, counts_options) {
+                Ok(number) => *sum += number,
+                Err(code) => issues.push(ValidationIssue::new(code).field(sample).value(value)),
+            }
+        }
+        if !issues.is_empty() {
+            error_vec.push(LineError {
+                line,
+                issues,
+                severity: Severity::Error,
+            });
+        }
+    }
+    // the sums are only known if every row was read
+    if let (Some(normalization), RowSelection::All) = (counts_options.normalization, options.rows) {
+        let expected = normalization.expected_sum();
+        for (sample, sum) in samples.iter().zip(sums) {
+            if (sum - expected).abs() > expected * counts_options.sum_tolerance {
+                error_vec.push(LineError::error(
+                    0,
+                    ValidationIssue::new(IssueCode::ColumnSumMismatch)
+                        .field(sample)
+                        .value(sample)
+                        .detail(format!(
+                            "sum {}, expected {} for {}",
+                            sum, expected, normalization
+                        )),
+                ));
+            }
+        }
+    }
+    error_vec
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_counts_are_validated_against_annotation() {
+        let mut models = Models::load(&[], &[]).unwrap();
+        let file = std::fs::File::open("tests/counts.tsv").unwrap();
+        let lines =
+            |error_vec: Vec<LineError>| error_vec.iter().map(|e| e.line).collect::<Vec<_>>();
+        assert_eq!(
+            lines(validate(file, &models, &ReadOptions::default())),
+            [5, 6]
+        );
+        models
+            .load_annotation(Path::new("tests/genes.gff3"))
+            .unwrap();
+        let file = std::fs::File::open("tests/counts.tsv").unwrap();
+        assert_eq!(
+            lines(validate(file, &models, &ReadOptions::default())),
+            [4, 5, 6]
+        );
+    }
+
+    #[test]
+    fn test_counts_must_be_non_negative_integers() {
+        let models = Models::load(&[], &[]).unwrap();
+        let input = "gene_id\tS1\nb0001\t12.0\nb0002\t-3\nb0003\t4.5\nb0004\t7\n";
+        let codes = |tolerance| {
+            let options = ReadOptions {
+                counts: CountsOptions {
+                    tolerance,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            validate(input.as_bytes(), &models, &options)
+                .iter()
+                .map(|e| e.issues[0].code)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            codes(None),
+            [
+                IssueCode::FractionalCount,
+                IssueCode::NegativeCount,
+                IssueCode::FractionalCount
+            ]
+        );
+        assert_eq!(
+            codes(Some(1e-6)),
+            [IssueCode::NegativeCount, IssueCode::FractionalCount]
+        );
+    }
+
+    #[test]
+    fn test_normalized_samples_add_up() {
+        let models = Models::load(&[], &[]).unwrap();
+        let input = "gene_id\tS1\tS2\nb0001\t60.5\t1200\nb0002\t39.6\t800\n";
+        let options = ReadOptions {
+            counts: CountsOptions {
+                normalization: Some(Normalization::Percent),
+                sum_tolerance: 0.01,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let error_vec = validate(input.as_bytes(), &models, &options);
+        assert_eq!(error_vec.len(), 1);
+        assert_eq!(error_vec[0].line, 0);
+        assert_eq!(error_vec[0].issues[0].field.as_deref(), Some("S2"));
+    }
+}
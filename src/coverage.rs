@@ -0,0 +1,468 @@
+use crate::compression;
+use crate::missing;
+use crate::models::Models;
+use crate::outliers::VALUE_COLUMNS;
+use crate::replicates::SAMPLE_COLUMNS;
+use crate::runner::InputFormat;
+use crate::uniprot;
+use crate::validators::ReadOptions;
+use csv::ReaderBuilder;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/// Species of a compartment of a model measured by a metabolomics file.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CompartmentCoverage {
+    pub model: String,
+    pub compartment: String,
+    pub species: usize,
+    /// species with an identifier among the `met_id` of the file
+    pub measured: usize,
+    pub not_measured: usize,
+}
+
+/// The `met_id` of the rows read as the validation reads them.
+fn measured_metabolites(content: &[u8], options: &ReadOptions) -> HashSet<String> {
+    let mut rdr = ReaderBuilder::new().flexible(true).from_reader(content);
+    options.rename_headers(&mut rdr);
+    let column = match rdr.headers() {
+        Ok(headers) => match headers.iter().position(|h| h == "met_id") {
+            Some(column) => column,
+            None => return HashSet::new(),
+        },
+        Err(_) => return HashSet::new(),
+    };
+    // unreadable records are reported by the validation
+    options
+        .rows
+        .select(rdr.records().enumerate())
+        .filter_map(|(_, record)| Some(record.ok()?.get(column)?.trim().to_string()))
+        .collect()
+}
+
+/// Coverage of the species of each SBML model by the metabolites of a met
+/// file, per compartment in alphabetical order. A species is measured if an
+/// identifier of its annotations is a `met_id` of the file, as when the
+/// metabolites are checked against the models.
+pub fn metabolites(
+    content: &[u8],
+    models: &Models,
+    options: &ReadOptions,
+) -> Vec<CompartmentCoverage> {
+    let measured = measured_metabolites(content, options);
+    let mut rows = Vec::new();
+    for (model, species) in models.species() {
+        let mut compartments: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+        for sp in species {
+            let counts = compartments.entry(&sp.compartment).or_default();
+            counts.0 += 1;
+            if sp.identifiers.iter().any(|id| measured.contains(id)) {
+                counts.1 += 1;
+            }
+        }
+        rows.extend(
+            compartments
+                .into_iter()
+                .map(|(compartment, (species, measured))| CompartmentCoverage {
+                    model: model.to_string(),
+                    compartment: compartment.to_string(),
+                    species,
+                    measured,
+                    not_measured: species - measured,
+                }),
+        );
+    }
+    rows
+}
+
+/// Summary line of each model, e.g. `model iML1515.xml: 52 of 1877 species
+/// measured (2.8%), c: 40 of 1071, e: 12 of 341, p: 0 of 465`.
+pub fn summary(rows: &[CompartmentCoverage]) -> Vec<String> {
+    let mut models: Vec<&str> = rows.iter().map(|r| r.model.as_str()).collect();
+    models.dedup();
+    models
+        .into_iter()
+        .map(|model| {
+            let compartments: Vec<&CompartmentCoverage> =
+                rows.iter().filter(|r| r.model == model).collect();
+            let species: usize = compartments.iter().map(|r| r.species).sum();
+            let measured: usize = compartments.iter().map(|r| r.measured).sum();
+            let details: Vec<String> = compartments
+                .iter()
+                .map(|r| format!("{}: {} of {}", r.compartment, r.measured, r.species))
+                .collect();
+            format!(
+                "model {}: {} of {} species measured ({:.1}%), {}",
+                model,
+                measured,
+                species,
+                percent(measured, species),
+                details.join(", ")
+            )
+        })
+        .collect()
+}
+
+/// Reactions of a subsystem of a model measured in a sample of a flux file.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct SubsystemCoverage {
+    pub model: String,
+    pub sample: String,
+    pub subsystem: String,
+    pub reactions: usize,
+    /// reactions with a value in the sample
+    pub measured: usize,
+    /// measured reactions with a flux other than zero
+    pub active: usize,
+}
+
+/// Flux of each reaction in each sample of a flux file, read as the
+/// validation reads it, the samples in the order they first appear.
+fn fluxes(content: &[u8], options: &ReadOptions) -> Vec<(String, HashMap<String, f64>)> {
+    let mut rdr = ReaderBuilder::new().flexible(true).from_reader(content);
+    options.rename_headers(&mut rdr);
+    let columns = match rdr.headers() {
+        Ok(headers) => {
+            let position = |name: &str| headers.iter().position(|h| h == name);
+            match (
+                position("reaction_id"),
+                position("sample"),
+                position("value"),
+            ) {
+                (Some(id), Some(sample), Some(value)) => (id, sample, value),
+                _ => return Vec::new(),
+            }
+        }
+        Err(_) => return Vec::new(),
+    };
+    let mut samples: Vec<(String, HashMap<String, f64>)> = Vec::new();
+    // unreadable records and values are reported by the validation
+    for (_, record) in options.rows.select(rdr.records().enumerate()) {
+        let record = match record {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        let (id, sample, value) = columns;
+        let value = match record.get(value).and_then(|v| v.trim().parse::<f64>().ok()) {
+            Some(value) if value.is_finite() => value,
+            _ => continue,
+        };
+        let sample = record.get(sample).unwrap_or_default();
+        let reactions = match samples.iter().position(|(s, _)| s == sample) {
+            Some(i) => &mut samples[i].1,
+            None => {
+                samples.push((sample.to_string(), HashMap::new()));
+                &mut samples.last_mut().expect("just pushed").1
+            }
+        };
+        reactions.insert(record.get(id).unwrap_or_default().to_string(), value);
+    }
+    samples
+}
+
+/// Coverage of the subsystems (SBML groups) of each model by the reactions of
+/// a flux file, per sample. A reaction of the model, e.g. `R_PGI`, is
+/// measured as `R_PGI` or `PGI`.
+pub fn subsystems(
+    content: &[u8],
+    models: &Models,
+    options: &ReadOptions,
+) -> Vec<SubsystemCoverage> {
+    let samples = fluxes(content, options);
+    let mut rows = Vec::new();
+    for (model, groups) in models.subsystems() {
+        for (sample, reactions) in &samples {
+            for group in groups {
+                let fluxes: Vec<f64> = group
+                    .members
+                    .iter()
+                    .filter_map(|id| {
+                        reactions
+                            .get(id)
+                            .or_else(|| reactions.get(id.strip_prefix("R_")?))
+                    })
+                    .copied()
+                    .collect();
+                rows.push(SubsystemCoverage {
+                    model: model.to_string(),
+                    sample: sample.clone(),
+                    subsystem: group.name.clone(),
+                    reactions: group.members.len(),
+                    measured: fluxes.len(),
+                    active: fluxes.iter().filter(|f| **f != 0.0).count(),
+                });
+            }
+        }
+    }
+    rows
+}
+
+/// Summary line of each model and sample, e.g. `model iML1515.xml, SIM1: 35 of
+/// 42 subsystems carry flux, 410 of 2712 reactions measured (15.1%)`.
+pub fn subsystem_summary(rows: &[SubsystemCoverage]) -> Vec<String> {
+    let mut keys: Vec<(&str, &str)> = rows
+        .iter()
+        .map(|r| (r.model.as_str(), r.sample.as_str()))
+        .collect();
+    keys.dedup();
+    keys.into_iter()
+        .map(|(model, sample)| {
+            let subsystems: Vec<&SubsystemCoverage> = rows
+                .iter()
+                .filter(|r| r.model == model && r.sample == sample)
+                .collect();
+            let reactions: usize = subsystems.iter().map(|r| r.reactions).sum();
+            let measured: usize = subsystems.iter().map(|r| r.measured).sum();
+            format!(
+                "model {}, {}: {} of {} subsystems carry flux, {} of {} reactions measured \
+                 ({:.1}%)",
+                model,
+                sample,
+                subsystems.iter().filter(|r| r.active > 0).count(),
+                subsystems.len(),
+                measured,
+                reactions,
+                percent(measured, reactions)
+            )
+        })
+        .collect()
+}
+
+/// Proteins of a reference proteome quantified by a proteomics file.
+#[derive(Debug, PartialEq)]
+pub struct ProteinCoverage {
+    /// proteins of the reference
+    pub reference: usize,
+    /// proteins of the reference with a value in any sample
+    pub quantified: usize,
+    /// proteins of the reference with a value in each sample, in the order the
+    /// samples first appear, empty for prot which has none
+    pub samples: Vec<(String, usize)>,
+}
+
+/// Entry accessions of a reference proteome: the accessions of the headers of
+/// a (possibly compressed) UniProt FASTA file, e.g. `>sp|P0A6F5|CH60_ECOLI`,
+/// or the first field of each line of a list, without isoforms and versions.
+pub fn read_proteome(path: &Path) -> io::Result<HashSet<String>> {
+    let mut accessions = HashSet::new();
+    let mut fasta = None;
+    for line in BufReader::new(compression::open(path)?).lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let is_fasta = *fasta.get_or_insert(line.starts_with('>'));
+        let id = match line.strip_prefix('>') {
+            Some(header) => header
+                .split('|')
+                .nth(1)
+                .or_else(|| header.split_whitespace().next()),
+            // the sequences of a FASTA file
+            None if is_fasta => None,
+            None => line.split(&[',', '\t', ' '][..]).next(),
+        };
+        if let Some(id) = id {
+            accessions.insert(uniprot::entry(id).to_string());
+        }
+    }
+    Ok(accessions)
+}
+
+/// Whether a field is a value, i.e., a number.
+fn is_quantified(value: &str) -> bool {
+    !missing::is_missing(value) && value.trim().parse::<f64>().is_ok()
+}
+
+/// Coverage of a reference proteome by the proteins with a value in a prot or
+/// tidy_prot file, read as the validation reads them. A group is quantified
+/// for each of its members, an isoform for its entry.
+pub fn proteins(
+    content: &[u8],
+    format: &InputFormat,
+    options: &ReadOptions,
+    reference: &HashSet<String>,
+) -> ProteinCoverage {
+    let proteomics = &options.proteomics;
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(format.has_headers())
+        .flexible(true)
+        .from_reader(content);
+    options.rename_headers(&mut rdr);
+    // uniprot, sample and value columns of tidy_prot
+    let columns = match (format, rdr.headers()) {
+        (InputFormat::TidyProt, Ok(headers)) => {
+            let position = |names: &[&str]| headers.iter().position(|h| names.contains(&h));
+            position(&["uniprot"]).zip(position(&SAMPLE_COLUMNS).zip(position(&VALUE_COLUMNS)))
+        }
+        _ => None,
+    };
+    let records: Vec<csv::StringRecord> = options
+        .rows
+        .select(rdr.records().enumerate())
+        .filter_map(|(_, record)| record.ok())
+        .collect();
+    let mut quantified: HashSet<&str> = HashSet::new();
+    let mut order: Vec<String> = Vec::new();
+    let mut samples: HashMap<String, HashSet<&str>> = HashMap::new();
+    for record in &records {
+        let (id, sample) = match columns {
+            Some((id, (sample, value))) => {
+                if !is_quantified(record.get(value).unwrap_or_default()) {
+                    continue;
+                }
+                (record.get(id).unwrap_or_default(), record.get(sample))
+            }
+            None if matches!(format, InputFormat::TidyProt) => continue,
+            None => {
+                if !record.iter().skip(1).any(is_quantified) {
+                    continue;
+                }
+                (record.get(0).unwrap_or_default(), None)
+            }
+        };
+        let members: Vec<&str> = proteomics
+            .members(id)
+            .into_iter()
+            .map(uniprot::entry)
+            .filter(|entry| reference.contains(*entry))
+            .collect();
+        quantified.extend(&members);
+        if let Some(sample) = sample {
+            if !samples.contains_key(sample) {
+                order.push(sample.to_string());
+            }
+            samples
+                .entry(sample.to_string())
+                .or_default()
+                .extend(members);
+        }
+    }
+    ProteinCoverage {
+        reference: reference.len(),
+        quantified: quantified.len(),
+        samples: order
+            .into_iter()
+            .map(|sample| {
+                let n = samples.get(&sample).map_or(0, HashSet::len);
+                (sample, n)
+            })
+            .collect(),
+    }
+}
+
+fn percent(part: usize, whole: usize) -> f64 {
+    100.0 * part as f64 / whole.max(1) as f64
+}
+
+/// Summary line of the coverage of the reference `name`, e.g. `proteome
+/// UP000000625.fasta: 1520 of 4403 proteins quantified (34.5%), SIM1: 1498
+/// (34.0%), SIM2: 1431 (32.5%)`.
+pub fn protein_summary(name: &str, coverage: &ProteinCoverage) -> String {
+    let mut line = format!(
+        "{}: {} of {} proteins quantified ({:.1}%)",
+        name,
+        coverage.quantified,
+        coverage.reference,
+        percent(coverage.quantified, coverage.reference)
+    );
+    for (sample, n) in &coverage.samples {
+        line.push_str(&format!(
+            ", {}: {} ({:.1}%)",
+            sample,
+            n,
+            percent(*n, coverage.reference)
+        ));
+    }
+    line
+}
+
+/// Write the coverage as a JSON array if `path` ends with `.json`, as CSV
+/// otherwise.
+pub fn write<T: Serialize>(path: &Path, rows: &[T]) -> io::Result<()> {
+    if path.extension().is_some_and(|ext| ext == "json") {
+        let mut json = serde_json::to_string_pretty(rows).expect("coverage is serializable");
+        json.push('\n');
+        return std::fs::write(path, json);
+    }
+    let mut writer = csv::Writer::from_path(path)?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_subsystems_are_covered_per_sample() {
+        let models = Models::load(&[PathBuf::from("tests/e_coli_core_groups.xml")], &[]).unwrap();
+        let content = std::fs::read("tests/flux_tidy.csv").unwrap();
+        let rows = subsystems(&content, &models, &ReadOptions::default());
+        assert_eq!(
+            rows.iter()
+                .map(|r| (r.subsystem.as_str(), r.reactions, r.measured, r.active))
+                .collect::<Vec<_>>(),
+            [
+                ("Glycolysis/Gluconeogenesis", 5, 5, 5),
+                ("Pentose Phosphate Pathway", 2, 0, 0),
+                // the species of the group are left out
+                ("Extracellular exchange", 1, 1, 1),
+            ]
+        );
+        assert_eq!(
+            subsystem_summary(&rows),
+            [
+                "model tests/e_coli_core_groups.xml, SIM1: 2 of 3 subsystems carry flux, 6 of 8 \
+              reactions measured (75.0%)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_species_coverage_is_counted_per_compartment() {
+        let models = Models::load(&[PathBuf::from("tests/iCLAU786.xml")], &[]).unwrap();
+        let content = std::fs::read("tests/met_tidy.csv").unwrap();
+        let rows = metabolites(&content, &models, &ReadOptions::default());
+        assert!(!rows.is_empty());
+        assert!(rows
+            .iter()
+            .all(|r| r.measured + r.not_measured == r.species));
+        assert_eq!(rows.iter().map(|r| r.species).sum::<usize>(), 1094);
+        assert!(rows.iter().any(|r| r.measured > 0));
+        assert!(summary(&rows)[0].starts_with("model tests/iCLAU786.xml: "));
+    }
+
+    #[test]
+    fn test_protein_coverage_is_counted_per_sample() {
+        let reference: HashSet<String> = ["P0A6F5", "Q00496", "P0A9B2", "E0X97"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let content =
+            b"uniprot,sample,value\nP0A6F5-2,S1,1\nQ00496,S1,NA\nQ00496,S2,3\nP12345,S2,4\n";
+        let coverage = proteins(
+            content,
+            &InputFormat::TidyProt,
+            &ReadOptions::default(),
+            &reference,
+        );
+        assert_eq!(
+            coverage,
+            ProteinCoverage {
+                reference: 4,
+                quantified: 2,
+                samples: vec![("S1".to_string(), 1), ("S2".to_string(), 1)],
+            }
+        );
+        assert_eq!(
+            protein_summary("proteome", &coverage),
+            "proteome: 2 of 4 proteins quantified (50.0%), S1: 1 (25.0%), S2: 1 (25.0%)"
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use crate::bom;
+use crate::compression::Compression;
+use crate::replicates::SAMPLE_COLUMNS;
+use crate::report::{Part, ValidationReport};
+use crate::runner::InputFormat;
+use crate::validators::{IssueCode, LineError, ValidationIssue};
+use argh::FromArgs;
+use csv::ReaderBuilder;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+#[derive(Debug, FromArgs)]
+/// Compare the sample names of several omics files, e.g. the layers of a multi-omics study.
+#[argh(subcommand, name = "crosscheck")]
+pub struct CrosscheckArgs {
+    /// omics files as FILE=FORMAT, e.g. proteins.csv=tidy_prot rna.tsv=rna
+    #[argh(positional)]
+    layers: Vec<Layer>,
+}
+
+/// An omics file and its format, given as `FILE=FORMAT`.
+#[derive(Debug)]
+pub struct Layer {
+    pub path: PathBuf,
+    pub format: InputFormat,
+}
+
+impl FromStr for Layer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, format) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("expected FILE=FORMAT, found {}", s))?;
+        Ok(Layer {
+            path: PathBuf::from(path),
+            format: format
+                .parse()
+                .map_err(|_| format!("unknown format {}", format))?,
+        })
+    }
+}
+
+/// Sample names of an omics file and the line where each first appears: the
+/// `sample` or `Experiment` column, or the columns of the counts format.
+pub(crate) fn samples<R: Read>(
+    input: R,
+    format: &InputFormat,
+) -> io::Result<BTreeMap<String, usize>> {
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(format.delimiter())
+        .flexible(true)
+        .from_reader(input);
+    let headers = rdr.headers()?.clone();
+    if let InputFormat::Counts = format {
+        return Ok(headers.iter().skip(1).map(|h| (h.to_string(), 1)).collect());
+    }
+    let column = match format {
+        InputFormat::Prot | InputFormat::Datapackage => None,
+        _ => headers.iter().position(|h| SAMPLE_COLUMNS.contains(&h)),
+    }
+    .ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("the {} format has no sample names", format),
+        )
+    })?;
+    let mut samples = BTreeMap::new();
+    for (i, record) in rdr.records().enumerate() {
+        // unreadable records are reported by `validate`
+        let record = match record {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        match record.get(column) {
+            Some(sample) if !sample.is_empty() => {
+                samples.entry(sample.to_string()).or_insert(i + 2);
+            }
+            _ => {}
+        }
+    }
+    Ok(samples)
+}
+
+/// Experiment of each Run of an RNA sheet. A counts matrix with a column per
+/// Run instead of per Experiment has not been aggregated yet.
+fn experiment_runs<R: Read>(input: R) -> io::Result<BTreeMap<String, String>> {
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(b'\t')
+        .flexible(true)
+        .from_reader(input);
+    let headers = rdr.headers()?.clone();
+    let position = |name: &str| headers.iter().position(|h| h == name);
+    let (experiment, run) = match (position("Experiment"), position("Run")) {
+        (Some(experiment), Some(run)) => (experiment, run),
+        _ => return Ok(BTreeMap::new()),
+    };
+    Ok(rdr
+        .records()
+        .filter_map(Result::ok)
+        .filter_map(|record| {
+            let run = record.get(run).filter(|r| !r.is_empty())?;
+            Some((run.to_string(), record.get(experiment)?.to_string()))
+        })
+        .collect())
+}
+
+/// Report, in each layer, the samples that are missing from some of the other
+/// layers, with the size of the intersection and the union in the summary.
+pub fn run(args: CrosscheckArgs) -> io::Result<ValidationReport> {
+    if args.layers.len() < 2 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "crosscheck needs at least two FILE=FORMAT layers",
+        ));
+    }
+    let mut layers = Vec::new();
+    let mut runs = BTreeMap::new();
+    for layer in &args.layers {
+        let input = Compression::detect(&layer.path).decoder(File::open(&layer.path)?)?;
+        let (mut input, _) = bom::skip(input)?;
+        let mut content = Vec::new();
+        input.read_to_end(&mut content)?;
+        let context =
+            |e: io::Error| io::Error::new(e.kind(), format!("{}: {}", layer.path.display(), e));
+        let samples = samples(content.as_slice(), &layer.format).map_err(context)?;
+        if let InputFormat::Rna = layer.format {
+            runs.extend(experiment_runs(content.as_slice()).map_err(context)?);
+        }
+        layers.push((layer.path.display().to_string(), samples));
+    }
+    Ok(crosscheck(&layers, &runs))
+}
+
+/// Compare the samples of the layers; `runs` gives the Experiment of the Runs
+/// of the rna layers, to explain a counts matrix with a column per Run.
+fn crosscheck(
+    layers: &[(String, BTreeMap<String, usize>)],
+    runs: &BTreeMap<String, String>,
+) -> ValidationReport {
+    let union: BTreeSet<&String> = layers.iter().flat_map(|(_, s)| s.keys()).collect();
+    let n_shared = union
+        .iter()
+        .filter(|sample| layers.iter().all(|(_, s)| s.contains_key(**sample)))
+        .count();
+    let parts = layers
+        .iter()
+        .map(|(name, samples)| {
+            let issues = samples
+                .iter()
+                .filter_map(|(sample, &line)| {
+                    let missing: Vec<&str> = layers
+                        .iter()
+                        .filter(|(_, other)| !other.contains_key(sample))
+                        .map(|(other, _)| other.as_str())
+                        .collect();
+                    (!missing.is_empty()).then(|| {
+                        let mut detail = format!("missing from {}", missing.join(", "));
+                        if let Some(experiment) = runs.get(sample) {
+                            detail += &format!(
+                                "; it is a Run of {}, aggregate the runs by Experiment",
+                                experiment
+                            );
+                        }
+                        LineError::error(
+                            line,
+                            ValidationIssue::new(IssueCode::SampleNotInAllLayers)
+                                .field("sample")
+                                .value(sample)
+                                .detail(detail),
+                        )
+                    })
+                })
+                .collect();
+            Part {
+                name: Some(format!("layer {}", name)),
+                issues,
+            }
+        })
+        .collect();
+    ValidationReport {
+        parts,
+        summary: vec![format!(
+            "{} of {} samples are in all {} layers",
+            n_shared,
+            union.len(),
+            layers.len()
+        )],
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_samples_missing_from_a_layer_are_reported() {
+        let counts = samples(
+            File::open("tests/counts.tsv").unwrap(),
+            &InputFormat::Counts,
+        )
+        .unwrap();
+        let multi = samples(File::open("tests/multi.csv").unwrap(), &InputFormat::Multi).unwrap();
+        let report = crosscheck(
+            &[
+                ("counts.tsv".to_string(), counts),
+                ("multi.csv".to_string(), multi),
+            ],
+            &BTreeMap::new(),
+        );
+        assert_eq!(report.parts[0].issues.len(), 1);
+        assert_eq!(
+            report.parts[0].issues[0].issues[0].value.as_deref(),
+            Some("SIM2")
+        );
+        assert!(report.parts[1].issues.is_empty());
+    }
+
+    #[test]
+    fn test_counts_columns_match_the_experiments() {
+        let rna = "Experiment\tRun\nSRX2389071\tSRR5069221\nSRX2389072\tSRR5069222\n";
+        let counts = "gene_id\tSRX2389071\tSRR5069222\nb0001\t10\t3\n";
+        let layers = [
+            (
+                "counts.tsv".to_string(),
+                samples(counts.as_bytes(), &InputFormat::Counts).unwrap(),
+            ),
+            (
+                "rna.tsv".to_string(),
+                samples(rna.as_bytes(), &InputFormat::Rna).unwrap(),
+            ),
+        ];
+        let report = crosscheck(&layers, &experiment_runs(rna.as_bytes()).unwrap());
+        assert_eq!(
+            report.parts[0].issues[0].issues[0].detail.as_deref(),
+            Some(
+                "missing from rna.tsv; it is a Run of SRX2389072, aggregate the runs by Experiment"
+            )
+        );
+        assert_eq!(
+            report.parts[1].issues[0].issues[0].value.as_deref(),
+            Some("SRX2389072")
+        );
+        assert_eq!(report.summary, ["1 of 3 samples are in all 2 layers"]);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::bom;
+use crate::compression::Compression;
+use crate::models::Models;
+use crate::runner::{check_dataset, validate_input, InputFormat};
+use crate::scale;
+use crate::schema::{self, ForeignKey, TableSchema};
+use crate::validators::{IssueCode, LineError, ReadOptions, ValidationIssue};
+use md5::Md5;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// [Frictionless Data Package](https://specs.frictionlessdata.io/data-package/)
+/// descriptor, only the parts used for validation.
+#[derive(Debug, Deserialize)]
+struct DataPackage {
+    resources: Vec<Resource>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Resource {
+    name: Option<String>,
+    path: PathBuf,
+    bytes: Option<u64>,
+    /// `<algorithm>:<hex>`, md5 if the algorithm is omitted
+    hash: Option<String>,
+    schema: Option<TableSchema>,
+    /// extension to validate the resource against a built-in format, e.g. `"tidy_prot"`
+    omics_format: Option<String>,
+    #[serde(default)]
+    dialect: Dialect,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Dialect {
+    delimiter: Option<char>,
+}
+
+/// Compare the hash of `content` with the declared `<algorithm>:<hex>`.
+fn check_hash(content: &[u8], declared: &str) -> Result<(), String> {
+    let (algorithm, expected) = declared.split_once(':').unwrap_or(("md5", declared));
+    let actual = match algorithm {
+        "md5" => format!("{:x}", Md5::digest(content)),
+        "sha256" => format!("{:x}", Sha256::digest(content)),
+        other => return Err(format!("unsupported hash algorithm {}", other)),
+    };
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{}: declared {}, found {}",
+            algorithm, expected, actual
+        ))
+    }
+}
+
+impl Resource {
+    fn name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+
+    /// The rows of the resource, decompressed and without BOM.
+    fn table(&self, base: &Path) -> io::Result<Vec<u8>> {
+        let mut content = Vec::new();
+        Compression::detect(&self.path)
+            .decoder(std::fs::File::open(base.join(&self.path))?)?
+            .read_to_end(&mut content)?;
+        Ok(bom::strip(&content).0.to_vec())
+    }
+
+    fn delimiter(&self) -> u8 {
+        self.dialect.delimiter.unwrap_or(',') as u8
+    }
+
+    /// An error concerning the resource, with its name.
+    fn error(&self, e: io::Error) -> io::Error {
+        io::Error::new(e.kind(), format!("resource {}: {}", self.name(), e))
+    }
+
+    /// Check the declared size and hash, then the content against the
+    /// built-in format and/or the table schema. Errors concerning the whole
+    /// file are reported at line 0.
+    fn validate(
+        &self,
+        base: &Path,
+        models: &Models,
+        options: &ReadOptions,
+    ) -> io::Result<Vec<LineError>> {
+        let content = std::fs::read(base.join(&self.path))?;
+        let file_error =
+            |code, detail: String| LineError::error(0, ValidationIssue::new(code).detail(detail));
+        // the issues found besides the rows of the built-in format, which are
+        // streamed as they are read
+        let mut error_vec = Vec::new();
+        if let Some(bytes) = self.bytes.filter(|&b| b != content.len() as u64) {
+            error_vec.push(file_error(
+                IssueCode::SizeMismatch,
+                format!("declared {} bytes, found {}", bytes, content.len()),
+            ));
+        }
+        if let Some(Err(msg)) = self.hash.as_ref().map(|h| check_hash(&content, h)) {
+            error_vec.push(file_error(IssueCode::HashMismatch, msg));
+        }
+        // the declared size and hash are those of the file as stored
+        let content = if self.omics_format.is_some() || self.schema.is_some() {
+            let mut decompressed = Vec::new();
+            Compression::detect(&self.path)
+                .decoder(content.as_slice())?
+                .read_to_end(&mut decompressed)?;
+            decompressed
+        } else {
+            content
+        };
+        let (content, has_bom) = bom::strip(&content);
+        if has_bom {
+            error_vec.push(bom::warning());
+        }
+        let mut rows = Vec::new();
+        if let Some(format) = &self.omics_format {
+            let format: InputFormat = format.parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("resource {}: unknown format {}", self.name(), format),
+                )
+            })?;
+            rows = validate_input(content, &format, models, options)?;
+            error_vec.extend(scale::check(content, &format, &options.scales));
+            if self.schema.is_none() && format.has_headers() {
+                error_vec.extend(check_dataset(content, format.delimiter(), options));
+            }
+        }
+        if let Some(schema) = &self.schema {
+            let delimiter = self.delimiter();
+            error_vec.extend(schema.validate(content, delimiter, options)?);
+            error_vec.extend(check_dataset(content, delimiter, options));
+        }
+        options.stream.write_all(&error_vec);
+        error_vec.extend(rows);
+        error_vec.sort_by_key(|e| e.line);
+        Ok(error_vec)
+    }
+}
+
+/// Validate every resource of the package described in `descriptor`, whose
+/// paths are relative to the descriptor. Returns the errors by resource name.
+pub fn validate(
+    descriptor: &Path,
+    models: &Models,
+    options: &ReadOptions,
+) -> io::Result<Vec<(String, Vec<LineError>)>> {
+    let package: DataPackage = serde_json::from_reader(std::fs::File::open(descriptor)?)?;
+    let base = descriptor.parent().unwrap_or_else(|| Path::new("."));
+    let mut results = package
+        .resources
+        .iter()
+        .map(|resource| {
+            info!(resource = %resource.name(), "validating resource");
+            let options = options.part(format!("resource {}", resource.name()));
+            Ok((resource.name(), resource.validate(base, models, &options)?))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    // the foreign keys are checked once every resource is valid on its own
+    for (resource, (name, error_vec)) in package.resources.iter().zip(&mut results) {
+        let keys = resource.schema.iter().flat_map(|s| &s.foreign_keys);
+        for key in keys {
+            let found = check_foreign_key(&package, base, resource, key)?;
+            let options = options.part(format!("resource {}", name));
+            options.stream.write_all(&found);
+            error_vec.extend(found);
+            error_vec.sort_by_key(|e| e.line);
+        }
+    }
+    Ok(results)
+}
+
+/// Rows of `resource` whose foreign key is not a key of the rows of the
+/// resource it references.
+fn check_foreign_key(
+    package: &DataPackage,
+    base: &Path,
+    resource: &Resource,
+    key: &ForeignKey,
+) -> io::Result<Vec<LineError>> {
+    let referenced = match key.reference.resource.as_str() {
+        "" => resource,
+        name => package
+            .resources
+            .iter()
+            .find(|r| r.name.as_deref() == Some(name))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "resource {}: foreign key to the unknown resource {}",
+                        resource.name(),
+                        name
+                    ),
+                )
+            })?,
+    };
+    let columns = key.fields.names();
+    let reference_columns = key.reference.fields.names();
+    let rows = schema::key_values(&resource.table(base)?, resource.delimiter(), &columns)
+        .map_err(|e| resource.error(e))?;
+    let keys: HashSet<Vec<String>> = schema::key_values(
+        &referenced.table(base)?,
+        referenced.delimiter(),
+        &reference_columns,
+    )
+    .map_err(|e| referenced.error(e))?
+    .into_iter()
+    .map(|(_, key)| key)
+    .collect();
+    Ok(schema::check_foreign_key(
+        &rows,
+        &columns,
+        &keys,
+        &format!("{}:{}", referenced.name(), reference_columns.join(",")),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_valid_datapackage_has_no_errors() {
+        let results = validate(
+            Path::new("tests/datapackage.json"),
+            &Models::load(&[], &[]).unwrap(),
+            &ReadOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|(_, error_vec)| error_vec.is_empty()));
+    }
+
+    #[test]
+    fn test_hash_mismatch_is_detected() {
+        assert!(check_hash(b"a", "0cc175b9c0f1b6a831c399e269772661").is_ok());
+        assert!(check_hash(b"b", "md5:0cc175b9c0f1b6a831c399e269772661").is_err());
+        assert!(check_hash(b"a", "sha1:86f7e437faa5a7fce15d1ddcb9eaeaea377667b8").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use crate::validators::{LineError, ValidationIssue};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tracing::debug;
+
+/// Checks run at the same time by default, see `--remote-jobs`.
+pub const DEFAULT_JOBS: usize = 8;
+
+/// A check of a record waiting on the network (a remote file, an online
+/// database), run once the rows of the file are read so that the checks of
+/// the whole file run concurrently instead of blocking each row in turn.
+pub trait RemoteCheck: Send + Sync {
+    /// Identity of the check: a check requested by several rows runs once.
+    fn key(&self) -> String;
+    /// Issue of the rows requesting the check, `None` if it passes.
+    fn run(&self) -> Option<ValidationIssue>;
+}
+
+/// Remote checks requested by the rows of a file, with their lines.
+#[derive(Default)]
+pub struct RemoteChecks {
+    checks: Vec<(usize, Box<dyn RemoteCheck>)>,
+}
+
+impl RemoteChecks {
+    pub fn push(&mut self, line: usize, checks: Vec<Box<dyn RemoteCheck>>) {
+        self.checks
+            .extend(checks.into_iter().map(|check| (line, check)));
+    }
+
+    /// Run the checks on `jobs` threads, each distinct check once, and report
+    /// the failed ones as errors of the lines requesting them.
+    pub fn run(self, jobs: usize) -> Vec<LineError> {
+        let mut keys: HashMap<String, usize> = HashMap::new();
+        let mut unique: Vec<&dyn RemoteCheck> = Vec::new();
+        let mut lines = Vec::with_capacity(self.checks.len());
+        for (line, check) in &self.checks {
+            let n = unique.len();
+            let index = *keys.entry(check.key()).or_insert(n);
+            if index == n {
+                unique.push(check.as_ref());
+            }
+            lines.push((*line, index));
+        }
+        if unique.is_empty() {
+            return Vec::new();
+        }
+        let jobs = jobs.clamp(1, unique.len());
+        debug!(checks = unique.len(), jobs, "running remote checks");
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<ValidationIssue>>> =
+            Mutex::new(unique.iter().map(|_| None).collect());
+        std::thread::scope(|scope| {
+            for _ in 0..jobs {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let check = match unique.get(i) {
+                        Some(check) => check,
+                        None => break,
+                    };
+                    let issue = check.run();
+                    results.lock().unwrap()[i] = issue;
+                });
+            }
+        });
+        let results = results.into_inner().unwrap();
+        let mut error_vec: Vec<LineError> = lines
+            .into_iter()
+            .filter_map(|(line, index)| Some(LineError::error(line, results[index].clone()?)))
+            .collect();
+        error_vec.sort_by_key(|e| e.line);
+        error_vec
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::validators::IssueCode;
+
+    static RUNS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Unreachable(&'static str);
+
+    impl RemoteCheck for Unreachable {
+        fn key(&self) -> String {
+            self.0.to_string()
+        }
+        fn run(&self) -> Option<ValidationIssue> {
+            RUNS.fetch_add(1, Ordering::SeqCst);
+            (self.0 != "ok")
+                .then(|| ValidationIssue::new(IssueCode::UnreachableFastq).value(self.0))
+        }
+    }
+
+    #[test]
+    fn test_checks_run_once_per_key() {
+        let mut checks = RemoteChecks::default();
+        checks.push(3, vec![Box::new(Unreachable("s3://a/b.fastq"))]);
+        checks.push(
+            2,
+            vec![
+                Box::new(Unreachable("ok")),
+                Box::new(Unreachable("s3://a/b.fastq")),
+            ],
+        );
+        let error_vec = checks.run(2);
+        assert_eq!(RUNS.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            error_vec.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use crate::bom;
+use crate::compression::Compression;
+use crate::runner::InputFormat;
+use argh::FromArgs;
+use csv::ReaderBuilder;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, FromArgs)]
+/// Compare two versions of an omics file record by record.
+#[argh(subcommand, name = "diff")]
+pub struct DiffArgs {
+    /// previous version of the file
+    #[argh(positional)]
+    old: PathBuf,
+
+    /// new version of the file
+    #[argh(positional)]
+    new: PathBuf,
+
+    /// format of both files. Currently supported: {{prot, tidy_prot, met, flux, ec_usage,
+    /// thermo_conc, thermo_dg, medium, strain_design, rna, counts, feature_table, multi}}
+    #[argh(option, short = 'f', default = "InputFormat::TidyProt")]
+    format: InputFormat,
+}
+
+/// Fields of a record, other than its key, by name.
+type Fields = BTreeMap<String, String>;
+
+/// Columns identifying a record of a format with headers. The counts format
+/// is read as one record per gene and sample.
+pub(crate) fn key_columns(format: &InputFormat) -> io::Result<&'static [&'static str]> {
+    Ok(match format {
+        InputFormat::TidyProt => &["uniprot", "sample"],
+        InputFormat::Met => &["met_id", "sample"],
+        InputFormat::Flux => &["reaction_id", "sample"],
+        InputFormat::EcUsage => &["uniprot", "reaction_id", "sample"],
+        InputFormat::ThermoConc => &["met_id", "compartment"],
+        InputFormat::ThermoDg => &["reaction_id"],
+        InputFormat::Medium => &["exchange_reaction_or_met_id"],
+        InputFormat::StrainDesign => &["strain", "gene_or_reaction_id"],
+        InputFormat::Rna => &["Experiment"],
+        InputFormat::FeatureTable => &["feature_id", "sample"],
+        InputFormat::Multi => &["omics_type", "id", "sample"],
+        InputFormat::Prot | InputFormat::Counts => &[],
+        InputFormat::Datapackage => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "diff compares omics files, not data packages",
+            ))
+        }
+    })
+}
+
+/// Records of an omics file by key. Only the first record of a duplicated
+/// key is kept, the duplicates are reported by `validate`.
+fn records<R: Read>(input: R, format: &InputFormat) -> io::Result<BTreeMap<Vec<String>, Fields>> {
+    let keys = key_columns(format)?;
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(format.delimiter())
+        .has_headers(format.has_headers())
+        .flexible(true)
+        .from_reader(input);
+    let headers = if format.has_headers() {
+        rdr.headers()?.clone()
+    } else {
+        Default::default()
+    };
+    let positions = keys
+        .iter()
+        .map(|key| {
+            headers.iter().position(|h| h == *key).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("missing key column {}", key),
+                )
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    let mut records = BTreeMap::new();
+    for record in rdr.records() {
+        let record = record?;
+        match format {
+            // uniprot, then the values
+            InputFormat::Prot => {
+                let fields = record
+                    .iter()
+                    .enumerate()
+                    .skip(1)
+                    .map(|(i, value)| (format!("value {}", i), value.to_string()))
+                    .collect();
+                records
+                    .entry(vec![record.get(0).unwrap_or_default().to_string()])
+                    .or_insert(fields);
+            }
+            InputFormat::Counts => {
+                let gene = record.get(0).unwrap_or_default();
+                for (sample, value) in headers.iter().zip(record.iter()).skip(1) {
+                    records
+                        .entry(vec![gene.to_string(), sample.to_string()])
+                        .or_insert_with(|| {
+                            Fields::from([("count".to_string(), value.to_string())])
+                        });
+                }
+            }
+            _ => {
+                let key = positions
+                    .iter()
+                    .map(|&i| record.get(i).unwrap_or_default().to_string())
+                    .collect();
+                let fields = headers
+                    .iter()
+                    .zip(record.iter())
+                    .enumerate()
+                    .filter(|(i, _)| !positions.contains(i))
+                    .map(|(_, (name, value))| (name.to_string(), value.to_string()))
+                    .collect();
+                records.entry(key).or_insert(fields);
+            }
+        }
+    }
+    Ok(records)
+}
+
+/// Values are compared as numbers when both are, so that `1.0` equals `1`.
+fn same_value(old: &str, new: &str) -> bool {
+    match (old.trim().parse::<f64>(), new.trim().parse::<f64>()) {
+        (Ok(old), Ok(new)) => old == new,
+        _ => old == new,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Change {
+    Added(Vec<String>),
+    Removed(Vec<String>),
+    /// key, field, old and new value
+    Changed(Vec<String>, String, String, String),
+}
+
+fn diff(old: &BTreeMap<Vec<String>, Fields>, new: &BTreeMap<Vec<String>, Fields>) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for (key, old_fields) in old {
+        let new_fields = match new.get(key) {
+            Some(fields) => fields,
+            None => {
+                changes.push(Change::Removed(key.clone()));
+                continue;
+            }
+        };
+        // a column added or removed is a change from or to an empty value
+        let fields = old_fields
+            .keys()
+            .chain(new_fields.keys().filter(|f| !old_fields.contains_key(*f)));
+        for field in fields {
+            let old_value = old_fields.get(field).map_or("", String::as_str);
+            let new_value = new_fields.get(field).map_or("", String::as_str);
+            if !same_value(old_value, new_value) {
+                changes.push(Change::Changed(
+                    key.clone(),
+                    field.clone(),
+                    old_value.to_string(),
+                    new_value.to_string(),
+                ));
+            }
+        }
+    }
+    changes.extend(
+        new.keys()
+            .filter(|key| !old.contains_key(*key))
+            .map(|key| Change::Added(key.clone())),
+    );
+    changes
+}
+
+fn read(path: &Path, format: &InputFormat) -> io::Result<BTreeMap<Vec<String>, Fields>> {
+    let input = Compression::detect(path).decoder(File::open(path)?)?;
+    let (input, _) = bom::skip(input)?;
+    records(input, format)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+/// Print the added (`+`), removed (`-`) and changed (`~`) records of the new
+/// version, then the number of each.
+pub fn run(args: DiffArgs) -> io::Result<()> {
+    let old = read(&args.old, &args.format)?;
+    let new = read(&args.new, &args.format)?;
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+    for change in diff(&old, &new) {
+        match change {
+            Change::Added(key) => {
+                added += 1;
+                println!("+ {}", key.join(", "));
+            }
+            Change::Removed(key) => {
+                removed += 1;
+                println!("- {}", key.join(", "));
+            }
+            Change::Changed(key, field, old, new) => {
+                changed += 1;
+                println!("~ {}: {} {} -> {}", key.join(", "), field, old, new);
+            }
+        }
+    }
+    println!(
+        "{} added, {} removed, {} changed values",
+        added, removed, changed
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_records_are_diffed_by_key() {
+        let old = "uniprot,sample,value\nQ00496,SIM1,1.0\nQ00496,SIM2,2\nE0X97,SIM1,3\n";
+        let new = "uniprot,sample,value\nQ00496,SIM1,1\nQ00496,SIM2,2.5\nE0X97,SIM2,3\n";
+        let format = InputFormat::TidyProt;
+        let changes = diff(
+            &records(old.as_bytes(), &format).unwrap(),
+            &records(new.as_bytes(), &format).unwrap(),
+        );
+        let key = |k: &[&str]| k.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                Change::Removed(key(&["E0X97", "SIM1"])),
+                Change::Changed(
+                    key(&["Q00496", "SIM2"]),
+                    "value".to_string(),
+                    "2".to_string(),
+                    "2.5".to_string()
+                ),
+                Change::Added(key(&["E0X97", "SIM2"])),
+            ]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::runner::InputFormat;
+use argh::FromArgs;
+use std::io::{self, Write};
+
+#[derive(Debug, FromArgs)]
+/// Print a small valid file of a format, to start from a correct template.
+#[argh(subcommand, name = "example")]
+pub struct ExampleArgs {
+    /// format of the example. Currently supported: {{prot, tidy_prot, met, flux, ec_usage,
+    /// thermo_conc, thermo_dg, medium, strain_design, rna, counts, feature_table, multi}}
+    #[argh(option, short = 'f', default = "InputFormat::TidyProt")]
+    format: InputFormat,
+}
+
+/// Example of a format. The metabolites are BiGG identifiers, valid against
+/// the models of most organisms.
+fn example(format: &InputFormat) -> io::Result<&'static str> {
+    Ok(match format {
+        InputFormat::Prot => "Q00496,100001,21283\nQ7B2Q4,123.3444,0\nE0X9C7,10.2,21283\n",
+        InputFormat::TidyProt => {
+            "uniprot,sample,value\nQ00496,glc_1,100001\nQ7B2Q4,glc_1,100.2\nQ00496,glc_2,98500\nQ7B2Q4,glc_2,97.5\n"
+        }
+        InputFormat::Met => {
+            "met_id,sample,value\nglc__D,glc_1,2\nacon_C,glc_1,18\nglc__D,glc_2,2.3\nacon_C,glc_2,17\n"
+        }
+        InputFormat::Flux => {
+            "reaction_id,sample,value,lb,ub,sd\nPGI,glc_1,4.86,4.2,5.5,0.3\nPFK,glc_1,7.48,7.1,7.9,0.2\n"
+        }
+        InputFormat::EcUsage => {
+            "uniprot,reaction_id,sample,usage\nP0A6T1,PGI,glc_1,0.0021\nP0A796,PFKNo1,glc_1,0.0013\nP0A6T1,PGI,glc_2,0.0019\nP0A796,PFKNo1,glc_2,0.0012\n"
+        }
+        InputFormat::ThermoConc => {
+            "met_id,compartment,conc_lb,conc_ub\nglc__D,c,1e-06,0.02\nglc__D,e,1e-06,0.1\nipdp,c,1e-06,0.001\n"
+        }
+        InputFormat::ThermoDg => {
+            "reaction_id,dg0,dg0_uncertainty\nPGI,2.6,1.0\nPFK,-15.6,1.5\nPYK,-24.5,1.2\n"
+        }
+        InputFormat::Medium => {
+            "exchange_reaction_or_met_id,concentration,unit\nEX_glc__D_e,10,mM\nEX_o2_e,20,mmol/gDW/h\nnh4_e,18.7,mM\npi_e,5,mM\n"
+        }
+        InputFormat::StrainDesign => {
+            "strain,gene_or_reaction_id,modification\nCLAU_001,CAETHG_RS09090,KO\nCLAU_001,rxn00225_c0,down\nCLAU_002,CAETHG_RS09090,OE\n"
+        }
+        InputFormat::Rna => {
+            "Experiment\tRun\tLibraryLayout\tPlatform\tR1\tR2\nSRX2389071\tSRR5069221\tPAIRED\tILLUMINA\t\t\nSRX2389072\tSRR5069222\tPAIRED\tILLUMINA\t\t\n"
+        }
+        InputFormat::Counts => "gene_id\tglc_1\tglc_2\nb0001\t10\t3\nb0002\t125\t98\n",
+        InputFormat::FeatureTable => {
+            "feature_id,mz,rt,sample,intensity,adduct,formula\nF1,181.0707,3.21,glc_1,10500,[M+H]+,C6H12O6\nF1,181.0707,3.21,glc_2,9800,[M+H]+,C6H12O6\nF2,203.0526,5.84,glc_1,1200,[M+Na]+,C6H12O6\n"
+        }
+        InputFormat::Multi => {
+            "omics_type,id,sample,value\nprot,Q00496,glc_1,100001\nmet,glc__D,glc_1,2\ngene,b0001,glc_1,10\nflux,PGI,glc_1,4.86\n"
+        }
+        InputFormat::Datapackage => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a data package is a directory of resources, see the datapackage.json in tests/",
+            ))
+        }
+    })
+}
+
+pub fn run(args: ExampleArgs) -> io::Result<()> {
+    io::stdout().write_all(example(&args.format)?.as_bytes())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::models::Models;
+    use crate::runner::validate_input;
+    use crate::validators::ReadOptions;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_examples_are_valid() {
+        let models = Models::load(&[PathBuf::from("tests/iCLAU786.xml")], &[]).unwrap();
+        // the proteins are not in the gene products of iCLAU786
+        let no_models = Models::load(&[], &[]).unwrap();
+        let ec_model = Models::load(&[PathBuf::from("tests/ec_model.xml")], &[]).unwrap();
+        // iCLAU786 has ModelSEED reaction ids
+        let e_coli = Models::load(&[PathBuf::from("tests/e_coli_core_groups.xml")], &[]).unwrap();
+        for format in [
+            InputFormat::Prot,
+            InputFormat::TidyProt,
+            InputFormat::Met,
+            InputFormat::Flux,
+            InputFormat::EcUsage,
+            InputFormat::ThermoConc,
+            InputFormat::ThermoDg,
+            InputFormat::Medium,
+            InputFormat::StrainDesign,
+            InputFormat::Rna,
+            InputFormat::Counts,
+            InputFormat::FeatureTable,
+            InputFormat::Multi,
+        ] {
+            let content = example(&format).unwrap();
+            let models = match format {
+                InputFormat::Prot | InputFormat::TidyProt => &no_models,
+                InputFormat::EcUsage => &ec_model,
+                InputFormat::ThermoDg | InputFormat::Medium => &e_coli,
+                _ => &models,
+            };
+            let error_vec =
+                validate_input(content.as_bytes(), &format, models, &ReadOptions::default())
+                    .unwrap();
+            assert!(error_vec.is_empty(), "{}: {:?}", format, error_vec);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::validators::{IssueCode, ValidationIssue};
+use csv::StringRecord;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Columns holding identifiers, checked in any format that has one; the
+/// first column of the formats without header (the accessions of prot).
+const COLUMNS: [&str; 9] = [
+    "uniprot",
+    "met_id",
+    "reaction_id",
+    "gene_id",
+    "locus_tag",
+    "feature_id",
+    "id",
+    "exchange_reaction_or_met_id",
+    "gene_or_reaction_id",
+];
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// `2-Sep` or `2-Sep-19`, as Excel displays a gene name such as SEPT2.
+static RE_DAY_MONTH: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^(\d{1,2})-([a-z]{3})(-\d{2}|-\d{4})?$").unwrap());
+/// `2019-09-02`, the same date exported as ISO 8601.
+static RE_ISO_DATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\d{4}-(\d{2})-(\d{2})( 00:00(:00)?)?$").unwrap());
+/// `1.00E+05`, as Excel displays an identifier read as a large number.
+static RE_SCIENTIFIC: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\d)(?:\.(\d+))?[Ee]\+(\d{1,3})$").unwrap());
+
+/// Gene name that Excel reads as the `day` of `month`: SEPT2 for `2-Sep`,
+/// MARCH1 for `1-Mar`, DEC1 for `1-Dec`.
+fn gene_name(month: usize, day: u32) -> Option<String> {
+    if !(1..=31).contains(&day) {
+        return None;
+    }
+    let prefix = match month {
+        3 => "MARCH".to_string(),
+        9 => "SEPT".to_string(),
+        _ => MONTHS.get(month.checked_sub(1)?)?.to_uppercase(),
+    };
+    Some(format!("{}{}", prefix, day))
+}
+
+/// The digits of a number in scientific notation, those beyond the precision
+/// shown being lost, e.g. `100000` for `1.00E+05`.
+fn expand(first: &str, decimals: &str, exponent: usize) -> String {
+    let mut digits = format!("{}{}", first, decimals);
+    if exponent + 1 >= digits.len() {
+        digits.extend(std::iter::repeat_n('0', exponent + 1 - digits.len()));
+    } else {
+        digits.insert(exponent + 1, '.');
+    }
+    digits
+}
+
+/// Likely original value of an identifier mangled by Excel and what Excel
+/// read it as, `None` if it does not look mangled.
+pub fn original(value: &str) -> Option<(String, &'static str)> {
+    let value = value.trim();
+    if let Some(caps) = RE_DAY_MONTH.captures(value) {
+        let month = MONTHS
+            .iter()
+            .position(|m| m.eq_ignore_ascii_case(&caps[2]))?;
+        return Some((gene_name(month + 1, caps[1].parse().ok()?)?, "a date"));
+    }
+    if let Some(caps) = RE_ISO_DATE.captures(value) {
+        let name = gene_name(caps[1].parse().ok()?, caps[2].parse().ok()?)?;
+        return Some((name, "a date"));
+    }
+    let caps = RE_SCIENTIFIC.captures(value)?;
+    let decimals = caps.get(2).map_or("", |m| m.as_str());
+    let expanded = expand(&caps[1], decimals, caps[3].parse().ok()?);
+    Some((expanded, "a number in scientific notation"))
+}
+
+/// Position and name of the identifier column, the first column without
+/// header.
+pub fn column(headers: Option<&StringRecord>) -> Option<(usize, String)> {
+    match headers {
+        Some(headers) => headers
+            .iter()
+            .enumerate()
+            .find(|(_, h)| COLUMNS.contains(h))
+            .map(|(i, h)| (i, h.to_string())),
+        None => Some((0, COLUMNS[0].to_string())),
+    }
+}
+
+/// Issue of an identifier mangled by Excel in `field`.
+pub fn check(field: &str, value: &str) -> Option<ValidationIssue> {
+    let (likely, read_as) = original(value)?;
+    Some(
+        ValidationIssue::new(IssueCode::ExcelArtifact)
+            .field(field)
+            .value(value)
+            .detail(format!("read as {} by Excel, likely {}", read_as, likely)),
+    )
+}
+
+/// Issue of the identifier of a record, whose column is given by [`column`].
+pub fn issue(record: &StringRecord, column: Option<&(usize, String)>) -> Option<ValidationIssue> {
+    let (index, name) = column?;
+    check(name, record.get(*index)?)
+}
+
+/// Replace the issues of an identifier mangled by Excel, e.g. an invalid
+/// UniProt ID, with the issue explaining it.
+pub fn replace(issues: &mut Vec<ValidationIssue>, artifact: Option<ValidationIssue>) {
+    if let Some(artifact) = artifact {
+        issues.retain(|issue| issue.field != artifact.field && issue.value != artifact.value);
+        issues.push(artifact);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_excel_artifacts_are_explained() {
+        let likely = |value| original(value).map(|(likely, _)| likely);
+        assert_eq!(likely("2-Sep").as_deref(), Some("SEPT2"));
+        assert_eq!(likely("1-Mar-19").as_deref(), Some("MARCH1"));
+        assert_eq!(likely("01-dec").as_deref(), Some("DEC1"));
+        assert_eq!(likely("2019-09-02").as_deref(), Some("SEPT2"));
+        assert_eq!(likely("1.00E+05").as_deref(), Some("100000"));
+        assert_eq!(likely("2.31E+19").as_deref(), Some("23100000000000000000"));
+        assert_eq!(likely("1.2345E+02").as_deref(), Some("123.45"));
+        for value in ["P0A6F5", "glc__D", "b0001", "40-Sep", "2-Sun", "1.5E-3"] {
+            assert_eq!(original(value), None, "{}", value);
+        }
+        let record = StringRecord::from(vec!["2-Sep", "S1", "4"]);
+        let headers = StringRecord::from(vec!["gene_id", "sample", "value"]);
+        let issue = issue(&record, column(Some(&headers)).as_ref()).unwrap();
+        assert_eq!(issue.field.as_deref(), Some("gene_id"));
+        assert_eq!(
+            issue.detail.as_deref(),
+            Some("read as a date by Excel, likely SEPT2")
+        );
+        let mut issues = vec![ValidationIssue::new(IssueCode::InvalidUniprotId)
+            .field("uniprot")
+            .value("1.00E+05")];
+        replace(&mut issues, check("uniprot", "1.00E+05"));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].code, IssueCode::ExcelArtifact);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::validators::{IssueCode, LineError, ReadOptions, Severity, ValidationIssue};
+use csv::ReaderBuilder;
+use std::collections::HashMap;
+
+/// Columns that must have the same value in every row (lane) of an experiment.
+const SHARED_COLUMNS: [&str; 2] = ["LibraryLayout", "Platform"];
+
+/// Report the rows of an RNA sheet whose LibraryLayout or Platform differs
+/// from the first row of their experiment.
+pub fn check(content: &[u8], options: &ReadOptions) -> Vec<LineError> {
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(b'\t')
+        .flexible(true)
+        .from_reader(content);
+    options.rename_headers(&mut rdr);
+    let headers = match rdr.headers() {
+        Ok(headers) => headers.clone(),
+        Err(_) => return Vec::new(),
+    };
+    let position = |name: &str| headers.iter().position(|h| h == name);
+    let experiment = match position("Experiment") {
+        Some(experiment) => experiment,
+        None => return Vec::new(),
+    };
+    let columns: Vec<(&str, usize)> = SHARED_COLUMNS
+        .iter()
+        .filter_map(|&name| Some((name, position(name)?)))
+        .collect();
+    // line and shared values of the first row of each experiment
+    let mut first: HashMap<String, (usize, Vec<String>)> = HashMap::new();
+    let mut error_vec = Vec::new();
+    // unreadable records and empty experiments are reported by the validation of the rows
+    for (i, record) in options.rows.select(rdr.records().enumerate()) {
+        let record = match record {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        let line = i + 2;
+        let name = record.get(experiment).unwrap_or_default();
+        if name.is_empty() {
+            continue;
+        }
+        let values: Vec<String> = columns
+            .iter()
+            .map(|&(_, c)| record.get(c).unwrap_or_default().to_string())
+            .collect();
+        let (first_line, first_values) = first
+            .entry(name.to_string())
+            .or_insert_with(|| (line, values.clone()));
+        let issues: Vec<ValidationIssue> = columns
+            .iter()
+            .zip(values.iter().zip(first_values.iter()))
+            .filter(|(_, (value, expected))| value != expected)
+            .map(|(&(column, _), (value, expected))| {
+                ValidationIssue::new(IssueCode::InconsistentExperiment)
+                    .field(column)
+                    .value(name)
+                    .detail(format!(
+                        "{} {} here, {} on line {}",
+                        column, value, expected, first_line
+                    ))
+            })
+            .collect();
+        if !issues.is_empty() {
+            error_vec.push(LineError {
+                line,
+                issues,
+                severity: Severity::Error,
+            });
+        }
+    }
+    error_vec
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lanes_of_an_experiment_agree() {
+        let input = "Experiment\tRun\tLibraryLayout\tPlatform\tR1\tR2\nSRX1\tSRR1\tPAIRED\tILLUMINA\t\t\nSRX2\tSRR2\tSINGLE\tILLUMINA\t\t\nSRX1\tSRR3\tSINGLE\tBGISEQ\t\t\nSRX1\tSRR4\tPAIRED\tILLUMINA\t\t\n";
+        let error_vec = check(input.as_bytes(), &ReadOptions::default());
+        assert_eq!(error_vec.len(), 1);
+        assert_eq!(error_vec[0].line, 4);
+        assert_eq!(
+            error_vec[0].issues[1].detail.as_deref(),
+            Some("Platform BGISEQ here, ILLUMINA on line 2")
+        );
+    }
+}
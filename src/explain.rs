@@ -0,0 +1,383 @@
+use crate::validators::IssueCode;
+
+/// Entry of the registry of issue codes shown by `explain`, in the manner of
+/// `rustc --explain`.
+#[derive(Debug, Clone, Copy)]
+pub struct Explanation {
+    /// what usually produces the issue
+    pub causes: &'static str,
+    /// how to make the issue go away
+    pub fix: &'static str,
+}
+
+const fn entry(causes: &'static str, fix: &'static str) -> Explanation {
+    Explanation { causes, fix }
+}
+
+/// Causes and fix of every issue code. The match is exhaustive so that a new
+/// code cannot be added without its explanation.
+pub fn explanation(code: IssueCode) -> Explanation {
+    match code {
+        IssueCode::Parse => entry(
+            "A record could not be read: a row with more or fewer fields than the header, a \
+             number that does not parse (e.g. `1,5` with a decimal comma) or invalid UTF-8.",
+            "Open the file at the reported line; check the delimiter of the format (tabs for rna \
+             and counts) and export numbers with a decimal point.",
+        ),
+        IssueCode::UnknownColumn => entry(
+            "The header has a column that is not part of the format, often a notes or index \
+             column added by a spreadsheet, or a misspelled column name.",
+            "Remove or rename the column, map synonyms with --rename FROM=TO, or pass \
+             --allow-extra-columns to ignore extra columns.",
+        ),
+        IssueCode::InvalidValue => entry(
+            "A value is outside what its column accepts: a negative intensity or retention time, \
+             an m/z outside --mz-range, a negative standard deviation.",
+            "Correct the value at the reported line; the detail names the rule it breaks.",
+        ),
+        IssueCode::EmptyField => entry(
+            "A required field (an identifier) is empty, e.g. a row left over after deleting data \
+             in a spreadsheet.",
+            "Fill in the field or remove the row.",
+        ),
+        IssueCode::EmptySample => entry(
+            "The sample name is empty, so the value cannot be attributed to any sample.",
+            "Fill in the sample name or remove the row.",
+        ),
+        IssueCode::FractionalCount => entry(
+            "A counts matrix has fractional values, which usually means that normalized values \
+             (TPM, FPKM) were exported instead of raw counts, or that counts were written as \
+             `12.0`.",
+            "Export the raw counts; accept values close to integers with --count-tolerance, or \
+             validate normalized matrices with --normalized tpm|percent.",
+        ),
+        IssueCode::NegativeCount => entry(
+            "A count is negative, e.g. after a batch correction or a log transformation.",
+            "Export the raw counts before any correction.",
+        ),
+        IssueCode::ColumnSumMismatch => entry(
+            "With --normalized, a sample does not add up to 1e6 (TPM) or 100 (percent), often a \
+             column of raw counts among normalized ones or a filtered matrix.",
+            "Renormalize the sample, or widen --sum-tolerance if rounding is to blame.",
+        ),
+        IssueCode::OutOfBounds => entry(
+            "A flux lies outside its lower and upper bounds, or the lower bound is greater than \
+             the upper bound, e.g. swapped lb and ub columns or conc_lb and conc_ub columns of \
+             concentration bounds.",
+            "Check the bounds exported with the fluxes or concentrations.",
+        ),
+        IssueCode::InvalidUniprotId => entry(
+            "The identifier is not a well-formed UniProt accession: a gene name, a truncated \
+             accession, an isoform rejected by --profile strict or a FASTA header.",
+            "Map the identifiers to UniProt accessions (e.g. P12345); use --allow-groups for \
+             protein groups and --profile lenient to accept isoforms.",
+        ),
+        IssueCode::NotInModel => entry(
+            "The metabolite is in none of the models (-m) or universal namespaces (--universal), \
+             e.g. a typo, another namespace (KEGG instead of BiGG) or a missing compartment. \
+             Likewise for the protein of an ec_usage file without a prot_ pseudo-metabolite in \
+             the ecModel, the reaction of a thermo_dg file, and the id of a strain_design file, \
+             neither a gene product nor a reaction of the models.",
+            "Translate the identifiers to the namespace of the model, or validate against the \
+             universal namespace with --universal.",
+        ),
+        IssueCode::UnknownUniprotId => entry(
+            "The accession is well-formed but not in the database fetched with `fetch-db \
+             uniprot-swissprot`: an unreviewed (TrEMBL) entry, or a merged or deleted one.",
+            "Check the accession on uniprot.org and replace it with the current entry.",
+        ),
+        IssueCode::NotInAnnotation => entry(
+            "The gene is not in the genome annotation given with --annotation, e.g. another \
+             assembly, or locus tags instead of gene IDs.",
+            "Use the annotation the counts were produced with.",
+        ),
+        IssueCode::DecoyEntry => entry(
+            "The protein is a contaminant (`CON__`), a decoy (`REV__`) or a FASTA header (`sp|`, \
+             `tr|`) left in the search engine output.",
+            "Remove the rows, e.g. with --decoys strip --fix, or report them as warnings with \
+             --decoys warn.",
+        ),
+        IssueCode::ByteOrderMark => entry(
+            "The file starts with a UTF-8 byte order mark, added by Excel (\"CSV UTF-8\") and \
+             some Windows editors. It is skipped, but other tools may read it as part of the \
+             first column name.",
+            "Save the file as UTF-8 without BOM, or remove the mark with \
+             `sed -i '1s/^\\xEF\\xBB\\xBF//'`.",
+        ),
+        IssueCode::NumericOverflow => entry(
+            "A value is too large or too small (but not zero) to be represented: beyond 1.8e308 \
+             or below 4.9e-324 as a 64-bit float, beyond 7.9e28 or below 1e-28 with \
+             --exact-decimal. It is usually a unit error or a corrupted export.",
+            "Check the value and its unit; rescale the column if the values are legitimately \
+             that large.",
+        ),
+        IssueCode::PrecisionLoss => entry(
+            "A value has more significant digits than the representation keeps: about 16 for \
+             64-bit floats, 28 with --exact-decimal, e.g. an identifier or a checksum in a \
+             numeric column.",
+            "Round the value, or use --exact-decimal if the digits matter.",
+        ),
+        IssueCode::MissingFastq => entry(
+            "R1 or R2 points to a file that does not exist: a relative path resolved from another \
+             directory, or files not uploaded yet.",
+            "Fix the path, or ignore the check until the upload with --ignore OV020.",
+        ),
+        IssueCode::MalformedFastq => entry(
+            "The FASTQ file is truncated (an interrupted transfer) or is not in FASTQ format.",
+            "Transfer the file again and compare its checksum.",
+        ),
+        IssueCode::InvalidFastqUrl => entry(
+            "R1 or R2 is an http, https or s3 URL that cannot be parsed.",
+            "Correct the URL, e.g. `s3://bucket/path/to/R1.fastq.gz`.",
+        ),
+        IssueCode::UnreachableFastq => entry(
+            "With --check-remote, the remote FASTQ file could not be reached: a wrong URL, \
+             missing credentials or a server error.",
+            "Check the URL and the credentials; retry with a longer --timeout or more --retries.",
+        ),
+        IssueCode::EmptyFastq => entry(
+            "The remote FASTQ file exists but is empty, usually a failed upload.",
+            "Upload the file again.",
+        ),
+        IssueCode::InterleavedFastq => entry(
+            "Mates 1 and 2 alternate in a single file, but the workflow expects split files.",
+            "Split the file into R1 and R2, e.g. with `seqfu deinterleave` or `reformat.sh`.",
+        ),
+        IssueCode::DuplicateReadIds => entry(
+            "With --fastq-duplicate-ids, read IDs appear more than once, e.g. lanes concatenated \
+             twice.",
+            "Concatenate the lanes again, once each.",
+        ),
+        IssueCode::LayoutMismatch => entry(
+            "For local data (empty Run), R1 and R2 do not match the LibraryLayout: PAIRED needs \
+             both, SINGLE only R1.",
+            "Fill in R2 or correct the LibraryLayout.",
+        ),
+        IssueCode::PlatformMismatch => entry(
+            "The reads do not look like those of the declared Platform, e.g. PACBIO_SMRT for \
+             150bp reads.",
+            "Correct the Platform, or promote the warning with --promote platform-mismatch=error.",
+        ),
+        IssueCode::InconsistentExperiment => entry(
+            "The rows (runs or lanes) of an experiment do not share the same LibraryLayout or \
+             Platform, e.g. runs of two experiments under one SRX, or a row copied from another \
+             experiment.",
+            "Correct the Experiment of the row, or its LibraryLayout and Platform so that they \
+             match the first row of the experiment.",
+        ),
+        IssueCode::InconsistentFeature => entry(
+            "A feature has another m/z or retention time than on its first row, usually two \
+             features merged under the same ID.",
+            "Give each feature a single m/z and retention time, or split it.",
+        ),
+        IssueCode::InvalidAdduct => entry(
+            "The adduct is not in the `[M+H]+` notation, or its charge does not match its ions.",
+            "Write the adduct as `[nM+X-Y]z`, e.g. `[M+Na]+` or `[M-2H]2-`.",
+        ),
+        IssueCode::MassMismatch => entry(
+            "The neutral mass, or the m/z given the adduct, deviates from the monoisotopic mass \
+             of the formula by more than --mass-tolerance ppm: a wrong annotation or adduct.",
+            "Check the formula and the adduct, or widen --mass-tolerance for low-resolution \
+             instruments.",
+        ),
+        IssueCode::InvalidFormula => entry(
+            "The formula has an unknown element (e.g. `Cl` written `CL`), a malformed count or \
+             charge.",
+            "Write the formula in Hill notation with the elements of the periodic table.",
+        ),
+        IssueCode::MissingColumn => entry(
+            "A column declared by the Table Schema of a Data Package resource is missing from \
+             the file.",
+            "Add the column, or remove the field from the schema.",
+        ),
+        IssueCode::MissingValue => entry(
+            "A field with the `required` constraint is empty.",
+            "Fill in the value, or drop the constraint if the field is optional.",
+        ),
+        IssueCode::InvalidType => entry(
+            "A value does not have the type declared by the Table Schema, e.g. text in a number \
+             field or a date in another format.",
+            "Correct the value or the type of the field.",
+        ),
+        IssueCode::ConstraintViolated => entry(
+            "A value breaks a constraint of the Table Schema (minimum, maximum, pattern, enum...).",
+            "Correct the value, or the constraint if it is too narrow.",
+        ),
+        IssueCode::DuplicateValue => entry(
+            "A field with the `unique` constraint has the same value on several rows.",
+            "Remove the duplicated rows or make the values unique.",
+        ),
+        IssueCode::DuplicateKey => entry(
+            "The primary key (e.g. feature and sample) or a unique key of the configuration or \
+             schema appears on several rows, often data pasted twice.",
+            "Remove the duplicated rows.",
+        ),
+        IssueCode::ExcelSerialDate => entry(
+            "A date field holds a number such as 44562, the serial number Excel stores dates as.",
+            "Format the column as text (ISO 8601, e.g. 2022-01-01) before exporting.",
+        ),
+        IssueCode::AmbiguousDate => entry(
+            "A date such as 03/04/2022 can be read as March 4 or April 3.",
+            "Write dates in ISO 8601, e.g. 2022-04-03.",
+        ),
+        IssueCode::UnknownTerm => entry(
+            "A value of a column mapped to an ontology in the configuration is not a term of the \
+             OBO file loaded with --obo: a label instead of an ID, or another ontology.",
+            "Use the term ID, e.g. EFO:0000001.",
+        ),
+        IssueCode::ObsoleteTerm => entry(
+            "The ontology term has been made obsolete.",
+            "Use the replacement term given in the detail.",
+        ),
+        IssueCode::SizeMismatch => entry(
+            "A resource of a Data Package does not have the declared `bytes`: it was modified \
+             or truncated after the descriptor was written.",
+            "Transfer the file again, or update the descriptor.",
+        ),
+        IssueCode::HashMismatch => entry(
+            "A resource of a Data Package does not have the declared `hash`.",
+            "Transfer the file again, or update the descriptor.",
+        ),
+        IssueCode::InvalidWell => entry(
+            "A value of the `well` column is not a well of the plate given with --plate, e.g. \
+             I13 on a 96-well plate, or the plate is wrong.",
+            "Correct the well or pass the right --plate.",
+        ),
+        IssueCode::Plugin => entry(
+            "A plugin given with --plugin rejected the record, with its own message.",
+            "See the documentation of the plugin.",
+        ),
+        IssueCode::TooFewReplicates => entry(
+            "With --min-replicates, a condition has fewer distinct samples than required, or the \
+             conditions are not recognized in the sample names.",
+            "Add replicates, or adjust the `condition` column or --replicate-pattern.",
+        ),
+        IssueCode::MalformedSampleName => entry(
+            "A sample name does not follow the `[naming]` convention of the configuration: a \
+             missing separator or a token outside its vocabulary.",
+            "Rename the sample, or extend the vocabulary of the token.",
+        ),
+        IssueCode::SampleNotInAllLayers => entry(
+            "In `crosscheck`, a sample is missing from some of the omics layers: a failed \
+             measurement or a sample named differently.",
+            "Use the same sample names in all layers, or drop the sample before the joint \
+             analysis.",
+        ),
+        IssueCode::MissingRate => entry(
+            "A column has more missing values (empty, NA, NaN, null) than --max-missing-rate or \
+             its `[max_missing]` threshold, e.g. a quantification channel that came out empty.",
+            "Export the file again, or raise the threshold of the column.",
+        ),
+        IssueCode::Outlier => entry(
+            "With --outliers, a value is far from the other values of its identifier across the \
+             samples: a unit error, a failed sample or a real biological effect.",
+            "Check the sample; raise --outlier-threshold if the variation is expected.",
+        ),
+        IssueCode::InvalidDialect => entry(
+            "With --profile imodulon, the sheet is not in the TSV dialect read by the \
+             modulome-workflow: comma-separated, Windows line endings or quoted fields, usually \
+             after saving it from a spreadsheet.",
+            "Export the sheet as tab-separated text with Unix line endings and no quotes, e.g. \
+             with `dos2unix` and `tr -d '\"'`.",
+        ),
+        IssueCode::NonPortablePath => entry(
+            "R1 or R2 separates the directories with backslashes, as written on Windows. Other \
+             systems read them as separators too, but tools such as the modulome-workflow do not.",
+            "Replace the backslashes with /, which every system reads.",
+        ),
+        IssueCode::MixedLineEndings => entry(
+            "Some lines end with CRLF (Windows) and others with LF, usually after rows were \
+             appended to the file on another system or by another tool.",
+            "Rewrite the file with --fix, which ends every line like the first one.",
+        ),
+        IssueCode::StrayCarriageReturn => entry(
+            "A carriage return is inside a line instead of ending it, e.g. a cell pasted from a \
+             spreadsheet. It is read as the end of the row, which breaks the row and the field \
+             before it, often reported as an invalid identifier.",
+            "Rewrite the file with --fix, which removes the carriage returns inside the lines.",
+        ),
+        IssueCode::NotTidy => entry(
+            "With --check-tidy, the file is not one measurement per row: an identifier is \
+             measured twice in a sample, or columns are named with numbers or samples, left over \
+             from a wide table.",
+            "Merge or remove the repeated rows, and melt the wide columns into the sample and \
+             value columns.",
+        ),
+        IssueCode::TransposedMatrix => entry(
+            "The samples of a prot or counts matrix are in rows and the identifiers in columns: \
+             the first row is mostly UniProt accessions (or genes of the --annotation) and the \
+             first column mostly not, e.g. sample names. This happens when a matrix is exported \
+             transposed, and would otherwise fail with an invalid identifier on every row.",
+            "Transpose the matrix so that each row is an identifier and each column a sample.",
+        ),
+        IssueCode::ExcelArtifact => entry(
+            "The identifier was opened in Excel, which read it as a date (SEPT2 becoming 2-Sep or \
+             2019-09-02) or as a number written in scientific notation (100000 becoming \
+             1.00E+05), and the file was saved with the converted value.",
+            "Restore the likely original given in the report, from the source of the data if the \
+             digits of a number were lost, and import the identifier columns as text.",
+        ),
+        IssueCode::NotAllowed => entry(
+            "The value is not a line of the file given for its column with --allowed \
+             COLUMN=FILE, e.g. a misspelled sample or a strain that is not part of the study.",
+            "Correct the value, or add it to the list if it is a new valid value.",
+        ),
+        IssueCode::ForeignKeyViolation => entry(
+            "The key of the row, e.g. its sample, is not a key of the rows of the file it \
+             references with --fk or the foreignKeys of a Data Package schema: the referenced \
+             row is missing or the key is misspelled in one of the files.",
+            "Add the missing row to the referenced file, e.g. the sample to the metadata, or \
+             correct the key.",
+        ),
+        IssueCode::ScaleMismatch => entry(
+            "The values look log-transformed (none above 40, often negative ones) in a file \
+             declared linear with --scale or the `[scale]` of the configuration, or raw (large \
+             and never negative) in a file declared log, e.g. the output of the wrong processing \
+             step. Transformed twice or not at all, the values silently skew every downstream \
+             analysis.",
+            "Export the values on the declared scale, or correct the declared scale.",
+        ),
+        IssueCode::MissingBatch => entry(
+            "With --check-batches, the batch or run_date column of a sample is empty, so that \
+             the batch effects cannot be corrected for it.",
+            "Fill in the batch or run date of the sample from the lab records.",
+        ),
+        IssueCode::ConfoundedBatch => entry(
+            "With --check-batches, all the samples of a batch (or run date) are of a single \
+             condition, so that the effect of the batch cannot be told from the one of the \
+             condition.",
+            "Process the conditions together in each batch, or check the batch and condition of \
+             the samples.",
+        ),
+        IssueCode::WrongCompartment => entry(
+            "The metabolite is in the models, but none of their species has it in the \
+             compartment of the `compartment` column, e.g. an extracellular measurement of a \
+             metabolite that is only cytosolic in the model, or a compartment named otherwise.",
+            "Correct the compartment, using the compartment ids of the model (e.g. c, e), or add \
+             the species to the model.",
+        ),
+        IssueCode::EnzymeNotInReaction => entry(
+            "The protein of an ec_usage row has a prot_ pseudo-metabolite in the ecModel, but the \
+             reaction of the row does not consume it, e.g. the usage of an isozyme reported on \
+             the arm reaction or on another isozyme reaction (PFKNo1 instead of PFKNo2).",
+            "Report the usage on the reaction of the ecModel that consumes the protein, as \
+             listed in the detail, or validate against the ecModel that produced the usages.",
+        ),
+        IssueCode::Unphysiological => entry(
+            "A bound of a thermo_conc file is outside the physiological concentrations (1 nM to \
+             0.1 M), or a standard Gibbs free energy or its uncertainty in a thermo_dg file is \
+             beyond 1000 kJ/mol: concentrations in mM instead of mol/L, energies in J/mol \
+             instead of kJ/mol, or the placeholder of an unknown estimate.",
+            "Convert the values to mol/L and kJ/mol, and leave out the reactions whose energy \
+             could not be estimated.",
+        ),
+        IssueCode::NotAnExchange => entry(
+            "The component of a medium file is neither an exchange reaction of the models (a \
+             reaction with a single species) nor the species of one: a typo such as EX_02_e for \
+             EX_o2_e, which silently leaves the compound out of the simulated medium and may \
+             make it anaerobic, or an internal reaction.",
+            "Correct the identifier, using the exchange reactions of the model (e.g. EX_o2_e) or \
+             their species (e.g. o2_e).",
+        ),
+    }
+}
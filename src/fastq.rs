@@ -0,0 +1,783 @@
+use crate::compression::{self, Compression};
+use crate::remote::{NetworkOptions, RemoteFile};
+use flate2::read::MultiGzDecoder;
+use once_cell::sync::Lazy;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{debug, info};
+
+/// Reads shorter than this are flagged as suspicious.
+const MIN_READ_LENGTH: usize = 20;
+/// Offset of the Phred+33 (Sanger/Illumina 1.8+) quality encoding.
+const PHRED_OFFSET: u8 = 33;
+/// Size of the Bloom filter used for duplicated read IDs (16 MiB).
+const BLOOM_BITS: u64 = 1 << 27;
+/// Number of hash functions of the Bloom filter.
+const BLOOM_HASHES: u64 = 4;
+/// Number of leading read pairs inspected to decide if a file is interleaved.
+const INTERLEAVED_PAIRS: usize = 1000;
+/// Upper bound of the candidate duplicated IDs kept for the confirmation pass.
+const MAX_DUPLICATE_CANDIDATES: usize = 100_000;
+/// Problems found in a file before its scan is aborted, see `--fastq-max-errors`.
+pub const DEFAULT_MAX_ERRORS: usize = 10;
+/// Bytes read at least from the end of a file for a spot check, enough for a
+/// BGZF block (up to 64 KiB) to start in them.
+const MIN_TAIL_BYTES: u64 = 1 << 17;
+
+/// Statistics of every FASTQ scanned during this run, keyed by path.
+/// Files referenced from several rows are only scanned once.
+static SCANNED: Lazy<Mutex<BTreeMap<PathBuf, FastqStats>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Content-Length (if reported) of every remote FASTQ checked during this run.
+static REMOTE: Lazy<Mutex<BTreeMap<String, Option<u64>>>> =
+    Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+/// Options of the FASTQ checks, from the command line.
+#[derive(Debug, Clone)]
+pub struct FastqOptions {
+    /// request remote files to verify that they are reachable
+    pub check_remote: bool,
+    /// look for read IDs appearing more than once in a file
+    pub duplicate_ids: bool,
+    /// stop scanning a file after this many malformed records
+    pub max_errors: usize,
+    /// only check this many records at the start and at the end of a file
+    pub spotcheck: Option<usize>,
+}
+
+impl Default for FastqOptions {
+    fn default() -> Self {
+        FastqOptions {
+            check_remote: false,
+            duplicate_ids: false,
+            max_errors: DEFAULT_MAX_ERRORS,
+            spotcheck: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum FastqError {
+    /// The file could not be opened.
+    Missing,
+    /// Some records could not be parsed; `pos` (1-based, unknown at the end
+    /// of a spot check) and `msg` are those of the first one. `aborted` if the
+    /// scan stopped at the maximum of problems.
+    Malformed {
+        pos: Option<usize>,
+        msg: String,
+        problems: usize,
+        aborted: bool,
+    },
+    /// The remote location is not a valid URL.
+    InvalidUrl(String),
+    /// The remote location could not be reached.
+    Unreachable(String),
+    /// The remote file exists but has no content.
+    Empty,
+    /// Mates 1 and 2 alternate in a single file.
+    Interleaved,
+    /// Some read IDs appear more than once; `example` is one of them.
+    DuplicateIds { count: usize, example: String },
+}
+
+/// Quality score encoding, guessed from the range of quality characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualityEncoding {
+    /// Sanger / Illumina 1.8+
+    Phred33,
+    /// Illumina 1.3 to 1.7
+    Phred64,
+    /// not enough evidence to decide
+    Unknown,
+}
+
+/// Read-length distribution and quality summary of a FASTQ file.
+#[derive(Debug, Clone, Default)]
+pub struct FastqStats {
+    pub records: usize,
+    /// read length -> number of reads with that length
+    pub length_distribution: BTreeMap<usize, usize>,
+    quality_sum: u64,
+    bases: u64,
+    /// smallest and largest raw quality characters seen
+    quality_range: Option<(u8, u8)>,
+    /// true while every quality score seen so far is zero
+    all_zero_quality: bool,
+    /// only the first and last records were read, see `--fastq-spotcheck`
+    pub spot_checked: bool,
+}
+
+impl FastqStats {
+    fn push(&mut self, qual: &[u8]) {
+        if self.records == 0 {
+            self.all_zero_quality = true;
+        }
+        self.records += 1;
+        *self.length_distribution.entry(qual.len()).or_insert(0) += 1;
+        for &q in qual {
+            self.quality_range = match self.quality_range {
+                Some((lo, hi)) => Some((lo.min(q), hi.max(q))),
+                None => Some((q, q)),
+            };
+            let score = q.saturating_sub(PHRED_OFFSET);
+            self.quality_sum += score as u64;
+            self.all_zero_quality &= score == 0;
+        }
+        self.bases += qual.len() as u64;
+    }
+
+    pub fn min_length(&self) -> usize {
+        self.length_distribution.keys().next().copied().unwrap_or(0)
+    }
+
+    pub fn max_length(&self) -> usize {
+        self.length_distribution.keys().last().copied().unwrap_or(0)
+    }
+
+    pub fn mean_length(&self) -> f64 {
+        if self.records == 0 {
+            return 0.;
+        }
+        self.bases as f64 / self.records as f64
+    }
+
+    pub fn mean_quality(&self) -> f64 {
+        if self.bases == 0 {
+            return 0.;
+        }
+        self.quality_sum as f64 / self.bases as f64
+    }
+
+    pub fn quality_encoding(&self) -> QualityEncoding {
+        match self.quality_range {
+            // characters below ';' only exist in Phred+33
+            Some((lo, _)) if lo < b';' => QualityEncoding::Phred33,
+            // Phred+64 files never go below '@' and reach well above 'J'
+            Some((lo, hi)) if lo >= b'@' && hi > b'J' => QualityEncoding::Phred64,
+            _ => QualityEncoding::Unknown,
+        }
+    }
+
+    /// Number of reads shorter than [`MIN_READ_LENGTH`].
+    pub fn short_reads(&self) -> usize {
+        self.length_distribution
+            .range(..MIN_READ_LENGTH)
+            .map(|(_, n)| n)
+            .sum()
+    }
+
+    /// Human readable flags for suspicious files.
+    pub fn flags(&self) -> Vec<String> {
+        let mut flags = Vec::new();
+        if self.records > 0 && self.all_zero_quality {
+            flags.push("all quality scores are zero".to_string());
+        }
+        let short = self.short_reads();
+        if short > 0 {
+            flags.push(format!(
+                "{} reads shorter than {}bp",
+                short, MIN_READ_LENGTH
+            ));
+        }
+        flags
+    }
+}
+
+impl fmt::Display for FastqStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} reads{}, length min/mean/max {}/{:.1}/{}, mean quality {:.1}",
+            self.records,
+            if self.spot_checked {
+                " (first and last records)"
+            } else {
+                ""
+            },
+            self.min_length(),
+            self.mean_length(),
+            self.max_length(),
+            self.mean_quality()
+        )
+    }
+}
+
+/// Read a line into `line` without its line ending, `false` at the end of the input.
+fn read_line(reader: &mut impl BufRead, line: &mut Vec<u8>) -> io::Result<bool> {
+    line.clear();
+    if reader.read_until(b'\n', line)? == 0 {
+        return Ok(false);
+    }
+    while matches!(line.last(), Some(b'\n' | b'\r')) {
+        line.pop();
+    }
+    Ok(true)
+}
+
+/// The four lines of a FASTQ record, read into buffers reused from record to
+/// record so that memory does not grow with the file.
+#[derive(Default)]
+struct RecordLines {
+    header: Vec<u8>,
+    seq: Vec<u8>,
+    sep: Vec<u8>,
+    qual: Vec<u8>,
+}
+
+impl RecordLines {
+    /// Read the next record, `None` at the end of the input, with the problem
+    /// of the record if it is malformed. The lines not starting with `@` where
+    /// a header is expected are skipped, so that the scan finds the next
+    /// record after a broken one.
+    fn next(&mut self, reader: &mut impl BufRead) -> io::Result<Option<Result<(), String>>> {
+        let mut skipped = 0;
+        loop {
+            if !read_line(reader, &mut self.header)? {
+                return Ok(
+                    (skipped > 0).then(|| Err(format!("{} lines without a header", skipped)))
+                );
+            }
+            if self.header.starts_with(b"@") {
+                break;
+            }
+            skipped += 1;
+        }
+        // a file with CR line endings is read as a single line
+        if self.header.contains(&b'\r') {
+            return Ok(Some(Err(
+                "lines end with CR only (classic Mac line endings), convert them to LF".to_string(),
+            )));
+        }
+        for line in [&mut self.seq, &mut self.sep, &mut self.qual] {
+            if !read_line(reader, line)? {
+                return Ok(Some(Err(
+                    "truncated record, the file ends before its quality".to_string(),
+                )));
+            }
+        }
+        Ok(Some(if skipped > 0 {
+            Err(format!("{} lines without a header", skipped))
+        } else if std::str::from_utf8(&self.header).is_err() {
+            Err("header is not valid UTF-8".to_string())
+        } else if !self.sep.starts_with(b"+") {
+            Err("no + separator after the sequence".to_string())
+        } else if self.seq.len() != self.qual.len() {
+            Err(format!(
+                "{} quality scores for {} bases",
+                self.qual.len(),
+                self.seq.len()
+            ))
+        } else if self.qual.iter().any(|q| !(b'!'..=b'~').contains(q)) {
+            Err("quality scores outside of ! to ~".to_string())
+        } else {
+            Ok(())
+        }))
+    }
+
+    /// ID and description of a well-formed record.
+    fn id_desc(&self) -> (&str, Option<&str>) {
+        let header = std::str::from_utf8(&self.header[1..]).unwrap_or_default();
+        match header.split_once(char::is_whitespace) {
+            Some((id, desc)) => (id, Some(desc.trim())),
+            None => (header, None),
+        }
+    }
+}
+
+/// Scan a FASTQ file, checking every record and collecting [`FastqStats`].
+/// The records are streamed, so a file of any size is scanned in constant
+/// memory, and the scan is aborted after [`FastqOptions::max_errors`] malformed
+/// records instead of reporting the same broken file over and over.
+pub fn scan(fastq_path: &Path, options: &FastqOptions) -> Result<FastqStats, FastqError> {
+    if let Some(stats) = SCANNED.lock().unwrap().get(fastq_path) {
+        return Ok(stats.clone());
+    }
+    let mut reader =
+        BufReader::new(compression::open(fastq_path).map_err(|_| FastqError::Missing)?);
+    let stats = match options.spotcheck {
+        Some(n) => {
+            info!(path = %fastq_path.display(), records = n, "spot-checking FASTQ");
+            let mut scan = Scan::new(options);
+            if !scan.read(&mut reader, Some(n)) && !scan.aborted {
+                scan.read_tail(&mut reader, fastq_path, n);
+            }
+            let mut stats = scan.finish(fastq_path)?;
+            stats.spot_checked = true;
+            stats
+        }
+        None => {
+            info!(path = %fastq_path.display(), "scanning FASTQ");
+            let mut scan = Scan::new(options);
+            scan.ids = options.duplicate_ids.then(DuplicateIds::new);
+            scan.read(&mut reader, None);
+            scan.finish(fastq_path)?
+        }
+    };
+    debug!(path = %fastq_path.display(), %stats, "scanned FASTQ");
+    SCANNED
+        .lock()
+        .unwrap()
+        .insert(fastq_path.to_path_buf(), stats.clone());
+    Ok(stats)
+}
+
+/// Statistics and problems of the records of a file read so far.
+struct Scan {
+    stats: FastqStats,
+    interleaved: InterleavedCheck,
+    ids: Option<DuplicateIds>,
+    lines: RecordLines,
+    /// number of the last record read, unknown in the tail of a spot check
+    pos: Option<usize>,
+    /// first problem and its record
+    first: Option<(Option<usize>, String)>,
+    problems: usize,
+    max_errors: usize,
+    aborted: bool,
+}
+
+impl Scan {
+    fn new(options: &FastqOptions) -> Self {
+        Scan {
+            stats: FastqStats::default(),
+            interleaved: InterleavedCheck::default(),
+            ids: None,
+            lines: RecordLines::default(),
+            pos: Some(0),
+            first: None,
+            problems: 0,
+            max_errors: options.max_errors.max(1),
+            aborted: false,
+        }
+    }
+
+    /// Read the records of `reader`, at most `limit` of them, until the end or
+    /// the maximum of problems; `true` at the end of the input.
+    fn read(&mut self, reader: &mut impl BufRead, limit: Option<usize>) -> bool {
+        let mut read = 0;
+        while limit.is_none_or(|limit| read < limit) {
+            let problem = match self.lines.next(reader) {
+                Ok(None) => return true,
+                Ok(Some(Ok(()))) => {
+                    self.stats.push(&self.lines.qual);
+                    let (id, desc) = self.lines.id_desc();
+                    // the mates are only paired from the start of the file
+                    if self.pos.is_some() {
+                        self.interleaved.push(id, desc);
+                    }
+                    if let Some(ids) = self.ids.as_mut() {
+                        ids.insert(id);
+                    }
+                    None
+                }
+                Ok(Some(Err(msg))) => Some(msg),
+                // e.g. a corrupt compressed stream, nothing can be read after it
+                Err(e) => {
+                    self.aborted = true;
+                    Some(e.to_string())
+                }
+            };
+            read += 1;
+            self.pos = self.pos.map(|pos| pos + 1);
+            if let Some(problem) = problem {
+                self.problems += 1;
+                self.first.get_or_insert((self.pos, problem));
+                if self.aborted || self.problems >= self.max_errors {
+                    self.aborted = true;
+                    return false;
+                }
+            }
+        }
+        false
+    }
+
+    /// Read at least the last `n` records of the file if it can be entered
+    /// from the end (uncompressed or BGZF), otherwise the rest of `reader`,
+    /// the head of the file.
+    fn read_tail(&mut self, reader: &mut impl BufRead, fastq_path: &Path, n: usize) {
+        // the records of the head tell how many bytes the last ones take
+        let record_bytes = (self.stats.mean_length() as u64 + 16) * 2;
+        let window = (record_bytes * (n as u64 + 2) * 2).max(MIN_TAIL_BYTES);
+        match tail(fastq_path, window) {
+            Ok(Some(tail)) => {
+                self.pos = None;
+                self.read(&mut io::Cursor::new(tail), None);
+            }
+            Ok(None) => {
+                self.read(reader, None);
+            }
+            Err(e) => {
+                self.problems += 1;
+                self.first.get_or_insert((None, e.to_string()));
+                self.aborted = true;
+            }
+        }
+    }
+
+    fn finish(self, fastq_path: &Path) -> Result<FastqStats, FastqError> {
+        if let Some((pos, msg)) = self.first {
+            debug!(path = %fastq_path.display(), problems = self.problems, aborted = self.aborted, "malformed FASTQ");
+            return Err(FastqError::Malformed {
+                pos,
+                msg,
+                problems: self.problems,
+                aborted: self.aborted,
+            });
+        }
+        if self.interleaved.is_interleaved() {
+            return Err(FastqError::Interleaved);
+        }
+        if let Some(ids) = self.ids {
+            ids.confirm(fastq_path)?;
+        }
+        Ok(self.stats)
+    }
+}
+
+/// Whether a gzip member starts at the beginning of `bytes` with the `BC`
+/// extra field of BGZF blocks.
+fn is_bgzf_block(bytes: &[u8]) -> bool {
+    bytes.len() >= 16 && bytes[..4] == [0x1f, 0x8b, 0x08, 0x04] && bytes[12..14] == *b"BC"
+}
+
+/// Decompressed content of the last records of a file, from the first record
+/// starting in its last `window` bytes (for a BGZF file, as written by
+/// `bgzip`, the first block starting in them). `None` if the file is not
+/// larger than `window` or cannot be entered from the end (gzip, zstd and
+/// bzip2 streams), it must then be read from the start.
+fn tail(fastq_path: &Path, window: u64) -> io::Result<Option<Vec<u8>>> {
+    let mut file = File::open(fastq_path)?;
+    let start = file.metadata()?.len().saturating_sub(window);
+    if start == 0 {
+        return Ok(None);
+    }
+    let content = match Compression::detect(fastq_path) {
+        Compression::None => {
+            file.seek(SeekFrom::Start(start))?;
+            let mut content = Vec::new();
+            file.read_to_end(&mut content)?;
+            content
+        }
+        Compression::Gzip => {
+            let mut magic = [0; 16];
+            if file.read_exact(&mut magic).is_err() || !is_bgzf_block(&magic) {
+                return Ok(None);
+            }
+            file.seek(SeekFrom::Start(start))?;
+            let mut compressed = Vec::new();
+            file.read_to_end(&mut compressed)?;
+            // a block header may also appear inside the compressed data, the
+            // first candidate that decompresses up to the end is a block
+            (0..compressed.len())
+                .filter(|&i| is_bgzf_block(&compressed[i..]))
+                .find_map(|i| {
+                    let mut content = Vec::new();
+                    MultiGzDecoder::new(&compressed[i..])
+                        .read_to_end(&mut content)
+                        .ok()
+                        .map(|_| content)
+                })
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "no BGZF block found at the end of the file",
+                    )
+                })?
+        }
+        Compression::Zstd | Compression::Bzip2 => return Ok(None),
+    };
+    Ok(Some(
+        first_record(&content).map_or_else(Vec::new, |offset| content[offset..].to_vec()),
+    ))
+}
+
+/// Offset of the first record starting on a line of `content`, read from the
+/// middle of a file: a header is a line starting with `@` followed by a
+/// sequence, a line starting with `+` and a quality of the same length, since
+/// quality lines may also start with `@`.
+fn first_record(content: &[u8]) -> Option<usize> {
+    let mut offsets = vec![0];
+    offsets.extend(
+        content
+            .iter()
+            .enumerate()
+            .filter(|(_, &b)| b == b'\n')
+            .map(|(i, _)| i + 1),
+    );
+    let line = |k: usize| -> Option<&[u8]> {
+        let start = *offsets.get(k)?;
+        let end = offsets.get(k + 1).map_or(content.len(), |&end| end - 1);
+        let line = content.get(start..end)?;
+        Some(line.strip_suffix(b"\r").unwrap_or(line))
+    };
+    // the first line is likely the end of a line cut by the window
+    (1..offsets.len()).find_map(|k| {
+        let (header, seq, sep, qual) = (line(k)?, line(k + 1)?, line(k + 2)?, line(k + 3)?);
+        (header.starts_with(b"@") && sep.starts_with(b"+") && seq.len() == qual.len())
+            .then(|| offsets[k])
+    })
+}
+
+/// Split a read into its template name and mate number, from either the
+/// `name/1` ID suffix or the Casava 1.8+ `1:N:0:...` description.
+fn mate<'a>(id: &'a str, desc: Option<&str>) -> Option<(&'a str, u8)> {
+    if let Some((name, mate)) = id.rsplit_once('/') {
+        match mate {
+            "1" => return Some((name, 1)),
+            "2" => return Some((name, 2)),
+            _ => {}
+        }
+    }
+    match desc?.split(':').next()? {
+        "1" => Some((id, 1)),
+        "2" => Some((id, 2)),
+        _ => None,
+    }
+}
+
+/// Detects files where mates 1 and 2 of the same template alternate, by
+/// looking at the first [`INTERLEAVED_PAIRS`] pairs.
+#[derive(Default)]
+struct InterleavedCheck {
+    /// template name of the pending mate 1
+    previous: Option<String>,
+    records: usize,
+    pairs: usize,
+    broken: bool,
+}
+
+impl InterleavedCheck {
+    fn push(&mut self, id: &str, desc: Option<&str>) {
+        if self.broken || self.records >= 2 * INTERLEAVED_PAIRS {
+            return;
+        }
+        self.records += 1;
+        match (mate(id, desc), self.previous.take()) {
+            (Some((name, 1)), None) => self.previous = Some(name.to_string()),
+            (Some((name, 2)), Some(previous)) if name == previous => self.pairs += 1,
+            _ => self.broken = true,
+        }
+    }
+
+    fn is_interleaved(&self) -> bool {
+        !self.broken && self.pairs > 0
+    }
+}
+
+/// Fixed-size Bloom filter, so that memory does not grow with the number of reads.
+struct BloomFilter {
+    bits: Vec<u64>,
+}
+
+impl BloomFilter {
+    fn new() -> Self {
+        BloomFilter {
+            bits: vec![0; (BLOOM_BITS / 64) as usize],
+        }
+    }
+
+    /// Insert `item`, returning whether it was (possibly) already present.
+    fn insert(&mut self, item: &str) -> bool {
+        // double hashing: h1 + i * h2 simulates BLOOM_HASHES independent functions
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        let h1 = hasher.finish();
+        h1.hash(&mut hasher);
+        let h2 = hasher.finish() | 1;
+        let mut present = true;
+        for i in 0..BLOOM_HASHES {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) % BLOOM_BITS;
+            let (word, mask) = ((bit / 64) as usize, 1 << (bit % 64));
+            present &= self.bits[word] & mask != 0;
+            self.bits[word] |= mask;
+        }
+        present
+    }
+}
+
+/// Two-pass detection of duplicated read IDs: the Bloom filter finds candidates
+/// while scanning, which are then confirmed exactly with a second pass.
+struct DuplicateIds {
+    seen: BloomFilter,
+    candidates: HashSet<String>,
+}
+
+impl DuplicateIds {
+    fn new() -> Self {
+        DuplicateIds {
+            seen: BloomFilter::new(),
+            candidates: HashSet::new(),
+        }
+    }
+
+    fn insert(&mut self, id: &str) {
+        if self.seen.insert(id) && self.candidates.len() < MAX_DUPLICATE_CANDIDATES {
+            self.candidates.insert(id.to_string());
+        }
+    }
+
+    fn confirm(self, fastq_path: &Path) -> Result<(), FastqError> {
+        if self.candidates.is_empty() {
+            return Ok(());
+        }
+        let mut reader =
+            BufReader::new(compression::open(fastq_path).map_err(|_| FastqError::Missing)?);
+        let mut lines = RecordLines::default();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        // the file was already checked, so the malformed records were reported
+        while let Ok(Some(record)) = lines.next(&mut reader) {
+            let (id, _) = lines.id_desc();
+            if record.is_ok() && self.candidates.contains(id) {
+                *counts.entry(id.to_string()).or_insert(0) += 1;
+            }
+        }
+        let mut duplicated: Vec<String> = counts
+            .into_iter()
+            .filter(|(_, n)| *n > 1)
+            .map(|(id, _)| id)
+            .collect();
+        duplicated.sort();
+        match duplicated.first() {
+            Some(example) => Err(FastqError::DuplicateIds {
+                count: duplicated.len(),
+                example: example.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Check a FASTQ given as an HTTP(S) or S3 URL, requested with
+/// [`FastqOptions::check_remote`] if this build is able to.
+pub fn check_remote(location: &str, network: &NetworkOptions) -> Result<(), FastqError> {
+    let remote = RemoteFile::parse(location).map_err(FastqError::InvalidUrl)?;
+    if !remote.can_check() || REMOTE.lock().unwrap().contains_key(location) {
+        return Ok(());
+    }
+    info!(location, "checking remote FASTQ");
+    let size = remote.size(network).map_err(FastqError::Unreachable)?;
+    debug!(location, ?size, "remote FASTQ reachable");
+    if size == Some(0) {
+        return Err(FastqError::Empty);
+    }
+    REMOTE.lock().unwrap().insert(location.to_string(), size);
+    Ok(())
+}
+
+/// Statistics of an already scanned FASTQ file.
+pub fn stats(fastq_path: &Path) -> Option<FastqStats> {
+    SCANNED.lock().unwrap().get(fastq_path).cloned()
+}
+
+/// Statistics of all the FASTQ files successfully scanned so far.
+pub fn scanned() -> Vec<(PathBuf, FastqStats)> {
+    SCANNED
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(path, stats)| (path.clone(), stats.clone()))
+        .collect()
+}
+
+/// Content-Length of all the remote FASTQ files successfully checked so far.
+pub fn remote_checked() -> Vec<(String, Option<u64>)> {
+    REMOTE
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(location, length)| (location.clone(), *length))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fastq_stats_are_collected() {
+        let stats = scan(Path::new("tests/valid.fastq"), &FastqOptions::default()).unwrap();
+        assert_eq!(stats.records, 2);
+        assert!(stats.min_length() >= MIN_READ_LENGTH);
+        assert!(stats.mean_quality() > 0.);
+        assert!(stats.flags().is_empty());
+        assert_eq!(stats.quality_encoding(), QualityEncoding::Phred33);
+    }
+
+    #[test]
+    fn test_malformed_records_are_counted() {
+        let content = b"@r1\nACGT\n+\nIIII\n@r2\nACGT\n+\nII\nACGT\n+\n@r3\nAC\n-\nII\n@r4\nA";
+        let mut reader = io::Cursor::new(&content[..]);
+        let mut lines = RecordLines::default();
+        let mut found = Vec::new();
+        while let Some(result) = lines.next(&mut reader).unwrap() {
+            found.push(result);
+        }
+        assert_eq!(
+            found,
+            vec![
+                Ok(()),
+                Err("2 quality scores for 4 bases".to_string()),
+                Err("2 lines without a header".to_string()),
+                Err("truncated record, the file ends before its quality".to_string()),
+            ]
+        );
+        match scan(Path::new("tests/invalid.fastq"), &FastqOptions::default()) {
+            Err(FastqError::Malformed {
+                pos,
+                problems,
+                aborted,
+                ..
+            }) => assert_eq!((pos, problems, aborted), (Some(2), 1, false)),
+            other => panic!("expected a malformed FASTQ, got {:?}", other),
+        }
+        let mut mac = io::Cursor::new(&b"@r1\rACGT\r+\rIIII\r"[..]);
+        assert_eq!(
+            RecordLines::default().next(&mut mac).unwrap(),
+            Some(Err(
+                "lines end with CR only (classic Mac line endings), convert them to LF".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_records_are_found_in_the_middle_of_a_file() {
+        // cut in a quality line starting with @
+        let content = b"II\n@IIII\n@r2\nACGT\n+\n@III\n";
+        assert_eq!(first_record(content), Some(9));
+        assert!(first_record(b"ACGT\n+\n").is_none());
+        let mut block = [0; 16];
+        block[..4].copy_from_slice(&[0x1f, 0x8b, 0x08, 0x04]);
+        block[12..14].copy_from_slice(b"BC");
+        assert!(is_bgzf_block(&block));
+        assert!(!is_bgzf_block(&block[..8]));
+    }
+
+    #[test]
+    fn test_interleaved_fastq_is_detected() {
+        assert!(matches!(
+            scan(
+                Path::new("tests/interleaved.fastq"),
+                &FastqOptions::default()
+            ),
+            Err(FastqError::Interleaved)
+        ));
+    }
+
+    #[test]
+    fn test_duplicated_ids_are_confirmed() {
+        let mut ids = DuplicateIds::new();
+        ids.insert("SRR12132977.1");
+        ids.insert("SRR12132977.1");
+        assert!(ids.candidates.contains("SRR12132977.1"));
+        // the file has no duplicated IDs, so the candidate is discarded
+        assert!(ids.confirm(Path::new("tests/valid.fastq")).is_ok());
+    }
+}
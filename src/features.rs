@@ -0,0 +1,328 @@
+use crate::chemistry::{ppm, Adduct, Formula};
+use crate::excel;
+use crate::validators::{
+    parse_issue, unknown_columns, IssueCode, LineError, ReadOptions, Severity, ValidationIssue,
+};
+use csv::ReaderBuilder;
+use serde::Deserialize;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Read;
+use std::str::FromStr;
+
+/// Columns of the format, the annotation columns being optional.
+const COLUMNS: [&str; 8] = [
+    "feature_id",
+    "mz",
+    "rt",
+    "sample",
+    "intensity",
+    "adduct",
+    "formula",
+    "neutral_mass",
+];
+
+/// Range of m/z measured by the instrument, given as `MIN:MAX`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MzRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl FromStr for MzRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (min, max) = s
+            .split_once(':')
+            .ok_or_else(|| format!("expected MIN:MAX, found {}", s))?;
+        let parse = |bound: &str| {
+            bound
+                .parse::<f64>()
+                .map_err(|_| format!("invalid m/z {}", bound))
+        };
+        let (min, max) = (parse(min)?, parse(max)?);
+        if min < max {
+            Ok(MzRange { min, max })
+        } else {
+            Err(format!("empty m/z range {}", s))
+        }
+    }
+}
+
+impl fmt::Display for MzRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.min, self.max)
+    }
+}
+
+/// Options of the feature table checks, from the command line.
+#[derive(Debug, Clone)]
+pub struct FeatureOptions {
+    /// only m/z > 0 is required if `None`
+    pub mz_range: Option<MzRange>,
+    /// deviation allowed between the reported masses and the formula, in ppm
+    pub mass_tolerance: f64,
+}
+
+impl Default for FeatureOptions {
+    fn default() -> Self {
+        FeatureOptions {
+            mz_range: None,
+            mass_tolerance: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FeatureRecord {
+    feature_id: String,
+    mz: f64,
+    rt: f64,
+    sample: String,
+    intensity: f64,
+    adduct: Option<String>,
+    formula: Option<String>,
+    neutral_mass: Option<f64>,
+}
+
+impl FeatureRecord {
+    /// The reported mass that deviates from the theoretical mass of the
+    /// formula by more than `tolerance` ppm, if any: the neutral mass, or
+    /// the m/z if the adduct is known.
+    fn mass_mismatch(&self, tolerance: f64) -> Option<ValidationIssue> {
+        // malformed formulas are reported on their own
+        let formula = Formula::parse(self.formula.as_deref()?).ok()?;
+        let mass = formula.monoisotopic_mass()?;
+        let (field, reported, expected) = match (self.neutral_mass, self.adduct.as_deref()) {
+            (Some(neutral_mass), _) => ("neutral_mass", neutral_mass, mass),
+            (None, Some(adduct)) => ("mz", self.mz, Adduct::parse(adduct).ok()?.mz(mass)?),
+            (None, None) => return None,
+        };
+        let deviation = ppm(reported, expected);
+        (deviation.abs() > tolerance).then(|| {
+            ValidationIssue::new(IssueCode::MassMismatch)
+                .field(field)
+                .value(reported.to_string())
+                .detail(format!(
+                    "expected {:.5} from {}, {:+.1} ppm",
+                    expected,
+                    self.formula.as_deref().unwrap_or_default(),
+                    deviation
+                ))
+        })
+    }
+}
+
+/// Validate an untargeted LC-MS feature table in long form:
+///
+/// ```csv
+/// feature_id,mz,rt,sample,intensity
+/// FEATURE_ID,MZ,RETENTION_TIME,SAMPLE_NAME,NUMBER_VALUE
+/// ```
+///
+/// The m/z must be positive and in the range of the instrument, see
+/// [`FeatureOptions`], and the retention time and intensity non-negative. A
+/// feature must keep the same m/z and retention time on all its rows and
+/// appear once per sample. Annotated tables may have an `adduct` column, e.g.
+/// `[M+H]+`, see [`Adduct`], and a `formula` whose monoisotopic mass must
+/// agree with the `neutral_mass` or, given the adduct, the m/z. No model is
+/// needed.
+pub fn validate<R: Read>(input: R, options: &ReadOptions) -> Vec<LineError> {
+    let feature_options = &options.features;
+    let mut rdr = ReaderBuilder::new()
+        .flexible(options.allow_extra_columns)
+        .from_reader(input);
+    options.rename_headers(&mut rdr);
+    let mut error_vec = Vec::new();
+    match rdr.headers() {
+        Ok(headers) if !options.allow_extra_columns => {
+            error_vec.extend(unknown_columns(headers, &COLUMNS))
+        }
+        Ok(_) => {}
+        Err(e) => return vec![LineError::error(1, parse_issue(&e))],
+    }
+    // line, m/z and retention time of the first row of each feature
+    let mut features: HashMap<String, (usize, f64, f64)> = HashMap::new();
+    let mut keys: HashMap<(String, String), usize> = HashMap::new();
+    for (i, result) in options
+        .rows
+        .select(rdr.deserialize::<FeatureRecord>().enumerate())
+    {
+        let line = i + 2;
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                error_vec.push(LineError::error(line, parse_issue(&e)));
+                continue;
+            }
+        };
+        let mut issues = Vec::new();
+        let invalid = |field: &str, value: f64, detail: String| {
+            ValidationIssue::new(IssueCode::InvalidValue)
+                .field(field)
+                .value(value.to_string())
+                .detail(detail)
+        };
+        if record.feature_id.is_empty() {
+            issues.push(ValidationIssue::new(IssueCode::EmptyField).field("feature_id"));
+        }
+        issues.extend(excel::check("feature_id", &record.feature_id));
+        if record.sample.is_empty() {
+            issues.push(ValidationIssue::new(IssueCode::EmptySample).field("sample"));
+        }
+        match feature_options.mz_range {
+            _ if record.mz.is_nan() || record.mz <= 0.0 => {
+                issues.push(invalid("mz", record.mz, "m/z must be positive".into()))
+            }
+            Some(range) if record.mz < range.min || record.mz > range.max => issues.push(invalid(
+                "mz",
+                record.mz,
+                format!("outside the m/z range {}", range),
+            )),
+            _ => {}
+        }
+        if record.rt.is_nan() || record.rt < 0.0 {
+            issues.push(invalid(
+                "rt",
+                record.rt,
+                "retention time must be non-negative".into(),
+            ));
+        }
+        if record.intensity.is_nan() || record.intensity < 0.0 {
+            issues.push(invalid(
+                "intensity",
+                record.intensity,
+                "intensity must be non-negative".into(),
+            ));
+        }
+        if let Some(adduct) = record.adduct.as_deref() {
+            if let Err(detail) = Adduct::parse(adduct) {
+                issues.push(
+                    ValidationIssue::new(IssueCode::InvalidAdduct)
+                        .field("adduct")
+                        .value(adduct)
+                        .detail(detail),
+                );
+            }
+        }
+        if let Some(formula) = record.formula.as_deref() {
+            if let Err(detail) = Formula::parse(formula) {
+                issues.push(
+                    ValidationIssue::new(IssueCode::InvalidFormula)
+                        .field("formula")
+                        .value(formula)
+                        .detail(detail),
+                );
+            }
+        }
+        issues.extend(record.mass_mismatch(feature_options.mass_tolerance));
+        if !record.feature_id.is_empty() {
+            match features.entry(record.feature_id.clone()) {
+                Entry::Occupied(entry) => {
+                    let (first, mz, rt) = *entry.get();
+                    if mz != record.mz || rt != record.rt {
+                        issues.push(
+                            ValidationIssue::new(IssueCode::InconsistentFeature)
+                                .field("feature_id")
+                                .value(&record.feature_id)
+                                .detail(format!(
+                                    "m/z {} and retention time {} on line {}",
+                                    mz, rt, first
+                                )),
+                        );
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert((line, record.mz, record.rt));
+                }
+            }
+            if !record.sample.is_empty() {
+                let key = (record.feature_id, record.sample);
+                if let Some(first) = keys.get(&key) {
+                    issues.push(
+                        ValidationIssue::new(IssueCode::DuplicateKey)
+                            .value(format!("{}, {}", key.0, key.1))
+                            .detail(format!("also on line {}", first)),
+                    );
+                } else {
+                    keys.insert(key, line);
+                }
+            }
+        }
+        if !issues.is_empty() {
+            error_vec.push(LineError {
+                line,
+                issues,
+                severity: Severity::Error,
+            });
+        }
+    }
+    error_vec
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_feature_table_is_validated() {
+        let file = std::fs::File::open("tests/features.csv").unwrap();
+        let error_vec = validate(file, &ReadOptions::default());
+        let codes: Vec<(usize, IssueCode)> = error_vec
+            .iter()
+            .flat_map(|e| e.issues.iter().map(move |issue| (e.line, issue.code)))
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                (5, IssueCode::InvalidValue),
+                (6, IssueCode::InconsistentFeature),
+                (7, IssueCode::DuplicateKey)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mz_outside_instrument_range() {
+        let input = "feature_id,mz,rt,sample,intensity\nF1,2500.1,3.2,S1,10\n";
+        let range: MzRange = "50:2000".parse().unwrap();
+        let options = ReadOptions {
+            features: FeatureOptions {
+                mz_range: Some(range),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(validate(input.as_bytes(), &ReadOptions::default()).is_empty());
+        assert_eq!(validate(input.as_bytes(), &options).len(), 1);
+        assert!("2000:50".parse::<MzRange>().is_err());
+    }
+
+    #[test]
+    fn test_malformed_adducts_are_reported() {
+        let input = "feature_id,mz,rt,sample,intensity,adduct\nF1,181.07,3.2,S1,10,[M+H]+\nF2,203.05,5.8,S1,12,[M+Na]\nF3,179.06,4.1,S1,9,\n";
+        let error_vec = validate(input.as_bytes(), &ReadOptions::default());
+        assert_eq!(error_vec.len(), 1);
+        assert_eq!(error_vec[0].line, 3);
+        assert_eq!(error_vec[0].issues[0].code, IssueCode::InvalidAdduct);
+    }
+
+    #[test]
+    fn test_masses_must_match_the_formula() {
+        let input = "feature_id,mz,rt,sample,intensity,adduct,formula,neutral_mass\nF1,181.0707,3.2,S1,10,[M+H]+,C6H12O6,\nF2,203.0526,5.8,S1,12,[M+Na]+,C6H12O6,180.0634\nF3,181.0707,4.1,S1,9,[M+H]+,C6H14O6,\nF4,100,4.3,S1,9,,C6H12O6,180.1\n";
+        let options = ReadOptions {
+            features: FeatureOptions {
+                mass_tolerance: 5.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let error_vec = validate(input.as_bytes(), &options);
+        assert_eq!(error_vec.iter().map(|e| e.line).collect::<Vec<_>>(), [4, 5]);
+        assert!(error_vec
+            .iter()
+            .all(|e| e.issues[0].code == IssueCode::MassMismatch));
+    }
+}
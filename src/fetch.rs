@@ -0,0 +1,85 @@
+use crate::cache;
+use crate::remote::{self, NetworkOptions, RequestError};
+use argh::FromArgs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+const BIGG_MODELS_URL: &str = "http://bigg.ucsd.edu/static/models";
+
+#[derive(Debug, FromArgs)]
+/// Download an SBML model from BiGG Models, to be used with -m.
+#[argh(subcommand, name = "fetch-model")]
+pub struct FetchModelArgs {
+    /// identifier of the model in BiGG, e.g. iML1515
+    #[argh(positional)]
+    id: String,
+
+    /// where the model is written, `<id>.xml` by default
+    #[argh(option, short = 'o')]
+    output: Option<PathBuf>,
+}
+
+/// Parse a downloaded model in full with the `sbml` feature, otherwise check
+/// it with the streaming reader of `-m`.
+#[cfg(feature = "sbml")]
+fn check_sbml(content: &str) -> Result<(), String> {
+    rust_sbml::ModelRaw::parse(content)
+        .map(|_| ())
+        .map_err(|e| format!("{:?}", e))
+}
+
+#[cfg(not(feature = "sbml"))]
+fn check_sbml(content: &str) -> Result<(), String> {
+    crate::models::read_sbml(content.as_bytes()).map(|_| ())
+}
+
+pub fn run(args: FetchModelArgs, network: &NetworkOptions) -> Result<(), io::Error> {
+    // the identifier ends up in the URL and in the default output path
+    if args.id.is_empty()
+        || !args
+            .id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid model identifier {}", args.id),
+        ));
+    }
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("{}.xml", args.id)));
+    let cached = cache::dir()?
+        .join("models")
+        .join(format!("{}.xml", args.id));
+    if cached.exists() {
+        std::fs::copy(&cached, &output)?;
+        eprintln!("model {} written to {} (cached)", args.id, output.display());
+        return Ok(());
+    }
+    let url = format!("{}/{}.xml", BIGG_MODELS_URL, args.id);
+    let response = remote::request(network, "GET", &url).map_err(|e| match e {
+        RequestError::Status(404) => io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("model {} not found in BiGG Models", args.id),
+        ),
+        e => io::Error::other(format!("{}: {}", url, e)),
+    })?;
+    let mut content = String::new();
+    response.into_reader().read_to_string(&mut content)?;
+    // refuse to write something that -m would reject later
+    check_sbml(&content).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: invalid SBML: {}", url, e),
+        )
+    })?;
+    if let Some(parent) = cached.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&cached, &content)?;
+    cache::evict(&cached)?;
+    std::fs::write(&output, content)?;
+    eprintln!("model {} written to {}", args.id, output.display());
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use crate::validators::{IssueCode, LineError, Severity, ValidationIssue};
+
+/// Columns read by the [modulome-workflow](https://github.com/avsastry/modulome-workflow/tree/65c5bd3c9facef6a41899429403c531923aa5204/2_process_data#setup)
+/// to process the data, with their exact casing.
+const PROCESSING_COLUMNS: [&str; 6] =
+    ["Experiment", "LibraryLayout", "Platform", "Run", "R1", "R2"];
+/// Metadata columns documented by the quality control step of the workflow,
+/// needed to compute the iModulons.
+const METADATA_COLUMNS: [&str; 3] = ["project", "condition", "reference_condition"];
+
+fn dialect_issue(line: usize, detail: &str) -> LineError {
+    LineError::error(
+        line,
+        ValidationIssue::new(IssueCode::InvalidDialect).detail(detail),
+    )
+}
+
+/// Check that an RNA sheet can be read as is by the modulome-workflow, with
+/// `--profile imodulon`.
+pub fn check(content: &[u8]) -> Vec<LineError> {
+    let text = String::from_utf8_lossy(content);
+    let mut lines = text.split('\n').enumerate().map(|(i, l)| (i + 1, l));
+    let header = match lines.next() {
+        Some((_, header)) => header,
+        None => return Vec::new(),
+    };
+    if !header.contains('\t') {
+        // the columns cannot be told apart
+        return vec![dialect_issue(1, "the header is not tab-separated")];
+    }
+    let mut error_vec = Vec::new();
+    let crlf = std::iter::once((1, header))
+        .chain(lines.clone())
+        .find(|(_, l)| l.ends_with('\r'));
+    if let Some((line, _)) = crlf {
+        error_vec.push(dialect_issue(line, "Windows line endings (CRLF)"));
+    }
+    let quoted = std::iter::once((1, header))
+        .chain(lines)
+        .find(|(_, l)| l.contains('"'));
+    if let Some((line, _)) = quoted {
+        error_vec.push(dialect_issue(line, "quoted field"));
+    }
+    let headers: Vec<&str> = header.trim_end_matches('\r').split('\t').collect();
+    let mut issues = Vec::new();
+    for column in PROCESSING_COLUMNS.iter().chain(&METADATA_COLUMNS) {
+        if headers.contains(column) {
+            continue;
+        }
+        match headers
+            .iter()
+            .find(|h| h.trim().eq_ignore_ascii_case(column))
+        {
+            Some(header) => issues.push(
+                ValidationIssue::new(IssueCode::UnknownColumn)
+                    .value(*header)
+                    .detail(format!("expected `{}`", column)),
+            ),
+            None => issues.push(ValidationIssue::new(IssueCode::MissingColumn).value(*column)),
+        }
+    }
+    if !issues.is_empty() {
+        error_vec.push(LineError {
+            line: 1,
+            issues,
+            severity: Severity::Error,
+        });
+    }
+    error_vec.sort_by_key(|e| e.line);
+    error_vec
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sheet_follows_the_workflow() {
+        let valid = "Experiment\tLibraryLayout\tPlatform\tRun\tR1\tR2\tproject\tcondition\treference_condition\nSRX2389071\tPAIRED\tILLUMINA\tSRR5069221\t\t\tglc\twt\twt\n";
+        assert!(check(valid.as_bytes()).is_empty());
+        let sheet = "Experiment\tLibrarylayout\tPlatform\tRun\tR1\tR2\tproject\tcondition\r\nSRX2389071\tPAIRED\tILLUMINA\tSRR5069221\t\t\t\"glc\"\twt\r\n";
+        let error_vec = check(sheet.as_bytes());
+        let codes: Vec<_> = error_vec
+            .iter()
+            .flat_map(|e| e.issues.iter().map(|i| i.code))
+            .collect();
+        assert_eq!(
+            codes,
+            [
+                IssueCode::InvalidDialect,
+                IssueCode::UnknownColumn,
+                IssueCode::MissingColumn,
+                IssueCode::InvalidDialect
+            ]
+        );
+        assert_eq!(
+            check(b"Experiment,Run\nSRX2389071,SRR5069221\n")[0].issues[0].code,
+            IssueCode::InvalidDialect
+        );
+    }
+}
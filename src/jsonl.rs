@@ -0,0 +1,153 @@
+use crate::report::{self, format_issue, Diagnostics, Overrides, ValidationReport};
+use crate::validators::LineError;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Writer of the issues as soon as they are found, disabled unless the output
+/// is JSON Lines. Cloned for each part of the input, which tags its issues.
+#[derive(Debug, Clone, Default)]
+pub struct Stream {
+    /// `None` if disabled
+    settings: Option<Arc<Settings>>,
+    /// archive member, file or resource being validated
+    part: Option<String>,
+}
+
+#[derive(Debug)]
+struct Settings {
+    overrides: Overrides,
+    /// where the lines are written
+    to: Diagnostics,
+}
+
+/// An issue, written as a line of JSON as soon as it is found.
+#[derive(Debug, Serialize)]
+pub(crate) struct Record<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    part: Option<&'a str>,
+    line: usize,
+    severity: String,
+    code: &'static str,
+    name: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<&'a str>,
+    message: String,
+}
+
+/// Last line of the output, once every issue has been written.
+#[derive(Debug, Serialize)]
+struct Summary<'a> {
+    errors: usize,
+    warnings: usize,
+    summary: &'a [String],
+}
+
+impl Stream {
+    /// Write the issues as configured by `to`, with the severities given by
+    /// `overrides`.
+    pub fn new(overrides: Overrides, to: Diagnostics) -> Self {
+        Stream {
+            settings: Some(Arc::new(Settings { overrides, to })),
+            part: None,
+        }
+    }
+
+    /// Whether the issues are written as they are found.
+    pub fn enabled(&self) -> bool {
+        self.settings.is_some()
+    }
+
+    /// The stream of a part of the input.
+    pub fn part(&self, part: String) -> Self {
+        Stream {
+            settings: self.settings.clone(),
+            part: Some(part),
+        }
+    }
+
+    /// Write each issue of a line as a JSON object on its own line, if enabled.
+    pub fn write(&self, error: &LineError) {
+        let settings = match &self.settings {
+            Some(settings) => settings,
+            None => return,
+        };
+        for e in settings.overrides.apply(vec![error.clone()]) {
+            for record in records(self.part.as_deref(), &e) {
+                report::emit(
+                    settings.to,
+                    &serde_json::to_string(&record).expect("issues are serializable"),
+                );
+            }
+        }
+    }
+
+    pub fn write_all(&self, error_vec: &[LineError]) {
+        error_vec.iter().for_each(|e| self.write(e));
+    }
+}
+
+/// A record per issue of a line.
+pub(crate) fn records<'a>(part: Option<&'a str>, e: &'a LineError) -> Vec<Record<'a>> {
+    e.issues
+        .iter()
+        .map(|issue| Record {
+            part,
+            line: e.line,
+            severity: e.severity.to_string(),
+            code: issue.code.id(),
+            name: issue.code.into(),
+            field: issue.field.as_deref(),
+            value: issue.value.as_deref(),
+            message: format_issue(issue),
+        })
+        .collect()
+}
+
+/// Last line of the output, with the number of lines with errors and
+/// warnings and the summaries.
+pub fn summary(report: &ValidationReport) -> String {
+    serde_json::to_string(&Summary {
+        errors: report.n_errors(),
+        warnings: report.n_warnings(),
+        summary: &report.summary,
+    })
+    .expect("summaries are serializable")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::report::Part;
+    use crate::validators::{IssueCode, ValidationIssue};
+
+    #[test]
+    fn test_parts_share_the_stream() {
+        assert!(!Stream::default().part("file a.csv".to_string()).enabled());
+        let stream = Stream::new(Overrides::default(), Diagnostics::default());
+        let part = stream.part("file a.csv".to_string());
+        assert!(part.enabled());
+        assert_eq!(part.part.as_deref(), Some("file a.csv"));
+        assert_eq!(stream.part, None);
+    }
+
+    #[test]
+    fn test_summary_counts_the_lines() {
+        let report = ValidationReport {
+            parts: vec![Part {
+                name: None,
+                issues: vec![
+                    LineError::error(2, ValidationIssue::new(IssueCode::EmptyField)),
+                    LineError::warning(3, ValidationIssue::new(IssueCode::DecoyEntry)),
+                ],
+            }],
+            summary: vec!["1 of 2 metabolites in iCLAU786".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            summary(&report),
+            r#"{"errors":1,"warnings":1,"summary":["1 of 2 metabolites in iCLAU786"]}"#
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use crate::report::message;
+use crate::runner::InputFormat;
+use crate::uniprot::PATTERN as UNIPROT_PATTERN;
+use crate::validators::{IssueCode, MEDIUM_UNITS, MODIFICATIONS};
+use argh::FromArgs;
+use serde_json::{json, Map, Value};
+use std::io::{self, Write};
+
+#[derive(Debug, FromArgs)]
+/// Print the expected structure of the built-in formats as JSON schemas.
+#[argh(subcommand, name = "schema-out")]
+pub struct SchemaOutArgs {
+    /// format to print the schema of, all of them (keyed by format) if not given
+    #[argh(option, short = 'f')]
+    format: Option<InputFormat>,
+}
+
+/// Formats with a schema, i.e., all but the data packages, which have one per
+/// resource in their `datapackage.json`.
+const FORMATS: [InputFormat; 13] = [
+    InputFormat::Prot,
+    InputFormat::TidyProt,
+    InputFormat::Met,
+    InputFormat::Flux,
+    InputFormat::EcUsage,
+    InputFormat::ThermoConc,
+    InputFormat::ThermoDg,
+    InputFormat::Medium,
+    InputFormat::StrainDesign,
+    InputFormat::Rna,
+    InputFormat::Counts,
+    InputFormat::FeatureTable,
+    InputFormat::Multi,
+];
+
+fn non_empty() -> Value {
+    json!({"type": "string", "minLength": 1})
+}
+
+fn uniprot() -> Value {
+    json!({
+        "type": "string",
+        "pattern": UNIPROT_PATTERN,
+        "errorMessage": message(IssueCode::InvalidUniprotId),
+    })
+}
+
+fn number() -> Value {
+    json!({"type": "number"})
+}
+
+/// Schema of the rows of a format with header, an object per row as in the
+/// samplesheets of nf-core pipelines; `extra` is the schema of the other
+/// columns, `false` if the format has a fixed set of columns.
+fn rows(columns: &[(&str, Value)], required: &[&str], extra: Value) -> Value {
+    let properties: Map<String, Value> = columns
+        .iter()
+        .map(|(name, schema)| (name.to_string(), schema.clone()))
+        .collect();
+    json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": extra,
+        },
+    })
+}
+
+/// Columns and constraints of `format` that can be told without a model or
+/// the data itself; the identifiers in models, the sums of the samples or the
+/// FASTQ files are left to `validate`.
+fn items(format: &InputFormat) -> Option<Value> {
+    Some(match format {
+        // without header, an array of values per row
+        InputFormat::Prot => json!({
+            "type": "array",
+            "items": {
+                "type": "array",
+                "items": [uniprot()],
+                "additionalItems": number(),
+                "minItems": 2,
+            },
+        }),
+        InputFormat::TidyProt => rows(
+            &[
+                ("uniprot", uniprot()),
+                ("sample", non_empty()),
+                ("value", number()),
+            ],
+            &["uniprot", "sample", "value"],
+            json!(false),
+        ),
+        InputFormat::Met => rows(
+            &[
+                ("met_id", non_empty()),
+                ("sample", non_empty()),
+                ("value", number()),
+                ("compartment", json!({"type": "string"})),
+            ],
+            &["met_id", "sample", "value"],
+            json!(false),
+        ),
+        InputFormat::Flux => rows(
+            &[
+                ("reaction_id", non_empty()),
+                ("sample", non_empty()),
+                ("value", number()),
+                ("lb", number()),
+                ("ub", number()),
+                ("sd", json!({"type": "number", "minimum": 0})),
+            ],
+            &["reaction_id", "sample", "value"],
+            json!(false),
+        ),
+        InputFormat::EcUsage => rows(
+            &[
+                ("uniprot", uniprot()),
+                ("reaction_id", non_empty()),
+                ("sample", non_empty()),
+                ("usage", json!({"type": "number", "minimum": 0})),
+            ],
+            &["uniprot", "reaction_id", "sample", "usage"],
+            json!(false),
+        ),
+        InputFormat::ThermoConc => rows(
+            &[
+                ("met_id", non_empty()),
+                ("compartment", non_empty()),
+                ("conc_lb", json!({"type": "number", "exclusiveMinimum": 0})),
+                ("conc_ub", json!({"type": "number", "exclusiveMinimum": 0})),
+            ],
+            &["met_id", "compartment", "conc_lb", "conc_ub"],
+            json!(false),
+        ),
+        InputFormat::ThermoDg => rows(
+            &[
+                ("reaction_id", non_empty()),
+                ("dg0", number()),
+                ("dg0_uncertainty", json!({"type": "number", "minimum": 0})),
+            ],
+            &["reaction_id", "dg0"],
+            json!(false),
+        ),
+        InputFormat::Medium => rows(
+            &[
+                ("exchange_reaction_or_met_id", non_empty()),
+                ("concentration", json!({"type": "number", "minimum": 0})),
+                ("unit", json!({"type": "string", "enum": MEDIUM_UNITS})),
+            ],
+            &["exchange_reaction_or_met_id", "concentration", "unit"],
+            json!(false),
+        ),
+        InputFormat::StrainDesign => rows(
+            &[
+                ("strain", non_empty()),
+                ("gene_or_reaction_id", non_empty()),
+                (
+                    "modification",
+                    json!({"type": "string", "enum": MODIFICATIONS}),
+                ),
+            ],
+            &["strain", "gene_or_reaction_id", "modification"],
+            json!(false),
+        ),
+        InputFormat::Rna => rows(
+            &[
+                ("Experiment", non_empty()),
+                ("Run", non_empty()),
+                (
+                    "LibraryLayout",
+                    json!({"type": "string", "enum": ["PAIRED", "SINGLE"]}),
+                ),
+                (
+                    "Platform",
+                    json!({"type": "string", "enum": ["ILLUMINA", "BGISEQ", "ABI_SOLID", "PACBIO_SMRT"]}),
+                ),
+                ("R1", json!({"type": "string", "format": "file-path"})),
+                ("R2", json!({"type": "string", "format": "file-path"})),
+            ],
+            &["Experiment", "LibraryLayout", "Platform"],
+            json!(true),
+        ),
+        // a column per sample
+        InputFormat::Counts => rows(
+            &[("gene_id", non_empty())],
+            &["gene_id"],
+            json!({"type": "integer", "minimum": 0}),
+        ),
+        InputFormat::FeatureTable => rows(
+            &[
+                ("feature_id", non_empty()),
+                ("mz", json!({"type": "number", "exclusiveMinimum": 0})),
+                ("rt", json!({"type": "number", "minimum": 0})),
+                ("sample", non_empty()),
+                ("intensity", json!({"type": "number", "minimum": 0})),
+                ("adduct", json!({"type": "string"})),
+                ("formula", json!({"type": "string"})),
+                ("neutral_mass", number()),
+            ],
+            &["feature_id", "mz", "rt", "sample", "intensity"],
+            json!(false),
+        ),
+        InputFormat::Multi => rows(
+            &[
+                (
+                    "omics_type",
+                    json!({"type": "string", "enum": ["prot", "met", "gene", "flux"]}),
+                ),
+                ("id", non_empty()),
+                ("sample", non_empty()),
+                ("value", number()),
+            ],
+            &["omics_type", "id", "sample", "value"],
+            json!(false),
+        ),
+        InputFormat::Datapackage => return None,
+    })
+}
+
+/// JSON schema (draft-07, as used by nf-core) of the rows of `format`.
+pub fn schema(format: &InputFormat) -> Option<Value> {
+    let mut schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema",
+        "title": format!("omics_valid {}", format),
+        "description": format!("Rows of the {} format of omics_valid", format),
+    });
+    if let (Value::Object(schema), Value::Object(items)) = (&mut schema, items(format)?) {
+        schema.extend(items);
+    }
+    Some(schema)
+}
+
+pub fn run(args: SchemaOutArgs) -> io::Result<()> {
+    let schemas = match &args.format {
+        Some(format) => schema(format).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a data package has a schema per resource in its datapackage.json",
+            )
+        })?,
+        None => Value::Object(
+            FORMATS
+                .iter()
+                .filter_map(|format| Some((format.to_string(), schema(format)?)))
+                .collect(),
+        ),
+    };
+    let json = serde_json::to_string_pretty(&schemas).expect("schemas are serializable");
+    writeln!(io::stdout(), "{}", json)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_schemas_describe_the_columns() {
+        let schema = schema(&InputFormat::TidyProt).unwrap();
+        assert_eq!(schema["type"], "array");
+        assert_eq!(
+            schema["items"]["required"],
+            json!(["uniprot", "sample", "value"])
+        );
+        assert_eq!(schema["items"]["additionalProperties"], false);
+        assert_eq!(
+            schema["items"]["properties"]["uniprot"]["pattern"],
+            UNIPROT_PATTERN
+        );
+        let counts = super::schema(&InputFormat::Counts).unwrap();
+        assert_eq!(counts["items"]["additionalProperties"]["type"], "integer");
+        assert!(super::schema(&InputFormat::Datapackage).is_none());
+    }
+}
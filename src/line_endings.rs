@@ -0,0 +1,165 @@
+use crate::validators::{IssueCode, LineError, ValidationIssue};
+use std::borrow::Cow;
+use std::io::{self, Read};
+
+/// Line endings of an input, observed while it is read.
+#[derive(Debug, Default)]
+pub struct Scan {
+    /// current line, 0-based
+    line: usize,
+    /// the last byte was a carriage return
+    after_cr: bool,
+    crlf: usize,
+    lf: usize,
+    first_crlf: Option<usize>,
+    first_lf: Option<usize>,
+    /// lines with a carriage return that does not end them
+    stray: Vec<usize>,
+}
+
+impl Scan {
+    fn push(&mut self, byte: u8) {
+        if self.after_cr {
+            self.after_cr = false;
+            if byte == b'\n' {
+                self.crlf += 1;
+                self.first_crlf.get_or_insert(self.line + 1);
+                self.line += 1;
+                return;
+            }
+            self.stray_cr();
+        }
+        match byte {
+            b'\r' => self.after_cr = true,
+            b'\n' => {
+                self.lf += 1;
+                self.first_lf.get_or_insert(self.line + 1);
+                self.line += 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn stray_cr(&mut self) {
+        if self.stray.last() != Some(&(self.line + 1)) {
+            self.stray.push(self.line + 1);
+        }
+    }
+
+    /// Errors for the lines with stray carriage returns, and a warning on the
+    /// first line whose ending differs from the first one's if they are mixed.
+    pub fn issues(mut self) -> Vec<LineError> {
+        if self.after_cr {
+            self.stray_cr();
+        }
+        let mut error_vec: Vec<LineError> = self
+            .stray
+            .iter()
+            .map(|&line| {
+                LineError::error(
+                    line,
+                    ValidationIssue::new(IssueCode::StrayCarriageReturn).detail(
+                        "a carriage return (\\r) that does not end the line, e.g. pasted from a \
+                         spreadsheet cell; --fix removes it",
+                    ),
+                )
+            })
+            .collect();
+        if let (Some(first_crlf), Some(first_lf)) = (self.first_crlf, self.first_lf) {
+            error_vec.push(LineError::warning(
+                first_crlf.max(first_lf),
+                ValidationIssue::new(IssueCode::MixedLineEndings).detail(format!(
+                    "{} lines end with CRLF and {} with LF; --fix ends them all like the first",
+                    self.crlf, self.lf
+                )),
+            ));
+        }
+        error_vec.sort_by_key(|e| e.line);
+        error_vec
+    }
+}
+
+/// Reader recording the line endings of what is read through it in `scan`.
+pub struct Watched<'a, R> {
+    inner: R,
+    scan: &'a mut Scan,
+}
+
+pub fn watch<R: Read>(inner: R, scan: &mut Scan) -> Watched<'_, R> {
+    Watched { inner, scan }
+}
+
+impl<R: Read> Read for Watched<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        for &byte in &buf[..n] {
+            self.scan.push(byte);
+        }
+        Ok(n)
+    }
+}
+
+/// The content with the stray carriage returns removed and every line ending
+/// like the first one, borrowed if it already does.
+pub fn normalize(content: &[u8]) -> Cow<'_, [u8]> {
+    let mut scan = Scan::default();
+    content.iter().for_each(|&byte| scan.push(byte));
+    let mixed = scan.first_crlf.is_some() && scan.first_lf.is_some();
+    if !mixed && scan.stray.is_empty() && !scan.after_cr {
+        return Cow::Borrowed(content);
+    }
+    let crlf = match (scan.first_crlf, scan.first_lf) {
+        (Some(crlf), Some(lf)) => crlf < lf,
+        (crlf, _) => crlf.is_some(),
+    };
+    let mut normalized = Vec::with_capacity(content.len());
+    for line in content.split_inclusive(|&b| b == b'\n') {
+        let (text, ended) = match line.strip_suffix(b"\n") {
+            Some(text) => (text, true),
+            None => (line, false),
+        };
+        normalized.extend(text.iter().filter(|&&b| b != b'\r'));
+        if ended {
+            normalized.extend_from_slice(if crlf { b"\r\n" } else { b"\n" });
+        }
+    }
+    Cow::Owned(normalized)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_line_endings_are_checked_and_normalized() {
+        let input = b"uniprot,sample,value\r\nQ00496\r,SIM1,1\r\nE0X97,SIM1,2\nP0A6F5,SIM1,3\r\n";
+        let mut scan = Scan::default();
+        let mut content = Vec::new();
+        // read in small chunks, which may split a CRLF
+        let mut reader = watch(&input[..], &mut scan);
+        let mut buf = [0; 3];
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            content.extend_from_slice(&buf[..n]);
+        }
+        let error_vec = scan.issues();
+        assert_eq!(
+            error_vec
+                .iter()
+                .map(|e| (e.line, e.issues[0].code))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, IssueCode::StrayCarriageReturn),
+                (3, IssueCode::MixedLineEndings)
+            ]
+        );
+        assert_eq!(
+            normalize(&content).as_ref(),
+            b"uniprot,sample,value\r\nQ00496,SIM1,1\r\nE0X97,SIM1,2\r\nP0A6F5,SIM1,3\r\n"
+        );
+        assert!(matches!(normalize(b"a\nb\n"), Cow::Borrowed(_)));
+    }
+}
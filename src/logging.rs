@@ -0,0 +1,22 @@
+use strum::EnumString;
+use tracing::Level;
+
+/// Format of the log events.
+#[derive(Debug, Clone, Copy, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    /// one JSON object per line, for workflow orchestrators
+    Json,
+}
+
+/// Write the events up to `level` to stderr, leaving stdout to the reports and data.
+pub fn init(level: Level, format: LogFormat) {
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(std::io::stderr);
+    match format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder.json().init(),
+    }
+}
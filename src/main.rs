@@ -1,24 +1,99 @@
 use std::process;
 
+mod accessions;
+mod allowed;
+mod archive;
+mod batch;
+mod bench;
+mod bom;
+mod bundle;
+mod cache;
+mod certificate;
+mod chemistry;
+mod codeclimate;
+mod compression;
+mod config;
+mod confounding;
+mod convert;
+mod counts;
+mod coverage;
+mod crosscheck;
+mod datapackage;
+mod deferred;
+mod diff;
+mod example;
+mod excel;
+mod experiments;
+mod explain;
+mod fastq;
+mod features;
+mod fetch;
+mod imodulon;
+mod jsonl;
+mod jsonschema;
+mod line_endings;
+mod logging;
+mod meta;
+mod missing;
+mod models;
+mod multiqc;
+mod naming;
+mod normalize;
+mod numbers;
+mod ontology;
+mod outliers;
+mod paths;
+mod plugins;
+mod preview;
+mod profile;
+mod proteomics;
+mod redact;
+mod remote;
+mod replicates;
+mod report;
+mod rewrite;
 mod runner;
+mod scale;
+mod schema;
+mod serve;
+mod sqlite;
+mod stats;
+mod suppressions;
+mod tidy;
+mod transposed;
+mod uniprot;
+mod uniprot_api;
+mod unique;
 mod validators;
-use runner::{run, Args, InputFormat};
+mod wells;
+use runner::run;
 
 const VERSION_STR: &str = concat!("omics_valid v", env!("CARGO_PKG_VERSION"));
 
 fn main() {
-    let args: Args = argh::from_env();
+    let args = runner::from_env();
     if args.version {
         println!("{}", VERSION_STR);
         process::exit(0);
     }
-    if let (&None, &InputFormat::Met) = (&args.model, &args.format) {}
-    if let Err(err) = run(args) {
-        // If there is no message, don't print it this will happen where
-        // validation errors were found (printed to stdout)
-        if err.to_string() != "" {
-            eprintln!("{}", err);
+    logging::init(args.log_level, args.log_format);
+    let redact_paths = args.redact_paths;
+    match run(args) {
+        Ok(Some(report)) => {
+            report.present();
+            if report.failed() {
+                process::exit(1);
+            }
+        }
+        Ok(None) => {}
+        Err(err) => {
+            let message = err.to_string();
+            if redact_paths {
+                eprintln!("{}", redact::apply(&message));
+            } else {
+                eprintln!("{}", message);
+            }
+            process::exit(1);
         }
-        process::exit(1);
     }
 }
@@ -0,0 +1,102 @@
+use crate::crosscheck;
+use crate::runner::InputFormat;
+use csv::ReaderBuilder;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::Path;
+
+const TOOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Compact descriptor of a valid dataset, attached by DataLad run records and
+/// DVC stages to the file they produced.
+#[derive(Debug, Serialize)]
+pub struct Metadata {
+    format: String,
+    /// of the file as stored, like the certificate
+    file_sha256: String,
+    records: usize,
+    samples: Vec<String>,
+    /// identifiers.org prefix of the identifiers, if the format has a single one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    namespace: Option<&'static str>,
+    tool_version: &'static str,
+}
+
+/// Namespace of the identifiers of a format, guessed from the first one for
+/// the metabolites, which may come from BiGG or MetaNetX.
+fn namespace(format: &InputFormat, first_id: Option<&str>) -> Option<&'static str> {
+    match format {
+        InputFormat::Prot | InputFormat::TidyProt | InputFormat::EcUsage => Some("uniprot"),
+        InputFormat::Met | InputFormat::ThermoConc => match first_id {
+            Some(id) if id.starts_with("MNXM") => Some("metanetx.chemical"),
+            Some(_) => Some("bigg.metabolite"),
+            None => None,
+        },
+        InputFormat::Flux | InputFormat::ThermoDg => Some("bigg.reaction"),
+        InputFormat::Rna => Some("insdc.sra"),
+        _ => None,
+    }
+}
+
+impl Metadata {
+    /// Describe the decompressed `content` of a file, whose hash is given
+    /// with [`Metadata::with_hash`].
+    pub fn describe(content: &[u8], format: &InputFormat) -> io::Result<Self> {
+        let mut rdr = ReaderBuilder::new()
+            .delimiter(format.delimiter())
+            .has_headers(format.has_headers())
+            .flexible(true)
+            .from_reader(content);
+        let mut records = 0;
+        let mut first_id = None;
+        for record in rdr.records() {
+            let record = record?;
+            if first_id.is_none() {
+                first_id = record.get(0).map(str::to_string);
+            }
+            records += 1;
+        }
+        // the prot format has no sample names
+        let samples = crosscheck::samples(content, format)
+            .map(|samples| samples.into_keys().collect())
+            .unwrap_or_default();
+        Ok(Metadata {
+            format: format.to_string(),
+            file_sha256: String::new(),
+            records,
+            samples,
+            namespace: namespace(format, first_id.as_deref()),
+            tool_version: TOOL_VERSION,
+        })
+    }
+
+    pub fn with_hash(self, file_sha256: String) -> Self {
+        Metadata {
+            file_sha256,
+            ..self
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(&mut file, self)?;
+        writeln!(file)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_dataset_is_described() {
+        let content = std::fs::read("tests/uni_tidy.csv").unwrap();
+        let metadata = Metadata::describe(&content, &InputFormat::TidyProt).unwrap();
+        assert_eq!(metadata.namespace, Some("uniprot"));
+        assert!(metadata.records > 0);
+        assert!(!metadata.samples.is_empty());
+        let metadata = Metadata::describe(b"MNXM1,S1,1\n", &InputFormat::Prot).unwrap();
+        assert_eq!(metadata.records, 1);
+        assert!(metadata.samples.is_empty());
+    }
+}
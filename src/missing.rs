@@ -0,0 +1,102 @@
+use crate::validators::{IssueCode, LineError, ValidationIssue};
+use csv::ReaderBuilder;
+use std::collections::BTreeMap;
+
+/// Values counted as missing, besides empty fields (compared case-insensitively).
+const MISSING_VALUES: [&str; 4] = ["na", "nan", "null", "n/a"];
+
+/// Highest fractions of missing values, from the command line and the
+/// `[max_missing]` table of the configuration.
+#[derive(Debug, Clone, Default)]
+pub struct MissingOptions {
+    /// fraction allowed in every column, not checked if `None`
+    pub max_rate: Option<f64>,
+    /// column -> fraction allowed, taking precedence over `max_rate`
+    pub columns: BTreeMap<String, f64>,
+}
+
+impl MissingOptions {
+    fn max_rate(&self, column: &str) -> Option<f64> {
+        self.columns.get(column).copied().or(self.max_rate)
+    }
+
+    /// Whether any column is checked.
+    pub fn enabled(&self) -> bool {
+        self.max_rate.is_some() || !self.columns.is_empty()
+    }
+}
+
+pub(crate) fn is_missing(value: &str) -> bool {
+    let value = value.trim();
+    value.is_empty() || MISSING_VALUES.iter().any(|m| value.eq_ignore_ascii_case(m))
+}
+
+/// Report, at line 0, the columns with a higher fraction of missing values
+/// than allowed.
+pub fn check(content: &[u8], delimiter: u8, options: &MissingOptions) -> Vec<LineError> {
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content);
+    let headers = match rdr.headers() {
+        Ok(headers) => headers.clone(),
+        Err(_) => return Vec::new(),
+    };
+    let mut missing = vec![0usize; headers.len()];
+    let mut n_rows = 0;
+    // unreadable records are reported by the validation of the format
+    for record in rdr.records().flatten() {
+        n_rows += 1;
+        for (i, n) in missing.iter_mut().enumerate() {
+            if record.get(i).is_none_or(is_missing) {
+                *n += 1;
+            }
+        }
+    }
+    if n_rows == 0 {
+        return Vec::new();
+    }
+    headers
+        .iter()
+        .zip(missing)
+        .filter_map(|(column, n)| {
+            let max_rate = options.max_rate(column)?;
+            let rate = n as f64 / n_rows as f64;
+            (rate > max_rate).then(|| {
+                LineError::error(
+                    0,
+                    ValidationIssue::new(IssueCode::MissingRate)
+                        .field(column)
+                        .value(column)
+                        .detail(format!(
+                            "{:.1}% missing ({} of {}), more than {}%",
+                            rate * 100.0,
+                            n,
+                            n_rows,
+                            max_rate * 100.0
+                        )),
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_empty_channels_are_reported() {
+        let input = "gene_id\tS1\tS2\nb0001\t10\t\nb0002\t125\tNA\nb0003\t\t3\n";
+        let options = MissingOptions {
+            max_rate: Some(0.5),
+            columns: [("S1".to_string(), 0.0)].into_iter().collect(),
+        };
+        let error_vec = check(input.as_bytes(), b'\t', &options);
+        let columns: Vec<_> = error_vec
+            .iter()
+            .map(|e| e.issues[0].field.as_deref().unwrap())
+            .collect();
+        assert_eq!(columns, ["S1", "S2"]);
+    }
+}
@@ -0,0 +1,835 @@
+use crate::bench::{Bench, Phase};
+use crate::compression;
+use crate::uniprot;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::{debug, info};
+
+/// Source of valid identifiers: an SBML model or a universal namespace.
+struct Model {
+    name: String,
+    identifiers: HashSet<String>,
+    /// names of the gene products: their id, label and locus tags
+    genes: HashSet<String>,
+    /// UniProt accessions of the gene products
+    uniprot: HashSet<String>,
+    /// identifiers found in this model
+    matched: AtomicUsize,
+    /// identifiers found in this model and no other
+    only: AtomicUsize,
+    /// measured genes found in the gene products of this model
+    genes_matched: AtomicUsize,
+    /// species of an SBML model, none for a universal namespace
+    species: Vec<Species>,
+    /// compartments of the species of each identifier
+    compartments: HashMap<String, BTreeSet<String>>,
+    /// ids of the reactions of an SBML model
+    reactions: HashSet<String>,
+    /// groups of reactions of an SBML model, its subsystems
+    subsystems: Vec<Group>,
+    /// reactions consuming the protein pseudo-metabolite of each UniProt
+    /// accession of an enzyme-constrained model
+    enzymes: BTreeMap<String, BTreeSet<String>>,
+    /// species of the exchange reactions, by reaction id
+    exchanges: BTreeMap<String, String>,
+}
+
+impl Model {
+    fn new(name: String, identifiers: HashSet<String>) -> Self {
+        Model {
+            name,
+            identifiers,
+            genes: HashSet::new(),
+            uniprot: HashSet::new(),
+            matched: AtomicUsize::new(0),
+            only: AtomicUsize::new(0),
+            genes_matched: AtomicUsize::new(0),
+            species: Vec::new(),
+            compartments: HashMap::new(),
+            reactions: HashSet::new(),
+            subsystems: Vec::new(),
+            enzymes: BTreeMap::new(),
+            exchanges: BTreeMap::new(),
+        }
+    }
+
+    fn with_species(mut self, species: Vec<Species>) -> Self {
+        for sp in &species {
+            for id in std::iter::once(&sp.id).chain(&sp.identifiers) {
+                self.compartments
+                    .entry(id.clone())
+                    .or_default()
+                    .insert(sp.compartment.clone());
+            }
+        }
+        self.species = species;
+        self
+    }
+
+    /// The reactions and their groups, keeping the reactions of the groups,
+    /// which may also hold species or genes, and the groups with any.
+    fn with_reactions(mut self, reactions: HashSet<String>, groups: Vec<Group>) -> Self {
+        self.subsystems = groups
+            .into_iter()
+            .map(|mut group| {
+                group.members.retain(|id| reactions.contains(id));
+                group
+            })
+            .filter(|group| !group.members.is_empty())
+            .collect();
+        self.reactions = reactions;
+        self
+    }
+
+    fn with_enzymes(mut self, enzymes: BTreeMap<String, BTreeSet<String>>) -> Self {
+        self.enzymes = enzymes;
+        self
+    }
+
+    fn with_exchanges(mut self, exchanges: BTreeMap<String, String>) -> Self {
+        self.exchanges = exchanges;
+        self
+    }
+
+    fn with_gene_products(mut self, gene_products: &[GeneProduct]) -> Self {
+        for gene_product in gene_products {
+            self.genes.extend(gene_product.names());
+            self.uniprot.extend(gene_product.accessions("uniprot"));
+        }
+        self
+    }
+}
+
+/// Gene product of the SBML fbc package, the genes of the gene-reaction rules.
+#[derive(Debug, Default, PartialEq)]
+struct GeneProduct {
+    id: String,
+    label: Option<String>,
+    /// namespace and identifier of each annotation, e.g., `("uniprot", "U5RTK1")`
+    annotations: Vec<(String, String)>,
+}
+
+impl GeneProduct {
+    fn accessions<'a>(&'a self, namespace: &'a str) -> impl Iterator<Item = String> + 'a {
+        self.annotations
+            .iter()
+            .filter(move |(ns, _)| ns == namespace)
+            .map(|(_, id)| id.clone())
+    }
+
+    /// Names a gene of this product may be measured as: the id and label,
+    /// with or without the `G_` prefix added by COBRApy, and the locus tags.
+    fn names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for name in std::iter::once(&self.id).chain(&self.label) {
+            names.push(name.clone());
+            if let Some(stripped) = name.strip_prefix("G_") {
+                names.push(stripped.to_string());
+            }
+        }
+        names.extend(self.accessions("refseq_locus_tag"));
+        names.extend(self.accessions("ncbigene"));
+        names
+    }
+}
+
+/// Namespace and identifier of an annotation URI, e.g.,
+/// `https://identifiers.org/uniprot/U5RTK1` or `http://identifiers.org/uniprot:U5RTK1`.
+fn split_uri(uri: &str) -> Option<(String, String)> {
+    let mut segments = uri.rsplit('/');
+    let last = segments.next()?;
+    let (namespace, id) = match last.split_once(':') {
+        Some((namespace, id)) if !namespace.is_empty() => (namespace, id),
+        _ => (segments.next()?, last),
+    };
+    Some((namespace.to_lowercase(), id.to_string()))
+}
+
+/// Species of an SBML model, for the coverage of the measured metabolites.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Species {
+    pub id: String,
+    pub compartment: String,
+    /// identifiers of its annotations, as in [`Sbml::species`]
+    pub identifiers: Vec<String>,
+}
+
+fn species<R: BufRead>(e: &BytesStart, reader: &Reader<R>) -> Result<Species, String> {
+    let mut species = Species::default();
+    for attr in e.attributes() {
+        let attr = attr.map_err(|e| e.to_string())?;
+        match attr.key {
+            b"id" => {
+                species.id = attr
+                    .unescape_and_decode_value(reader)
+                    .map_err(|e| e.to_string())?
+            }
+            b"compartment" => {
+                species.compartment = attr
+                    .unescape_and_decode_value(reader)
+                    .map_err(|e| e.to_string())?
+            }
+            _ => {}
+        }
+    }
+    Ok(species)
+}
+
+/// Group of the SBML groups package, e.g. the subsystems of the reactions
+/// exported by COBRApy.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Group {
+    /// name of the group, its id if it has none
+    pub name: String,
+    /// ids of the elements of the group
+    pub members: Vec<String>,
+}
+
+/// Value of the attribute `key` of an element, with or without prefix.
+fn attribute<R: BufRead>(
+    e: &BytesStart,
+    reader: &Reader<R>,
+    key: &[u8],
+) -> Result<Option<String>, String> {
+    for attr in e.attributes() {
+        let attr = attr.map_err(|e| e.to_string())?;
+        let local = attr.key.rsplit(|&b| b == b':').next().unwrap_or_default();
+        if local == key {
+            return attr
+                .unescape_and_decode_value(reader)
+                .map(Some)
+                .map_err(|e| e.to_string());
+        }
+    }
+    Ok(None)
+}
+
+fn group<R: BufRead>(e: &BytesStart, reader: &Reader<R>) -> Result<Group, String> {
+    let id = attribute(e, reader, b"id")?.unwrap_or_default();
+    let name = attribute(e, reader, b"name")?.filter(|n| !n.is_empty());
+    Ok(Group {
+        name: name.unwrap_or(id),
+        members: Vec::new(),
+    })
+}
+
+/// UniProt accession of a protein pseudo-metabolite of an enzyme-constrained
+/// (GECKO) model, e.g. `prot_P0A6T1` or `M_prot_P0A6T1_c`; the protein pool,
+/// `prot_pool`, is not one.
+fn protein(species: &str) -> Option<&str> {
+    let id = species.strip_prefix("M_").unwrap_or(species);
+    let accession = id.strip_prefix("prot_")?.split('_').next()?;
+    Some(accession).filter(|a| uniprot::parse(a).is_ok())
+}
+
+/// What validators need from an SBML model.
+#[derive(Debug, Default)]
+pub(crate) struct Sbml {
+    /// identifiers in the species annotations, i.e., the last segment of their URIs
+    species: HashSet<String>,
+    /// the species with their compartment and identifiers
+    species_list: Vec<Species>,
+    gene_products: Vec<GeneProduct>,
+    /// ids of the reactions
+    reactions: HashSet<String>,
+    groups: Vec<Group>,
+    /// ids of the reactions consuming each protein pseudo-metabolite, by
+    /// accession
+    enzymes: BTreeMap<String, BTreeSet<String>>,
+    /// species of the exchange reactions, by reaction id
+    exchanges: BTreeMap<String, String>,
+}
+
+fn gene_product<R: BufRead>(e: &BytesStart, reader: &Reader<R>) -> Result<GeneProduct, String> {
+    let mut gene_product = GeneProduct::default();
+    for attr in e.attributes() {
+        let attr = attr.map_err(|e| e.to_string())?;
+        let value = attr
+            .unescape_and_decode_value(reader)
+            .map_err(|e| e.to_string())?;
+        match attr.key {
+            b"fbc:id" => gene_product.id = value,
+            b"fbc:label" => gene_product.label = Some(value),
+            _ => {}
+        }
+    }
+    Ok(gene_product)
+}
+
+/// Read the species annotations, the fbc gene products, the groups of
+/// reactions, the protein pseudo-metabolites and the exchange reactions (those
+/// with a single species, as in COBRApy) of an SBML model.
+/// Genome-scale models are large, so they are read in a single streaming pass
+/// instead of being parsed into a [`rust_sbml::ModelRaw`].
+pub(crate) fn read_sbml<R: BufRead>(input: R) -> Result<Sbml, String> {
+    let mut reader = Reader::from_reader(input);
+    let mut buf = Vec::new();
+    let mut sbml = Sbml::default();
+    let mut is_sbml = false;
+    let mut in_species = false;
+    let mut in_gene_product = false;
+    let mut in_group = false;
+    // reaction being read, whether in its reactants, and its species
+    let mut reaction: Option<String> = None;
+    let mut in_reactants = false;
+    let mut participants: Vec<String> = Vec::new();
+    loop {
+        match reader.read_event(&mut buf).map_err(|e| e.to_string())? {
+            Event::Start(e) if e.local_name() == b"sbml" => is_sbml = true,
+            Event::Start(e) if e.local_name() == b"species" => {
+                sbml.species_list.push(species(&e, &reader)?);
+                in_species = true;
+            }
+            Event::Empty(e) if e.local_name() == b"species" => {
+                sbml.species_list.push(species(&e, &reader)?);
+            }
+            Event::End(e) if e.local_name() == b"species" => in_species = false,
+            Event::Start(e) if e.local_name() == b"geneProduct" => {
+                sbml.gene_products.push(gene_product(&e, &reader)?);
+                in_gene_product = true;
+            }
+            Event::Empty(e) if e.local_name() == b"geneProduct" => {
+                sbml.gene_products.push(gene_product(&e, &reader)?);
+            }
+            Event::End(e) if e.local_name() == b"geneProduct" => in_gene_product = false,
+            Event::Start(e) if e.local_name() == b"reaction" => {
+                reaction = attribute(&e, &reader, b"id")?;
+                sbml.reactions.extend(reaction.clone());
+            }
+            Event::Empty(e) if e.local_name() == b"reaction" => {
+                sbml.reactions.extend(attribute(&e, &reader, b"id")?);
+            }
+            Event::End(e) if e.local_name() == b"reaction" => {
+                if let (Some(reaction), [species]) = (reaction.take(), participants.as_slice()) {
+                    sbml.exchanges.insert(reaction, species.clone());
+                }
+                participants.clear();
+            }
+            Event::Start(e) if e.local_name() == b"listOfReactants" => in_reactants = true,
+            Event::End(e) if e.local_name() == b"listOfReactants" => in_reactants = false,
+            Event::Start(e) | Event::Empty(e)
+                if reaction.is_some() && e.local_name() == b"speciesReference" =>
+            {
+                if let (Some(species), Some(reaction)) =
+                    (attribute(&e, &reader, b"species")?, reaction.as_ref())
+                {
+                    if let Some(accession) = protein(&species).filter(|_| in_reactants) {
+                        sbml.enzymes
+                            .entry(accession.to_string())
+                            .or_default()
+                            .insert(reaction.clone());
+                    }
+                    participants.push(species);
+                }
+            }
+            Event::Start(e) if e.local_name() == b"group" => {
+                sbml.groups.push(group(&e, &reader)?);
+                in_group = true;
+            }
+            Event::End(e) if e.local_name() == b"group" => in_group = false,
+            Event::Start(e) | Event::Empty(e) if in_group && e.local_name() == b"member" => {
+                if let (Some(id), Some(group)) =
+                    (attribute(&e, &reader, b"idRef")?, sbml.groups.last_mut())
+                {
+                    group.members.push(id);
+                }
+            }
+            Event::Start(e) | Event::Empty(e) if in_species || in_gene_product => {
+                for attr in e.attributes() {
+                    let attr = attr.map_err(|e| e.to_string())?;
+                    if attr.key.ends_with(b":resource") {
+                        let uri = attr
+                            .unescape_and_decode_value(&reader)
+                            .map_err(|e| e.to_string())?;
+                        if in_species {
+                            if let Some(id) = uri.split('/').next_back() {
+                                sbml.species.insert(id.to_string());
+                                if let Some(species) = sbml.species_list.last_mut() {
+                                    species.identifiers.push(id.to_string());
+                                }
+                            }
+                        } else if let (Some(annotation), Some(gene_product)) =
+                            (split_uri(&uri), sbml.gene_products.last_mut())
+                        {
+                            gene_product.annotations.push(annotation);
+                        }
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    // the proteins that no reaction consumes, e.g. of a light ecModel
+    for species in &sbml.species_list {
+        if let Some(accession) = protein(&species.id) {
+            sbml.enzymes.entry(accession.to_string()).or_default();
+        }
+    }
+    if is_sbml {
+        Ok(sbml)
+    } else {
+        Err("not an SBML document".to_string())
+    }
+}
+
+/// Identifiers in the first column of a namespace dump, e.g., MetaNetX
+/// `chem_prop.tsv`. Comments (`#`) and the header are skipped. For the BiGG
+/// `bigg_models_metabolites.txt`, the `universal_bigg_id` (without compartment)
+/// is also accepted.
+fn namespace_identifiers(content: &str) -> HashSet<String> {
+    let mut lines = content
+        .lines()
+        .filter(|l| !l.starts_with('#') && !l.trim().is_empty())
+        .peekable();
+    let columns = match lines.peek().and_then(|l| l.split('\t').next()) {
+        Some("bigg_id") => 2,
+        Some("ID") => 1,
+        _ => 0,
+    };
+    if columns > 0 {
+        lines.next();
+    }
+    lines
+        .flat_map(|l| l.split('\t').take(columns.max(1)))
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect()
+}
+
+/// Gene identifiers of a GFF3 or GTF genome annotation: the `gene_id`
+/// attributes and the `ID` of the gene features, also without their `gene-` or
+/// `gene:` prefix.
+fn annotation_gene_ids(content: &str) -> HashSet<String> {
+    let mut ids = HashSet::new();
+    for line in content.lines().filter(|l| !l.starts_with('#')) {
+        let columns: Vec<&str> = line.split('\t').collect();
+        let (feature, attributes) = match columns.as_slice() {
+            [_, _, feature, _, _, _, _, _, attributes, ..] => (feature, attributes),
+            _ => continue,
+        };
+        for attribute in attributes.split(';').map(str::trim) {
+            // GFF3 uses `key=value`, GTF `key "value"`
+            let (key, value) = match attribute.split_once('=') {
+                Some(pair) => pair,
+                None => match attribute.split_once(' ') {
+                    Some((key, value)) => (key, value.trim_matches('"')),
+                    None => continue,
+                },
+            };
+            match key {
+                "gene_id" => {
+                    ids.insert(value.to_string());
+                }
+                "ID" if feature.ends_with("gene") => {
+                    ids.insert(value.to_string());
+                    if let Some(id) = value
+                        .strip_prefix("gene-")
+                        .or_else(|| value.strip_prefix("gene:"))
+                    {
+                        ids.insert(id.to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    ids
+}
+
+/// Models supplied with `-m` and namespaces with `--universal`; an identifier
+/// is accepted if it is in any of them. Gene identifiers are checked against
+/// the genome annotation instead, if given with `--annotation`.
+pub struct Models {
+    models: Vec<Model>,
+    genes: Option<HashSet<String>>,
+    /// measured genes looked up in the gene products of the models
+    genes_checked: AtomicUsize,
+    /// time of the lookups with `--bench`
+    bench: Bench,
+}
+
+impl Models {
+    pub fn load(paths: &[PathBuf], universal: &[PathBuf]) -> io::Result<Self> {
+        let mut models = paths
+            .iter()
+            .map(|path| {
+                info!(path = %path.display(), "loading SBML model");
+                let sbml = read_sbml(BufReader::new(File::open(path)?)).map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}: invalid SBML: {}", path.display(), e),
+                    )
+                })?;
+                debug!(
+                    path = %path.display(),
+                    identifiers = sbml.species.len(),
+                    gene_products = sbml.gene_products.len(),
+                    "model loaded"
+                );
+                Ok(Model::new(path.display().to_string(), sbml.species)
+                    .with_gene_products(&sbml.gene_products)
+                    .with_species(sbml.species_list)
+                    .with_reactions(sbml.reactions, sbml.groups)
+                    .with_enzymes(sbml.enzymes)
+                    .with_exchanges(sbml.exchanges))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+        for path in universal {
+            info!(path = %path.display(), "loading universal namespace");
+            let identifiers = namespace_identifiers(&std::fs::read_to_string(path)?);
+            debug!(path = %path.display(), identifiers = identifiers.len(), "namespace loaded");
+            models.push(Model::new(path.display().to_string(), identifiers));
+        }
+        Ok(Models {
+            models,
+            genes: None,
+            genes_checked: AtomicUsize::new(0),
+            bench: Bench::default(),
+        })
+    }
+
+    /// Account the time of the lookups to `bench`.
+    pub fn measure(&mut self, bench: Bench) {
+        self.bench = bench;
+    }
+
+    /// Check the gene identifiers against a GFF3 or GTF file, possibly compressed.
+    pub fn load_annotation(&mut self, path: &Path) -> io::Result<()> {
+        info!(path = %path.display(), "loading genome annotation");
+        let mut content = String::new();
+        compression::open(path)?.read_to_string(&mut content)?;
+        let genes = annotation_gene_ids(&content);
+        debug!(path = %path.display(), genes = genes.len(), "annotation loaded");
+        self.genes = Some(genes);
+        Ok(())
+    }
+
+    /// Whether `id` is a gene of the annotation, `None` without annotation.
+    /// Without annotation, the gene is looked up in the gene products of the
+    /// models for the summary instead: models only include metabolic genes.
+    pub fn contains_gene(&self, id: &str) -> Option<bool> {
+        match &self.genes {
+            Some(genes) => Some(genes.contains(id)),
+            None => {
+                self.contains_gene_product(id);
+                None
+            }
+        }
+    }
+
+    /// Whether `id` names a gene product of any model, `None` if the models
+    /// have no gene products. The matches are counted for the summary.
+    pub fn contains_gene_product(&self, id: &str) -> Option<bool> {
+        if self.models.iter().all(|m| m.genes.is_empty()) {
+            return None;
+        }
+        self.genes_checked.fetch_add(1, Ordering::Relaxed);
+        let mut found = false;
+        for model in self.models.iter().filter(|m| m.genes.contains(id)) {
+            model.genes_matched.fetch_add(1, Ordering::Relaxed);
+            found = true;
+        }
+        Some(found)
+    }
+
+    /// Whether `id` names a gene product of any model, as
+    /// [`Models::contains_gene_product`] but not counted for the summary.
+    pub fn names_gene_product(&self, id: &str) -> Option<bool> {
+        if self.models.iter().all(|m| m.genes.is_empty()) {
+            return None;
+        }
+        Some(self.models.iter().any(|m| m.genes.contains(id)))
+    }
+
+    /// Whether `accession` annotates a gene product of any model, `None` if
+    /// no gene product has UniProt annotations.
+    pub fn contains_uniprot(&self, accession: &str) -> Option<bool> {
+        self.has_uniprot()
+            .then(|| self.models.iter().any(|m| m.uniprot.contains(accession)))
+    }
+
+    /// UniProt accessions of the gene products of every model.
+    pub(crate) fn uniprot_accessions(&self) -> HashSet<String> {
+        self.models
+            .iter()
+            .flat_map(|m| m.uniprot.iter().cloned())
+            .collect()
+    }
+
+    /// Whether the gene products of any model have UniProt annotations.
+    pub fn has_uniprot(&self) -> bool {
+        self.models.iter().any(|m| !m.uniprot.is_empty())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    /// Whether the genes are checked against a genome annotation.
+    pub fn has_annotation(&self) -> bool {
+        self.genes.is_some()
+    }
+
+    /// Species of each SBML model, by name of the model.
+    pub(crate) fn species(&self) -> impl Iterator<Item = (&str, &[Species])> {
+        self.models
+            .iter()
+            .filter(|m| !m.species.is_empty())
+            .map(|m| (m.name.as_str(), m.species.as_slice()))
+    }
+
+    /// Subsystems (groups of reactions) of each SBML model that has any, by
+    /// name of the model.
+    pub(crate) fn subsystems(&self) -> impl Iterator<Item = (&str, &[Group])> {
+        self.models
+            .iter()
+            .filter(|m| !m.subsystems.is_empty())
+            .map(|m| (m.name.as_str(), m.subsystems.as_slice()))
+    }
+
+    /// Compartments of the species of any model that `id` identifies, empty
+    /// if it is only in a universal namespace or nowhere.
+    pub fn compartments(&self, id: &str) -> BTreeSet<&str> {
+        self.models
+            .iter()
+            .filter_map(|m| m.compartments.get(id))
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Whether the reaction `id` is in any model, given with or without the
+    /// `R_` prefix of the SBML ids; `None` if no model has reactions.
+    pub fn contains_reaction(&self, id: &str) -> Option<bool> {
+        let with_prefix = format!("R_{}", id);
+        self.models
+            .iter()
+            .any(|m| !m.reactions.is_empty())
+            .then(|| {
+                self.models
+                    .iter()
+                    .any(|m| m.reactions.contains(id) || m.reactions.contains(&with_prefix))
+            })
+    }
+
+    /// Whether any model has exchange reactions.
+    pub fn has_exchanges(&self) -> bool {
+        self.models.iter().any(|m| !m.exchanges.is_empty())
+    }
+
+    /// Whether `id` is an exchange reaction of any model, or the species it
+    /// exchanges, with or without the `R_` and `M_` prefixes of the SBML ids;
+    /// `None` if no model has exchange reactions.
+    pub fn is_exchange(&self, id: &str) -> Option<bool> {
+        let (reaction_id, species_id) = (format!("R_{}", id), format!("M_{}", id));
+        self.has_exchanges().then(|| {
+            self.models
+                .iter()
+                .flat_map(|m| &m.exchanges)
+                .any(|(reaction, species)| {
+                    [id, reaction_id.as_str()].contains(&reaction.as_str())
+                        || [id, species_id.as_str()].contains(&species.as_str())
+                })
+        })
+    }
+
+    /// Whether any model is enzyme-constrained, with protein pseudo-metabolites.
+    pub fn has_enzymes(&self) -> bool {
+        self.models.iter().any(|m| !m.enzymes.is_empty())
+    }
+
+    /// Reactions of any model consuming the pseudo-metabolite of the protein
+    /// `accession`, `None` if no model has it.
+    pub fn enzyme_reactions(&self, accession: &str) -> Option<BTreeSet<&str>> {
+        let mut found = None;
+        for reactions in self.models.iter().filter_map(|m| m.enzymes.get(accession)) {
+            found
+                .get_or_insert_with(BTreeSet::new)
+                .extend(reactions.iter().map(String::as_str));
+        }
+        found
+    }
+
+    /// Whether `id` is in any model, counting the matches of each model.
+    pub fn contains(&self, id: &str) -> bool {
+        self.bench
+            .time(Phase::ModelLookup, || self.count_matches(id))
+    }
+
+    fn count_matches(&self, id: &str) -> bool {
+        let matching: Vec<&Model> = self
+            .models
+            .iter()
+            .filter(|m| m.identifiers.contains(id))
+            .collect();
+        for model in &matching {
+            model.matched.fetch_add(1, Ordering::Relaxed);
+        }
+        if let [model] = matching.as_slice() {
+            model.only.fetch_add(1, Ordering::Relaxed);
+        }
+        !matching.is_empty()
+    }
+
+    /// Which models matched the identifiers checked so far, only worth
+    /// reporting for more than one model, and how many of the measured genes
+    /// are gene products of each model.
+    pub fn report(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.models.len() > 1 {
+            lines.extend(self.models.iter().map(|m| {
+                format!(
+                    "model {}: matched {} identifiers ({} only in this model)",
+                    m.name,
+                    m.matched.load(Ordering::Relaxed),
+                    m.only.load(Ordering::Relaxed)
+                )
+            }));
+        }
+        let checked = self.genes_checked.load(Ordering::Relaxed);
+        if checked > 0 {
+            lines.extend(self.models.iter().filter(|m| !m.genes.is_empty()).map(|m| {
+                format!(
+                    "model {}: {} of {} genes are gene products of the model",
+                    m.name,
+                    m.genes_matched.load(Ordering::Relaxed),
+                    checked
+                )
+            }));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_identifiers_are_matched_in_any_model() {
+        let path = PathBuf::from("tests/iCLAU786.xml");
+        let models = Models::load(&[path.clone(), path], &[]).unwrap();
+        assert!(models.contains("glc__D"));
+        assert!(!models.contains("clearly_not_a_metabolite"));
+        let report = models.report();
+        assert_eq!(report.len(), 2);
+        assert!(report[0].ends_with("matched 1 identifiers (0 only in this model)"));
+        assert_eq!(
+            models
+                .compartments("glc__D")
+                .into_iter()
+                .collect::<Vec<_>>(),
+            ["c", "e"]
+        );
+        assert!(models.compartments("clearly_not_a_metabolite").is_empty());
+        for reaction in ["rxn00225_c0", "R_rxn00225_c0"] {
+            assert_eq!(models.contains_reaction(reaction), Some(true));
+        }
+        assert_eq!(models.contains_reaction("PGI"), Some(false));
+    }
+
+    #[test]
+    #[cfg(feature = "sbml")]
+    fn test_streamed_identifiers_match_the_parsed_model() {
+        let content = std::fs::read_to_string("tests/iCLAU786.xml").unwrap();
+        let model = rust_sbml::ModelRaw::parse(&content).unwrap();
+        let expected: HashSet<String> = model
+            .list_of_species
+            .species
+            .iter()
+            .filter_map(|sp| sp.annotation.as_ref())
+            .flat_map(|annot| annot.into_iter().filter_map(|rs| rs.split('/').next_back()))
+            .map(String::from)
+            .collect();
+        assert_eq!(read_sbml(content.as_bytes()).unwrap().species, expected);
+        assert!(read_sbml("<notsbml/>".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_gene_products_are_read_with_their_annotations() {
+        let sbml = read_sbml(BufReader::new(File::open("tests/iCLAU786.xml").unwrap())).unwrap();
+        assert_eq!(sbml.gene_products.len(), 784);
+        let gene_product = &sbml.gene_products[0];
+        assert_eq!(gene_product.id, "G_CAETHG_RS09090");
+        assert!(gene_product
+            .annotations
+            .contains(&("uniprot".to_string(), "U5RTK1".to_string())));
+        let models = Models::load(&[PathBuf::from("tests/iCLAU786.xml")], &[]).unwrap();
+        for gene in ["G_CAETHG_RS09090", "CAETHG_RS09090"] {
+            assert_eq!(models.contains_gene_product(gene), Some(true));
+        }
+        // the chromosome of every gene product is not a gene
+        assert_eq!(models.contains_gene("NC_022592.1"), None);
+        assert_eq!(models.contains_uniprot("U5RTK1"), Some(true));
+        assert_eq!(models.contains_uniprot("P0A9B2"), Some(false));
+        assert_eq!(
+            models.report(),
+            ["model tests/iCLAU786.xml: 2 of 3 genes are gene products of the model"]
+        );
+        let models = Models::load(&[], &[]).unwrap();
+        assert_eq!(models.contains_uniprot("U5RTK1"), None);
+    }
+
+    #[test]
+    fn test_protein_pseudo_metabolites_are_read() {
+        assert_eq!(protein("M_prot_P0A6T1_c"), Some("P0A6T1"));
+        assert_eq!(protein("prot_pool"), None);
+        let models = Models::load(&[PathBuf::from("tests/ec_model.xml")], &[]).unwrap();
+        assert!(models.has_enzymes());
+        // the reaction drawing the protein from the pool produces it
+        assert_eq!(
+            models.enzyme_reactions("P0A6T1"),
+            Some(BTreeSet::from(["R_PGI"]))
+        );
+        assert_eq!(models.enzyme_reactions("P0A9B2"), Some(BTreeSet::new()));
+        assert_eq!(models.enzyme_reactions("P0AB71"), None);
+    }
+
+    #[test]
+    fn test_exchange_reactions_are_read() {
+        let models = Models::load(&[PathBuf::from("tests/e_coli_core_groups.xml")], &[]).unwrap();
+        for id in ["EX_o2_e", "R_EX_o2_e", "o2_e", "M_o2_e"] {
+            assert_eq!(models.is_exchange(id), Some(true), "{}", id);
+        }
+        // a typo, and a reaction without species
+        assert_eq!(models.is_exchange("EX_02_e"), Some(false));
+        assert_eq!(models.is_exchange("PGI"), Some(false));
+        let models = Models::load(&[], &[]).unwrap();
+        assert_eq!(models.is_exchange("EX_o2_e"), None);
+    }
+
+    #[test]
+    fn test_genes_are_read_from_annotations() {
+        let mut models = Models::load(&[], &[]).unwrap();
+        assert_eq!(models.contains_gene("b0001"), None);
+        models
+            .load_annotation(Path::new("tests/genes.gff3"))
+            .unwrap();
+        for gene in ["b0001", "b0002", "gene-b0002", "b0003"] {
+            assert_eq!(models.contains_gene(gene), Some(true));
+        }
+        assert_eq!(models.contains_gene("cds-NP_414542.1"), Some(false));
+        models
+            .load_annotation(Path::new("tests/genes.gtf"))
+            .unwrap();
+        assert_eq!(models.contains_gene("b0001"), Some(true));
+        assert_eq!(models.contains_gene("b0001.t1"), Some(false));
+    }
+
+    #[test]
+    fn test_identifiers_are_matched_in_universal_namespace() {
+        let models = Models::load(&[], &[PathBuf::from("tests/bigg_metabolites.txt")]).unwrap();
+        assert!(models.contains("glc__D"));
+        assert!(models.contains("glc__D_e"));
+        assert!(!models.contains("universal_bigg_id"));
+        assert!(!models.contains("D-Glucose"));
+        assert_eq!(models.contains_reaction("PGI"), None);
+    }
+}
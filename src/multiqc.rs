@@ -0,0 +1,156 @@
+use crate::report::{Part, ValidationReport};
+use crate::validators::Severity;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A table of [MultiQC custom content](https://multiqc.info/docs/custom_content/),
+/// picked up by MultiQC from a file named `*_mqc.json`.
+#[derive(Debug, Serialize)]
+pub struct CustomContent {
+    id: &'static str,
+    section_name: &'static str,
+    description: String,
+    plot_type: &'static str,
+    pconfig: PlotConfig,
+    headers: BTreeMap<&'static str, Header>,
+    /// a row per validated file, archive member or resource
+    data: BTreeMap<String, Row>,
+}
+
+#[derive(Debug, Serialize)]
+struct PlotConfig {
+    id: &'static str,
+    title: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct Header {
+    title: &'static str,
+    description: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+struct Row {
+    errors: usize,
+    warnings: usize,
+    status: &'static str,
+}
+
+impl Row {
+    /// Counts of a part; with `--max-error-rate`, which only applies to a single
+    /// file, its errors may be tolerated by the `report`.
+    fn new(part: &Part, report: &ValidationReport) -> Self {
+        let count = |severity| {
+            part.issues
+                .iter()
+                .filter(|e| e.severity == severity)
+                .count()
+        };
+        let errors = count(Severity::Error);
+        let failed = match report.error_rate {
+            Some(_) => report.failed(),
+            None => errors > 0,
+        };
+        Row {
+            errors,
+            warnings: count(Severity::Warning),
+            status: if failed { "fail" } else { "pass" },
+        }
+    }
+}
+
+/// Custom content summarizing the report: the lines with errors and warnings of
+/// each part, with the summaries as the description of the section.
+pub fn custom_content(report: &ValidationReport) -> CustomContent {
+    let data = report
+        .parts
+        .iter()
+        .map(|part| {
+            let name = match (&report.input, &part.name) {
+                (Some(input), Some(name)) => format!("{} {}", input, name),
+                // the layers of crosscheck
+                (None, Some(name)) => name.clone(),
+                (Some(input), None) => input.clone(),
+                (None, None) => "stdin".to_string(),
+            };
+            (name, Row::new(part, report))
+        })
+        .collect();
+    let mut description = "Validation of the omics files by omics_valid.".to_string();
+    for line in &report.summary {
+        description += &format!(" {}.", line);
+    }
+    let headers = [
+        ("errors", "Errors", "lines with errors", Some("{:,.0f}")),
+        (
+            "warnings",
+            "Warnings",
+            "lines with warnings",
+            Some("{:,.0f}"),
+        ),
+        ("status", "Status", "pass if the file is valid", None),
+    ]
+    .into_iter()
+    .map(|(key, title, description, format)| {
+        (
+            key,
+            Header {
+                title,
+                description,
+                format,
+            },
+        )
+    })
+    .collect();
+    CustomContent {
+        id: "omics_valid",
+        section_name: "omics_valid",
+        description,
+        plot_type: "table",
+        pconfig: PlotConfig {
+            id: "omics_valid_table",
+            title: "omics_valid: validation",
+        },
+        headers,
+        data,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::validators::{IssueCode, LineError, ValidationIssue};
+
+    #[test]
+    fn test_parts_are_rows() {
+        let report = ValidationReport {
+            parts: vec![
+                Part {
+                    name: Some("member a.csv".to_string()),
+                    issues: vec![
+                        LineError::error(2, ValidationIssue::new(IssueCode::EmptySample)),
+                        LineError::warning(3, ValidationIssue::new(IssueCode::DecoyEntry)),
+                    ],
+                },
+                Part {
+                    name: Some("member b.csv".to_string()),
+                    issues: Vec::new(),
+                },
+            ],
+            summary: vec!["1 of 2 metabolites in iCLAU786".to_string()],
+            input: Some("bundle.zip".to_string()),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(custom_content(&report)).unwrap();
+        assert_eq!(json["plot_type"], "table");
+        assert_eq!(json["data"]["bundle.zip member a.csv"]["errors"], 1);
+        assert_eq!(json["data"]["bundle.zip member a.csv"]["status"], "fail");
+        assert_eq!(json["data"]["bundle.zip member b.csv"]["status"], "pass");
+        assert!(json["description"]
+            .as_str()
+            .unwrap()
+            .ends_with("1 of 2 metabolites in iCLAU786."));
+    }
+}
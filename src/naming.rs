@@ -0,0 +1,217 @@
+use crate::replicates::SAMPLE_COLUMNS;
+use crate::validators::{IssueCode, LineError, ValidationIssue};
+use csv::ReaderBuilder;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::io;
+
+/// Naming convention of the samples, the `[naming]` table of the configuration:
+///
+/// ```toml
+/// [naming]
+/// pattern = "{strain}_{condition}_{replicate}"
+///
+/// [naming.tokens]
+/// strain = ["WT", "dpgi"]
+/// condition = ["glc", "ac"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Naming {
+    /// `{token}`s separated by literal text
+    pub pattern: String,
+    /// token -> accepted values, any non-empty value is accepted if absent
+    pub tokens: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Literal(String),
+    Token(String),
+}
+
+/// Naming convention checked by [`check`], loaded with [`load`].
+#[derive(Debug, Clone)]
+pub struct Grammar {
+    pieces: Vec<Piece>,
+    tokens: BTreeMap<String, Vec<String>>,
+}
+
+impl Grammar {
+    /// Split the pattern into tokens and literals. Two tokens must be separated
+    /// by a literal, otherwise the names could not be split.
+    fn new(naming: Naming) -> Result<Self, String> {
+        let mut pieces = Vec::new();
+        let mut rest = naming.pattern.as_str();
+        while !rest.is_empty() {
+            match rest.find('{') {
+                Some(0) => {
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| format!("unclosed token in {}", naming.pattern))?;
+                    let token = &rest[1..end];
+                    if token.is_empty() || token.contains('{') {
+                        return Err(format!("invalid token in {}", naming.pattern));
+                    }
+                    if let Some(Piece::Token(previous)) = pieces.last() {
+                        return Err(format!(
+                            "{{{}}} and {{{}}} must be separated in {}",
+                            previous, token, naming.pattern
+                        ));
+                    }
+                    pieces.push(Piece::Token(token.to_string()));
+                    rest = &rest[end + 1..];
+                }
+                start => {
+                    let end = start.unwrap_or(rest.len());
+                    pieces.push(Piece::Literal(rest[..end].to_string()));
+                    rest = &rest[end..];
+                }
+            }
+        }
+        for token in naming.tokens.keys() {
+            if !pieces.contains(&Piece::Token(token.clone())) {
+                return Err(format!("{{{}}} is not in {}", token, naming.pattern));
+            }
+        }
+        Ok(Grammar {
+            pieces,
+            tokens: naming.tokens,
+        })
+    }
+
+    /// Components of a sample name by token, or what is malformed in it. A
+    /// token ends at the first occurrence of the literal that follows it.
+    fn parse<'a>(&'a self, name: &'a str) -> Result<Vec<(&'a str, &'a str)>, String> {
+        let mut components = Vec::new();
+        let mut rest = name;
+        for (i, piece) in self.pieces.iter().enumerate() {
+            match piece {
+                Piece::Literal(literal) => {
+                    rest = rest
+                        .strip_prefix(literal.as_str())
+                        .ok_or_else(|| format!("expected `{}` at `{}`", literal, rest))?;
+                }
+                Piece::Token(token) => {
+                    let end = match self.pieces.get(i + 1) {
+                        Some(Piece::Literal(literal)) => {
+                            rest.find(literal.as_str()).ok_or_else(|| {
+                                format!("{{{}}} is not followed by `{}`", token, literal)
+                            })?
+                        }
+                        _ => rest.len(),
+                    };
+                    let value = &rest[..end];
+                    if value.is_empty() {
+                        return Err(format!("empty {{{}}}", token));
+                    }
+                    if let Some(vocabulary) = self.tokens.get(token) {
+                        if !vocabulary.iter().any(|v| v == value) {
+                            return Err(format!(
+                                "{{{}}} {} is not one of {}",
+                                token,
+                                value,
+                                vocabulary.join(", ")
+                            ));
+                        }
+                    }
+                    components.push((token.as_str(), value));
+                    rest = &rest[end..];
+                }
+            }
+        }
+        if rest.is_empty() {
+            Ok(components)
+        } else {
+            Err(format!("unexpected `{}` at the end", rest))
+        }
+    }
+}
+
+/// The naming convention of the configuration, if any.
+pub fn load(naming: Option<Naming>) -> io::Result<Option<Grammar>> {
+    naming
+        .map(Grammar::new)
+        .transpose()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("[naming] {}", e)))
+}
+
+/// Report the sample names that do not follow the naming convention, once
+/// per name on the first line it appears.
+pub fn check(content: &[u8], delimiter: u8, grammar: &Grammar) -> Vec<LineError> {
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content);
+    let headers = match rdr.headers() {
+        Ok(headers) => headers.clone(),
+        Err(_) => return Vec::new(),
+    };
+    let (column, field) = match headers
+        .iter()
+        .enumerate()
+        .find(|(_, h)| SAMPLE_COLUMNS.contains(h))
+    {
+        Some(found) => found,
+        None => return Vec::new(),
+    };
+    let mut seen = HashSet::new();
+    let mut error_vec = Vec::new();
+    for (i, record) in rdr.records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        let name = record.get(column).unwrap_or_default();
+        if name.is_empty() || !seen.insert(name.to_string()) {
+            continue;
+        }
+        if let Err(detail) = grammar.parse(name) {
+            error_vec.push(LineError::error(
+                i + 2,
+                ValidationIssue::new(IssueCode::MalformedSampleName)
+                    .field(field)
+                    .value(name)
+                    .detail(detail),
+            ));
+        }
+    }
+    error_vec
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sample_names_are_parsed() {
+        let grammar = Grammar::new(Naming {
+            pattern: "{strain}_{condition}_{replicate}".to_string(),
+            tokens: [(
+                "strain".to_string(),
+                vec!["WT".to_string(), "dpgi".to_string()],
+            )]
+            .into_iter()
+            .collect(),
+        })
+        .unwrap();
+        assert_eq!(
+            grammar.parse("dpgi_glc_1").unwrap(),
+            [("strain", "dpgi"), ("condition", "glc"), ("replicate", "1")]
+        );
+        assert_eq!(
+            grammar.parse("MG1655_glc_1"),
+            Err("{strain} MG1655 is not one of WT, dpgi".to_string())
+        );
+        assert_eq!(
+            grammar.parse("WT_glc"),
+            Err("{condition} is not followed by `_`".to_string())
+        );
+        assert_eq!(grammar.parse("WT__1"), Err("empty {condition}".to_string()));
+        assert!(Grammar::new(Naming {
+            pattern: "{strain}{condition}".to_string(),
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
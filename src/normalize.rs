@@ -0,0 +1,169 @@
+use crate::diff::key_columns;
+use crate::runner::InputFormat;
+use crate::validators::ReadOptions;
+use csv::{ReaderBuilder, StringRecord, Trim, WriterBuilder};
+use std::io;
+
+/// Columns of each format in their standard order; the others follow in the
+/// order of the input.
+fn standard_columns(format: &InputFormat) -> &'static [&'static str] {
+    match format {
+        InputFormat::TidyProt => &["uniprot", "sample", "value"],
+        InputFormat::Met => &["met_id", "sample", "value"],
+        InputFormat::Flux => &["reaction_id", "sample", "value", "lb", "ub", "sd"],
+        InputFormat::EcUsage => &["uniprot", "reaction_id", "sample", "usage"],
+        InputFormat::ThermoConc => &["met_id", "compartment", "conc_lb", "conc_ub"],
+        InputFormat::ThermoDg => &["reaction_id", "dg0", "dg0_uncertainty"],
+        InputFormat::Medium => &["exchange_reaction_or_met_id", "concentration", "unit"],
+        InputFormat::StrainDesign => &["strain", "gene_or_reaction_id", "modification"],
+        InputFormat::Rna => &["Experiment", "Run", "LibraryLayout", "Platform", "R1", "R2"],
+        InputFormat::Counts => &["gene_id"],
+        InputFormat::FeatureTable => &[
+            "feature_id",
+            "mz",
+            "rt",
+            "sample",
+            "intensity",
+            "adduct",
+            "formula",
+            "neutral_mass",
+        ],
+        InputFormat::Multi => &["omics_type", "id", "sample", "value"],
+        InputFormat::Prot | InputFormat::Datapackage => &[],
+    }
+}
+
+/// Columns of accessions whose case does not matter (UniProt and SRA), which
+/// are uppercased. The identifiers of models and genomes are case-sensitive
+/// (`glc__D`, `b0001`) and only trimmed.
+const UPPERCASE_COLUMNS: [&str; 3] = ["uniprot", "Experiment", "Run"];
+
+/// Canonical form of a valid file, so that two versions of the data can be
+/// compared line by line: standard column order, trimmed fields, uppercased
+/// accessions, rows sorted by the key of the format and `\n` line endings.
+pub fn canonical(
+    content: &[u8],
+    format: &InputFormat,
+    options: &ReadOptions,
+) -> io::Result<Vec<u8>> {
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(format.delimiter())
+        .has_headers(format.has_headers())
+        .flexible(true)
+        .trim(Trim::All)
+        .from_reader(content);
+    let headers = if format.has_headers() {
+        options.rename_headers(&mut rdr);
+        rdr.headers()?.clone()
+    } else {
+        StringRecord::new()
+    };
+    let position = |name: &str| headers.iter().position(|h| h == name);
+    let standard: Vec<usize> = standard_columns(format)
+        .iter()
+        .filter_map(|c| position(c))
+        .collect();
+    // positions of the output columns in the input, `None` without header
+    let order: Option<Vec<usize>> = format.has_headers().then(|| {
+        let others = (0..headers.len()).filter(|i| !standard.contains(i));
+        standard.iter().copied().chain(others).collect()
+    });
+    let keys: Vec<usize> = match format {
+        // the identifier of the row
+        InputFormat::Prot | InputFormat::Counts => vec![0],
+        _ => key_columns(format)?
+            .iter()
+            .filter_map(|c| position(c))
+            .collect(),
+    };
+    let uppercase: Vec<usize> = match format {
+        InputFormat::Prot => vec![0],
+        _ => UPPERCASE_COLUMNS
+            .iter()
+            .filter_map(|c| position(c))
+            .collect(),
+    };
+    let multi_id = match format {
+        InputFormat::Multi => position("omics_type").zip(position("id")),
+        _ => None,
+    };
+    let mut rows = Vec::new();
+    for record in rdr.records() {
+        let record = record?;
+        // only the ids of the proteins are accessions
+        let prot_id = multi_id
+            .filter(|&(omics_type, _)| record.get(omics_type) == Some("prot"))
+            .map(|(_, id)| id);
+        let fields: Vec<String> = record
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                if uppercase.contains(&i) || prot_id == Some(i) {
+                    field.to_uppercase()
+                } else {
+                    field.to_string()
+                }
+            })
+            .collect();
+        rows.push(fields);
+    }
+    let key = |row: &Vec<String>| -> Vec<String> {
+        keys.iter()
+            .map(|&i| row.get(i).cloned().unwrap_or_default())
+            .collect()
+    };
+    // stable, the rows of the same key keep their order
+    rows.sort_by_cached_key(key);
+    let mut wtr = WriterBuilder::new()
+        .delimiter(format.delimiter())
+        .flexible(true)
+        .from_writer(Vec::new());
+    let reorder = |fields: Vec<String>| -> Vec<String> {
+        match &order {
+            Some(order) => order
+                .iter()
+                .map(|&i| fields.get(i).cloned().unwrap_or_default())
+                .collect(),
+            None => fields,
+        }
+    };
+    if format.has_headers() {
+        wtr.write_record(reorder(headers.iter().map(String::from).collect()))?;
+    }
+    for row in rows {
+        wtr.write_record(reorder(row))?;
+    }
+    wtr.into_inner()
+        .map_err(|e| io::Error::other(e.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_files_are_canonicalized() {
+        let input = "sample,uniprot,value\r\nSIM2 , q00496,2\r\nSIM1,Q7B2Q4,1\r\nSIM1,q00496,3\r\n";
+        let output = canonical(
+            input.as_bytes(),
+            &InputFormat::TidyProt,
+            &ReadOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "uniprot,sample,value\nQ00496,SIM1,3\nQ00496,SIM2,2\nQ7B2Q4,SIM1,1\n"
+        );
+        let input = "omics_type,id,sample,value\nmet,glc__D,S1,2\nprot,q00496,S1,1\n";
+        let output = canonical(
+            input.as_bytes(),
+            &InputFormat::Multi,
+            &ReadOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "omics_type,id,sample,value\nmet,glc__D,S1,2\nprot,Q00496,S1,1\n"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::validators::{IssueCode, ValidationIssue};
+use csv::StringRecord;
+use rust_decimal::Decimal;
+
+/// Significant digits of a number written in decimal, without leading and
+/// trailing zeros, and the power of ten of the first one. `None` if the text
+/// is not a decimal number (e.g. `NaN`, `inf` or an empty field).
+fn significant_digits(text: &str) -> Option<(String, i64)> {
+    let text = text.trim().trim_start_matches(['+', '-']);
+    let (mantissa, exponent) = match text.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => (mantissa, exponent.parse::<i64>().ok()?),
+        None => (text, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if integer.is_empty() && fraction.is_empty()
+        || !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let all = format!("{}{}", integer, fraction);
+    let leading = all.len() - all.trim_start_matches('0').len();
+    let digits = all.trim_matches('0').to_string();
+    Some((digits, exponent + integer.len() as i64 - leading as i64 - 1))
+}
+
+/// Issue of a numeric value that cannot be read without loss: a value out of
+/// the range of the representation (error) or with more digits than it keeps
+/// (warning). `None` if the value is exact or not a number at all, which is
+/// reported by the parsing of the record. The values are read as 28-digit
+/// decimals with `exact_decimal`, as 64-bit floats otherwise.
+pub fn check(text: &str, exact_decimal: bool) -> Option<ValidationIssue> {
+    if exact_decimal {
+        check_decimal(text)
+    } else {
+        check_float(text)
+    }
+}
+
+fn check_float(text: &str) -> Option<ValidationIssue> {
+    let (digits, _) = significant_digits(text)?;
+    let value = text.trim().parse::<f64>().ok()?;
+    if value.is_infinite() {
+        Some(
+            ValidationIssue::new(IssueCode::NumericOverflow)
+                .detail("above ±1.8e308 (64-bit float)"),
+        )
+    } else if value == 0.0 && !digits.is_empty() {
+        Some(ValidationIssue::new(IssueCode::NumericOverflow).detail("rounded to 0 (64-bit float)"))
+    } else {
+        // the shortest representation reading back as the same float
+        let (read, _) = significant_digits(&format!("{:e}", value))?;
+        (read != digits).then(|| {
+            ValidationIssue::new(IssueCode::PrecisionLoss).detail(format!(
+                "read as {} (64-bit float, about 16 significant digits)",
+                value
+            ))
+        })
+    }
+}
+
+fn check_decimal(text: &str) -> Option<ValidationIssue> {
+    let (digits, exponent) = significant_digits(text)?;
+    let text = text.trim();
+    let parsed = if text.contains(['e', 'E']) {
+        Decimal::from_scientific(text)
+    } else {
+        Decimal::from_str_exact(text)
+    };
+    match parsed {
+        // 7.9e28 is the largest decimal
+        Err(_) if exponent >= 28 => {
+            Some(ValidationIssue::new(IssueCode::NumericOverflow).detail("above ±7.9e28 (decimal)"))
+        }
+        Err(_) if exponent < -28 => {
+            Some(ValidationIssue::new(IssueCode::NumericOverflow).detail("rounded to 0 (decimal)"))
+        }
+        Err(_) => Some(
+            ValidationIssue::new(IssueCode::PrecisionLoss).detail("more than 28 digits (decimal)"),
+        ),
+        Ok(value) => {
+            let (read, _) = significant_digits(&value.to_string())?;
+            (read != digits).then(|| {
+                ValidationIssue::new(IssueCode::PrecisionLoss)
+                    .detail(format!("read as {} (decimal, 28 digits)", value))
+            })
+        }
+    }
+}
+
+/// Positions of the columns with the given names, `None` without header,
+/// where every column after the identifier is numeric (the prot format).
+pub fn columns(headers: Option<&StringRecord>, names: &[&str]) -> Option<Vec<usize>> {
+    headers.map(|headers| {
+        headers
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| names.contains(h))
+            .map(|(i, _)| i)
+            .collect()
+    })
+}
+
+/// Issues of the numeric fields of a record, as errors and warnings.
+pub fn issues(
+    record: &StringRecord,
+    headers: Option<&StringRecord>,
+    columns: Option<&[usize]>,
+    exact_decimal: bool,
+) -> (Vec<ValidationIssue>, Vec<ValidationIssue>) {
+    let positions: Box<dyn Iterator<Item = usize>> = match columns {
+        Some(columns) => Box::new(columns.iter().copied()),
+        None => Box::new(1..record.len()),
+    };
+    positions
+        .filter_map(|i| {
+            let text = record.get(i)?;
+            let field = match headers.and_then(|h| h.get(i)) {
+                Some(name) => name.to_string(),
+                None => format!("column {}", i + 1),
+            };
+            Some(check(text, exact_decimal)?.field(field).value(text))
+        })
+        .partition(|issue| issue.code == IssueCode::NumericOverflow)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_values_out_of_range_are_reported() {
+        assert_eq!(
+            significant_digits("-0.00120e3"),
+            Some(("12".to_string(), 0))
+        );
+        assert!(check_float("3.5e38").is_none());
+        assert!(check_float("0.1").is_none());
+        assert!(check_float("NA").is_none());
+        assert_eq!(
+            check_float("1e400").unwrap().code,
+            IssueCode::NumericOverflow
+        );
+        assert_eq!(
+            check_float("1e-400").unwrap().code,
+            IssueCode::NumericOverflow
+        );
+        assert_eq!(
+            check_float("123456789012345678901").unwrap().code,
+            IssueCode::PrecisionLoss
+        );
+        assert!(check_decimal("123456789012345678901").is_none());
+        assert_eq!(
+            check_decimal("1e40").unwrap().code,
+            IssueCode::NumericOverflow
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use crate::validators::{IssueCode, ValidationIssue};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+use tracing::info;
+
+/// Ontologies loaded with [`load`] and the columns validated against them.
+#[derive(Debug, Default, Clone)]
+pub struct Ontologies {
+    /// ontology name -> terms
+    terms: HashMap<String, HashMap<String, Term>>,
+    /// column name -> ontology name
+    columns: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Term {
+    obsolete: bool,
+    /// `replaced_by` of an obsolete term
+    replaced_by: Option<String>,
+}
+
+/// Name of the ontology (the `ontology:` header, lowercased) and its terms,
+/// read from the `[Term]` stanzas of an OBO file.
+fn read_obo<R: BufRead>(input: R) -> io::Result<(Option<String>, HashMap<String, Term>)> {
+    let mut name = None;
+    let mut terms = HashMap::new();
+    let mut in_term = false;
+    let mut current: Option<(String, Term)> = None;
+    for line in input.lines() {
+        let line = line?;
+        let line = line.trim();
+        if line.starts_with('[') {
+            terms.extend(current.take());
+            in_term = line == "[Term]";
+            continue;
+        }
+        let (tag, value) = match line.split_once(':') {
+            Some((tag, value)) => (tag, value.trim()),
+            None => continue,
+        };
+        // trailing modifiers and comments, e.g. `id: EFO:0000001 ! cell line`
+        let value = value.split(" !").next().unwrap_or_default().trim();
+        match tag {
+            "ontology" if name.is_none() && !in_term => name = Some(value.to_lowercase()),
+            "id" if in_term => {
+                terms.extend(current.take());
+                current = Some((value.to_string(), Term::default()));
+            }
+            "is_obsolete" => {
+                if let Some((_, term)) = &mut current {
+                    term.obsolete = value == "true";
+                }
+            }
+            "replaced_by" => {
+                if let Some((_, term)) = &mut current {
+                    term.replaced_by = Some(value.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    terms.extend(current);
+    Ok((name, terms))
+}
+
+/// Load the OBO files and the mapping of columns to ontologies, which are
+/// named after the `ontology:` header of their file or, without it, its stem.
+pub fn load(paths: &[PathBuf], columns: BTreeMap<String, String>) -> io::Result<Ontologies> {
+    let mut ontologies = Ontologies::default();
+    for path in paths {
+        let (name, terms) = read_obo(BufReader::new(File::open(path)?))?;
+        let name = name.unwrap_or_else(|| {
+            path.file_stem()
+                .map(|s| s.to_string_lossy().to_lowercase())
+                .unwrap_or_default()
+        });
+        info!(ontology = %name, terms = terms.len(), "loaded ontology");
+        ontologies.terms.insert(name, terms);
+    }
+    for (column, ontology) in &columns {
+        if !ontologies.terms.contains_key(&ontology.to_lowercase()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "column {} is mapped to the ontology {}, which was not loaded with --obo",
+                    column, ontology
+                ),
+            ));
+        }
+    }
+    ontologies.columns = columns;
+    Ok(ontologies)
+}
+
+impl Ontologies {
+    /// Check a value of `column` if it is mapped to an ontology: the term must
+    /// exist and not be obsolete. `EFO_0000001` is read as `EFO:0000001`.
+    pub fn check(&self, column: &str, value: &str) -> Option<ValidationIssue> {
+        let ontology = self.columns.get(column)?;
+        let terms = &self.terms[&ontology.to_lowercase()];
+        let id = if value.contains(':') {
+            value.to_string()
+        } else {
+            value.replacen('_', ":", 1)
+        };
+        let issue = |code| ValidationIssue::new(code).field(column).value(value);
+        match terms.get(&id) {
+            None => Some(issue(IssueCode::UnknownTerm).detail(format!("not in {}", ontology))),
+            Some(Term {
+                obsolete: true,
+                replaced_by,
+            }) => Some(match replaced_by {
+                Some(replacement) => {
+                    issue(IssueCode::ObsoleteTerm).detail(format!("replaced by {}", replacement))
+                }
+                None => issue(IssueCode::ObsoleteTerm),
+            }),
+            Some(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_obo_terms_are_read() {
+        let obo = "format-version: 1.2\nontology: efo\n\n[Term]\nid: EFO:0000001\nname: experimental factor\n\n[Term]\nid: EFO:0000002\nis_obsolete: true\nreplaced_by: EFO:0000001\n\n[Typedef]\nid: part_of\n";
+        let (name, terms) = read_obo(obo.as_bytes()).unwrap();
+        assert_eq!(name.as_deref(), Some("efo"));
+        assert_eq!(terms.len(), 2);
+        assert!(!terms["EFO:0000001"].obsolete);
+        assert_eq!(
+            terms["EFO:0000002"].replaced_by.as_deref(),
+            Some("EFO:0000001")
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use crate::runner::InputFormat;
+use crate::validators::{IssueCode, LineError, ValidationIssue};
+use csv::ReaderBuilder;
+use std::collections::BTreeMap;
+
+/// Columns of the identifiers of the tidy formats, the first found is used.
+const ID_COLUMNS: [&str; 5] = ["uniprot", "met_id", "reaction_id", "feature_id", "id"];
+/// Columns of the values of the tidy formats.
+pub(crate) const VALUE_COLUMNS: [&str; 3] = ["value", "intensity", "usage"];
+/// Scales the median absolute deviation to the standard deviation of a normal
+/// distribution.
+const MAD_SCALE: f64 = 1.4826;
+/// Fewer values do not have a meaningful median.
+const MIN_VALUES: usize = 3;
+
+fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Robust z-scores of the values: their distance to the median in scaled
+/// median absolute deviations. `None` if the deviation is zero.
+fn robust_z_scores(values: &[f64]) -> Option<Vec<f64>> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let median = median(&sorted);
+    let mut deviations: Vec<f64> = values.iter().map(|v| (v - median).abs()).collect();
+    deviations.sort_by(f64::total_cmp);
+    let mad = MAD_SCALE * self::median(&deviations);
+    (mad > 0.0).then(|| values.iter().map(|v| (v - median) / mad).collect())
+}
+
+/// Values of each identifier across the samples, with their line and sample.
+type Measurements = BTreeMap<String, Vec<(usize, String, f64)>>;
+
+fn measurements(content: &[u8], format: &InputFormat) -> Measurements {
+    let mut measurements = Measurements::new();
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(format.delimiter())
+        .flexible(true)
+        .from_reader(content);
+    let headers = match rdr.headers() {
+        Ok(headers) => headers.clone(),
+        Err(_) => return measurements,
+    };
+    let position = |names: &[&str]| headers.iter().position(|h| names.contains(&h));
+    let columns = (
+        position(&ID_COLUMNS),
+        position(&["sample"]),
+        position(&VALUE_COLUMNS),
+        position(&["omics_type"]),
+    );
+    // unreadable records and values are reported by the validation of the format
+    for (i, record) in rdr.records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        let line = i + 2;
+        if let InputFormat::Counts = format {
+            let gene = record.get(0).unwrap_or_default();
+            for (sample, value) in headers.iter().zip(record.iter()).skip(1) {
+                if let Ok(value) = value.parse::<f64>() {
+                    measurements.entry(gene.to_string()).or_default().push((
+                        line,
+                        sample.to_string(),
+                        value,
+                    ));
+                }
+            }
+            continue;
+        }
+        if let (Some(id), Some(sample), Some(value), omics_type) = columns {
+            let value = match record.get(value).and_then(|v| v.parse::<f64>().ok()) {
+                Some(value) if value.is_finite() => value,
+                _ => continue,
+            };
+            let id = record.get(id).unwrap_or_default();
+            // the identifiers of the multi format are unique per omics type
+            let key = match omics_type.and_then(|c| record.get(c)) {
+                Some(omics_type) => format!("{}:{}", omics_type, id),
+                None => id.to_string(),
+            };
+            let sample = record.get(sample).unwrap_or_default().to_string();
+            measurements
+                .entry(key)
+                .or_default()
+                .push((line, sample, value));
+        }
+    }
+    measurements
+}
+
+/// Warn about the values whose robust z-score across the samples of their
+/// identifier is above `threshold`, not checked if `None`.
+pub fn check(content: &[u8], format: &InputFormat, threshold: Option<f64>) -> Vec<LineError> {
+    match threshold {
+        Some(threshold) => check_with(content, format, threshold),
+        None => Vec::new(),
+    }
+}
+
+fn check_with(content: &[u8], format: &InputFormat, threshold: f64) -> Vec<LineError> {
+    let mut error_vec = Vec::new();
+    for (id, values) in measurements(content, format) {
+        if values.len() < MIN_VALUES {
+            continue;
+        }
+        let scores = match robust_z_scores(&values.iter().map(|v| v.2).collect::<Vec<_>>()) {
+            Some(scores) => scores,
+            None => continue,
+        };
+        for ((line, sample, value), z) in values.into_iter().zip(scores) {
+            if z.abs() > threshold {
+                error_vec.push(LineError::warning(
+                    line,
+                    ValidationIssue::new(IssueCode::Outlier)
+                        .field(&sample)
+                        .value(value.to_string())
+                        .detail(format!("robust z-score {:+.1} of {} in {}", z, id, sample)),
+                ));
+            }
+        }
+    }
+    error_vec.sort_by_key(|e| e.line);
+    error_vec
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_extreme_values_are_outliers() {
+        let input = "uniprot,sample,value\nQ00496,S1,100\nQ00496,S2,104\nQ00496,S3,98\nQ00496,S4,101\nQ00496,S5,5000\nE0X97,S1,1\nE0X97,S2,1\n";
+        let error_vec = check_with(input.as_bytes(), &InputFormat::TidyProt, 3.5);
+        assert_eq!(error_vec.len(), 1);
+        assert_eq!(error_vec[0].line, 6);
+        assert_eq!(error_vec[0].issues[0].field.as_deref(), Some("S5"));
+    }
+}
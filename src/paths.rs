@@ -0,0 +1,79 @@
+use crate::remote;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+/// Whether a path written in a sheet starts with a Windows drive, e.g. `C:\`.
+fn has_drive(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/')
+}
+
+/// Path of a file written in a sheet, as it is opened on this system: the
+/// backslashes of a sheet written on Windows are read as separators elsewhere.
+pub fn local(path: &Path) -> Cow<'_, Path> {
+    match path.to_str() {
+        Some(text) if !cfg!(windows) && text.contains('\\') && !remote::is_remote(text) => {
+            Cow::Owned(PathBuf::from(text.replace('\\', "/")))
+        }
+        _ => Cow::Borrowed(path),
+    }
+}
+
+/// Why a path written in a sheet only works on some systems, if it does.
+pub fn portability(path: &Path) -> Option<String> {
+    let text = path.to_str()?;
+    if remote::is_remote(text) || !text.contains('\\') {
+        return None;
+    }
+    Some(format!(
+        "backslash separators, read as {}; write / for the sheet to be read on every system",
+        text.replace('\\', "/")
+    ))
+}
+
+/// Likely reason for a file of a sheet to be missing, beyond a wrong path.
+pub fn missing_reason(path: &Path) -> Option<String> {
+    let text = match path.to_str() {
+        Some(text) => text,
+        None => return Some("the path is not valid UTF-8".to_string()),
+    };
+    if text.ends_with('\r') {
+        Some("the path ends with a carriage return, left by Windows line endings".to_string())
+    } else if text != text.trim() {
+        Some("the path starts or ends with spaces".to_string())
+    } else if has_drive(text) && !cfg!(windows) {
+        Some(format!(
+            "absolute Windows path on drive {}:, which does not exist on this system",
+            &text[..1]
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_windows_paths_are_explained() {
+        let path = Path::new(r"data\R1.fastq.gz");
+        assert_eq!(
+            portability(path).as_deref(),
+            Some("backslash separators, read as data/R1.fastq.gz; write / for the sheet to be read on every system")
+        );
+        assert!(portability(Path::new("data/R1.fastq.gz")).is_none());
+        assert!(portability(Path::new(r"https://example.org/a\b.fastq")).is_none());
+        if !cfg!(windows) {
+            assert_eq!(local(path), Path::new("data/R1.fastq.gz"));
+            assert!(missing_reason(Path::new(r"C:\data\R1.fastq.gz"))
+                .unwrap()
+                .starts_with("absolute Windows path on drive C:"));
+        }
+        assert!(missing_reason(Path::new("R1.fastq.gz\r")).is_some());
+        assert!(missing_reason(Path::new("R1.fastq.gz")).is_none());
+    }
+}
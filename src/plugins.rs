@@ -0,0 +1,175 @@
+use crate::validators::{IssueCode, LineError, ReadOptions, ValidationIssue};
+use csv::ReaderBuilder;
+use serde_json::{Map, Value};
+use std::io;
+use std::path::Path;
+
+/// Load the plugins `names` from `<dir>/<name>.wasm`.
+pub fn load(dir: &Path, names: &[String]) -> io::Result<Vec<Plugin>> {
+    names
+        .iter()
+        .map(|name| Plugin::load(&dir.join(format!("{}.wasm", name)), name))
+        .collect()
+}
+
+/// Run every plugin on each selected record of `input`, with the header synonyms renamed.
+pub fn validate<R: io::Read>(
+    plugins: &mut [Plugin],
+    input: R,
+    has_headers: bool,
+    delimiter: u8,
+    options: &ReadOptions,
+) -> io::Result<Vec<LineError>> {
+    let mut rdr = ReaderBuilder::new()
+        .flexible(true)
+        .has_headers(has_headers)
+        .delimiter(delimiter)
+        .from_reader(input);
+    let headers = if has_headers {
+        options.rename_headers(&mut rdr);
+        Some(rdr.headers()?.clone())
+    } else {
+        None
+    };
+    let off = if has_headers { 2 } else { 1 };
+    let mut error_vec = Vec::new();
+    for (i, result) in options.rows.select(rdr.records().enumerate()) {
+        // unreadable records are already reported by the built-in validation
+        let record = match result {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        let fields: Map<String, Value> = record
+            .iter()
+            .enumerate()
+            .map(|(j, value)| {
+                let key = match headers.as_ref().and_then(|h| h.get(j)) {
+                    Some(header) => header.to_string(),
+                    None => j.to_string(),
+                };
+                (key, Value::from(value))
+            })
+            .collect();
+        let json = Value::Object(fields).to_string();
+        for plugin in plugins.iter_mut() {
+            if let Some(msg) = plugin.validate_record(&json)? {
+                error_vec.push(LineError::error(
+                    i + off,
+                    ValidationIssue::new(IssueCode::Plugin)
+                        .field(plugin.name())
+                        .detail(msg),
+                ));
+            }
+        }
+    }
+    Ok(error_vec)
+}
+
+/// A WebAssembly module with custom validations of records.
+///
+/// The module must export:
+/// * `memory`.
+/// * `alloc(len: i32) -> i32`, reserving `len` bytes for the input of `validate`.
+/// * `validate(ptr: i32, len: i32) -> i64`, receiving a record as a UTF-8 JSON
+///   object that maps the column names (or indices, for files without header)
+///   to their values. It returns 0 if the record is valid, otherwise
+///   `(msg_ptr << 32) | msg_len` pointing to a UTF-8 error message.
+#[cfg(feature = "plugins")]
+pub struct Plugin {
+    name: String,
+    store: wasmtime::Store<()>,
+    memory: wasmtime::Memory,
+    alloc: wasmtime::TypedFunc<i32, i32>,
+    validate: wasmtime::TypedFunc<(i32, i32), i64>,
+}
+
+#[cfg(feature = "plugins")]
+impl Plugin {
+    fn load(path: &Path, name: &str) -> io::Result<Self> {
+        use wasmtime::{Engine, Instance, Module, Store};
+
+        let to_io = |e: wasmtime::Error| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("plugin {}: {}", name, e),
+            )
+        };
+        let engine = Engine::default();
+        let module = Module::from_file(&engine, path).map_err(to_io)?;
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[]).map_err(to_io)?;
+        let memory = instance.get_memory(&mut store, "memory").ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("plugin {}: no exported memory", name),
+            )
+        })?;
+        let alloc = instance
+            .get_typed_func(&mut store, "alloc")
+            .map_err(to_io)?;
+        let validate = instance
+            .get_typed_func(&mut store, "validate")
+            .map_err(to_io)?;
+        Ok(Plugin {
+            name: name.to_string(),
+            store,
+            memory,
+            alloc,
+            validate,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Validate a JSON record, returning the error message if invalid.
+    fn validate_record(&mut self, record: &str) -> io::Result<Option<String>> {
+        let to_io = |e: wasmtime::Error| {
+            io::Error::new(io::ErrorKind::Other, format!("plugin {}: {}", self.name, e))
+        };
+        let len = record.len() as i32;
+        let ptr = self.alloc.call(&mut self.store, len).map_err(to_io)?;
+        self.memory
+            .write(&mut self.store, ptr as usize, record.as_bytes())
+            .map_err(|e| to_io(e.into()))?;
+        let packed = self
+            .validate
+            .call(&mut self.store, (ptr, len))
+            .map_err(to_io)?;
+        if packed == 0 {
+            return Ok(None);
+        }
+        let (msg_ptr, msg_len) = ((packed >> 32) as usize, (packed & 0xffff_ffff) as usize);
+        let mut msg = vec![0; msg_len];
+        self.memory
+            .read(&self.store, msg_ptr, &mut msg)
+            .map_err(|e| to_io(e.into()))?;
+        Ok(Some(String::from_utf8_lossy(&msg).into_owned()))
+    }
+}
+
+/// Without the `plugins` feature no plugin can be loaded.
+#[cfg(not(feature = "plugins"))]
+pub enum Plugin {}
+
+#[cfg(not(feature = "plugins"))]
+impl Plugin {
+    fn load(_path: &Path, name: &str) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "cannot load plugin {}: omics_valid was built without the `plugins` feature",
+                name
+            ),
+        ))
+    }
+
+    fn name(&self) -> &str {
+        match *self {}
+    }
+
+    fn validate_record(&mut self, _record: &str) -> io::Result<Option<String>> {
+        match *self {}
+    }
+}
@@ -0,0 +1,226 @@
+use crate::missing;
+use crate::uniprot;
+use crate::validators::ReadOptions;
+use chrono::NaiveDate;
+use csv::ReaderBuilder;
+use strum::Display;
+
+/// Values shown for each column.
+const EXAMPLES: usize = 3;
+
+/// Type inferred for the values of a column, the narrowest that every value
+/// that is not missing has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Kind {
+    /// only missing values
+    Empty,
+    Integer,
+    Number,
+    Boolean,
+    /// ISO 8601, e.g. 2021-03-04
+    Date,
+    /// UniProt accessions
+    Uniprot,
+    String,
+}
+
+impl Kind {
+    /// Narrowest kind of a value.
+    fn of(value: &str) -> Self {
+        let value = value.trim();
+        if value.parse::<i64>().is_ok() {
+            Kind::Integer
+        } else if value.parse::<f64>().is_ok() {
+            Kind::Number
+        } else if ["true", "false"]
+            .iter()
+            .any(|b| value.eq_ignore_ascii_case(b))
+        {
+            Kind::Boolean
+        } else if NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+            Kind::Date
+        } else if uniprot::parse(value).is_ok() {
+            Kind::Uniprot
+        } else {
+            Kind::String
+        }
+    }
+
+    /// Kind of a column with values of both kinds.
+    fn merge(self, other: Kind) -> Kind {
+        match (self, other) {
+            (Kind::Empty, kind) | (kind, Kind::Empty) => kind,
+            (a, b) if a == b => a,
+            (Kind::Integer, Kind::Number) | (Kind::Number, Kind::Integer) => Kind::Number,
+            _ => Kind::String,
+        }
+    }
+}
+
+/// Preview of a column.
+#[derive(Debug, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub kind: Kind,
+    /// first distinct values that are not missing
+    pub examples: Vec<String>,
+    pub missing: usize,
+    pub rows: usize,
+    /// values of a type other than the one of most values, for a string
+    /// column that would otherwise be numeric, e.g. `n.d.` in a value column
+    pub odd: Vec<String>,
+}
+
+/// Most frequent delimiter of the first line among tab, comma and semicolon,
+/// so that a file can be previewed before its format is known.
+pub fn delimiter(content: &[u8]) -> u8 {
+    let line = content.split(|&b| b == b'\n').next().unwrap_or_default();
+    [b'\t', b',', b';']
+        .into_iter()
+        .max_by_key(|d| line.iter().filter(|b| *b == d).count())
+        .unwrap_or(b',')
+}
+
+/// Inferred type, examples and missing values of each column of the rows
+/// selected by `options`, the columns named by their position without header.
+pub fn columns(
+    content: &[u8],
+    delimiter: u8,
+    has_headers: bool,
+    options: &ReadOptions,
+) -> Vec<Column> {
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(has_headers)
+        .flexible(true)
+        .from_reader(content);
+    if has_headers {
+        options.rename_headers(&mut rdr);
+    }
+    let mut columns: Vec<Column> = match rdr.headers() {
+        Ok(headers) if has_headers => headers
+            .iter()
+            .map(|name| Column {
+                name: name.to_string(),
+                kind: Kind::Empty,
+                examples: Vec::new(),
+                missing: 0,
+                rows: 0,
+                odd: Vec::new(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    // kinds of the values of each column, to tell the odd ones
+    let mut kinds: Vec<Vec<(Kind, String)>> = vec![Vec::new(); columns.len()];
+    // unreadable records are reported by the validation
+    for (_, record) in options.rows.select(rdr.records().enumerate()) {
+        let record = match record {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        for (i, value) in record.iter().enumerate() {
+            if i >= columns.len() {
+                columns.push(Column {
+                    name: format!("column {}", i + 1),
+                    kind: Kind::Empty,
+                    examples: Vec::new(),
+                    missing: 0,
+                    rows: 0,
+                    odd: Vec::new(),
+                });
+                kinds.push(Vec::new());
+            }
+            let column = &mut columns[i];
+            column.rows += 1;
+            if missing::is_missing(value) {
+                column.missing += 1;
+                continue;
+            }
+            let kind = Kind::of(value);
+            column.kind = column.kind.merge(kind);
+            if column.examples.len() < EXAMPLES && !column.examples.iter().any(|e| e == value) {
+                column.examples.push(value.to_string());
+            }
+            kinds[i].push((kind, value.to_string()));
+        }
+    }
+    for (column, values) in columns.iter_mut().zip(kinds) {
+        let numeric = values
+            .iter()
+            .filter(|(kind, _)| matches!(kind, Kind::Integer | Kind::Number))
+            .count();
+        if column.kind == Kind::String && numeric * 2 > values.len() {
+            column.odd = values
+                .into_iter()
+                .filter(|(kind, _)| !matches!(kind, Kind::Integer | Kind::Number))
+                .map(|(_, value)| value)
+                .take(EXAMPLES)
+                .collect();
+        }
+    }
+    columns
+}
+
+/// A line per column, e.g. `value: number, 2 of 120 missing (1.7%), e.g.
+/// 100001, 123.3444, 10.2`.
+pub fn lines(columns: &[Column]) -> Vec<String> {
+    columns
+        .iter()
+        .map(|column| {
+            let mut line = format!(
+                "{}: {}, {} of {} missing ({:.1}%)",
+                column.name,
+                column.kind,
+                column.missing,
+                column.rows,
+                100.0 * column.missing as f64 / column.rows.max(1) as f64
+            );
+            if !column.examples.is_empty() {
+                line.push_str(&format!(", e.g. {}", column.examples.join(", ")));
+            }
+            if !column.odd.is_empty() {
+                line.push_str(&format!(
+                    "; mostly numbers but for {}",
+                    column.odd.join(", ")
+                ));
+            }
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_columns_are_previewed() {
+        let content = b"uniprot\tsample\tvalue\tnotes\nQ00496\tS1\t1\t\nE0X97\tS1\t2.5\tNA\n\
+                        P0A6F5\tS2\tn.d.\t\nP0A6F5\tS2\t4\t\n";
+        let delimiter = delimiter(content);
+        assert_eq!(delimiter, b'\t');
+        let previewed = columns(content, delimiter, true, &ReadOptions::default());
+        assert_eq!(
+            previewed.iter().map(|c| c.kind).collect::<Vec<_>>(),
+            [Kind::String, Kind::String, Kind::String, Kind::Empty]
+        );
+        assert_eq!(previewed[0].examples, ["Q00496", "E0X97", "P0A6F5"]);
+        assert_eq!(previewed[2].odd, ["n.d."]);
+        assert_eq!(previewed[3].missing, 4);
+        assert_eq!(
+            lines(&previewed)[2],
+            "value: string, 0 of 4 missing (0.0%), e.g. 1, 2.5, n.d.; mostly numbers but for n.d."
+        );
+        let prot = columns(
+            b"Q00496,1,2\nP0A6F5,3,4.5\n",
+            b',',
+            false,
+            &ReadOptions::default(),
+        );
+        assert_eq!(prot[0].name, "column 1");
+        assert_eq!(prot[0].kind, Kind::Uniprot);
+        assert_eq!(prot[2].kind, Kind::Number);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::validators::IssueCode;
+use strum::{Display, EnumString};
+
+/// Presets of checks applied consistently across formats, given with
+/// `--profile`. The individual flags and `--promote` take precedence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Profile {
+    /// the checks as documented for each format
+    #[default]
+    Default,
+    /// Uniprot isoforms are rejected and warnings are errors
+    Strict,
+    /// extra columns and Uniprot isoforms are accepted, missing values are warnings
+    Lenient,
+    /// RNA sheets must have every column of the modulome-workflow, with its
+    /// casing and TSV dialect
+    Imodulon,
+}
+
+impl Profile {
+    /// Issues of missing values, reported as warnings by the lenient profile.
+    pub const MISSING_VALUES: [IssueCode; 3] = [
+        IssueCode::EmptyField,
+        IssueCode::EmptySample,
+        IssueCode::MissingValue,
+    ];
+
+    pub fn allow_extra_columns(self) -> bool {
+        self == Profile::Lenient
+    }
+
+    /// Whether isoforms such as `P12345-2` are accepted, `None` if left to the
+    /// grammar of the accessions, which accepts them.
+    pub fn isoforms(self) -> Option<bool> {
+        match self {
+            Profile::Default => None,
+            Profile::Strict => Some(false),
+            Profile::Lenient => Some(true),
+            Profile::Imodulon => None,
+        }
+    }
+
+    /// Whether the RNA sheet is checked against the modulome-workflow.
+    pub fn imodulon(self) -> bool {
+        self == Profile::Imodulon
+    }
+
+    pub fn warnings_as_errors(self) -> bool {
+        self == Profile::Strict
+    }
+
+    /// Codes reported as warnings, before `--promote` is applied.
+    pub fn demoted(self) -> &'static [IssueCode] {
+        match self {
+            Profile::Lenient => &Profile::MISSING_VALUES,
+            _ => &[],
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use crate::accessions::Accessions;
+use crate::models::Models;
+use crate::uniprot;
+use crate::uniprot_api;
+use crate::validators::{IssueCode, LineError, ReadOptions, ValidationIssue};
+use csv::{ReaderBuilder, WriterBuilder};
+use std::collections::HashSet;
+use std::io::{self, Write};
+use strum::{Display, EnumString};
+
+/// Prefixes of the entries that are not proteins of the sample: MaxQuant
+/// contaminants and reversed decoys, and UniProt FASTA headers left as IDs.
+const DECOY_PREFIXES: [&str; 4] = ["CON__", "REV__", "sp|", "tr|"];
+
+/// What to do with the rows of contaminants and decoys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum DecoyPolicy {
+    /// reject the rows
+    #[default]
+    Error,
+    /// report the rows as warnings
+    Warn,
+    /// accept the rows, which are removed by `--fix`
+    Strip,
+}
+
+/// Options of the protein checks, from the command line.
+#[derive(Debug, Clone, Default)]
+pub struct ProteomicsOptions {
+    pub decoys: DecoyPolicy,
+    /// accept protein groups such as `P12345;Q67890` as identifiers
+    pub allow_groups: bool,
+    /// accept (`Some(true)`) or reject isoforms such as `P12345-2`, accepted as
+    /// part of the accession grammar if `None`, see [`Profile`](crate::profile::Profile)
+    pub isoforms: Option<bool>,
+    /// look the proteins up in UniProtKB online, see `--check-uniprot-online`
+    pub check_online: bool,
+    /// accessions known to exist, see `--check-uniprot`
+    pub accessions: Option<Accessions>,
+}
+
+impl ProteomicsOptions {
+    /// Accessions of a protein group if groups are allowed, `id` otherwise.
+    pub fn members<'a>(&self, id: &'a str) -> Vec<&'a str> {
+        if self.allow_groups {
+            id.split(';').collect()
+        } else {
+            vec![id]
+        }
+    }
+}
+
+/// Prefix marking `id` as a contaminant or decoy entry, if any.
+pub fn decoy_prefix(id: &str) -> Option<&'static str> {
+    DECOY_PREFIXES.iter().copied().find(|p| id.starts_with(p))
+}
+
+/// Write `content` without the rows of contaminants and decoys, returning the
+/// number of rows removed. The identifiers are in the `uniprot` column, or the
+/// first one if there is no header.
+pub fn strip_decoys(
+    content: &[u8],
+    has_headers: bool,
+    output: impl Write,
+) -> Result<usize, io::Error> {
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(has_headers)
+        .flexible(true)
+        .from_reader(content);
+    let mut wtr = WriterBuilder::new().flexible(true).from_writer(output);
+    let column = if has_headers {
+        let headers = rdr.headers()?;
+        wtr.write_record(headers)?;
+        headers.iter().position(|h| h == "uniprot").unwrap_or(0)
+    } else {
+        0
+    };
+    let mut stripped = 0;
+    for result in rdr.records() {
+        let record = result?;
+        if decoy_prefix(record.get(column).unwrap_or_default()).is_some() {
+            stripped += 1;
+        } else {
+            wtr.write_record(&record)?;
+        }
+    }
+    wtr.flush()?;
+    Ok(stripped)
+}
+
+/// Distinct protein IDs with the line where each is first found, leaving out
+/// the decoys and the malformed rows.
+fn first_lines(content: &[u8], has_headers: bool, options: &ReadOptions) -> Vec<(usize, String)> {
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(has_headers)
+        .flexible(true)
+        .from_reader(content);
+    let column = if has_headers {
+        options.rename_headers(&mut rdr);
+        rdr.headers()
+            .ok()
+            .and_then(|headers| headers.iter().position(|h| h == "uniprot"))
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    let off = if has_headers { 2 } else { 1 };
+    let mut seen = HashSet::new();
+    let mut ids = Vec::new();
+    for (i, result) in options.rows.select(rdr.records().enumerate()) {
+        let record = match result {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        let id = record.get(column).unwrap_or_default();
+        if id.is_empty() || decoy_prefix(id).is_some() || !seen.insert(id.to_string()) {
+            continue;
+        }
+        ids.push((i + off, id.to_string()));
+    }
+    ids
+}
+
+/// Warnings for the proteins measured but absent from the gene products of the
+/// models, e.g., enzymes an ecModel cannot constrain, on the first line of each
+/// accession. A group is in the models if any of its members is. Decoys and
+/// malformed rows are left to the validation of the rows.
+pub fn absent_from_models(
+    content: &[u8],
+    has_headers: bool,
+    models: &Models,
+    options: &ReadOptions,
+) -> Vec<LineError> {
+    let proteomics = &options.proteomics;
+    first_lines(content, has_headers, options)
+        .into_iter()
+        .filter(|(_, id)| {
+            proteomics
+                .members(id)
+                .into_iter()
+                .all(|member| models.contains_uniprot(uniprot::entry(member)) == Some(false))
+        })
+        .map(|(line, id)| {
+            LineError::warning(
+                line,
+                ValidationIssue::new(IssueCode::NotInModel)
+                    .field("uniprot")
+                    .value(id)
+                    .detail("no gene product of the models is annotated with it"),
+            )
+        })
+        .collect()
+}
+
+/// Errors for the proteins that UniProtKB does not know, looked up online with
+/// `--check-uniprot-online`, on the first line of each ID. The first unknown
+/// member of a group is reported. IDs that are not accessions are left to the
+/// validation of the rows.
+pub fn unknown_online(
+    content: &[u8],
+    has_headers: bool,
+    options: &ReadOptions,
+) -> io::Result<Vec<LineError>> {
+    let proteomics = &options.proteomics;
+    let ids = first_lines(content, has_headers, options);
+    let mut entries: Vec<String> = ids
+        .iter()
+        .flat_map(|(_, id)| proteomics.members(id))
+        .filter_map(|member| uniprot::parse(member).ok())
+        .map(|accession| accession.entry.to_string())
+        .collect();
+    entries.sort();
+    entries.dedup();
+    let verdicts = uniprot_api::verdicts(&entries, &options.network)?;
+    Ok(ids
+        .iter()
+        .filter_map(|(line, id)| {
+            let unknown = proteomics.members(id).into_iter().find(|member| {
+                uniprot::parse(member)
+                    .is_ok_and(|accession| verdicts.get(accession.entry) == Some(&false))
+            })?;
+            Some(LineError::error(
+                *line,
+                ValidationIssue::new(IssueCode::UnknownUniprotId)
+                    .field("uniprot")
+                    .value(unknown)
+                    .detail("not found in UniProtKB by its ID mapping service"),
+            ))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_decoys_are_stripped() {
+        let input = "uniprot,sample,value\nQ00496,SIM1,1\nCON__P02769,SIM1,2\nREV__Q7B2Q4,SIM1,3\nsp|P0A6F5|CH60_ECOLI,SIM1,4\n";
+        let mut output = Vec::new();
+        assert_eq!(
+            strip_decoys(input.as_bytes(), true, &mut output).unwrap(),
+            3
+        );
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "uniprot,sample,value\nQ00496,SIM1,1\n"
+        );
+    }
+
+    #[test]
+    fn test_proteins_absent_from_the_model_are_warned() {
+        let models = Models::load(&[PathBuf::from("tests/iCLAU786.xml")], &[]).unwrap();
+        let input = "uniprot,sample,value\nU5RTK1,SIM1,1\nP0A9B2,SIM1,2\nP0A9B2,SIM2,3\nCON__P02769,SIM1,4\n";
+        let error_vec =
+            absent_from_models(input.as_bytes(), true, &models, &ReadOptions::default());
+        assert_eq!(error_vec.len(), 1);
+        assert_eq!(error_vec[0].line, 3);
+        assert_eq!(error_vec[0].issues[0].code, IssueCode::NotInModel);
+        let models = Models::load(&[], &[]).unwrap();
+        assert!(
+            absent_from_models(b"P0A9B2,1\n", false, &models, &ReadOptions::default()).is_empty()
+        );
+    }
+}
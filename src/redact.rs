@@ -0,0 +1,44 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::borrow::Cow;
+
+/// Home directories on Linux, macOS and Windows, e.g. `/home/alice`.
+static RE_HOME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:/home/[^/\s]+|/Users/[^/\s]+|/root\b|[A-Za-z]:\\Users\\[^\\\s]+)").unwrap()
+});
+/// Host (and credentials) of a URL, e.g. `https://sra.example.org`.
+static RE_HOST: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b([a-z][a-z0-9+.-]*)://[^/\s]+").unwrap());
+
+/// Replace the home directories of `text` with `~` and the hosts of its URLs
+/// with `<host>`, with `--redact-paths`.
+pub fn apply(text: &str) -> Cow<'_, str> {
+    match RE_HOME.replace_all(text, "~") {
+        Cow::Borrowed(text) => RE_HOST.replace_all(text, "$1://<host>"),
+        Cow::Owned(text) => Cow::Owned(RE_HOST.replace_all(&text, "$1://<host>").into_owned()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_paths_and_hosts_are_redacted() {
+        assert_eq!(
+            apply("/home/alice/data/R1.fastq Declared FASTQ path does not exist!"),
+            "~/data/R1.fastq Declared FASTQ path does not exist!"
+        );
+        assert_eq!(
+            apply(r"C:\Users\alice\R1.fastq and /Users/bob/R2.fastq"),
+            r"~\R1.fastq and ~/R2.fastq"
+        );
+        assert_eq!(
+            apply(
+                "https://user:pw@sra.internal:8443/SRR1/R1.fastq.gz Remote FASTQ is not reachable!"
+            ),
+            "https://<host>/SRR1/R1.fastq.gz Remote FASTQ is not reachable!"
+        );
+        assert_eq!(apply("s3://bucket/R1.fastq"), "s3://<host>/R1.fastq");
+        assert_eq!(apply("data/R1.fastq"), "data/R1.fastq");
+    }
+}
@@ -0,0 +1,374 @@
+use crate::deferred::DEFAULT_JOBS;
+use std::fmt;
+use std::io::Read;
+use std::time::Duration;
+#[cfg(feature = "network")]
+use tracing::{debug, warn};
+use url::Url;
+
+/// Settings of every network access, from the top-level options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NetworkOptions {
+    /// fail any network access instead of attempting it
+    pub offline: bool,
+    /// to connect, and between reads or writes
+    #[cfg_attr(not(any(feature = "network", feature = "s3")), allow(dead_code))]
+    pub timeout: Duration,
+    /// attempts after the first one failed
+    #[cfg_attr(not(any(feature = "network", feature = "s3")), allow(dead_code))]
+    pub retries: u32,
+    /// remote checks or requests run at the same time, see `--remote-jobs`
+    pub jobs: usize,
+}
+
+impl Default for NetworkOptions {
+    fn default() -> Self {
+        NetworkOptions {
+            offline: false,
+            timeout: Duration::from_secs(30),
+            retries: 3,
+            jobs: DEFAULT_JOBS,
+        }
+    }
+}
+
+/// HTTP agent with the timeouts of `options`; the TLS configuration is shared
+/// by all the agents.
+#[cfg(feature = "network")]
+fn agent(options: &NetworkOptions) -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(options.timeout)
+        .timeout_read(options.timeout)
+        .timeout_write(options.timeout)
+        .build()
+}
+
+/// Response to an HTTP request.
+#[cfg(feature = "network")]
+pub type Response = ureq::Response;
+
+/// Without the `network` feature no request is sent, so there is no response.
+#[cfg(not(feature = "network"))]
+pub enum Response {}
+
+#[cfg(not(feature = "network"))]
+impl Response {
+    pub fn into_reader(self) -> Box<dyn Read + Send + Sync + 'static> {
+        match self {}
+    }
+
+    pub fn header(&self, _name: &str) -> Option<&str> {
+        match *self {}
+    }
+}
+
+#[derive(Debug)]
+pub enum RequestError {
+    Offline,
+    /// built without the `network` feature
+    #[cfg_attr(feature = "network", allow(dead_code))]
+    Unsupported,
+    #[cfg_attr(not(feature = "network"), allow(dead_code))]
+    Status(u16),
+    #[cfg_attr(not(feature = "network"), allow(dead_code))]
+    Transport(String),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Offline => write!(f, "network access is disabled (--offline)"),
+            RequestError::Unsupported => write!(
+                f,
+                "network access is disabled: omics_valid was built without the `network` feature"
+            ),
+            RequestError::Status(code) => write!(f, "server answered with status {}", code),
+            RequestError::Transport(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// Delay before the attempt following `attempt` (0-based): 0.5s, 1s, 2s... up to a minute.
+#[cfg(feature = "network")]
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_millis(500u64.saturating_mul(2u64.saturating_pow(attempt)))
+        .min(Duration::from_secs(60))
+}
+
+/// Send an HTTP request, retrying with exponential backoff on transport errors,
+/// 429 and 5xx statuses.
+#[cfg(feature = "network")]
+pub fn request(
+    options: &NetworkOptions,
+    method: &str,
+    url: &str,
+) -> Result<Response, RequestError> {
+    let agent = agent(options);
+    with_retries(options, method, url, || {
+        agent.request(method, url).call().map_err(Box::new)
+    })
+}
+
+/// Send a form in a POST request, retried as [`request`].
+#[cfg(feature = "network")]
+pub fn post_form(
+    options: &NetworkOptions,
+    url: &str,
+    form: &[(&str, &str)],
+) -> Result<Response, RequestError> {
+    let agent = agent(options);
+    with_retries(options, "POST", url, || {
+        agent.post(url).send_form(form).map_err(Box::new)
+    })
+}
+
+/// Without the `network` feature every request fails.
+#[cfg(not(feature = "network"))]
+pub fn request(
+    options: &NetworkOptions,
+    _method: &str,
+    _url: &str,
+) -> Result<Response, RequestError> {
+    if options.offline {
+        return Err(RequestError::Offline);
+    }
+    Err(RequestError::Unsupported)
+}
+
+#[cfg(not(feature = "network"))]
+pub fn post_form(
+    options: &NetworkOptions,
+    url: &str,
+    _form: &[(&str, &str)],
+) -> Result<Response, RequestError> {
+    request(options, "POST", url)
+}
+
+#[cfg(feature = "network")]
+fn with_retries(
+    options: &NetworkOptions,
+    method: &str,
+    url: &str,
+    call: impl Fn() -> Result<Response, Box<ureq::Error>>,
+) -> Result<Response, RequestError> {
+    if options.offline {
+        return Err(RequestError::Offline);
+    }
+    let mut attempt = 0;
+    loop {
+        debug!(method, url, attempt, "request");
+        match call().map_err(|e| *e) {
+            Ok(response) => return Ok(response),
+            Err(ureq::Error::Status(code, _))
+                if !(code == 429 || code >= 500) || attempt == options.retries =>
+            {
+                return Err(RequestError::Status(code))
+            }
+            Err(ureq::Error::Transport(e)) if attempt == options.retries => {
+                return Err(RequestError::Transport(e.to_string()))
+            }
+            Err(e) => {
+                let delay = backoff(attempt);
+                warn!(url, error = %e, ?delay, "request failed, retrying");
+                std::thread::sleep(delay);
+            }
+        }
+        attempt += 1;
+    }
+}
+
+/// A file referenced by URL instead of a local path.
+#[derive(Debug)]
+pub enum RemoteFile {
+    Http(Url),
+    #[cfg_attr(not(feature = "s3"), allow(dead_code))]
+    S3 {
+        bucket: String,
+        key: String,
+    },
+}
+
+/// Whether a location should be treated as a URL instead of a local path.
+pub fn is_remote(location: &str) -> bool {
+    ["http://", "https://", "s3://"]
+        .iter()
+        .any(|scheme| location.starts_with(scheme))
+}
+
+impl RemoteFile {
+    /// Parse an HTTP(S) or S3 URL pointing to a file.
+    pub fn parse(location: &str) -> Result<Self, String> {
+        let url = Url::parse(location).map_err(|e| e.to_string())?;
+        if url.path().ends_with('/') || url.path().is_empty() {
+            return Err("URL does not point to a file".to_string());
+        }
+        match url.scheme() {
+            "http" | "https" => Ok(RemoteFile::Http(url)),
+            "s3" => match url.host_str() {
+                Some(bucket) => Ok(RemoteFile::S3 {
+                    bucket: bucket.to_string(),
+                    key: url.path().trim_start_matches('/').to_string(),
+                }),
+                None => Err("missing bucket".to_string()),
+            },
+            scheme => Err(format!("unsupported scheme {}", scheme)),
+        }
+    }
+
+    /// Whether this build is able to request the file.
+    pub fn can_check(&self) -> bool {
+        match self {
+            RemoteFile::Http(_) => cfg!(feature = "network"),
+            RemoteFile::S3 { .. } => cfg!(feature = "s3"),
+        }
+    }
+
+    /// Open the file for reading. The body of an HTTP(S) response is streamed
+    /// as it is read; an S3 object is downloaded first.
+    pub fn open(&self, options: &NetworkOptions) -> Result<Box<dyn Read + Send>, String> {
+        match self {
+            RemoteFile::Http(url) => {
+                let response = request(options, "GET", url.as_str()).map_err(|e| e.to_string())?;
+                Ok(Box::new(response.into_reader()))
+            }
+            #[cfg(feature = "s3")]
+            RemoteFile::S3 { bucket, key } => {
+                let content = s3::object(options, bucket, key)?;
+                Ok(Box::new(std::io::Cursor::new(content)))
+            }
+            #[cfg(not(feature = "s3"))]
+            RemoteFile::S3 { .. } => {
+                Err("s3:// URLs can only be read when built with the `s3` feature".to_string())
+            }
+        }
+    }
+
+    /// Request the metadata of the file, returning its size if known.
+    pub fn size(&self, options: &NetworkOptions) -> Result<Option<u64>, String> {
+        if options.offline {
+            return Err(RequestError::Offline.to_string());
+        }
+        match self {
+            RemoteFile::Http(url) => content_length(options, url),
+            #[cfg(feature = "s3")]
+            RemoteFile::S3 { bucket, key } => s3::object_size(options, bucket, key).map(Some),
+            #[cfg(not(feature = "s3"))]
+            RemoteFile::S3 { .. } => Ok(None),
+        }
+    }
+}
+
+/// Issue a HEAD request to `url`, returning the Content-Length if the server reports it.
+fn content_length(options: &NetworkOptions, url: &Url) -> Result<Option<u64>, String> {
+    let response = request(options, "HEAD", url.as_str()).map_err(|e| e.to_string())?;
+    Ok(response
+        .header("Content-Length")
+        .and_then(|length| length.parse().ok()))
+}
+
+#[cfg(feature = "s3")]
+mod s3 {
+    use super::NetworkOptions;
+    use aws_config::retry::RetryConfig;
+    use aws_config::timeout::TimeoutConfig;
+    use aws_sdk_s3::Client;
+    use once_cell::sync::Lazy;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tokio::runtime::Runtime;
+
+    /// Client with the runtime its requests are run on.
+    type Connection = Arc<(Runtime, Client)>;
+
+    /// Clients by network settings: the credential discovery is slow, so each
+    /// one is created once and shared by all the requests with its settings.
+    static CLIENTS: Lazy<Mutex<HashMap<NetworkOptions, Connection>>> = Lazy::new(Default::default);
+
+    /// Client configured with the standard credential discovery (environment,
+    /// profile files, instance metadata...) and `options`.
+    fn client(options: &NetworkOptions) -> Connection {
+        let mut clients = CLIENTS.lock().unwrap();
+        let client = clients
+            .entry(*options)
+            .or_insert_with(|| Arc::new(connect(options)));
+        Arc::clone(client)
+    }
+
+    fn connect(options: &NetworkOptions) -> (Runtime, Client) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to start the async runtime");
+        let config = runtime.block_on(
+            aws_config::from_env()
+                .retry_config(RetryConfig::standard().with_max_attempts(options.retries + 1))
+                .timeout_config(
+                    TimeoutConfig::builder()
+                        .connect_timeout(options.timeout)
+                        .read_timeout(options.timeout)
+                        .build(),
+                )
+                .load(),
+        );
+        (runtime, Client::new(&config))
+    }
+
+    /// Size of an S3 object. HeadObject requires read access to the object, so
+    /// a success also confirms that the caller is able to download it.
+    pub fn object_size(options: &NetworkOptions, bucket: &str, key: &str) -> Result<u64, String> {
+        let client = client(options);
+        let (runtime, client) = &*client;
+        let output = runtime
+            .block_on(client.head_object().bucket(bucket).key(key).send())
+            .map_err(|e| match e.into_service_error() {
+                e if e.is_not_found() => "object does not exist".to_string(),
+                e => format!("object cannot be read: {}", e),
+            })?;
+        Ok(output.content_length().max(0) as u64)
+    }
+
+    /// Content of an S3 object, collected from the asynchronous body.
+    pub fn object(options: &NetworkOptions, bucket: &str, key: &str) -> Result<Vec<u8>, String> {
+        let client = client(options);
+        let (runtime, client) = &*client;
+        runtime.block_on(async {
+            let output = client
+                .get_object()
+                .bucket(bucket)
+                .key(key)
+                .send()
+                .await
+                .map_err(|e| match e.into_service_error() {
+                    e if e.is_no_such_key() => "object does not exist".to_string(),
+                    e => format!("object cannot be read: {}", e),
+                })?;
+            let data = output.body.collect().await.map_err(|e| e.to_string())?;
+            Ok(data.into_bytes().to_vec())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "network")]
+    fn test_backoff_is_exponential_and_bounded() {
+        assert_eq!(backoff(0), Duration::from_millis(500));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(40), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_remote_url_syntax_is_validated() {
+        assert!(RemoteFile::parse("https://example.org/data/R1.fastq.gz").is_ok());
+        assert!(RemoteFile::parse("https://example.org/").is_err());
+        assert!(RemoteFile::parse("https://exa mple.org/R1.fastq.gz").is_err());
+        assert!(RemoteFile::parse("ftp://example.org/R1.fastq.gz").is_err());
+        assert!(matches!(
+            RemoteFile::parse("s3://bucket/path/to/R1.fastq.gz"),
+            Ok(RemoteFile::S3 { bucket, key }) if bucket == "bucket" && key == "path/to/R1.fastq.gz"
+        ));
+    }
+}
@@ -0,0 +1,116 @@
+use crate::validators::{IssueCode, LineError, ValidationIssue};
+use csv::ReaderBuilder;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Columns naming the samples, in the tidy formats and the RNA metadata.
+pub(crate) const SAMPLE_COLUMNS: [&str; 2] = ["sample", "Experiment"];
+pub(crate) const CONDITION_COLUMN: &str = "condition";
+/// The condition of `glc_1` is `glc`: everything before the last underscore.
+pub const DEFAULT_PATTERN: &str = "^(.+)_[^_]+$";
+
+/// Options of the replicate checks, from the command line.
+#[derive(Debug, Clone, Default)]
+pub struct ReplicateOptions {
+    /// replicates required per condition, not checked if `None`
+    pub min: Option<usize>,
+    /// extracts the condition from the sample name (first group) when there is
+    /// no condition column
+    pub pattern: Option<Regex>,
+}
+
+/// Condition of a sample: the value of the condition column if there is one,
+/// otherwise the first group of `pattern` in the sample name, or the whole
+/// name if it does not match.
+pub(crate) fn condition<'a>(
+    sample: &'a str,
+    column: Option<&'a str>,
+    pattern: Option<&Regex>,
+) -> &'a str {
+    match column {
+        Some(condition) => condition,
+        None => pattern
+            .and_then(|re| re.captures(sample)?.get(1))
+            .map_or(sample, |m| m.as_str()),
+    }
+}
+
+/// Report the conditions with fewer replicates (distinct samples) than
+/// required, at line 0. The rows are grouped by the `condition` column or, if
+/// there is none, by the condition found in the sample names.
+pub fn check(content: &[u8], delimiter: u8, options: &ReplicateOptions) -> Vec<LineError> {
+    let min = match options.min {
+        Some(min) => min,
+        None => return Vec::new(),
+    };
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content);
+    let headers = match rdr.headers() {
+        Ok(headers) => headers.clone(),
+        Err(_) => return Vec::new(),
+    };
+    let position = |name: &str| headers.iter().position(|h| h == name);
+    let sample_column = match SAMPLE_COLUMNS.iter().find_map(|c| position(c)) {
+        Some(column) => column,
+        None => return Vec::new(),
+    };
+    let condition_column = position(CONDITION_COLUMN);
+    let mut conditions: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    // unreadable records are reported by the validation of the format
+    for record in rdr.records().flatten() {
+        let sample = record.get(sample_column).unwrap_or_default();
+        if sample.is_empty() {
+            continue;
+        }
+        let condition = condition(
+            sample,
+            condition_column.map(|c| record.get(c).unwrap_or_default()),
+            options.pattern.as_ref(),
+        );
+        conditions
+            .entry(condition.to_string())
+            .or_default()
+            .insert(sample.to_string());
+    }
+    conditions
+        .into_iter()
+        .filter(|(_, samples)| samples.len() < min)
+        .map(|(condition, samples)| {
+            LineError::error(
+                0,
+                ValidationIssue::new(IssueCode::TooFewReplicates)
+                    .field(CONDITION_COLUMN)
+                    .value(condition)
+                    .detail(format!(
+                        "{} of {} replicates: {}",
+                        samples.len(),
+                        min,
+                        samples.into_iter().collect::<Vec<_>>().join(", ")
+                    )),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_conditions_need_replicates() {
+        let options = ReplicateOptions {
+            min: Some(2),
+            pattern: Some(Regex::new(DEFAULT_PATTERN).unwrap()),
+        };
+        let input =
+            "uniprot,sample,value\nQ00496,glc_1,1\nQ7B2Q4,glc_1,2\nQ00496,glc_2,3\nQ00496,ac_1,4\n";
+        let error_vec = check(input.as_bytes(), b',', &options);
+        assert_eq!(error_vec.len(), 1);
+        assert_eq!(error_vec[0].issues[0].value.as_deref(), Some("ac"));
+        let input = "Experiment,condition\nS1,glc\nS2,glc\nS3,ac\n";
+        let error_vec = check(input.as_bytes(), b',', &options);
+        assert_eq!(error_vec[0].issues[0].value.as_deref(), Some("ac"));
+    }
+}
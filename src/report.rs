@@ -0,0 +1,604 @@
+use crate::bench::Measurements;
+use crate::codeclimate;
+use crate::explain;
+use crate::jsonl;
+use crate::multiqc;
+use crate::redact;
+use crate::suppressions::Suppression;
+use crate::validators::{IssueCode, LineError, Severity, ValidationIssue};
+use itertools::Itertools;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use std::time::Duration;
+use strum::EnumString;
+
+/// Where the diagnostics (issues and summaries) are written; modes writing
+/// data to stdout must use stderr.
+#[derive(Debug, Clone, Copy, Default, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum ErrorStream {
+    #[default]
+    Stdout,
+    Stderr,
+}
+
+/// How the report is presented.
+#[derive(Debug, Clone, Copy, Default, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum OutputFormat {
+    /// issues grouped by message, then the summaries
+    #[default]
+    Text,
+    /// GitLab Code Quality JSON, without the summaries
+    Codeclimate,
+    /// a JSON object per issue as soon as it is found, then one with the counts
+    /// and the summaries
+    Jsonl,
+    /// MultiQC custom content, a table of the counts of each file
+    Multiqc,
+}
+
+/// How the diagnostics are written.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Diagnostics {
+    pub stream: ErrorStream,
+    /// hide the home directories and hosts, see [`redact::apply`]
+    pub redact: bool,
+}
+
+/// Write a line of diagnostics as configured by `to`.
+pub fn emit(to: Diagnostics, line: &str) {
+    let line = if to.redact {
+        redact::apply(line)
+    } else {
+        Cow::Borrowed(line)
+    };
+    // a closed pipe must not abort the validation, hence the ignored results
+    match to.stream {
+        ErrorStream::Stdout => {
+            let _ = writeln!(std::io::stdout(), "{}", line);
+        }
+        ErrorStream::Stderr => {
+            let _ = writeln!(std::io::stderr(), "{}", line);
+        }
+    }
+}
+
+/// Severity overrides of issue codes, from `--ignore` and `--promote`.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    /// `None` if the code is ignored
+    severities: HashMap<IssueCode, Option<Severity>>,
+    /// report the warnings of the other codes as errors
+    warnings_as_errors: bool,
+    /// accepted known issues, from `--ignore-file`
+    suppressions: Vec<Suppression>,
+}
+
+pub(crate) fn unknown_code(code: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("unknown issue code {}", code),
+    )
+}
+
+impl Overrides {
+    /// Do not report the issues with `code`.
+    pub fn ignore(&mut self, code: &str) -> io::Result<()> {
+        let code = IssueCode::lookup(code).ok_or_else(|| unknown_code(code))?;
+        self.severities.insert(code, None);
+        Ok(())
+    }
+
+    /// Report the issues with the code as `CODE=SEVERITY` with that severity.
+    pub fn promote(&mut self, code_severity: &str) -> io::Result<()> {
+        let (code, severity) = code_severity.split_once('=').ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("expected CODE=SEVERITY, found {}", code_severity),
+            )
+        })?;
+        let code = IssueCode::lookup(code).ok_or_else(|| unknown_code(code))?;
+        let severity = match severity.parse::<Severity>() {
+            Ok(severity) if severity != Severity::Suppressed => severity,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown severity {}, expected error or warning", severity),
+                ))
+            }
+        };
+        self.report_as(code, severity);
+        Ok(())
+    }
+
+    /// Report the issues with `code` with `severity`.
+    pub fn report_as(&mut self, code: IssueCode, severity: Severity) {
+        self.severities.insert(code, Some(severity));
+    }
+
+    /// Report the warnings as errors, except for the codes given a severity.
+    pub fn warnings_as_errors(&mut self) {
+        self.warnings_as_errors = true;
+    }
+
+    /// Report the issues matching a suppression as suppressed, whatever their
+    /// severity.
+    pub fn suppress(&mut self, suppressions: Vec<Suppression>) {
+        self.suppressions.extend(suppressions);
+    }
+
+    /// Change the severity of the issues, splitting the lines whose issues
+    /// end up with different severities and dropping the ignored issues.
+    pub fn apply(&self, error_vec: Vec<LineError>) -> Vec<LineError> {
+        if self.severities.is_empty() && !self.warnings_as_errors && self.suppressions.is_empty() {
+            return error_vec;
+        }
+        error_vec
+            .into_iter()
+            .flat_map(|e| {
+                let default = if self.warnings_as_errors {
+                    Severity::Error
+                } else {
+                    e.severity
+                };
+                let severity_of = |issue: &ValidationIssue| match self.severities.get(&issue.code) {
+                    Some(None) => None,
+                    _ if self.suppressions.iter().any(|s| s.matches(issue)) => {
+                        Some(Severity::Suppressed)
+                    }
+                    Some(severity) => *severity,
+                    None => Some(default),
+                };
+                [Severity::Error, Severity::Warning, Severity::Suppressed]
+                    .into_iter()
+                    .map(|severity| LineError {
+                        line: e.line,
+                        issues: e
+                            .issues
+                            .iter()
+                            .filter(|issue| severity_of(issue) == Some(severity))
+                            .cloned()
+                            .collect(),
+                        severity,
+                    })
+                    .filter(|e| !e.issues.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// Issues found in a part of the input.
+#[derive(Debug)]
+pub struct Part {
+    /// e.g. `resource uni_tidy`, `None` if the input is a single file
+    pub name: Option<String>,
+    pub issues: Vec<LineError>,
+}
+
+/// Outcome of a validation, presented by [`ValidationReport::present`].
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    /// the validated file as given, `None` for stdin
+    pub input: Option<String>,
+    pub parts: Vec<Part>,
+    /// model and FASTQ summaries
+    pub summary: Vec<String>,
+    pub elapsed: Duration,
+    /// with `--bench`
+    pub bench: Option<Measurements>,
+    /// with `--max-error-rate`
+    pub error_rate: Option<ErrorRate>,
+    /// with `--error-stream` and `--redact-paths`
+    pub diagnostics: Diagnostics,
+    /// with `--output`
+    pub output: OutputFormat,
+}
+
+/// Fraction of the records of a file allowed to have errors.
+#[derive(Debug, Clone, Copy)]
+pub struct ErrorRate {
+    pub max: f64,
+    /// records validated
+    pub records: usize,
+    /// line of the first record, the errors before it concern the whole file
+    pub first_line: usize,
+}
+
+impl ErrorRate {
+    /// Records with errors, `None` if an error concerns the whole file.
+    fn records_with_errors(&self, report: &ValidationReport) -> Option<usize> {
+        let mut lines = HashSet::new();
+        for e in report.lines().filter(|e| e.severity == Severity::Error) {
+            if e.line < self.first_line {
+                return None;
+            }
+            lines.insert(e.line);
+        }
+        Some(lines.len())
+    }
+
+    fn exceeded(&self, report: &ValidationReport) -> bool {
+        match self.records_with_errors(report) {
+            Some(n) => n as f64 > self.max * self.records as f64,
+            None => true,
+        }
+    }
+
+    /// Summary line of the rate of the report.
+    pub fn summary(&self, report: &ValidationReport) -> String {
+        match self.records_with_errors(report) {
+            Some(n) => format!(
+                "{} of {} records with errors ({:.2}%), at most {}% allowed",
+                n,
+                self.records,
+                100.0 * n as f64 / self.records.max(1) as f64,
+                100.0 * self.max
+            ),
+            None => "errors concerning the whole file, --max-error-rate does not apply".to_string(),
+        }
+    }
+}
+
+impl ValidationReport {
+    fn lines(&self) -> impl Iterator<Item = &LineError> {
+        self.parts.iter().flat_map(|part| part.issues.iter())
+    }
+
+    /// Lines with errors, as opposed to warnings.
+    pub fn n_errors(&self) -> usize {
+        self.lines()
+            .filter(|e| e.severity == Severity::Error)
+            .count()
+    }
+
+    pub fn n_warnings(&self) -> usize {
+        self.lines()
+            .filter(|e| e.severity == Severity::Warning)
+            .count()
+    }
+
+    /// Whether the input is invalid: it has errors or, with `--max-error-rate`,
+    /// too many records with errors.
+    pub fn failed(&self) -> bool {
+        match &self.error_rate {
+            Some(rate) => rate.exceeded(self),
+            None => self.n_errors() > 0,
+        }
+    }
+
+    /// Print the issues by part, the summaries and the measurements to the
+    /// stream of the report, the Code Quality report with `--output codeclimate`,
+    /// the last line of `--output jsonl` whose issues were already written, or
+    /// the MultiQC table with `--output multiqc`.
+    pub fn present(&self) {
+        let to = self.diagnostics;
+        match self.output {
+            OutputFormat::Text => {}
+            OutputFormat::Codeclimate => {
+                let issues = codeclimate::issues(self, self.input.as_deref().unwrap_or("stdin"));
+                emit(
+                    to,
+                    &serde_json::to_string_pretty(&issues).expect("issues are serializable"),
+                );
+                return;
+            }
+            OutputFormat::Jsonl => {
+                emit(to, &jsonl::summary(self));
+                return;
+            }
+            OutputFormat::Multiqc => {
+                let content = multiqc::custom_content(self);
+                emit(
+                    to,
+                    &serde_json::to_string_pretty(&content).expect("reports are serializable"),
+                );
+                return;
+            }
+        }
+        for part in &self.parts {
+            if let (Some(name), false) = (&part.name, part.issues.is_empty()) {
+                emit(to, &format!("{}:", name));
+            }
+            print_errors(to, &part.issues);
+        }
+        for line in &self.summary {
+            emit(to, line);
+        }
+        if let Some(bench) = &self.bench {
+            for line in bench.report() {
+                emit(to, &line);
+            }
+        }
+    }
+}
+
+/// Human-readable description of an issue.
+pub fn message(code: IssueCode) -> &'static str {
+    match code {
+        IssueCode::Parse => "unreadable record",
+        IssueCode::UnknownColumn => "unknown column, use --allow-extra-columns to ignore it",
+        IssueCode::InvalidValue => "invalid value",
+        IssueCode::EmptyField => "empty field",
+        IssueCode::EmptySample => "Empty sample?",
+        IssueCode::FractionalCount => "fractional count, normalized values instead of raw counts?",
+        IssueCode::NegativeCount => "negative count",
+        IssueCode::ColumnSumMismatch => "sample does not add up to the total of the normalization",
+        IssueCode::OutOfBounds => "value outside its bounds",
+        IssueCode::InvalidUniprotId => "invalid Uniprot ID",
+        IssueCode::NotInModel => "not in model!",
+        IssueCode::UnknownUniprotId => "Uniprot ID not found in the accession database",
+        IssueCode::NotInAnnotation => "not in annotation!",
+        IssueCode::DecoyEntry => "contaminant or decoy entry, see --decoys",
+        IssueCode::ByteOrderMark => "byte order mark at the start of the file ignored",
+        IssueCode::NumericOverflow => "out of the range of numbers",
+        IssueCode::PrecisionLoss => "loses digits when read",
+        IssueCode::MissingFastq => "Declared FASTQ path does not exist!",
+        IssueCode::MalformedFastq => "Malformed FASTQ",
+        IssueCode::InvalidFastqUrl => "Invalid FASTQ URL!",
+        IssueCode::UnreachableFastq => "Remote FASTQ is not reachable!",
+        IssueCode::EmptyFastq => "Remote FASTQ is empty!",
+        IssueCode::InterleavedFastq => {
+            "Interleaved paired reads in FASTQ! R1 and R2 must be split into separate files"
+        }
+        IssueCode::DuplicateReadIds => "Duplicated read IDs in FASTQ!",
+        IssueCode::LayoutMismatch => {
+            "Inconsistent experiment: R1 and R2 did not match the LibraryLayout! (assuming local data since field 'Run' is empty)"
+        }
+        IssueCode::PlatformMismatch => "Platform inconsistent with the reads",
+        IssueCode::InconsistentExperiment => "experiment inconsistent with its other rows",
+        IssueCode::InconsistentFeature => "feature with another m/z or retention time",
+        IssueCode::InvalidAdduct => "malformed adduct",
+        IssueCode::MassMismatch => "mass inconsistent with the formula",
+        IssueCode::InvalidFormula => "malformed chemical formula",
+        IssueCode::MissingColumn => "missing column",
+        IssueCode::MissingValue => "missing required value",
+        IssueCode::InvalidType => "has an invalid type",
+        IssueCode::ConstraintViolated => "violates a constraint",
+        IssueCode::DuplicateValue => "duplicated value in unique column",
+        IssueCode::DuplicateKey => "duplicated key",
+        IssueCode::ExcelSerialDate => "number instead of a date",
+        IssueCode::AmbiguousDate => "ambiguous date",
+        IssueCode::UnknownTerm => "unknown ontology term",
+        IssueCode::ObsoleteTerm => "obsolete ontology term",
+        IssueCode::SizeMismatch => "size mismatch",
+        IssueCode::HashMismatch => "hash mismatch",
+        IssueCode::InvalidWell => "invalid well",
+        IssueCode::Plugin => "rejected by plugin",
+        IssueCode::TooFewReplicates => "condition with too few replicates",
+        IssueCode::MalformedSampleName => "does not follow the naming convention",
+        IssueCode::SampleNotInAllLayers => "sample missing from other layers",
+        IssueCode::MissingRate => "column with too many missing values",
+        IssueCode::Outlier => "extreme outlier",
+        IssueCode::InvalidDialect => "not in the TSV dialect of the workflow",
+        IssueCode::NotTidy => "not a tidy table",
+        IssueCode::NonPortablePath => "path only valid on Windows",
+        IssueCode::MixedLineEndings => "mixed line endings",
+        IssueCode::StrayCarriageReturn => "stray carriage return",
+        IssueCode::TransposedMatrix => "matrix looks transposed",
+        IssueCode::ExcelArtifact => "identifier mangled by Excel",
+        IssueCode::NotAllowed => "not in the allowed values of the column",
+        IssueCode::ForeignKeyViolation => "key not in the referenced file",
+        IssueCode::ScaleMismatch => "values on another scale than declared",
+        IssueCode::MissingBatch => "batch not given for the sample",
+        IssueCode::ConfoundedBatch => "batch confounded with a condition",
+        IssueCode::WrongCompartment => "not in this compartment of the model",
+        IssueCode::EnzymeNotInReaction => "protein not used by the reaction",
+        IssueCode::Unphysiological => "outside the physiological range",
+        IssueCode::NotAnExchange => "not an exchange reaction of the model",
+    }
+}
+
+/// Describe an issue code given as an id or a name, e.g. `OV020` or `missing-fastq`:
+/// its message, the common causes and how to fix it.
+pub fn explain(code: &str) -> io::Result<String> {
+    let code = IssueCode::lookup(code).ok_or_else(|| unknown_code(code))?;
+    let explanation = explain::explanation(code);
+    Ok(format!(
+        "{} {}: {}\n\nCommon causes:\n{}\n\nHow to fix:\n{}",
+        code.id(),
+        code,
+        message(code),
+        wrap(explanation.causes),
+        wrap(explanation.fix)
+    ))
+}
+
+/// Indent a paragraph and wrap it at 80 columns.
+fn wrap(text: &str) -> String {
+    let mut lines = vec![String::from(" ")];
+    for word in text.split_whitespace() {
+        let line = lines.last_mut().unwrap();
+        if line.len() > 2 && line.len() + word.len() >= 80 {
+            lines.push(format!("  {}", word));
+        } else {
+            line.push(' ');
+            line.push_str(word);
+        }
+    }
+    lines.join("\n")
+}
+
+/// Format an issue as `[VALUE] MESSAGE [(DETAIL)]`. The field is shown instead
+/// of the value when there is no value.
+pub fn format_issue(issue: &ValidationIssue) -> String {
+    let detail = issue.detail.as_deref().unwrap_or_default();
+    match (issue.code, issue.value.as_deref(), issue.field.as_deref()) {
+        (IssueCode::Parse, _, _) => detail.to_string(),
+        (IssueCode::Plugin, _, plugin) => format!("[{}] {}", plugin.unwrap_or_default(), detail),
+        (code, value, field) => {
+            let mut formatted = match (value.filter(|v| !v.is_empty()), field) {
+                (Some(value), _) => format!("{} {}", value, message(code)),
+                (None, Some(field)) => format!("{}: {}", field, message(code)),
+                (None, None) => message(code).to_string(),
+            };
+            if !detail.is_empty() {
+                formatted.push_str(&format!(" ({})", detail));
+            }
+            formatted
+        }
+    }
+}
+
+/// Format the issues of a line, separated by ";\t".
+pub fn format_issues(issues: &[ValidationIssue]) -> String {
+    issues.iter().map(format_issue).join(";\t")
+}
+
+/// Format the errors grouped by message, showing the first lines where they occur.
+pub fn format_errors(error_vec: &[LineError]) -> Vec<String> {
+    let mut error_map = error_vec
+        .iter()
+        .map(|e| ((e.severity, format_issues(&e.issues)), e.line))
+        .into_group_map();
+    error_map
+        .iter_mut()
+        .map(|((severity, msg), lines)| {
+            let n_lines = lines.len();
+            lines.truncate(3);
+            match severity {
+                Severity::Error => format!("{} lines{:?}: {}", n_lines, lines, msg),
+                Severity::Warning => format!("{} lines{:?}: warning: {}", n_lines, lines, msg),
+                Severity::Suppressed => {
+                    format!("{} lines{:?}: suppressed: {}", n_lines, lines, msg)
+                }
+            }
+        })
+        .collect()
+}
+
+/// Number of issues of each code and severity, by code.
+pub fn counts(error_vec: &[LineError]) -> Vec<((IssueCode, Severity), usize)> {
+    error_vec
+        .iter()
+        .flat_map(|e| e.issues.iter().map(move |issue| (issue.code, e.severity)))
+        .counts()
+        .into_iter()
+        .sorted_by_key(|((code, _), _)| code.id())
+        .collect()
+}
+
+/// Number of issues of a code with a severity, e.g. `OV004 empty-field: 3 errors`.
+pub fn count_line(code: IssueCode, severity: Severity, n: usize) -> String {
+    match severity {
+        Severity::Suppressed => format!("{} {}: {} suppressed", code.id(), code, n),
+        _ => format!("{} {}: {} {}s", code.id(), code, n, severity),
+    }
+}
+
+/// Print the errors grouped by message, showing the first lines where they occur.
+pub fn print_errors(to: Diagnostics, error_vec: &[LineError]) {
+    for line in format_errors(error_vec) {
+        emit(to, &line);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_issues_are_formatted() {
+        let issue = ValidationIssue::new(IssueCode::InvalidUniprotId)
+            .field("uniprot")
+            .value("E0X97");
+        assert_eq!(format_issue(&issue), "E0X97 invalid Uniprot ID");
+        let issue = ValidationIssue::new(IssueCode::EmptySample)
+            .field("sample")
+            .value("");
+        assert_eq!(format_issue(&issue), "sample: Empty sample?");
+        let issue = ValidationIssue::new(IssueCode::UnreachableFastq)
+            .value("https://example.org/R1.fastq")
+            .detail("404");
+        assert_eq!(
+            format_issue(&issue),
+            "https://example.org/R1.fastq Remote FASTQ is not reachable! (404)"
+        );
+    }
+
+    #[test]
+    fn test_codes_are_explained() {
+        let explanation = explain("missing-fastq").unwrap();
+        assert!(
+            explanation.starts_with("OV020 missing-fastq: Declared FASTQ path does not exist!\n")
+        );
+        assert!(explanation.contains("\nCommon causes:\n  R1 or R2 points"));
+        assert!(explanation.contains("\nHow to fix:\n  Fix the path"));
+        assert!(explanation.lines().all(|l| l.len() <= 80));
+        assert!(explain("OV999").is_err());
+    }
+
+    #[test]
+    fn test_report_fails_on_errors_only() {
+        let warning = LineError {
+            line: 3,
+            issues: vec![ValidationIssue::new(IssueCode::PlatformMismatch)],
+            severity: Severity::Warning,
+        };
+        let mut report = ValidationReport {
+            parts: vec![Part {
+                name: None,
+                issues: vec![warning],
+            }],
+            ..Default::default()
+        };
+        assert!(!report.failed());
+        report.parts.push(Part {
+            name: Some("member data/uni_tidy.csv".to_string()),
+            issues: vec![LineError::error(2, ValidationIssue::new(IssueCode::Parse))],
+        });
+        assert_eq!((report.n_errors(), report.n_warnings()), (1, 1));
+        assert!(report.failed());
+        report.error_rate = Some(ErrorRate {
+            max: 0.01,
+            records: 100,
+            first_line: 2,
+        });
+        assert!(!report.failed());
+        report.parts[1].issues.push(LineError::error(
+            0,
+            ValidationIssue::new(IssueCode::HashMismatch),
+        ));
+        assert!(report.failed());
+    }
+
+    #[test]
+    fn test_overrides_change_severities() {
+        let mut overrides = Overrides::default();
+        overrides.ignore("OV020").unwrap();
+        overrides.promote("platform-mismatch=error").unwrap();
+        assert!(overrides.ignore("OV999").is_err());
+        assert!(overrides.promote("OV028=fatal").is_err());
+        let error_vec = vec![
+            LineError {
+                line: 2,
+                issues: vec![
+                    ValidationIssue::new(IssueCode::MissingFastq),
+                    ValidationIssue::new(IssueCode::EmptyField),
+                ],
+                severity: Severity::Error,
+            },
+            LineError {
+                line: 3,
+                issues: vec![ValidationIssue::new(IssueCode::PlatformMismatch)],
+                severity: Severity::Warning,
+            },
+        ];
+        let error_vec = overrides.apply(error_vec);
+        assert_eq!(error_vec.len(), 2);
+        assert_eq!(error_vec[0].issues.len(), 1);
+        assert_eq!(error_vec[1].severity, Severity::Error);
+        assert!(overrides.promote("OV028=suppressed").is_err());
+        overrides.suppress(vec![Suppression {
+            code: IssueCode::PlatformMismatch,
+            key: "ILLUMINA".to_string(),
+        }]);
+        let error_vec = overrides.apply(vec![LineError::warning(
+            4,
+            ValidationIssue::new(IssueCode::PlatformMismatch).value("ILLUMINA"),
+        )]);
+        assert_eq!(error_vec[0].severity, Severity::Suppressed);
+    }
+}
@@ -0,0 +1,353 @@
+use crate::runner::InputFormat;
+use csv::{ByteRecord, ReaderBuilder, WriterBuilder};
+use itertools::Itertools;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Rewriting of the identifiers before the validation, so that near misses are
+/// accepted deliberately, the `[ids]` table of the configuration:
+///
+/// ```toml
+/// [ids]
+/// trim = true
+/// case = "upper"
+/// strip_prefixes = ["sp|", "M_"]
+/// map = "ids.tsv"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IdRules {
+    /// remove the surrounding whitespace
+    pub trim: bool,
+    pub case: Option<Case>,
+    /// the first prefix found is removed, with the rest of a UniProt FASTA
+    /// header (`sp|P0A6F5|CH60_ECOLI`) for the prefixes ending with `|`
+    pub strip_prefixes: Vec<String>,
+    /// TSV of an identifier and its replacement per line
+    pub map: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Case {
+    Upper,
+    Lower,
+}
+
+/// The rules with the mappings loaded.
+#[derive(Debug, Default, Clone)]
+pub struct Rules {
+    trim: bool,
+    case: Option<Case>,
+    strip_prefixes: Vec<String>,
+    map: HashMap<String, String>,
+    /// legacy identifier -> current one, from `--alias`
+    alias: HashMap<String, String>,
+}
+
+/// An identifier rewritten before the validation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rewrite {
+    pub line: usize,
+    pub from: String,
+    pub to: String,
+    /// the rules that changed it, in the order they are applied
+    pub rules: Vec<&'static str>,
+}
+
+/// Read a mapping, an identifier and its replacement separated by a tab per
+/// line, with blank lines, `#` comments and an `old_id` header skipped.
+pub fn read_map(path: &Path) -> io::Result<HashMap<String, String>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+    let mut map = HashMap::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('\t') {
+            Some(("old_id", _)) if i == 0 => {}
+            Some((from, to)) if !from.trim().is_empty() && !to.trim().is_empty() => {
+                map.insert(from.trim().to_string(), to.trim().to_string());
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "{}: line {}: expected an identifier and its replacement separated by a \
+                         tab",
+                        path.display(),
+                        i + 1
+                    ),
+                ))
+            }
+        }
+    }
+    Ok(map)
+}
+
+/// The rules of the `[ids]` table and the aliases of `--alias`, with their
+/// mappings loaded, `None` if there are neither.
+pub fn load(rules: Option<IdRules>, alias: Option<&Path>) -> io::Result<Option<Rules>> {
+    if rules.is_none() && alias.is_none() {
+        return Ok(None);
+    }
+    let rules = rules.unwrap_or_default();
+    Ok(Some(Rules {
+        map: match &rules.map {
+            Some(path) => read_map(path)?,
+            None => HashMap::new(),
+        },
+        alias: match alias {
+            Some(path) => read_map(path)?,
+            None => HashMap::new(),
+        },
+        trim: rules.trim,
+        case: rules.case,
+        strip_prefixes: rules.strip_prefixes,
+    }))
+}
+
+impl Rules {
+    /// The rewritten identifier and the rules that changed it, `None` if
+    /// none did.
+    fn apply(&self, id: &str) -> Option<(String, Vec<&'static str>)> {
+        let mut rules = Vec::new();
+        let mut current = id.to_string();
+        let mut step = |rule: &'static str, next: String, current: &mut String| {
+            if next != *current {
+                rules.push(rule);
+                *current = next;
+            }
+        };
+        if self.trim {
+            step("trim", current.trim().to_string(), &mut current);
+        }
+        if let Some(prefix) = self
+            .strip_prefixes
+            .iter()
+            .find(|prefix| current.starts_with(prefix.as_str()))
+        {
+            let rest = &current[prefix.len()..];
+            let rest = if prefix.ends_with('|') {
+                rest.split('|').next().unwrap_or(rest)
+            } else {
+                rest
+            };
+            step("prefix", rest.to_string(), &mut current);
+        }
+        match self.case {
+            Some(Case::Upper) => step("case", current.to_uppercase(), &mut current),
+            Some(Case::Lower) => step("case", current.to_lowercase(), &mut current),
+            None => {}
+        }
+        if let Some(to) = self.map.get(&current) {
+            step("map", to.clone(), &mut current);
+        }
+        if let Some(to) = self.alias.get(&current) {
+            step("alias", to.clone(), &mut current);
+        }
+        (!rules.is_empty()).then_some((current, rules))
+    }
+}
+
+/// Column of the identifiers of a format, `None` if it has none.
+fn id_column(format: &InputFormat, headers: &ByteRecord) -> Option<usize> {
+    let name: &[u8] = match format {
+        InputFormat::Prot | InputFormat::Counts => return Some(0),
+        InputFormat::TidyProt | InputFormat::EcUsage => b"uniprot",
+        InputFormat::Met | InputFormat::ThermoConc => b"met_id",
+        InputFormat::Flux | InputFormat::ThermoDg => b"reaction_id",
+        InputFormat::FeatureTable => b"feature_id",
+        InputFormat::Multi => b"id",
+        InputFormat::StrainDesign => b"gene_or_reaction_id",
+        InputFormat::Medium => b"exchange_reaction_or_met_id",
+        InputFormat::Rna | InputFormat::Datapackage => return None,
+    };
+    headers.iter().position(|h| h == name)
+}
+
+/// Length of a row without its line ending and the blank lines after it.
+fn line_end(row: &[u8]) -> usize {
+    row.iter()
+        .rposition(|b| !matches!(b, b'\r' | b'\n'))
+        .map_or(0, |i| i + 1)
+}
+
+/// Offset where a row starts, from the position of its record: csv places the
+/// record following a `\r\n` on the `\n`, which ends the previous row.
+pub(crate) fn row_start(content: &[u8], position: usize) -> usize {
+    match (
+        position.checked_sub(1).map(|i| content[i]),
+        content.get(position),
+    ) {
+        (Some(b'\r'), Some(b'\n')) => position + 1,
+        _ => position,
+    }
+}
+
+/// The content with the identifiers rewritten by the rules, if any, and the
+/// rewrites, each logged. Only the rows with a rewritten identifier are
+/// written again, the others are kept as they are.
+pub fn apply<'a>(
+    content: &'a [u8],
+    format: &InputFormat,
+    rules: Option<&Rules>,
+) -> (Cow<'a, [u8]>, Vec<Rewrite>) {
+    match rules {
+        Some(rules) => rewrite_with(rules, content, format),
+        None => (Cow::Borrowed(content), Vec::new()),
+    }
+}
+
+fn rewrite_with<'a>(
+    rules: &Rules,
+    content: &'a [u8],
+    format: &InputFormat,
+) -> (Cow<'a, [u8]>, Vec<Rewrite>) {
+    // unreadable rows are reported by the validation and left unchanged
+    let rows: Vec<(usize, ByteRecord)> = ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(format.delimiter())
+        .flexible(true)
+        .from_reader(content)
+        .into_byte_records()
+        .filter_map(Result::ok)
+        .map(|record| {
+            let position = record.position().map_or(0, |p| p.byte() as usize);
+            (row_start(content, position), record)
+        })
+        .collect();
+    let (first, column) = match (format.has_headers(), rows.first()) {
+        (true, Some((_, headers))) => match id_column(format, headers) {
+            Some(column) => (1, column),
+            None => return (Cow::Borrowed(content), Vec::new()),
+        },
+        (false, _) => (0, 0),
+        (true, None) => return (Cow::Borrowed(content), Vec::new()),
+    };
+    let mut rewritten = Vec::with_capacity(content.len());
+    let mut rewrites = Vec::new();
+    let mut copied = 0;
+    for (i, (start, record)) in rows.iter().enumerate().skip(first) {
+        let id = match record.get(column).map(std::str::from_utf8) {
+            Some(Ok(id)) => id,
+            _ => continue,
+        };
+        let (to, applied) = match rules.apply(id) {
+            Some(rewrite) => rewrite,
+            None => continue,
+        };
+        let end = rows.get(i + 1).map_or(content.len(), |(start, _)| *start);
+        let row = &content[*start..end];
+        let ending = &row[line_end(row)..];
+        let mut fields: Vec<&[u8]> = record.iter().collect();
+        fields[column] = to.as_bytes();
+        let mut writer = WriterBuilder::new()
+            .delimiter(format.delimiter())
+            .has_headers(false)
+            .from_writer(Vec::new());
+        if writer.write_record(&fields).is_err() {
+            continue;
+        }
+        let line = match writer.into_inner() {
+            Ok(line) => line,
+            Err(_) => continue,
+        };
+        rewritten.extend_from_slice(&content[copied..*start]);
+        rewritten.extend_from_slice(&line[..line_end(&line)]);
+        rewritten.extend_from_slice(ending);
+        copied = end;
+        info!(line = i + 1, from = id, to = %to, rules = %applied.join(", "), "identifier rewritten");
+        rewrites.push(Rewrite {
+            line: i + 1,
+            from: id.to_string(),
+            to,
+            rules: applied,
+        });
+    }
+    if rewrites.is_empty() {
+        return (Cow::Borrowed(content), rewrites);
+    }
+    rewritten.extend_from_slice(&content[copied..]);
+    (Cow::Owned(rewritten), rewrites)
+}
+
+/// Summary line of the rewrites, with the number of identifiers changed by
+/// each rule, followed by the rows rewritten by each alias.
+pub fn summary(rewrites: &[Rewrite]) -> Vec<String> {
+    if rewrites.is_empty() {
+        return Vec::new();
+    }
+    let aliases = rewrites
+        .iter()
+        .filter(|r| r.rules.contains(&"alias"))
+        .map(|r| ((r.from.as_str(), r.to.as_str()), r.line))
+        .into_group_map()
+        .into_iter()
+        .sorted_by_key(|(_, lines)| lines[0])
+        .map(|((from, to), mut lines)| {
+            let n_lines = lines.len();
+            lines.truncate(3);
+            format!("alias {} -> {}: {} lines{:?}", from, to, n_lines, lines)
+        });
+    let by_rule = rewrites
+        .iter()
+        .flat_map(|r| r.rules.iter())
+        .counts()
+        .into_iter()
+        .sorted()
+        .map(|(rule, n)| format!("{}: {}", rule, n))
+        .join(", ");
+    std::iter::once(format!(
+        "{} identifiers rewritten before the validation ({})",
+        rewrites.len(),
+        by_rule
+    ))
+    .chain(aliases)
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_identifiers_are_rewritten() {
+        let rules = Rules {
+            trim: true,
+            case: None,
+            strip_prefixes: vec!["sp|".to_string(), "M_".to_string()],
+            map: [("glc_D".to_string(), "glc__D".to_string())]
+                .into_iter()
+                .collect(),
+            alias: [("pyruvate".to_string(), "pyr".to_string())]
+                .into_iter()
+                .collect(),
+        };
+        let content =
+            b"met_id,sample,value\r\n M_acon_C ,S1,1\r\nglc_D,S1,\"2,5\"\r\npyruvate,S1,3\r\n";
+        let (rewritten, rewrites) = rewrite_with(&rules, content, &InputFormat::Met);
+        assert_eq!(
+            rewritten.as_ref(),
+            b"met_id,sample,value\r\nacon_C,S1,1\r\nglc__D,S1,\"2,5\"\r\npyr,S1,3\r\n"
+        );
+        assert_eq!(rewrites[0].rules, vec!["trim", "prefix"]);
+        assert_eq!((rewrites[1].line, rewrites[1].to.as_str()), (3, "glc__D"));
+        assert_eq!(
+            rules.apply("sp|P0A6F5|CH60_ECOLI"),
+            Some(("P0A6F5".to_string(), vec!["prefix"]))
+        );
+        assert_eq!(
+            summary(&rewrites),
+            [
+                "3 identifiers rewritten before the validation (alias: 1, map: 1, prefix: 1, trim: 1)",
+                "alias pyruvate -> pyr: 1 lines[4]"
+            ]
+        );
+    }
+}
@@ -1,83 +1,1763 @@
+use crate::accessions::{self, AccessionDb, FetchDbArgs};
+use crate::allowed::{self, AllowedList};
+use crate::archive::{self, ArchiveKind, MemberMapping};
+use crate::batch::{self, BatchOptions};
+use crate::bench::Bench;
+use crate::bom;
+use crate::bundle::Bundle;
+use crate::cache::{self, CacheArgs};
+use crate::certificate::{Certificate, HashingReader};
+use crate::compression::Compression;
+use crate::config::{Config, Rename};
+use crate::confounding;
+use crate::convert::{self, ConvertArgs};
+use crate::counts::{self, CountsOptions, Normalization};
+use crate::coverage;
+use crate::crosscheck::{self, CrosscheckArgs};
+use crate::datapackage;
+use crate::deferred;
+use crate::diff::{self, DiffArgs};
+use crate::example::{self, ExampleArgs};
+use crate::experiments;
+use crate::fastq::{self, FastqOptions};
+use crate::features::{self, FeatureOptions, MzRange};
+use crate::fetch::{self, FetchModelArgs};
+use crate::imodulon;
+use crate::jsonl;
+use crate::jsonschema::{self, SchemaOutArgs};
+use crate::line_endings;
+use crate::logging::LogFormat;
+use crate::meta::Metadata;
+use crate::missing::{self, MissingOptions};
+use crate::models::Models;
+use crate::naming;
+use crate::normalize;
+use crate::ontology;
+use crate::outliers;
+use crate::plugins;
+use crate::preview;
+use crate::profile::Profile;
+use crate::proteomics::{self, DecoyPolicy, ProteomicsOptions};
+use crate::remote::{self, NetworkOptions, RemoteFile};
+use crate::replicates::{self, ReplicateOptions};
+use crate::report::{
+    self, Diagnostics, ErrorRate, ErrorStream, OutputFormat, Overrides, Part, ValidationReport,
+};
+use crate::rewrite::{self, Rewrite};
+use crate::scale::{self, Scale};
+use crate::schema::{self, CrossFileKey};
+use crate::serve::{self, ServeArgs};
+use crate::sqlite;
+use crate::stats;
+use crate::suppressions;
+use crate::tidy;
+use crate::transposed;
+use crate::unique::{self, UniqueKeys};
 use crate::validators::*;
-use argh::FromArgs;
-use itertools::Itertools;
-use rust_sbml::ModelRaw;
-use std::path::PathBuf;
-use strum::EnumString;
+use crate::wells::PlateFormat;
+use argh::{FromArgs, SubCommands};
+use csv::ReaderBuilder;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use strum::{Display, EnumString};
+use tracing::Level;
 
-#[derive(Debug, EnumString)]
+#[derive(Debug, EnumString, Display)]
 #[strum(serialize_all = "snake_case")]
 pub enum InputFormat {
     Prot,
     TidyProt,
     Met,
     Flux,
+    EcUsage,
+    ThermoConc,
+    ThermoDg,
+    Medium,
+    StrainDesign,
     Rna,
+    Counts,
+    FeatureTable,
+    Multi,
+    Datapackage,
+}
+
+impl InputFormat {
+    pub(crate) fn has_headers(&self) -> bool {
+        !matches!(self, InputFormat::Prot)
+    }
+    pub(crate) fn delimiter(&self) -> u8 {
+        match self {
+            InputFormat::Rna => RnaRecord::delimiter(),
+            InputFormat::Counts => counts::DELIMITER,
+            _ => b',',
+        }
+    }
 }
 
 #[derive(Debug, FromArgs)]
 /// Omics format validator.
 pub struct Args {
-    /// input omics file.
+    /// display the version
+    #[argh(switch, short = 'v')]
+    pub version: bool,
+
+    /// level of the log events written to stderr: error, warn (default), info, debug or
+    /// trace. Must precede the command or file
+    #[argh(option, default = "Level::WARN")]
+    pub log_level: Level,
+
+    /// format of the log events: text (default) or json
+    #[argh(option, default = "LogFormat::Text")]
+    pub log_format: LogFormat,
+
+    /// never access the network: remote files are not checked and downloads fail
+    #[argh(switch)]
+    pub offline: bool,
+
+    /// timeout of the network requests in seconds, to connect and between reads (default 30)
+    #[argh(option, default = "30")]
+    pub timeout: u64,
+
+    /// attempts after a failed network request, with exponential backoff (default 3)
+    #[argh(option, default = "3")]
+    pub retries: u32,
+
+    /// replace the home directories in the diagnostics and errors with ~ and the hosts
+    /// of URLs with <host>, to share reports externally
+    #[argh(switch)]
+    pub redact_paths: bool,
+
+    #[argh(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, FromArgs)]
+#[argh(subcommand)]
+pub enum Command {
+    Validate(Box<ValidateArgs>),
+    Convert(ConvertArgs),
+    Summarize(SummarizeArgs),
+    FetchModel(FetchModelArgs),
+    Serve(ServeArgs),
+    Explain(ExplainArgs),
+    FetchDb(FetchDbArgs),
+    Cache(CacheArgs),
+    Crosscheck(CrosscheckArgs),
+    Diff(DiffArgs),
+    Example(ExampleArgs),
+    SchemaOut(SchemaOutArgs),
+}
+
+#[derive(Debug, FromArgs)]
+/// Validate an omics file (the default command).
+#[argh(subcommand, name = "validate")]
+pub struct ValidateArgs {
+    /// input omics file, or its http(s):// or s3:// URL.
     #[argh(positional)]
     file: Option<PathBuf>,
 
-    /// format of the file. Currently supported: {{prot, tidy_prot, met, rna}}
+    /// format of the file. Currently supported: {{prot, tidy_prot, met, flux, ec_usage,
+    /// thermo_conc, thermo_dg, medium, strain_design, rna, counts, feature_table, multi,
+    /// datapackage}}
     #[argh(option, short = 'f', default = "InputFormat::TidyProt")]
     pub format: InputFormat,
 
-    /// path to SBML model file, used for metabolite verification
+    /// validate the members of a zip/tar.gz input matching PATTERN with FORMAT, given as
+    /// PATTERN=FORMAT (repeatable). By default, every member is validated with --format
+    #[argh(option)]
+    pub member: Vec<MemberMapping>,
+
+    /// SQLite database to validate a table of (see --table) instead of a file,
+    /// with the column names as header (needs the `sqlite` feature)
+    #[argh(option)]
+    pub sqlite: Option<PathBuf>,
+
+    /// table of the --sqlite database to validate
+    #[argh(option)]
+    pub table: Option<String>,
+
+    /// validate the files matching this glob pattern instead of the input file,
+    /// `**` matching any subdirectory, e.g. `data/**/*.csv` (repeatable)
+    #[argh(option)]
+    pub files: Vec<String>,
+
+    /// validate the files listed in this file, one path per line relative to it,
+    /// instead of the input file
+    #[argh(option)]
+    pub manifest: Option<PathBuf>,
+
+    /// number of files of --files and --manifest validated in parallel (default:
+    /// the number of CPUs)
+    #[argh(option)]
+    pub jobs: Option<usize>,
+
+    /// validate every file of --files and --manifest even after one has errors,
+    /// instead of skipping the remaining ones
+    #[argh(switch)]
+    pub keep_going: bool,
+
+    /// path to SBML model file, used for metabolite verification and to look up
+    /// the proteins in its gene products. Repeatable: the identifiers are
+    /// accepted if found in any of the models
     #[argh(option, short = 'm')]
-    pub model: Option<PathBuf>,
+    pub model: Vec<PathBuf>,
 
-    /// display the version
-    #[argh(switch, short = 'v')]
-    pub version: bool,
+    /// universal namespace dump (e.g., BiGG `bigg_models_metabolites.txt` or MetaNetX
+    /// `chem_prop.tsv`) to verify metabolites against when there is no model (repeatable)
+    #[argh(option)]
+    pub universal: Vec<PathBuf>,
+
+    /// GFF3 or GTF genome annotation to verify the gene IDs of the counts and multi
+    /// formats against, instead of the model
+    #[argh(option)]
+    pub annotation: Option<PathBuf>,
+
+    /// accept counts within this distance of an integer (e.g. `12.0`), which must be
+    /// integer literals otherwise
+    #[argh(option)]
+    pub count_tolerance: Option<f64>,
+
+    /// the counts format holds normalized values, whose samples must add up to 1e6
+    /// (tpm) or 100 (percent)
+    #[argh(option)]
+    pub normalized: Option<Normalization>,
+
+    /// relative difference allowed between the sum of a sample and the total of the
+    /// normalization (default 0.01)
+    #[argh(option, default = "0.01")]
+    pub sum_tolerance: f64,
+
+    /// contaminant (`CON__`), decoy (`REV__`) and FASTA header (`sp|`, `tr|`) entries
+    /// of the protein formats: error (default), warn, or strip to accept them and
+    /// remove them with --fix
+    #[argh(option, default = "DecoyPolicy::Error")]
+    pub decoys: DecoyPolicy,
+
+    /// accept semicolon-separated protein groups (`P12345;Q67890`) in the uniprot
+    /// column, checking each member
+    #[argh(switch)]
+    pub allow_groups: bool,
+
+    /// write a fixed copy of the input here, `-` for stdout: the rows stripped by
+    /// --decoys strip are removed and the line endings normalized
+    #[argh(option)]
+    pub fix: Option<PathBuf>,
+
+    /// m/z range of the instrument for the feature_table format, given as MIN:MAX
+    /// (e.g. 50:2000). Any positive m/z is accepted by default
+    #[argh(option)]
+    pub mz_range: Option<MzRange>,
+
+    /// deviation allowed between the masses of the feature_table format and the
+    /// monoisotopic mass of their formula, in ppm (default 10)
+    #[argh(option, default = "10.0")]
+    pub mass_tolerance: f64,
+
+    /// OBO ontology (e.g. efo.obo) whose terms the columns mapped to it in the
+    /// `[ontology]` table of the configuration must belong to (repeatable)
+    #[argh(option)]
+    pub obo: Vec<PathBuf>,
+
+    /// restrict the values of a column of any format to the lines of a file,
+    /// given as COLUMN=FILE, e.g. sample=samples.txt (repeatable)
+    #[argh(option)]
+    pub allowed: Vec<AllowedList>,
+
+    /// check that the key of each row of a file is a key of the rows of
+    /// another, given as FILE:COLUMNS=FILE:COLUMNS with the columns separated
+    /// by commas, e.g. data.csv:sample=meta.csv:sample (repeatable)
+    #[argh(option)]
+    pub fk: Vec<CrossFileKey>,
+
+    /// format of the plates whose coordinates are in the `well` column of any format:
+    /// 96 (default), 384 or 1536
+    #[argh(option, default = "PlateFormat::Wells96")]
+    pub plate: PlateFormat,
+
+    /// report the conditions with fewer than N replicates (distinct samples) in the
+    /// formats with a sample column and the Data Package resources. Requires every
+    /// row to be validated
+    #[argh(option)]
+    pub min_replicates: Option<usize>,
+
+    /// regular expression whose first group is the condition of a sample name, used
+    /// by --min-replicates when there is no `condition` column (default `^(.+)_[^_]+$`,
+    /// e.g. glc_1 -> glc)
+    #[argh(option)]
+    pub replicate_pattern: Option<String>,
+
+    /// report the columns whose fraction of missing values (empty, NA, NaN, null)
+    /// exceeds RATE, e.g. 0.5. Set per column in the `[max_missing]` table of the
+    /// configuration
+    #[argh(option)]
+    pub max_missing_rate: Option<f64>,
+
+    /// warn about the values whose robust z-score (distance to the median in scaled
+    /// median absolute deviations) across the samples of their identifier exceeds
+    /// --outlier-threshold
+    #[argh(switch)]
+    pub outliers: bool,
+
+    /// robust z-score above which --outliers warns (default 3.5)
+    #[argh(option, default = "3.5")]
+    pub outlier_threshold: f64,
+
+    /// warn if the values do not look on SCALE: linear (raw intensities or counts)
+    /// or log. Set per format in the `[scale]` table of the configuration
+    #[argh(option)]
+    pub scale: Option<Scale>,
+
+    /// check that the tidy formats have one row per identifier and sample and
+    /// no columns left over from a wide table, e.g., named with numbers
+    #[argh(switch)]
+    pub check_tidy: bool,
+
+    /// check that the batch and run_date columns are filled for every sample and
+    /// that no batch holds a single condition
+    #[argh(switch)]
+    pub check_batches: bool,
+
+    /// fail only if more than RATE of the records, e.g. 0.01, have errors; all
+    /// the issues are still reported
+    #[argh(option)]
+    pub max_error_rate: Option<f64>,
+
+    /// check that the Uniprot IDs exist in the database downloaded with `fetch-db
+    /// uniprot-swissprot`
+    #[argh(switch)]
+    pub check_uniprot: bool,
+
+    /// check that the Uniprot IDs exist with the ID mapping service of UniProt,
+    /// in batches with the verdicts cached on disk
+    #[argh(switch)]
+    pub check_uniprot_online: bool,
+
+    /// check that remote (http/https, and s3 if built with the `s3` feature) FASTQ files are reachable
+    #[argh(switch)]
+    pub check_remote: bool,
+
+    /// number of remote checks (e.g. of --check-remote) run at the same time
+    /// once the rows of a file are read (default 8)
+    #[argh(option, default = "deferred::DEFAULT_JOBS")]
+    pub remote_jobs: usize,
+
+    /// look for duplicated read IDs in the FASTQ files (uses a fixed 16 MiB Bloom filter)
+    #[argh(switch)]
+    pub fastq_duplicate_ids: bool,
+
+    /// stop scanning a FASTQ file after N malformed records (default 10), the
+    /// rest of the file is not checked
+    #[argh(option, default = "fastq::DEFAULT_MAX_ERRORS")]
+    pub fastq_max_errors: usize,
+
+    /// only check the first N and last N records of each FASTQ file, read from
+    /// its end if it is uncompressed or BGZF-compressed, to catch truncated files
+    /// without a full scan
+    #[argh(option)]
+    pub fastq_spotcheck: Option<usize>,
+
+    /// ignore columns that are not part of the format instead of reporting them
+    #[argh(switch)]
+    pub allow_extra_columns: bool,
+
+    /// read the values as 28-digit decimals instead of 64-bit floats when
+    /// checking them for overflow and lost digits
+    #[argh(switch)]
+    pub exact_decimal: bool,
+
+    /// preset of checks: default, strict (Uniprot isoforms rejected, warnings are
+    /// errors), lenient (extra columns and isoforms accepted, missing values are
+    /// warnings) or imodulon (RNA sheets as read by the modulome-workflow). The
+    /// other options take precedence
+    #[argh(option, default = "Profile::Default")]
+    pub profile: Profile,
+
+    /// read the column FROM as the field TO, given as FROM=TO (repeatable)
+    #[argh(option)]
+    pub rename: Vec<Rename>,
+
+    /// configuration file, `omics_valid.toml` in the working directory by default
+    #[argh(option)]
+    pub config: Option<PathBuf>,
+
+    /// validate only the first N data rows
+    #[argh(option)]
+    pub head: Option<usize>,
+
+    /// validate only N data rows, sampled uniformly
+    #[argh(option)]
+    pub sample_rows: Option<usize>,
+
+    /// print the inferred type, example values and missing values of each
+    /// column instead of validating the file
+    #[argh(switch)]
+    pub preview: bool,
+
+    /// write a JSON validation certificate (hashes, format, version) here if the file is valid
+    #[argh(option)]
+    pub certificate: Option<PathBuf>,
+
+    /// write a JSON descriptor of the file (format, records, samples, identifier
+    /// namespace, SHA-256) here if it is valid, e.g. for DataLad or DVC
+    #[argh(option)]
+    pub meta_out: Option<PathBuf>,
+
+    /// write the canonical form of the file here if it is valid: standard column
+    /// order, trimmed fields, uppercased accessions, rows sorted by key and `\n`
+    /// line endings
+    #[argh(option)]
+    pub normalize: Option<PathBuf>,
+
+    /// write the coverage of the species of each model, per compartment, by the
+    /// metabolites of a met file here, as JSON if the path ends with .json and
+    /// CSV otherwise, with a summary line per model
+    #[argh(option)]
+    pub met_coverage: Option<PathBuf>,
+
+    /// write the reactions of each subsystem (SBML group) of the models measured
+    /// and carrying flux in each sample of a flux file here, as JSON if the path
+    /// ends with .json and CSV otherwise, with a summary line per model and sample
+    #[argh(option)]
+    pub subsystem_coverage: Option<PathBuf>,
+
+    /// report the fraction of the proteins of the reference proteome (--proteome)
+    /// or of the UniProt-annotated gene products of the models quantified by a
+    /// prot or tidy_prot file, in total and per sample
+    #[argh(switch)]
+    pub protein_coverage: bool,
+
+    /// reference proteome of --protein-coverage: a UniProt FASTA file or a list
+    /// of accessions, one per line
+    #[argh(option)]
+    pub proteome: Option<PathBuf>,
+
+    /// write the report of each input file in a directory named after it here:
+    /// report.json, the issues and summaries, and summary.txt, the text report
+    /// with the number of issues of each code
+    #[argh(option)]
+    pub report_dir: Option<PathBuf>,
+
+    /// with --report-dir, also write the header and the rows without errors to
+    /// valid.<ext> and those with errors to invalid.<ext>
+    #[argh(switch)]
+    pub split: bool,
+
+    /// name of a WebAssembly plugin in the plugins directory to validate each record with (repeatable)
+    #[argh(option)]
+    pub plugin: Vec<String>,
+
+    /// directory containing the `<name>.wasm` plugins
+    #[argh(option, default = "PathBuf::from(\"plugins\")")]
+    pub plugins_dir: PathBuf,
+
+    /// do not report the issues with this code, e.g. OV020 or missing-fastq (repeatable)
+    #[argh(option)]
+    pub ignore: Vec<String>,
+
+    /// report the issues with a code with another severity, given as CODE=error|warning
+    /// (repeatable)
+    #[argh(option)]
+    pub promote: Vec<String>,
+
+    /// file of accepted known issues, one CODE KEY per line where KEY is the
+    /// offending value, reported as suppressed instead of failing the
+    /// validation; `.omics_valid_ignore` in the working directory by default
+    #[argh(option)]
+    pub ignore_file: Option<PathBuf>,
+
+    /// TSV of legacy identifiers and their current ones (old_id, new_id),
+    /// applied to the identifier column before the validation, with the
+    /// rewritten rows reported
+    #[argh(option)]
+    pub alias: Option<PathBuf>,
+
+    /// where validation issues and summaries are written: stdout (default) or stderr
+    #[argh(option, default = "ErrorStream::Stdout")]
+    pub error_stream: ErrorStream,
+
+    /// format of the report: text (default), codeclimate, the Code Quality JSON of
+    /// GitLab merge requests, jsonl, a JSON object per issue as soon as it is found,
+    /// or multiqc, a custom content table for MultiQC reports
+    #[argh(option, default = "OutputFormat::Text")]
+    pub output: OutputFormat,
+
+    /// report the throughput (records/s, MB/s) and the time spent parsing, validating
+    /// and looking up identifiers in the models
+    #[argh(switch)]
+    pub bench: bool,
+}
+
+#[derive(Debug, FromArgs)]
+/// Count the issues of an omics file by code and describe its samples.
+#[argh(subcommand, name = "summarize")]
+pub struct SummarizeArgs {
+    /// input omics file.
+    #[argh(positional)]
+    file: Option<PathBuf>,
+
+    /// format of the file. Currently supported: {{prot, tidy_prot, met, rna, multi}}
+    #[argh(option, short = 'f', default = "InputFormat::TidyProt")]
+    format: InputFormat,
+
+    /// path to SBML model file, used for metabolite verification (repeatable)
+    #[argh(option, short = 'm')]
+    model: Vec<PathBuf>,
+
+    /// universal namespace dump to verify metabolites against (repeatable)
+    #[argh(option)]
+    universal: Vec<PathBuf>,
+
+    /// print the counts of the issues and the statistics of the samples as a
+    /// JSON object instead of tables
+    #[argh(switch)]
+    json: bool,
+
+    /// also report the number of distinct identifiers and samples, the N most
+    /// frequent identifiers and the identifiers of a single row
+    #[argh(option)]
+    top_ids: Option<usize>,
+}
+
+#[derive(Debug, FromArgs)]
+/// Describe an issue code.
+#[argh(subcommand, name = "explain")]
+pub struct ExplainArgs {
+    /// issue code, e.g. OV020 or missing-fastq
+    #[argh(positional)]
+    code: String,
+}
+
+/// Parse the command line like [`argh::from_env`], running `validate` if no
+/// command is given so that `omics_valid file.csv -f tidy_prot` keeps working.
+pub fn from_env() -> Args {
+    // argh only parses UTF-8, a path that is not is refused with its name
+    // instead of a panic
+    let strings: Vec<String> = std::env::args_os()
+        .map(|arg| {
+            arg.into_string().unwrap_or_else(|arg| {
+                eprintln!(
+                    "{} is not valid UTF-8, rename it or pass the file on stdin",
+                    arg.to_string_lossy()
+                );
+                std::process::exit(1)
+            })
+        })
+        .collect();
+    let cmd = strings
+        .first()
+        .and_then(|arg0| Path::new(arg0).file_name())
+        .and_then(|name| name.to_str())
+        .unwrap_or("omics_valid");
+    Args::from_args(
+        &[cmd],
+        &with_default_command(strings.get(1..).unwrap_or_default()),
+    )
+    .unwrap_or_else(|early_exit| match early_exit.status {
+        Ok(()) => {
+            println!("{}", early_exit.output);
+            std::process::exit(0)
+        }
+        Err(()) => {
+            eprintln!(
+                "{}\nRun {} --help for more information.",
+                early_exit.output, cmd
+            );
+            std::process::exit(1)
+        }
+    })
+}
+
+/// Top-level options with a value, which may precede the file of a bare invocation.
+const GLOBAL_OPTIONS: &[&str] = &["--log-level", "--log-format", "--timeout", "--retries"];
+/// Top-level switches, which may precede the file of a bare invocation.
+const GLOBAL_SWITCHES: &[&str] = &["--offline", "--redact-paths"];
+
+/// Insert `validate` after the top-level options unless they are followed by a
+/// command or a top-level flag.
+fn with_default_command(args: &[String]) -> Vec<&str> {
+    let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let mut start = 0;
+    loop {
+        match args.get(start) {
+            Some(arg) if GLOBAL_OPTIONS.contains(arg) => start += 2,
+            Some(arg) if GLOBAL_SWITCHES.contains(arg) => start += 1,
+            _ => break,
+        }
+    }
+    let start = start.min(args.len());
+    let explicit = match args.get(start) {
+        Some(&("-v" | "--version" | "--help" | "help")) => true,
+        Some(first) => Command::COMMANDS.iter().any(|c| c.name == *first),
+        None => false,
+    };
+    if !explicit {
+        args.insert(start, "validate");
+    }
+    args
 }
 
 /// Accept both a positional argument or stdin
-/// The output is boxed because we can have a `std::fs::File` or a `std::io::Stdin`.
-fn from_file_or_stdin(
+/// The output is boxed because we can have a `std::fs::File`, a `std::io::Stdin`
+/// or the body of a response, for an `http(s)://` or `s3://` URL.
+/// Compressed files are not decompressed, see [`Compression`].
+pub(crate) fn from_file_or_stdin(
     maybe_file: Option<PathBuf>,
+    network: &NetworkOptions,
 ) -> Result<Box<dyn std::io::Read + 'static>, std::io::Error> {
     match maybe_file {
-        Some(p) => Ok(Box::new(std::fs::File::open(p)?)),
+        Some(p) => match p.to_str().filter(|location| remote::is_remote(location)) {
+            Some(location) => {
+                let error =
+                    |kind, e: String| std::io::Error::new(kind, format!("{}: {}", location, e));
+                let file = RemoteFile::parse(location)
+                    .map_err(|e| error(std::io::ErrorKind::InvalidInput, e))?;
+                Ok(Box::new(
+                    file.open(network)
+                        .map_err(|e| error(std::io::ErrorKind::Other, e))?,
+                ))
+            }
+            None => Ok(Box::new(std::fs::File::open(p)?)),
+        },
         _ => Ok(Box::new(std::io::stdin())),
     }
 }
 
-pub fn run(args: Args) -> Result<(), std::io::Error> {
-    let file = from_file_or_stdin(args.file)?;
-    let error_vec = match args.format {
-        InputFormat::Prot => ProtRecord::validate_omics(file),
-        InputFormat::TidyProt => TidyProtRecord::validate_omics(file),
-        InputFormat::Met => {
-            // the unwraps are guaranteed by the previous verifications here and in main.rs
-            let model =
-                ModelRaw::parse(std::fs::read_to_string(args.model.unwrap())?.as_str()).unwrap();
-            TidyMetRecord::validate_omics(file, &model)
+/// Run the command, returning the report of the validation for the caller to
+/// present, or `None` for the commands that do not validate a file.
+pub fn run(args: Args) -> Result<Option<ValidationReport>, std::io::Error> {
+    let network = NetworkOptions {
+        offline: args.offline,
+        timeout: Duration::from_secs(args.timeout),
+        retries: args.retries,
+        jobs: deferred::DEFAULT_JOBS,
+    };
+    let redact = args.redact_paths;
+    let command = match args.command {
+        Some(Command::Validate(args)) => {
+            let bench = if args.bench {
+                Bench::new()
+            } else {
+                Bench::default()
+            };
+            let start = Instant::now();
+            let input = match (&args.file, &args.sqlite, &args.table) {
+                (Some(path), _, _) => Some(path.display().to_string()),
+                (None, Some(path), Some(table)) => {
+                    Some(format!("{} table {}", path.display(), table))
+                }
+                _ => None,
+            };
+            let diagnostics = Diagnostics {
+                stream: error_stream(&args),
+                redact,
+            };
+            let output = args.output;
+            let mut report = run_validate(*args, network, diagnostics, bench.clone())?;
+            report.input = input;
+            report.diagnostics = diagnostics;
+            report.output = output;
+            report.elapsed = start.elapsed();
+            report.bench = bench.measurements(report.elapsed);
+            return Ok(Some(report));
         }
-        InputFormat::Rna => RnaRecord::validate_omics(file),
+        Some(Command::Crosscheck(args)) => {
+            let mut report = crosscheck::run(args)?;
+            report.diagnostics.redact = redact;
+            return Ok(Some(report));
+        }
+        command => command,
+    };
+    match command {
+        Some(Command::Validate(_) | Command::Crosscheck(_)) => {
+            unreachable!("the commands with a report are run above")
+        }
+        Some(Command::Convert(args)) => {
+            // stdout is reserved for the converted data
+            let diagnostics = Diagnostics {
+                stream: ErrorStream::Stderr,
+                redact,
+            };
+            convert::run(args, &network, diagnostics)
+        }
+        Some(Command::Summarize(args)) => {
+            let diagnostics = Diagnostics {
+                stream: ErrorStream::Stdout,
+                redact,
+            };
+            run_summarize(args, &network, diagnostics)
+        }
+        Some(Command::FetchModel(args)) => fetch::run(args, &network),
+        Some(Command::Serve(args)) => serve::run(args),
+        Some(Command::FetchDb(args)) => accessions::run(args, &network),
+        Some(Command::Cache(args)) => cache::run(args),
+        Some(Command::Diff(args)) => diff::run(args),
+        Some(Command::Example(args)) => example::run(args),
+        Some(Command::SchemaOut(args)) => jsonschema::run(args),
+        Some(Command::Explain(args)) => {
+            println!("{}", report::explain(&args.code)?);
+            Ok(())
+        }
+        None => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "no command given, see --help",
+        )),
+    }
+    .map(|()| None)
+}
+
+/// Stream of the diagnostics of a validation.
+fn error_stream(args: &ValidateArgs) -> ErrorStream {
+    // the fixed copy is data, the diagnostics must not be mixed with it
+    if args.fix.as_deref() == Some(Path::new("-")) {
+        ErrorStream::Stderr
+    } else {
+        args.error_stream
+    }
+}
+
+fn run_validate(
+    args: ValidateArgs,
+    network: NetworkOptions,
+    diagnostics: Diagnostics,
+    bench: Bench,
+) -> Result<ValidationReport, std::io::Error> {
+    let check_remote = args.check_remote && !network.offline;
+    if args.check_remote && !check_remote {
+        eprintln!("note: remote files are not checked with --offline");
+    } else if args.check_remote && !cfg!(feature = "network") {
+        eprintln!(
+            "note: remote files are not checked, build with the `network` feature to enable it"
+        );
+    } else if args.check_remote && !cfg!(feature = "s3") {
+        eprintln!(
+            "note: s3:// locations are not checked, build with the `s3` feature to enable it"
+        );
+    }
+    let network = NetworkOptions {
+        jobs: args.remote_jobs,
+        ..network
+    };
+    let check_uniprot_online = args.check_uniprot_online && !network.offline;
+    if args.check_uniprot_online && !check_uniprot_online {
+        eprintln!("note: the proteins are not looked up online with --offline");
+    }
+    if args.profile.imodulon() && !matches!(args.format, InputFormat::Rna) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--profile imodulon only applies to the rna format",
+        ));
+    }
+    let rows = match (args.head, args.sample_rows) {
+        (Some(_), Some(_)) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "--head and --sample-rows cannot be used together",
+            ))
+        }
+        (Some(n), None) => RowSelection::Head(n),
+        (None, Some(n)) => RowSelection::Sample(n),
+        (None, None) => RowSelection::All,
+    };
+    let config = Config::load(args.config.as_deref())?;
+    let mut overrides = Overrides::default();
+    if args.profile.warnings_as_errors() {
+        overrides.warnings_as_errors();
+    }
+    for &code in args.profile.demoted() {
+        overrides.report_as(code, Severity::Warning);
+    }
+    for code in config.ignore.iter().chain(&args.ignore) {
+        overrides.ignore(code)?;
+    }
+    for (code, severity) in &config.promote {
+        overrides.promote(&format!("{}={}", code, severity))?;
+    }
+    for code_severity in &args.promote {
+        overrides.promote(code_severity)?;
+    }
+    overrides.suppress(suppressions::load(args.ignore_file.as_deref())?);
+    let mut rename: HashMap<String, String> = config.rename.into_iter().collect();
+    rename.extend(args.rename.iter().map(|r| (r.from.clone(), r.to.clone())));
+    let pattern = args
+        .replicate_pattern
+        .as_deref()
+        .unwrap_or(replicates::DEFAULT_PATTERN);
+    let replicates = ReplicateOptions {
+        min: args.min_replicates,
+        pattern: Some(Regex::new(pattern).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid --replicate-pattern: {}", e),
+            )
+        })?),
+    };
+    let options = ReadOptions {
+        rows,
+        allow_extra_columns: args.allow_extra_columns || args.profile.allow_extra_columns(),
+        rename,
+        fastq: FastqOptions {
+            check_remote,
+            duplicate_ids: args.fastq_duplicate_ids,
+            max_errors: args.fastq_max_errors,
+            spotcheck: args.fastq_spotcheck,
+        },
+        counts: CountsOptions {
+            tolerance: args.count_tolerance,
+            normalization: args.normalized,
+            sum_tolerance: args.sum_tolerance,
+        },
+        proteomics: ProteomicsOptions {
+            decoys: args.decoys,
+            allow_groups: args.allow_groups,
+            isoforms: args.profile.isoforms(),
+            check_online: check_uniprot_online,
+            accessions: args
+                .check_uniprot
+                .then(|| accessions::load(AccessionDb::UniprotSwissprot))
+                .transpose()?,
+        },
+        features: FeatureOptions {
+            mz_range: args.mz_range,
+            mass_tolerance: args.mass_tolerance,
+        },
+        plate: args.plate,
+        ontologies: ontology::load(&args.obo, config.ontology)?,
+        allowed: allowed::load(&args.allowed)?,
+        exact_decimal: args.exact_decimal,
+        imodulon: args.profile.imodulon(),
+        replicates,
+        check_batches: args.check_batches,
+        naming: naming::load(config.naming)?,
+        missing: MissingOptions {
+            max_rate: args.max_missing_rate,
+            columns: config.max_missing,
+        },
+        outlier_threshold: args.outliers.then_some(args.outlier_threshold),
+        check_tidy: args.check_tidy,
+        unique_keys: config.unique.map(UniqueKeys::keys).unwrap_or_default(),
+        scales: scale::declare(&args.format, config.scale, args.scale)?,
+        rewrite: rewrite::load(config.ids, args.alias.as_deref())?,
+        network,
+        stream: match args.output {
+            OutputFormat::Jsonl => jsonl::Stream::new(overrides.clone(), diagnostics),
+            _ => jsonl::Stream::default(),
+        },
+        bench,
+    };
+    if args.min_replicates.is_some() && !matches!(rows, RowSelection::All) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--min-replicates needs every row, it cannot be used with --head or --sample-rows",
+        ));
+    }
+    if args.certificate.is_some() && !matches!(rows, RowSelection::All) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "a certificate can only be issued if every row is validated",
+        ));
+    }
+    if args.normalize.is_some() && !matches!(rows, RowSelection::All) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--normalize writes the whole file, every row must be validated",
+        ));
+    }
+    if args.met_coverage.is_some() && !matches!(args.format, InputFormat::Met) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--met-coverage applies to the met format",
+        ));
+    }
+    if args.subsystem_coverage.is_some() && !matches!(args.format, InputFormat::Flux) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--subsystem-coverage applies to the flux format",
+        ));
+    }
+    let protein_coverage = args.protein_coverage || args.proteome.is_some();
+    if protein_coverage && !matches!(args.format, InputFormat::Prot | InputFormat::TidyProt) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--protein-coverage and --proteome apply to the prot and tidy_prot formats",
+        ));
+    }
+    if args.split && args.report_dir.is_none() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--split writes the valid and invalid rows in the --report-dir",
+        ));
+    }
+    if args.max_error_rate.is_some() && !args.fk.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--max-error-rate applies to the rows of the input, not to those of the --fk files",
+        ));
+    }
+    if args.split && !matches!(rows, RowSelection::All) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--split writes the whole file, every row must be validated",
+        ));
+    }
+    let mut models = Models::load(&args.model, &args.universal)?;
+    models.measure(options.bench.clone());
+    if let Some(path) = args.annotation.as_deref() {
+        models.load_annotation(path)?;
+    }
+    let proteome = match (&args.proteome, protein_coverage) {
+        (Some(path), _) => Some((
+            format!("proteome {}", path.display()),
+            coverage::read_proteome(path)?,
+        )),
+        (None, true) if models.has_uniprot() => Some((
+            "gene products of the models".to_string(),
+            models.uniprot_accessions(),
+        )),
+        (None, true) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "--protein-coverage needs a --proteome or a model whose gene products are \
+                 annotated with UniProt accessions",
+            ))
+        }
+        (None, false) => None,
+    };
+    if !args.files.is_empty() || args.manifest.is_some() {
+        return run_batch(&args, &models, &overrides, &options)
+            .and_then(|report| with_foreign_keys(report, &args.fk, &overrides, &options.stream));
+    }
+    let archive = args.file.as_deref().and_then(ArchiveKind::detect);
+    let remote_input = args
+        .file
+        .as_deref()
+        .and_then(Path::to_str)
+        .is_some_and(remote::is_remote);
+    if remote_input && (archive.is_some() || matches!(args.format, InputFormat::Datapackage)) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "archives and data packages must be downloaded first, only single omics files are read from URLs",
+        ));
+    }
+    if (args.fix.is_some()
+        || args.meta_out.is_some()
+        || args.max_error_rate.is_some()
+        || args.normalize.is_some()
+        || args.met_coverage.is_some()
+        || args.subsystem_coverage.is_some()
+        || proteome.is_some()
+        || args.report_dir.is_some()
+        || args.preview)
+        && (archive.is_some() || matches!(args.format, InputFormat::Datapackage))
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--fix, --meta-out, --max-error-rate, --normalize, --met-coverage, \
+             --subsystem-coverage, --protein-coverage, --report-dir and --preview require a \
+             single omics file",
+        ));
+    }
+    let table = match (&args.sqlite, &args.table) {
+        (Some(_), _) if args.file.is_some() || matches!(args.format, InputFormat::Datapackage) => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "--sqlite replaces the input file, it validates a table in an omics format",
+            ))
+        }
+        (Some(path), Some(table)) => Some(sqlite::read_table(path, table, &args.format)?),
+        (None, None) => None,
         _ => {
             return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "specification of omics not implemented yet",
+                std::io::ErrorKind::InvalidInput,
+                "--sqlite and --table must be given together",
             ))
         }
     };
-    if !error_vec.is_empty() {
-        let mut error_map = error_vec
-            .iter()
-            .map(|LineError { line, msg }| (msg, line))
-            .into_group_map();
-        error_map.iter_mut().for_each(|(msg, lines)| {
-            let n_lines = lines.len();
-            lines.truncate(3);
-            println!("{} lines{:?}: {}", n_lines, lines, msg)
+    if let InputFormat::Datapackage = args.format {
+        return run_datapackage(&args, &models, &overrides, &options)
+            .and_then(|report| with_foreign_keys(report, &args.fk, &overrides, &options.stream));
+    }
+    if let Some(path) = args.file.as_deref() {
+        if let Some(kind) = archive {
+            return run_archive(&args, &models, &overrides, path, kind, &options).and_then(
+                |report| with_foreign_keys(report, &args.fk, &overrides, &options.stream),
+            );
+        }
+    }
+    let mut plugins = plugins::load(&args.plugins_dir, &args.plugin)?;
+    // the certificate hashes the file as stored, before decompression
+    let source: Box<dyn Read> = match table {
+        Some(table) => Box::new(std::io::Cursor::new(table)),
+        None => from_file_or_stdin(args.file.clone(), &options.network)?,
+    };
+    let mut file = HashingReader::new(source);
+    let compression = args
+        .file
+        .as_deref()
+        .map_or(Compression::None, Compression::detect);
+    let (mut input, has_bom) = bom::skip(compression.decoder(&mut file)?)?;
+    if has_bom {
+        options.stream.write(&bom::warning());
+    }
+    if args.preview {
+        let mut content = Vec::new();
+        input.read_to_end(&mut content)?;
+        let columns = preview::columns(
+            &content,
+            preview::delimiter(&content),
+            args.format.has_headers(),
+            &options,
+        );
+        return Ok(ValidationReport {
+            summary: preview::lines(&columns),
+            ..Default::default()
         });
+    }
+    let needs_content = !plugins.is_empty()
+        || args.fix.is_some()
+        || args.meta_out.is_some()
+        || args.max_error_rate.is_some()
+        || args.normalize.is_some()
+        || args.report_dir.is_some()
+        || args.met_coverage.is_some()
+        || args.subsystem_coverage.is_some()
+        || proteome.is_some()
+        || options.rewrite.is_some()
+        || dataset_checks_enabled(&options)
+        || options.outlier_threshold.is_some()
+        || !options.scales.is_empty()
+        || options.check_tidy
+        || !options.unique_keys.is_empty();
+    let mut metadata = None;
+    let mut normalized = None;
+    let mut error_rate = None;
+    let mut bundled = None;
+    let mut met_coverage = None;
+    let mut subsystem_coverage = None;
+    let mut protein_summary = None;
+    let mut rewrites: Vec<Rewrite> = Vec::new();
+    let mut error_vec = if !needs_content {
+        validate_input(&mut input, &args.format, &models, &options)?
+    } else {
+        // the built-in validation, the plugins, the sample checks, the fix and the
+        // metadata all need the input
+        let mut content = Vec::new();
+        input.read_to_end(&mut content)?;
+        let (rewritten, found) = rewrite::apply(&content, &args.format, options.rewrite.as_ref());
+        if let Cow::Owned(rewritten) = rewritten {
+            content = rewritten;
+        }
+        rewrites = found;
+        let mut error_vec = validate_input(content.as_slice(), &args.format, &models, &options)?;
+        check_content(&content, &args.format, &options, &mut error_vec);
+        if !plugins.is_empty() {
+            let found = plugins::validate(
+                &mut plugins,
+                content.as_slice(),
+                args.format.has_headers(),
+                args.format.delimiter(),
+                &options,
+            )?;
+            options.stream.write_all(&found);
+            error_vec.extend(found);
+            error_vec.sort_by_key(|e| e.line);
+        }
+        if let Some(path) = args.fix.as_deref() {
+            write_fixed(&content, &args.format, &options, path)?;
+        }
+        if args.meta_out.is_some() {
+            metadata = Some(Metadata::describe(&content, &args.format)?);
+        }
+        if args.normalize.is_some() {
+            normalized = Some(normalize::canonical(&content, &args.format, &options)?);
+        }
+        if let Some(max) = args.max_error_rate {
+            error_rate = Some(ErrorRate {
+                max,
+                records: count_records(&content, &args.format, rows),
+                first_line: if args.format.has_headers() { 2 } else { 1 },
+            });
+        }
+        if args.met_coverage.is_some() {
+            met_coverage = Some(coverage::metabolites(&content, &models, &options));
+        }
+        if args.subsystem_coverage.is_some() {
+            subsystem_coverage = Some(coverage::subsystems(&content, &models, &options));
+        }
+        if let Some((name, reference)) = &proteome {
+            let coverage = coverage::proteins(&content, &args.format, &options, reference);
+            protein_summary = Some(coverage::protein_summary(name, &coverage));
+        }
+        if args.report_dir.is_some() {
+            bundled = Some(content);
+        }
+        error_vec
+    };
+    drop(input);
+    if has_bom {
+        error_vec.insert(0, bom::warning());
+    }
+    let mut report = with_foreign_keys(
+        ValidationReport {
+            parts: vec![Part {
+                name: None,
+                issues: overrides.apply(error_vec),
+            }],
+            summary: summary(&models),
+            error_rate,
+            ..Default::default()
+        },
+        &args.fk,
+        &overrides,
+        &options.stream,
+    )?;
+    if let Some(rate) = report.error_rate {
+        let line = rate.summary(&report);
+        report.summary.push(line);
+    }
+    report.summary.extend(rewrite::summary(&rewrites));
+    if let (Some(path), Some(rows)) = (&args.met_coverage, met_coverage) {
+        if rows.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "--met-coverage needs the species of an SBML model given with -m",
+            ));
+        }
+        coverage::write(path, &rows)?;
+        report.summary.extend(coverage::summary(&rows));
+    }
+    if let (Some(path), Some(rows)) = (&args.subsystem_coverage, subsystem_coverage) {
+        if models.subsystems().next().is_none() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "--subsystem-coverage needs the groups of an SBML model given with -m",
+            ));
+        }
+        coverage::write(path, &rows)?;
+        report.summary.extend(coverage::subsystem_summary(&rows));
+    }
+    report.summary.extend(protein_summary);
+    if let (Some(dir), Some(content)) = (&args.report_dir, bundled) {
+        let bundle = Bundle {
+            dir: dir.clone(),
+            split: args.split,
+        };
+        let input = match (&args.file, &args.table) {
+            (Some(path), _) => Some(path.clone()),
+            (None, Some(table)) => Some(PathBuf::from(table)),
+            (None, None) => None,
+        };
+        bundle.write(
+            input.as_deref(),
+            &args.format,
+            &content,
+            &report.parts[0].issues,
+            &report.summary,
+        )?;
+    }
+    if report.failed() {
+        return Ok(report);
+    }
+    if let (Some(path), Some(normalized)) = (&args.normalize, normalized) {
+        std::fs::write(path, normalized)?;
+    }
+    if args.certificate.is_none() && metadata.is_none() {
+        return Ok(report);
+    }
+    let file_sha256 = file.finish()?;
+    if let (Some(path), Some(metadata)) = (&args.meta_out, metadata) {
+        metadata.with_hash(file_sha256.clone()).write(path)?;
+    }
+    if let Some(path) = args.certificate {
+        Certificate::new(
+            args.file.as_deref(),
+            file_sha256,
+            args.format.to_string(),
+            &[args.model.as_slice(), args.universal.as_slice()].concat(),
+            report.n_warnings(),
+        )?
+        .write(&path)?;
+    }
+    Ok(report)
+}
 
-        Err(std::io::Error::new(std::io::ErrorKind::InvalidData, ""))
+/// The rows of a delimited file with header, decompressed and without BOM,
+/// and its delimiter, guessed from the header.
+fn read_table(path: &Path) -> Result<(Vec<u8>, u8), std::io::Error> {
+    let context =
+        |e: std::io::Error| std::io::Error::new(e.kind(), format!("{}: {}", path.display(), e));
+    let input = Compression::detect(path)
+        .decoder(File::open(path).map_err(context)?)
+        .map_err(context)?;
+    let (mut input, _) = bom::skip(input)?;
+    let mut content = Vec::new();
+    input.read_to_end(&mut content).map_err(context)?;
+    let delimiter = preview::delimiter(&content);
+    Ok((content, delimiter))
+}
+
+/// Add a part to the report for each `--fk` with the rows of its file whose
+/// key is not a key of the rows of the referenced file.
+fn with_foreign_keys(
+    mut report: ValidationReport,
+    keys: &[CrossFileKey],
+    overrides: &Overrides,
+    stream: &jsonl::Stream,
+) -> Result<ValidationReport, std::io::Error> {
+    for key in keys {
+        let key_values = |file: &schema::FileKey| {
+            let (content, delimiter) = read_table(&file.path)?;
+            let columns: Vec<&str> = file.columns.iter().map(String::as_str).collect();
+            schema::key_values(&content, delimiter, &columns).map_err(|e| {
+                std::io::Error::new(e.kind(), format!("{}: {}", file.path.display(), e))
+            })
+        };
+        let referenced: HashSet<Vec<String>> = key_values(&key.to)?
+            .into_iter()
+            .map(|(_, values)| values)
+            .collect();
+        let columns: Vec<&str> = key.from.columns.iter().map(String::as_str).collect();
+        let error_vec = schema::check_foreign_key(
+            &key_values(&key.from)?,
+            &columns,
+            &referenced,
+            &key.to.to_string(),
+        );
+        let name = format!("foreign key {}", key.from);
+        stream.part(name.clone()).write_all(&error_vec);
+        report.parts.push(Part {
+            name: Some(name),
+            issues: overrides.apply(error_vec),
+        });
+    }
+    Ok(report)
+}
+
+/// Number of records of the content that are validated.
+pub(crate) fn count_records(content: &[u8], format: &InputFormat, rows: RowSelection) -> usize {
+    let n = ReaderBuilder::new()
+        .has_headers(format.has_headers())
+        .delimiter(format.delimiter())
+        .flexible(true)
+        .from_reader(content)
+        .records()
+        .count();
+    match rows {
+        RowSelection::All => n,
+        RowSelection::Head(head) | RowSelection::Sample(head) => n.min(head),
+    }
+}
+
+/// Whether a check of the whole file (replicates, batches, naming convention,
+/// missing values, modulome-workflow sheet) is enabled, which requires the input
+/// in memory.
+fn dataset_checks_enabled(options: &ReadOptions) -> bool {
+    options.replicates.min.is_some()
+        || options.check_batches
+        || options.naming.is_some()
+        || options.missing.enabled()
+        || options.imodulon
+}
+
+/// Checks of the whole content of a file with headers, reported at line 0 or
+/// at the first line of the samples.
+pub(crate) fn check_dataset(
+    content: &[u8],
+    delimiter: u8,
+    options: &ReadOptions,
+) -> Vec<LineError> {
+    let mut error_vec = replicates::check(content, delimiter, &options.replicates);
+    if options.check_batches {
+        error_vec.extend(confounding::check(
+            content,
+            delimiter,
+            options.replicates.pattern.as_ref(),
+        ));
+    }
+    if let Some(grammar) = &options.naming {
+        error_vec.extend(naming::check(content, delimiter, grammar));
+    }
+    error_vec.extend(missing::check(content, delimiter, &options.missing));
+    if options.imodulon {
+        error_vec.extend(imodulon::check(content));
+    }
+    error_vec
+}
+
+/// Checks of the whole content of a file, after the validation of its records:
+/// the scale, and for the formats with headers [`check_dataset`], the outliers,
+/// the tidiness and the unique keys. The issues are streamed and merged into
+/// `error_vec` by line.
+pub(crate) fn check_content(
+    content: &[u8],
+    format: &InputFormat,
+    options: &ReadOptions,
+    error_vec: &mut Vec<LineError>,
+) {
+    // the scale of the matrices without header is checked too
+    let mut found = scale::check(content, format, &options.scales);
+    if format.has_headers() {
+        found.extend(check_dataset(content, format.delimiter(), options));
+        found.extend(outliers::check(content, format, options.outlier_threshold));
+        if options.check_tidy {
+            found.extend(tidy::check(content, format));
+        }
+        found.extend(unique::check(
+            content,
+            format.delimiter(),
+            &options.unique_keys,
+        ));
+    }
+    if !found.is_empty() {
+        options.stream.write_all(&found);
+        error_vec.extend(found);
+        error_vec.sort_by_key(|e| e.line);
+    }
+}
+
+/// Write the input with the fixes applied to `path`, or stdout if it is `-`.
+fn write_fixed(
+    content: &[u8],
+    format: &InputFormat,
+    options: &ReadOptions,
+    path: &Path,
+) -> Result<(), std::io::Error> {
+    let mut output: Box<dyn Write> = if path == Path::new("-") {
+        Box::new(std::io::stdout())
     } else {
+        Box::new(File::create(path)?)
+    };
+    let normalized = line_endings::normalize(content);
+    if let Cow::Owned(_) = normalized {
+        eprintln!("fix: line endings normalized");
+    }
+    let content = normalized.as_ref();
+    let strip = options.proteomics.decoys == DecoyPolicy::Strip;
+    match format {
+        InputFormat::Prot | InputFormat::TidyProt if strip => {
+            let stripped = proteomics::strip_decoys(content, format.has_headers(), output)?;
+            eprintln!("fix: {} contaminant and decoy rows removed", stripped);
+        }
+        _ => output.write_all(content)?,
+    }
+    Ok(())
+}
+
+/// Validate every resource of the Data Package whose descriptor is the input file.
+fn run_datapackage(
+    args: &ValidateArgs,
+    models: &Models,
+    overrides: &Overrides,
+    options: &ReadOptions,
+) -> Result<ValidationReport, std::io::Error> {
+    let descriptor = args.file.as_ref().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the path to the datapackage.json is required",
+        )
+    })?;
+    let results = datapackage::validate(descriptor, models, options)?;
+    report_parts("resource", results, models, overrides)
+}
+
+/// Validate the whole file and print how many issues of each code were found,
+/// followed by the summaries.
+fn run_summarize(
+    args: SummarizeArgs,
+    network: &NetworkOptions,
+    diagnostics: Diagnostics,
+) -> Result<(), std::io::Error> {
+    let models = Models::load(&args.model, &args.universal)?;
+    let compression = args
+        .file
+        .as_deref()
+        .map_or(Compression::None, Compression::detect);
+    let (mut input, has_bom) =
+        bom::skip(compression.decoder(from_file_or_stdin(args.file, network)?)?)?;
+    // the samples are described from the same content and options as validated
+    let options = ReadOptions::default();
+    let mut content = Vec::new();
+    input.read_to_end(&mut content)?;
+    let mut error_vec = validate_input(content.as_slice(), &args.format, &models, &options)?;
+    if has_bom {
+        error_vec.insert(0, bom::warning());
+    }
+    let counts = report::counts(&error_vec);
+    let n_failing = error_vec
+        .iter()
+        .filter(|e| e.severity == Severity::Error)
+        .count();
+    let samples = stats::describe(&content, &args.format, &options);
+    let identifiers = args
+        .top_ids
+        .and_then(|top| stats::identifiers(&content, &args.format, &options, top));
+    if args.json {
+        let issues: Vec<serde_json::Value> = counts
+            .iter()
+            .map(|((code, severity), n)| {
+                serde_json::json!({
+                    "code": code.id(),
+                    "name": code.to_string(),
+                    "severity": severity.to_string(),
+                    "count": n,
+                })
+            })
+            .collect();
+        let summary = serde_json::json!({
+            "issues": issues,
+            "lines_with_errors": n_failing,
+            "samples": samples,
+            "identifiers": identifiers,
+        });
+        println!("{}", summary);
+        return Ok(());
+    }
+    for ((code, severity), n) in counts {
+        println!("{}", report::count_line(code, severity, n));
+    }
+    println!("{} lines with errors", n_failing);
+    if let Some(samples) = samples {
+        for line in stats::table(&samples) {
+            println!("{}", line);
+        }
+    }
+    if let Some(identifiers) = identifiers {
+        for line in stats::identifier_lines(&identifiers) {
+            println!("{}", line);
+        }
+    }
+    for line in summary(&models) {
+        report::emit(diagnostics, &line);
+    }
+    Ok(())
+}
+
+/// Validate the files of --files and --manifest, with a table of the files in
+/// the summary.
+fn run_batch(
+    args: &ValidateArgs,
+    models: &Models,
+    overrides: &Overrides,
+    options: &ReadOptions,
+) -> Result<ValidationReport, std::io::Error> {
+    if args.file.is_some()
+        || args.sqlite.is_some()
+        || matches!(args.format, InputFormat::Datapackage)
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--files and --manifest replace the input file, they validate omics files of --format",
+        ));
+    }
+    if args.fix.is_some()
+        || args.meta_out.is_some()
+        || args.max_error_rate.is_some()
+        || args.normalize.is_some()
+        || args.certificate.is_some()
+        || args.met_coverage.is_some()
+        || args.subsystem_coverage.is_some()
+        || args.protein_coverage
+        || args.proteome.is_some()
+        || args.preview
+        || !args.plugin.is_empty()
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "--fix, --meta-out, --max-error-rate, --normalize, --certificate, --met-coverage, \
+             --subsystem-coverage, --protein-coverage, --preview and --plugin apply to a single \
+             file, not to --files and --manifest",
+        ));
+    }
+    let files = batch::files(&args.files, args.manifest.as_deref())?;
+    let bundle = args.report_dir.as_ref().map(|dir| Bundle {
+        dir: dir.clone(),
+        split: args.split,
+    });
+    if bundle.is_some() {
+        batch::check_names(&files, &args.format)?;
+    }
+    let jobs = args.jobs.unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+    });
+    let (parts, mut lines) = batch::run(
+        &files,
+        &args.format,
+        models,
+        options,
+        overrides,
+        &BatchOptions {
+            jobs,
+            keep_going: args.keep_going,
+            bundle,
+        },
+    );
+    lines.extend(summary(models));
+    Ok(ValidationReport {
+        parts,
+        summary: lines,
+        ..Default::default()
+    })
+}
+
+/// Validate the members of a zip or tar.gz archive that are mapped to a format.
+fn run_archive(
+    args: &ValidateArgs,
+    models: &Models,
+    overrides: &Overrides,
+    path: &Path,
+    kind: ArchiveKind,
+    options: &ReadOptions,
+) -> Result<ValidationReport, std::io::Error> {
+    let mut results = Vec::new();
+    archive::for_each_member(path, kind, |name, content| {
+        let format = if args.member.is_empty() {
+            Some(&args.format)
+        } else {
+            args.member
+                .iter()
+                .find(|m| m.pattern.matches(name))
+                .map(|m| &m.format)
+        };
+        if let Some(format) = format {
+            let options = options.part(format!("member {}", name));
+            let content = Compression::detect(Path::new(name)).decoder(content)?;
+            let (content, has_bom) = bom::skip(content)?;
+            if has_bom {
+                options.stream.write(&bom::warning());
+            }
+            let mut error_vec = validate_input(content, format, models, &options)?;
+            if has_bom {
+                error_vec.insert(0, bom::warning());
+            }
+            results.push((name.to_string(), error_vec));
+        }
         Ok(())
+    })?;
+    report_parts("member", results, models, overrides)
+}
+
+/// Report the errors of each named part (resource, archive member...) of the input.
+fn report_parts(
+    kind: &str,
+    results: Vec<(String, Vec<LineError>)>,
+    models: &Models,
+    overrides: &Overrides,
+) -> Result<ValidationReport, std::io::Error> {
+    Ok(ValidationReport {
+        parts: results
+            .into_iter()
+            .map(|(name, error_vec)| Part {
+                name: Some(format!("{} {}", kind, name)),
+                issues: overrides.apply(error_vec),
+            })
+            .collect(),
+        summary: summary(models),
+        ..Default::default()
+    })
+}
+
+/// Validate `input` against the built-in specification of the format.
+pub(crate) fn validate_input<R: std::io::Read>(
+    input: R,
+    format: &InputFormat,
+    models: &Models,
+    options: &ReadOptions,
+) -> Result<Vec<LineError>, std::io::Error> {
+    let mut scan = line_endings::Scan::default();
+    let mut error_vec = {
+        let mut input =
+            BufReader::new(options.bench.counted(line_endings::watch(input, &mut scan)));
+        // a transposed matrix is reported once instead of on every row
+        let head = transposed::head(&mut input, format)?;
+        if let Some(transposed) = transposed::check(&head, format, models) {
+            options.stream.write_all(std::slice::from_ref(&transposed));
+            return Ok(vec![transposed]);
+        }
+        let mut input = head.as_slice().chain(input);
+        match format {
+            InputFormat::Prot | InputFormat::TidyProt
+                if models.has_uniprot() || options.proteomics.check_online =>
+            {
+                // the proteins are looked up in the models and online once the rows
+                // are validated
+                let mut content = Vec::new();
+                input.read_to_end(&mut content)?;
+                let mut error_vec = match format {
+                    InputFormat::Prot => ProtRecord::validate_rows(content.as_slice(), options),
+                    _ => TidyProtRecord::validate_rows(content.as_slice(), options),
+                };
+                let absent =
+                    proteomics::absent_from_models(&content, format.has_headers(), models, options);
+                options.stream.write_all(&absent);
+                error_vec.extend(absent);
+                if options.proteomics.check_online {
+                    let unknown =
+                        proteomics::unknown_online(&content, format.has_headers(), options)?;
+                    options.stream.write_all(&unknown);
+                    error_vec.extend(unknown);
+                }
+                error_vec.sort_by_key(|e| e.line);
+                error_vec
+            }
+            InputFormat::Prot => ProtRecord::validate_rows(input, options),
+            InputFormat::TidyProt => TidyProtRecord::validate_rows(input, options),
+            InputFormat::Met => {
+                if models.is_empty() {
+                    return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "the met format requires a model (-m) or a universal namespace (--universal)",
+                ));
+                }
+                TidyMetRecord::validate_rows(input, models, options)
+            }
+            InputFormat::Flux => TidyFluxRecord::validate_rows(input, options),
+            InputFormat::EcUsage => {
+                if !models.has_enzymes() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "the ec_usage format requires an enzyme-constrained model (-m) with prot_ \
+                         pseudo-metabolites",
+                    ));
+                }
+                EcUsageRecord::validate_rows(input, models, options)
+            }
+            InputFormat::ThermoConc | InputFormat::ThermoDg => {
+                if models.is_empty() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("the {} format requires a model (-m)", format),
+                    ));
+                }
+                match format {
+                    InputFormat::ThermoConc => {
+                        ThermoConcRecord::validate_rows(input, models, options)
+                    }
+                    _ => ThermoDgRecord::validate_rows(input, models, options),
+                }
+            }
+            InputFormat::Medium => {
+                if !models.has_exchanges() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "the medium format requires a model (-m) with exchange reactions",
+                    ));
+                }
+                MediumRecord::validate_rows(input, models, options)
+            }
+            InputFormat::StrainDesign => {
+                if models.is_empty() {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "the strain_design format requires a model (-m)",
+                    ));
+                }
+                StrainDesignRecord::validate_rows(input, models, options)
+            }
+            InputFormat::Rna => {
+                // the rows of an experiment are compared, the sheets are small
+                let mut content = Vec::new();
+                input.read_to_end(&mut content)?;
+                let mut error_vec = RnaRecord::validate_rows(content.as_slice(), options);
+                let inconsistent = experiments::check(&content, options);
+                options.stream.write_all(&inconsistent);
+                error_vec.extend(inconsistent);
+                error_vec.sort_by_key(|e| e.line);
+                error_vec
+            }
+            // the issues of the whole matrix or table are only known at the end
+            InputFormat::Counts => {
+                let error_vec = counts::validate(input, models, options);
+                options.stream.write_all(&error_vec);
+                error_vec
+            }
+            InputFormat::FeatureTable => {
+                let error_vec = features::validate(input, options);
+                options.stream.write_all(&error_vec);
+                error_vec
+            }
+            // without models, every met row is reported as not in model
+            InputFormat::Multi => MultiRecord::validate_rows(input, models, options),
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "specification of omics not implemented yet",
+                ))
+            }
+        }
+    };
+    // the line endings are known once the input is read
+    let found = scan.issues();
+    options.stream.write_all(&found);
+    error_vec.extend(found);
+    error_vec.sort_by_key(|e| e.line);
+    Ok(error_vec)
+}
+
+/// Which models matched the identifiers and the FASTQ summary.
+fn summary(models: &Models) -> Vec<String> {
+    let mut lines = models.report();
+    lines.extend(fastq_summary());
+    lines
+}
+
+/// Read-length and quality statistics of the FASTQ files referenced in the
+/// input, flagging suspicious files, and the size of the remote ones.
+fn fastq_summary() -> Vec<String> {
+    let mut lines = Vec::new();
+    for (location, length) in fastq::remote_checked() {
+        match length {
+            Some(length) => lines.push(format!("FASTQ {}: reachable, {} bytes", location, length)),
+            None => lines.push(format!("FASTQ {}: reachable, unknown size", location)),
+        }
+    }
+    for (path, stats) in fastq::scanned() {
+        let flags = stats.flags();
+        if flags.is_empty() {
+            lines.push(format!("FASTQ {}: {}", path.display(), stats));
+        } else {
+            lines.push(format!(
+                "FASTQ {}: {} (suspicious: {})",
+                path.display(),
+                stats,
+                flags.join(", ")
+            ));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate_is_the_default_command() {
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            with_default_command(&args(&["file.csv", "-f", "tidy_prot"])),
+            ["validate", "file.csv", "-f", "tidy_prot"]
+        );
+        assert_eq!(with_default_command(&args(&[])), ["validate"]);
+        assert_eq!(
+            with_default_command(&args(&["explain", "OV020"])),
+            ["explain", "OV020"]
+        );
+        assert_eq!(with_default_command(&args(&["-v"])), ["-v"]);
+        assert_eq!(
+            with_default_command(&args(&["--log-level", "debug", "--offline", "file.csv"])),
+            ["--log-level", "debug", "--offline", "validate", "file.csv"]
+        );
+        let parsed = Args::from_args(
+            &["omics_valid"],
+            &with_default_command(&args(&["tests/uni_tidy.csv", "-f", "met"])),
+        )
+        .unwrap();
+        assert!(
+            matches!(parsed.command, Some(Command::Validate(v)) if matches!(v.format, InputFormat::Met))
+        );
     }
 }
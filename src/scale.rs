@@ -0,0 +1,189 @@
+use crate::outliers::VALUE_COLUMNS;
+use crate::runner::InputFormat;
+use crate::validators::{IssueCode, LineError, ValidationIssue};
+use csv::ReaderBuilder;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io;
+use strum::{Display, EnumString};
+
+/// Highest log-transformed value: the log2 of the brightest intensities is
+/// about 35, raw intensities and counts go far beyond.
+const LOG_MAX: f64 = 40.0;
+/// Fewer values do not tell the scale.
+const MIN_VALUES: usize = 20;
+
+/// Scale of the measured values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, EnumString, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Scale {
+    /// raw intensities or counts
+    Linear,
+    /// log-transformed values
+    Log,
+}
+
+/// Scales declared in the `[scale]` table of the configuration, by format
+/// name, and with `--scale` for the validated `format`, which takes
+/// precedence.
+pub fn declare(
+    format: &InputFormat,
+    mut scales: BTreeMap<String, Scale>,
+    declared: Option<Scale>,
+) -> io::Result<BTreeMap<String, Scale>> {
+    if let Some(name) = scales.keys().find(|n| n.parse::<InputFormat>().is_err()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("[scale]: unknown format {}", name),
+        ));
+    }
+    if let Some(declared) = declared {
+        scales.insert(format.to_string(), declared);
+    }
+    Ok(scales)
+}
+
+/// Name of the value columns and their numeric values: those after the
+/// identifier in the matrices (prot and counts), the value column otherwise.
+fn values(content: &[u8], format: &InputFormat) -> (String, Vec<f64>) {
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(format.delimiter())
+        .has_headers(format.has_headers())
+        .flexible(true)
+        .from_reader(content);
+    let column = match format {
+        InputFormat::Prot | InputFormat::Counts => None,
+        _ => match rdr
+            .headers()
+            .ok()
+            .and_then(|headers| headers.iter().position(|h| VALUE_COLUMNS.contains(&h)))
+        {
+            Some(column) => Some(column),
+            None => return (String::new(), Vec::new()),
+        },
+    };
+    let name = match column {
+        Some(column) => rdr.headers().ok().and_then(|h| h.get(column)),
+        None => None,
+    }
+    .unwrap_or("samples")
+    .to_string();
+    let mut values = Vec::new();
+    // unreadable records and missing values are reported by the validation
+    for record in rdr.records().filter_map(Result::ok) {
+        let fields: Vec<&str> = match column {
+            Some(column) => record.get(column).into_iter().collect(),
+            None => record.iter().skip(1).collect(),
+        };
+        values.extend(
+            fields
+                .into_iter()
+                .filter_map(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| v.is_finite()),
+        );
+    }
+    (name, values)
+}
+
+/// Scale the values look like: log if none is above [`LOG_MAX`], linear if
+/// some are and none is negative, `None` if there are too few values or
+/// negative and large values together, which neither scale explains.
+fn looks(values: &[f64]) -> Option<Scale> {
+    if values.len() < MIN_VALUES {
+        return None;
+    }
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max <= LOG_MAX {
+        Some(Scale::Log)
+    } else if min >= 0.0 {
+        Some(Scale::Linear)
+    } else {
+        None
+    }
+}
+
+/// Warn once if the values of the file look on another scale than declared,
+/// e.g. log-transformed intensities exported where raw ones are expected. The
+/// scales are declared by format name.
+pub fn check(
+    content: &[u8],
+    format: &InputFormat,
+    scales: &BTreeMap<String, Scale>,
+) -> Vec<LineError> {
+    match scales.get(&format.to_string()).copied() {
+        Some(declared) => check_with(content, format, declared),
+        None => Vec::new(),
+    }
+}
+
+fn check_with(content: &[u8], format: &InputFormat, declared: Scale) -> Vec<LineError> {
+    let (column, values) = values(content, format);
+    let looks = match looks(&values) {
+        Some(looks) if looks != declared => looks,
+        _ => return Vec::new(),
+    };
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let detail = match looks {
+        Scale::Log => format!(
+            "declared linear, but the {} values lie between {} and {}",
+            values.len(),
+            min,
+            max
+        ),
+        Scale::Linear => format!(
+            "declared log, but the values reach {} and none is negative",
+            max
+        ),
+    };
+    vec![LineError::warning(
+        0,
+        ValidationIssue::new(IssueCode::ScaleMismatch)
+            .field(&column)
+            .value(looks.to_string())
+            .detail(detail),
+    )]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tidy(values: &[f64]) -> String {
+        let mut content = String::from("met_id,sample,value\n");
+        for (i, value) in values.iter().enumerate() {
+            content.push_str(&format!("glc__D,S{},{}\n", i, value));
+        }
+        content
+    }
+
+    #[test]
+    fn test_log_values_are_told_from_linear_ones() {
+        let log: Vec<f64> = (0..30).map(|i| i as f64 - 3.5).collect();
+        let linear: Vec<f64> = (0..30).map(|i| 1000.0 * i as f64).collect();
+        assert_eq!(looks(&log), Some(Scale::Log));
+        assert_eq!(looks(&linear), Some(Scale::Linear));
+        assert_eq!(looks(&log[..10]), None);
+        let error_vec = check_with(tidy(&log).as_bytes(), &InputFormat::Met, Scale::Linear);
+        assert_eq!(error_vec.len(), 1);
+        assert_eq!(error_vec[0].line, 0);
+        assert_eq!(error_vec[0].issues[0].field.as_deref(), Some("value"));
+        assert!(check_with(tidy(&log).as_bytes(), &InputFormat::Met, Scale::Log).is_empty());
+        let error_vec = check_with(tidy(&linear).as_bytes(), &InputFormat::Met, Scale::Log);
+        assert_eq!(error_vec[0].issues[0].value.as_deref(), Some("linear"));
+        // the samples of a matrix
+        let prot: String = (0..10)
+            .map(|i| format!("P0A6F{},{}.5,{}.25\n", i, 20 + i, 21 + i))
+            .collect();
+        let error_vec = check_with(prot.as_bytes(), &InputFormat::Prot, Scale::Linear);
+        assert_eq!(error_vec[0].issues[0].field.as_deref(), Some("samples"));
+    }
+
+    #[test]
+    fn test_unknown_formats_are_rejected() {
+        let scales = BTreeMap::from([("proteins".to_string(), Scale::Linear)]);
+        assert!(declare(&InputFormat::Met, scales, None).is_err());
+    }
+}
@@ -0,0 +1,575 @@
+use crate::unique::UniqueIndex;
+use crate::validators::{IssueCode, LineError, ReadOptions, ValidationIssue};
+use crate::wells;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use csv::ReaderBuilder;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// [Frictionless Table Schema](https://specs.frictionlessdata.io/table-schema/)
+/// describing the columns of a generic tabular file.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableSchema {
+    pub fields: Vec<Field>,
+    #[serde(default)]
+    pub primary_key: Option<Columns>,
+    #[serde(default)]
+    pub foreign_keys: Vec<ForeignKey>,
+    /// column sets whose values must be unique across the rows, as a primary
+    /// key but with several of them allowed
+    #[serde(default)]
+    pub unique_keys: Vec<Vec<String>>,
+}
+
+/// Columns whose values must be a key of the rows of a resource, the same one
+/// if its name is empty.
+#[derive(Debug, Deserialize)]
+pub struct ForeignKey {
+    pub fields: Columns,
+    pub reference: Reference,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Reference {
+    #[serde(default)]
+    pub resource: String,
+    pub fields: Columns,
+}
+
+/// Columns of a file, given as `FILE:COLUMN,...`.
+#[derive(Debug, Clone)]
+pub struct FileKey {
+    pub path: PathBuf,
+    pub columns: Vec<String>,
+}
+
+/// A foreign key between two files, given as `FILE:COLUMNS=FILE:COLUMNS`,
+/// e.g. `data.csv:sample=meta.csv:sample`: the key of each row of the first
+/// file must be a key of the rows of the second.
+#[derive(Debug, Clone)]
+pub struct CrossFileKey {
+    pub from: FileKey,
+    pub to: FileKey,
+}
+
+impl FromStr for FileKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.rsplit_once(':') {
+            Some((path, columns))
+                if !path.is_empty() && columns.split(',').all(|c| !c.is_empty()) =>
+            {
+                Ok(FileKey {
+                    path: PathBuf::from(path),
+                    columns: columns.split(',').map(String::from).collect(),
+                })
+            }
+            _ => Err(format!("expected FILE:COLUMN, found {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for FileKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.columns.join(","))
+    }
+}
+
+impl FromStr for CrossFileKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected FILE:COLUMNS=FILE:COLUMNS, found {}", s))?;
+        let key = CrossFileKey {
+            from: from.parse()?,
+            to: to.parse()?,
+        };
+        if key.from.columns.len() != key.to.columns.len() {
+            return Err(format!(
+                "{} and {} do not have the same number of columns",
+                key.from, key.to
+            ));
+        }
+        Ok(key)
+    }
+}
+
+/// A single column name or a list of them.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Columns {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Columns {
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            Columns::One(name) => vec![name.as_str()],
+            Columns::Many(names) => names.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Field {
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub field_type: FieldType,
+    /// `default` (ISO-8601), `any` or a strftime pattern such as `%d/%m/%Y`
+    /// for the date and time types
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub constraints: Constraints,
+}
+
+/// Types of the Table Schema that are checked; the rest are accepted as is.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Date,
+    Datetime,
+    Time,
+    #[default]
+    #[serde(other)]
+    Any,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Constraints {
+    pub required: bool,
+    pub unique: bool,
+    /// only numeric bounds are supported
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub pattern: Option<String>,
+    #[serde(rename = "enum")]
+    pub allowed: Option<Vec<String>>,
+}
+
+const TRUE_VALUES: [&str; 4] = ["true", "True", "TRUE", "1"];
+const FALSE_VALUES: [&str; 4] = ["false", "False", "FALSE", "0"];
+
+/// Largest Excel serial number, 9999-12-31.
+const EXCEL_MAX_SERIAL: f64 = 2_958_465.0;
+
+static RE_SLASHED_DATE: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(|| {
+    Regex::new(r"^(\d{1,2})[/.-](\d{1,2})[/.-](\d{2}|\d{4})$").unwrap()
+});
+
+/// Check a date, datetime or time against the format of the field, telling
+/// apart the usual spreadsheet mistakes: Excel serial numbers and dates whose
+/// day and month cannot be told apart.
+fn check_temporal(
+    field_type: &FieldType,
+    format: Option<&str>,
+    value: &str,
+) -> Result<(), (IssueCode, String)> {
+    let parsed = match (field_type, format) {
+        (_, Some("any")) => true,
+        (FieldType::Date, None | Some("default")) => {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok()
+        }
+        (FieldType::Datetime, None | Some("default")) => {
+            DateTime::parse_from_rfc3339(value).is_ok()
+                || NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").is_ok()
+        }
+        (FieldType::Time, None | Some("default")) => {
+            NaiveTime::parse_from_str(value, "%H:%M:%S%.f").is_ok()
+        }
+        (FieldType::Date, Some(pattern)) => NaiveDate::parse_from_str(value, pattern).is_ok(),
+        (FieldType::Datetime, Some(pattern)) => {
+            NaiveDateTime::parse_from_str(value, pattern).is_ok()
+                || DateTime::parse_from_str(value, pattern).is_ok()
+        }
+        (_, Some(pattern)) => NaiveTime::parse_from_str(value, pattern).is_ok(),
+        (_, None) => true,
+    };
+    let is_time = *field_type == FieldType::Time;
+    match value.parse::<f64>() {
+        // a value in the same format, e.g. `%Y%m%d`, is not a serial number
+        _ if parsed => return Ok(()),
+        Ok(serial) if !is_time && (1.0..=EXCEL_MAX_SERIAL).contains(&serial) => {
+            let date =
+                NaiveDate::from_ymd_opt(1899, 12, 30).unwrap() + Duration::days(serial as i64);
+            return Err((
+                IssueCode::ExcelSerialDate,
+                format!("Excel serial number of {}?", date),
+            ));
+        }
+        _ => {}
+    }
+    let expected = match (field_type, format) {
+        (_, Some(pattern)) if pattern != "default" => pattern,
+        (FieldType::Date, _) => "YYYY-MM-DD",
+        (FieldType::Datetime, _) => "YYYY-MM-DDThh:mm:ss",
+        _ => "hh:mm:ss",
+    };
+    if let Some(captures) = RE_SLASHED_DATE.captures(value).filter(|_| !is_time) {
+        let (first, second): (u32, u32) =
+            (captures[1].parse().unwrap(), captures[2].parse().unwrap());
+        if (1..=12).contains(&first) && (1..=12).contains(&second) && first != second {
+            return Err((
+                IssueCode::AmbiguousDate,
+                format!("day and month cannot be told apart, expected {}", expected),
+            ));
+        }
+    }
+    Err((IssueCode::InvalidType, format!("expected {}", expected)))
+}
+
+impl Field {
+    /// Check a single value against the type and constraints of the field.
+    /// Empty values are missing values.
+    fn check(&self, value: &str, pattern: Option<&Regex>) -> Option<ValidationIssue> {
+        let constraints = &self.constraints;
+        if value.is_empty() {
+            return constraints
+                .required
+                .then(|| ValidationIssue::new(IssueCode::MissingValue).field(&self.name));
+        }
+        let invalid = |code, detail: String| {
+            Some(
+                ValidationIssue::new(code)
+                    .field(&self.name)
+                    .value(value)
+                    .detail(format!("{}: {}", self.name, detail)),
+            )
+        };
+        let number = match self.field_type {
+            FieldType::Number => match value.parse::<f64>() {
+                Ok(n) => Some(n),
+                Err(_) => return invalid(IssueCode::InvalidType, "expected a number".into()),
+            },
+            FieldType::Integer => match value.parse::<i64>() {
+                Ok(n) => Some(n as f64),
+                Err(_) => return invalid(IssueCode::InvalidType, "expected an integer".into()),
+            },
+            FieldType::Boolean => {
+                if !TRUE_VALUES.contains(&value) && !FALSE_VALUES.contains(&value) {
+                    return invalid(IssueCode::InvalidType, "expected a boolean".into());
+                }
+                None
+            }
+            FieldType::Date | FieldType::Datetime | FieldType::Time => {
+                if let Err((code, detail)) =
+                    check_temporal(&self.field_type, self.format.as_deref(), value)
+                {
+                    return invalid(code, detail);
+                }
+                None
+            }
+            FieldType::String | FieldType::Any => None,
+        };
+        if let Some(n) = number {
+            if let Some(min) = constraints.minimum.filter(|&min| n < min) {
+                return invalid(
+                    IssueCode::ConstraintViolated,
+                    format!("below the minimum {}", min),
+                );
+            }
+            if let Some(max) = constraints.maximum.filter(|&max| n > max) {
+                return invalid(
+                    IssueCode::ConstraintViolated,
+                    format!("above the maximum {}", max),
+                );
+            }
+        }
+        let length = value.chars().count();
+        if constraints.min_length.is_some_and(|min| length < min)
+            || constraints.max_length.is_some_and(|max| length > max)
+        {
+            return invalid(IssueCode::ConstraintViolated, "length out of bounds".into());
+        }
+        if pattern.is_some_and(|re| !re.is_match(value)) {
+            return invalid(
+                IssueCode::ConstraintViolated,
+                format!(
+                    "does not match the pattern {}",
+                    constraints.pattern.as_deref().unwrap_or_default()
+                ),
+            );
+        }
+        if let Some(allowed) = &constraints.allowed {
+            if !allowed.iter().any(|a| a == value) {
+                return invalid(
+                    IssueCode::ConstraintViolated,
+                    format!("not one of {}", allowed.join(", ")),
+                );
+            }
+        }
+        None
+    }
+}
+
+impl TableSchema {
+    /// Validate a delimited file with header against the schema.
+    pub fn validate<R: io::Read>(
+        &self,
+        input: R,
+        delimiter: u8,
+        options: &ReadOptions,
+    ) -> io::Result<Vec<LineError>> {
+        let mut rdr = ReaderBuilder::new().delimiter(delimiter).from_reader(input);
+        let headers = rdr.headers()?.clone();
+        let position = |name: &str| headers.iter().position(|h| h == name);
+        let mut error_vec = Vec::new();
+
+        let columns: Vec<Option<usize>> = self.fields.iter().map(|f| position(&f.name)).collect();
+        for (field, column) in self.fields.iter().zip(&columns) {
+            if column.is_none() {
+                error_vec.push(LineError::error(
+                    1,
+                    ValidationIssue::new(IssueCode::MissingColumn).value(&field.name),
+                ));
+            }
+        }
+        let patterns = self
+            .fields
+            .iter()
+            .map(|f| {
+                f.constraints
+                    .pattern
+                    .as_ref()
+                    .map(|p| Regex::new(&format!("^(?:{})$", p)))
+                    .transpose()
+            })
+            .collect::<Result<Vec<Option<Regex>>, _>>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let key_columns: Option<Vec<usize>> = self
+            .primary_key
+            .as_ref()
+            .and_then(|key| key.names().into_iter().map(position).collect());
+
+        let well = wells::column(Some(&headers));
+        let allowed = options.allowed.columns(Some(&headers));
+        let mut unique_seen: Vec<HashSet<String>> = vec![HashSet::new(); self.fields.len()];
+        let mut keys_seen = HashSet::new();
+        let mut unique_keys = UniqueIndex::new(&headers, &self.unique_keys);
+        for (i, result) in rdr.records().enumerate() {
+            let line = i + 2;
+            let record = match result {
+                Ok(record) => record,
+                Err(e) => {
+                    error_vec.push(LineError::error(
+                        line,
+                        ValidationIssue::new(IssueCode::Parse).detail(e),
+                    ));
+                    continue;
+                }
+            };
+            for (j, field) in self.fields.iter().enumerate() {
+                let value = match columns[j] {
+                    Some(column) => record.get(column).unwrap_or(""),
+                    None => continue,
+                };
+                let mut issue = field.check(value, patterns[j].as_ref());
+                if issue.is_none() && !value.is_empty() {
+                    issue = options.ontologies.check(&field.name, value);
+                }
+                if issue.is_none()
+                    && field.constraints.unique
+                    && !value.is_empty()
+                    && !unique_seen[j].insert(value.to_string())
+                {
+                    issue = Some(
+                        ValidationIssue::new(IssueCode::DuplicateValue)
+                            .field(&field.name)
+                            .value(value)
+                            .detail(&field.name),
+                    );
+                }
+                if let Some(issue) = issue {
+                    error_vec.push(LineError::error(line, issue));
+                }
+            }
+            if let Some(issue) = wells::issue(&record, well, options.plate) {
+                error_vec.push(LineError::error(line, issue));
+            }
+            for issue in options.allowed.issues(&record, &allowed) {
+                error_vec.push(LineError::error(line, issue));
+            }
+            for issue in unique_keys.check(line, &record) {
+                error_vec.push(LineError::error(line, issue));
+            }
+            if let Some(key_columns) = &key_columns {
+                let key: Vec<&str> = key_columns
+                    .iter()
+                    .map(|&c| record.get(c).unwrap_or(""))
+                    .collect();
+                if !keys_seen.insert(key.join("\t")) {
+                    error_vec.push(LineError::error(
+                        line,
+                        ValidationIssue::new(IssueCode::DuplicateKey).value(key.join(", ")),
+                    ));
+                }
+            }
+        }
+        Ok(error_vec)
+    }
+}
+
+/// Key of each row with its line, the value of `columns` in a delimited file
+/// with header. The rows whose key is empty are skipped, as they reference
+/// nothing.
+pub fn key_values(
+    content: &[u8],
+    delimiter: u8,
+    columns: &[&str],
+) -> io::Result<Vec<(usize, Vec<String>)>> {
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content);
+    let headers = rdr.headers()?.clone();
+    let positions = columns
+        .iter()
+        .map(|column| {
+            headers.iter().position(|h| h == *column).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("no column {} in the header", column),
+                )
+            })
+        })
+        .collect::<io::Result<Vec<usize>>>()?;
+    // unreadable records are reported by the validation
+    Ok(rdr
+        .records()
+        .enumerate()
+        .filter_map(|(i, record)| {
+            let record = record.ok()?;
+            let key: Vec<String> = positions
+                .iter()
+                .map(|&p| record.get(p).unwrap_or_default().to_string())
+                .collect();
+            (!key.iter().all(String::is_empty)).then(|| (i + 2, key))
+        })
+        .collect())
+}
+
+/// Errors for the rows whose key, the values of `columns`, is not among the
+/// keys of the rows referenced as `reference`.
+pub fn check_foreign_key(
+    rows: &[(usize, Vec<String>)],
+    columns: &[&str],
+    referenced: &HashSet<Vec<String>>,
+    reference: &str,
+) -> Vec<LineError> {
+    rows.iter()
+        .filter(|(_, key)| !referenced.contains(key))
+        .map(|(line, key)| {
+            LineError::error(
+                *line,
+                ValidationIssue::new(IssueCode::ForeignKeyViolation)
+                    .field(columns.join(", "))
+                    .value(key.join(", "))
+                    .detail(format!("not in {}", reference)),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_foreign_keys_are_checked() {
+        let key: CrossFileKey = "data/data.csv:strain,sample=meta.tsv:strain,sample"
+            .parse()
+            .unwrap();
+        assert_eq!(key.from.path, PathBuf::from("data/data.csv"));
+        assert_eq!(key.to.columns, ["strain", "sample"]);
+        assert!("data.csv:sample=meta.csv:strain,sample"
+            .parse::<CrossFileKey>()
+            .is_err());
+        assert!("data.csv=meta.csv:sample".parse::<CrossFileKey>().is_err());
+        let meta = b"sample\tstrain\nS1\tMG1655\nS2\tMG1655\n";
+        let referenced: HashSet<Vec<String>> = key_values(meta, b'\t', &["sample"])
+            .unwrap()
+            .into_iter()
+            .map(|(_, key)| key)
+            .collect();
+        let data = b"uniprot,sample,value\nQ00496,S1,1\nQ00496,S3,2\nQ00496,,3\n";
+        let rows = key_values(data, b',', &["sample"]).unwrap();
+        let error_vec = check_foreign_key(&rows, &["sample"], &referenced, "meta.tsv:sample");
+        assert_eq!(error_vec.len(), 1);
+        assert_eq!(error_vec[0].line, 3);
+        assert!(key_values(data, b',', &["strain"]).is_err());
+    }
+
+    #[test]
+    fn test_table_schema_constraints_are_checked() {
+        let schema: TableSchema = serde_json::from_str(
+            r#"{
+                "fields": [
+                    {"name": "met_id", "type": "string", "constraints": {"pattern": "[a-z0-9_]+(__[A-Z])?"}},
+                    {"name": "sample", "constraints": {"required": true}},
+                    {"name": "value", "type": "number", "constraints": {"minimum": 0}}
+                ],
+                "primaryKey": ["met_id", "sample"],
+                "uniqueKeys": [["sample", "value"]]
+            }"#,
+        )
+        .unwrap();
+        let input = "met_id,sample,value\nglc__D,SIM1,2\nglc__D,SIM1,3\nMNXM83,,-1\n\
+                     pyr,SIM1,2\n";
+        let error_vec = schema
+            .validate(input.as_bytes(), b',', &ReadOptions::default())
+            .unwrap();
+        // nothing on line 2; duplicated key on line 3; pattern, required and
+        // minimum on line 4; duplicated unique key on line 5
+        assert_eq!(error_vec.iter().filter(|e| e.line == 2).count(), 0);
+        assert_eq!(error_vec.iter().filter(|e| e.line == 3).count(), 1);
+        assert_eq!(error_vec.iter().filter(|e| e.line == 4).count(), 3);
+        assert_eq!(
+            error_vec
+                .iter()
+                .filter(|e| e.line == 5)
+                .map(|e| e.issues[0].detail.as_deref())
+                .collect::<Vec<_>>(),
+            [Some("also on line 2")]
+        );
+    }
+
+    #[test]
+    fn test_dates_are_iso_8601() {
+        let date = |format: Option<&str>, value| check_temporal(&FieldType::Date, format, value);
+        assert!(date(None, "2023-01-31").is_ok());
+        assert!(date(Some("%d/%m/%Y"), "31/01/2023").is_ok());
+        assert!(matches!(
+            date(None, "44957"),
+            Err((IssueCode::ExcelSerialDate, detail)) if detail.contains("2023-01-31")
+        ));
+        assert!(matches!(
+            date(None, "01/02/03"),
+            Err((IssueCode::AmbiguousDate, _))
+        ));
+        assert!(matches!(
+            date(None, "31/01/2023"),
+            Err((IssueCode::InvalidType, _))
+        ));
+        let datetime = |value| check_temporal(&FieldType::Datetime, None, value);
+        assert!(datetime("2023-01-31T10:15:00Z").is_ok());
+        assert!(datetime("2023-01-31T10:15:00").is_ok());
+        assert!(datetime("2023-01-31 10:15").is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use crate::bom;
+use crate::models::Models;
+use crate::report::format_errors;
+use crate::runner::{validate_input, InputFormat};
+use crate::validators::{ReadOptions, Severity};
+use argh::FromArgs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+
+#[derive(Debug, FromArgs)]
+/// Validate the files POSTed to /validate/<format> over HTTP.
+#[argh(subcommand, name = "serve")]
+pub struct ServeArgs {
+    /// address to listen on, 127.0.0.1:8080 by default
+    #[argh(option, default = "String::from(\"127.0.0.1:8080\")")]
+    address: String,
+
+    /// path to SBML model file, loaded once for all the requests (repeatable)
+    #[argh(option, short = 'm')]
+    model: Vec<PathBuf>,
+
+    /// universal namespace dump to verify metabolites against (repeatable)
+    #[argh(option)]
+    universal: Vec<PathBuf>,
+}
+
+pub fn run(args: ServeArgs) -> Result<(), io::Error> {
+    let models = Models::load(&args.model, &args.universal)?;
+    let listener = TcpListener::bind(&args.address)?;
+    eprintln!("listening on http://{}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        // a broken connection must not stop the server
+        if let Err(e) = stream.and_then(|stream| handle(stream, &models)) {
+            eprintln!("{}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Read a request and answer it, closing the connection.
+fn handle(mut stream: TcpStream, models: &Models) -> Result<(), io::Error> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "invalid Content-Length")
+                })?;
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let (status, text) = respond(&request_line, &body, models);
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        text.len(),
+        text
+    )
+}
+
+/// Status and body of the response to a request: the issues of the file if
+/// any, with 422 if some of them are errors.
+fn respond(request_line: &str, body: &[u8], models: &Models) -> (&'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let format = match (parts.next(), parts.next()) {
+        (Some("POST"), Some(path)) => path.strip_prefix("/validate/"),
+        _ => None,
+    };
+    let format = match format.map(str::parse::<InputFormat>) {
+        Some(Ok(format)) => format,
+        Some(Err(_)) => return ("400 Bad Request", "unknown format\n".to_string()),
+        None => {
+            return (
+                "404 Not Found",
+                "expected POST /validate/<format>\n".to_string(),
+            )
+        }
+    };
+    let (body, has_bom) = bom::strip(body);
+    match validate_input(body, &format, models, &ReadOptions::default()) {
+        Ok(mut error_vec) => {
+            if has_bom {
+                error_vec.insert(0, bom::warning());
+            }
+            let text: String = format_errors(&error_vec)
+                .into_iter()
+                .map(|line| line + "\n")
+                .collect();
+            if error_vec.iter().any(|e| e.severity == Severity::Error) {
+                ("422 Unprocessable Entity", text)
+            } else {
+                ("200 OK", text)
+            }
+        }
+        Err(e) => ("400 Bad Request", format!("{}\n", e)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_posted_files_are_validated() {
+        let models = Models::load(&[], &[]).unwrap();
+        let valid = std::fs::read("tests/uni_tidy.csv").unwrap();
+        let request = "POST /validate/tidy_prot HTTP/1.1\r\n";
+        assert_eq!(respond(request, &valid, &models), ("200 OK", String::new()));
+        let (status, text) = respond(request, b"uniprot,sample,value\nE0X97,SIM1,1\n", &models);
+        assert_eq!(status, "422 Unprocessable Entity");
+        assert!(text.contains("E0X97 invalid Uniprot ID"));
+        assert_eq!(respond("GET / HTTP/1.1", b"", &models).0, "404 Not Found");
+        assert_eq!(
+            respond("POST /validate/nope HTTP/1.1", b"", &models).0,
+            "400 Bad Request"
+        );
+    }
+}
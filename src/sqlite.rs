@@ -0,0 +1,106 @@
+use crate::runner::InputFormat;
+use std::io;
+use std::path::Path;
+
+/// Rows of a table of a SQLite database as delimited text in `format`, read by
+/// the same validators as a file: the column names of the table are the header
+/// (left out for the prot format), NULL is an empty field and numbers are
+/// written as stored. Line N of the issues is row N - 1 of the table.
+#[cfg(feature = "sqlite")]
+pub fn read_table(path: &Path, table: &str, format: &InputFormat) -> io::Result<Vec<u8>> {
+    use rusqlite::{Connection, OpenFlags};
+
+    Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(to_io)
+        .and_then(|conn| dump(&conn, table, format))
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+#[cfg(feature = "sqlite")]
+fn to_io(e: rusqlite::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+#[cfg(feature = "sqlite")]
+fn dump(conn: &rusqlite::Connection, table: &str, format: &InputFormat) -> io::Result<Vec<u8>> {
+    use rusqlite::types::ValueRef;
+
+    // the name is checked against the schema instead of being trusted in the query
+    let exists: bool = conn
+        .query_row(
+            "SELECT count(*) > 0 FROM sqlite_master WHERE type IN ('table', 'view') AND name = ?1",
+            [table],
+            |row| row.get(0),
+        )
+        .map_err(to_io)?;
+    if !exists {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no table {}", table),
+        ));
+    }
+    let mut stmt = conn
+        .prepare(&format!("SELECT * FROM \"{}\"", table.replace('"', "\"\"")))
+        .map_err(to_io)?;
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(format.delimiter())
+        .from_writer(Vec::new());
+    if format.has_headers() {
+        wtr.write_record(stmt.column_names())?;
+    }
+    let n_columns = stmt.column_count();
+    let mut rows = stmt.query([]).map_err(to_io)?;
+    while let Some(row) = rows.next().map_err(to_io)? {
+        let mut fields = Vec::with_capacity(n_columns);
+        for i in 0..n_columns {
+            fields.push(match row.get_ref(i).map_err(to_io)? {
+                ValueRef::Null => String::new(),
+                ValueRef::Integer(value) => value.to_string(),
+                ValueRef::Real(value) => value.to_string(),
+                ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned(),
+                ValueRef::Blob(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("binary value in column {} of {}", i + 1, table),
+                    ))
+                }
+            });
+        }
+        wtr.write_record(&fields)?;
+    }
+    wtr.into_inner()
+        .map_err(|e| io::Error::new(e.error().kind(), e.to_string()))
+}
+
+/// Without the `sqlite` feature no database can be read.
+#[cfg(not(feature = "sqlite"))]
+pub fn read_table(path: &Path, _table: &str, _format: &InputFormat) -> io::Result<Vec<u8>> {
+    Err(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+            "cannot read {}: omics_valid was built without the `sqlite` feature",
+            path.display()
+        ),
+    ))
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tables_are_read_as_csv() {
+        let conn = rusqlite::Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE proteomics (uniprot TEXT, sample TEXT, value REAL);
+             INSERT INTO proteomics VALUES ('Q00496', 'SIM1', 100001.5), ('E0X97', NULL, 3);",
+        )
+        .unwrap();
+        let content = dump(&conn, "proteomics", &InputFormat::TidyProt).unwrap();
+        assert_eq!(
+            String::from_utf8(content).unwrap(),
+            "uniprot,sample,value\nQ00496,SIM1,100001.5\nE0X97,,3\n"
+        );
+        assert!(dump(&conn, "metabolomics", &InputFormat::Met).is_err());
+    }
+}
@@ -0,0 +1,394 @@
+use crate::missing;
+use crate::outliers::VALUE_COLUMNS;
+use crate::replicates::SAMPLE_COLUMNS;
+use crate::runner::InputFormat;
+use crate::tidy;
+use crate::validators::ReadOptions;
+use csv::ReaderBuilder;
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// Quantiles of the values of a sample, interpolated between the closest ranks.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Quantiles {
+    pub min: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub max: f64,
+}
+
+impl Quantiles {
+    fn of(mut values: Vec<f64>) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+        let at = |q: f64| {
+            let rank = q * (values.len() - 1) as f64;
+            let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+            values[low] + (values[high] - values[low]) * (rank - low as f64)
+        };
+        Some(Quantiles {
+            min: values[0],
+            q1: at(0.25),
+            median: at(0.5),
+            q3: at(0.75),
+            max: values[values.len() - 1],
+        })
+    }
+}
+
+/// Descriptive statistics of the values of a sample.
+#[derive(Debug, Serialize)]
+pub struct SampleStats {
+    pub sample: String,
+    /// rows of the sample, genes of the counts format
+    pub records: usize,
+    /// empty, `NA`, `NaN`, `null` or `N/A` values
+    pub missing: usize,
+    /// values that are neither numbers nor missing, reported by the validation
+    pub invalid: usize,
+    /// of the numeric values, `None` if there is none
+    pub quantiles: Option<Quantiles>,
+}
+
+/// Values of a sample as they are read.
+#[derive(Default)]
+struct Values {
+    records: usize,
+    missing: usize,
+    invalid: usize,
+    numbers: Vec<f64>,
+}
+
+impl Values {
+    fn push(&mut self, value: &str) {
+        self.records += 1;
+        if missing::is_missing(value) {
+            self.missing += 1;
+        } else {
+            match value.parse::<f64>() {
+                Ok(number) => self.numbers.push(number),
+                Err(_) => self.invalid += 1,
+            }
+        }
+    }
+}
+
+/// Statistics of the values of each sample, in the order the samples first
+/// appear, with the headers and rows read as the validation reads them. `None`
+/// for the formats without a value per sample (prot, rna, datapackage).
+pub fn describe(
+    content: &[u8],
+    format: &InputFormat,
+    options: &ReadOptions,
+) -> Option<Vec<SampleStats>> {
+    if !format.has_headers() {
+        return None;
+    }
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(format.delimiter())
+        .flexible(true)
+        .from_reader(content);
+    options.rename_headers(&mut rdr);
+    let headers = rdr.headers().ok()?.clone();
+    let mut order: Vec<String> = Vec::new();
+    let mut samples: HashMap<String, Values> = HashMap::new();
+    let mut push = |sample: &str, value: &str| {
+        if !samples.contains_key(sample) {
+            order.push(sample.to_string());
+        }
+        samples.entry(sample.to_string()).or_default().push(value);
+    };
+    let columns = match format {
+        InputFormat::Counts => None,
+        _ => {
+            let position = |names: &[&str]| headers.iter().position(|h| names.contains(&h));
+            Some((position(&SAMPLE_COLUMNS)?, position(&VALUE_COLUMNS)?))
+        }
+    };
+    // unreadable records are reported by the validation
+    for (_, record) in options.rows.select(rdr.records().enumerate()) {
+        let record = match record {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        match columns {
+            Some((sample, value)) => push(
+                record.get(sample).unwrap_or_default(),
+                record.get(value).unwrap_or_default(),
+            ),
+            None => {
+                for (sample, value) in headers.iter().zip(record.iter()).skip(1) {
+                    push(sample, value);
+                }
+            }
+        }
+    }
+    Some(
+        order
+            .into_iter()
+            .map(|sample| {
+                let values = samples.remove(&sample).unwrap_or_default();
+                SampleStats {
+                    records: values.records,
+                    missing: values.missing,
+                    invalid: values.invalid,
+                    quantiles: Quantiles::of(values.numbers),
+                    sample,
+                }
+            })
+            .collect(),
+    )
+}
+
+/// Number of rows of an identifier.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Frequency {
+    pub id: String,
+    pub rows: usize,
+}
+
+/// Cardinality of the identifiers of a file, to spot a header row mixed into
+/// the data (an identifier named after a column) or a truncated export (the
+/// identifiers of the last rows missing from the other samples).
+#[derive(Debug, Serialize)]
+pub struct Identifiers {
+    /// distinct identifiers
+    pub distinct: usize,
+    /// distinct samples, 0 for prot which has none
+    pub samples: usize,
+    /// the most frequent identifiers, the first to appear first among ties
+    pub top: Vec<Frequency>,
+    /// identifiers of a single row in the order they appear, only for the
+    /// tidy formats with several samples, where each identifier has a row per
+    /// sample
+    pub singletons: Vec<String>,
+}
+
+/// Cardinality of the identifiers of the rows read as the validation reads
+/// them, with the `top` most frequent ones. The identifier of the multi
+/// format is its omics type and id, e.g. `prot:P0A6F5`. `None` for the
+/// formats without an identifier column (rna, feature_table, datapackage).
+pub fn identifiers(
+    content: &[u8],
+    format: &InputFormat,
+    options: &ReadOptions,
+    top: usize,
+) -> Option<Identifiers> {
+    let mut rdr = ReaderBuilder::new()
+        .has_headers(format.has_headers())
+        .delimiter(format.delimiter())
+        .flexible(true)
+        .from_reader(content);
+    options.rename_headers(&mut rdr);
+    let headers = rdr.headers().ok()?.clone();
+    let position = |names: &[&str]| headers.iter().position(|h| names.contains(&h));
+    let (key, sample) = match format {
+        InputFormat::Prot => (vec![0], None),
+        InputFormat::Counts => (vec![0], None),
+        _ => {
+            let key = tidy::id_columns(format)?
+                .iter()
+                .map(|name| position(&[*name]))
+                .collect::<Option<Vec<usize>>>()?;
+            (key, Some(position(&SAMPLE_COLUMNS)?))
+        }
+    };
+    // first row and number of rows of each identifier
+    let mut rows: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut samples: HashSet<String> = HashSet::new();
+    // unreadable records are reported by the validation
+    for (i, record) in options.rows.select(rdr.records().enumerate()) {
+        let record = match record {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        let id = key
+            .iter()
+            .map(|&c| record.get(c).unwrap_or_default())
+            .join(":");
+        rows.entry(id).or_insert((i, 0)).1 += 1;
+        if let Some(value) = sample.and_then(|c| record.get(c)) {
+            samples.insert(value.to_string());
+        }
+    }
+    let n_samples = match format {
+        InputFormat::Prot => 0,
+        InputFormat::Counts => headers.len().saturating_sub(1),
+        _ => samples.len(),
+    };
+    let by_first_row = rows
+        .into_iter()
+        .sorted_by_key(|(_, (first, _))| *first)
+        .collect::<Vec<_>>();
+    let singletons = if sample.is_some() && n_samples > 1 {
+        by_first_row
+            .iter()
+            .filter(|(_, (_, n))| *n == 1)
+            .map(|(id, _)| id.clone())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    Some(Identifiers {
+        distinct: by_first_row.len(),
+        samples: n_samples,
+        singletons,
+        top: by_first_row
+            .into_iter()
+            // stable, so the ties stay in the order they appear
+            .sorted_by_key(|(_, (_, n))| std::cmp::Reverse(*n))
+            .take(top)
+            .map(|(id, (_, rows))| Frequency { id, rows })
+            .collect(),
+    })
+}
+
+/// Lines of the report of the identifiers, with the first ten singletons.
+pub fn identifier_lines(ids: &Identifiers) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{} distinct identifiers, {} samples",
+        ids.distinct, ids.samples
+    )];
+    if !ids.top.is_empty() {
+        lines.push(format!(
+            "most frequent identifiers: {}",
+            ids.top
+                .iter()
+                .map(|f| format!("{} ({} rows)", f.id, f.rows))
+                .join(", ")
+        ));
+    }
+    if !ids.singletons.is_empty() {
+        let more = if ids.singletons.len() > 10 {
+            ", ..."
+        } else {
+            ""
+        };
+        lines.push(format!(
+            "{} identifiers in a single row: {}{}",
+            ids.singletons.len(),
+            ids.singletons.iter().take(10).join(", "),
+            more
+        ));
+    }
+    lines
+}
+
+/// Lines of the table of the samples, aligned on the longest name.
+pub fn table(stats: &[SampleStats]) -> Vec<String> {
+    let width = stats
+        .iter()
+        .map(|s| s.sample.len())
+        .max()
+        .unwrap_or(0)
+        .max(6);
+    let mut lines = vec![format!(
+        "{:width$}  {:>7}  {:>7}  {:>7}  {:>12}  {:>12}  {:>12}  {:>12}  {:>12}",
+        "sample",
+        "records",
+        "missing",
+        "invalid",
+        "min",
+        "q1",
+        "median",
+        "q3",
+        "max",
+        width = width
+    )];
+    for s in stats {
+        let quantiles = match s.quantiles {
+            Some(q) => [q.min, q.q1, q.median, q.q3, q.max]
+                .iter()
+                .map(|v| format!("{:>12}", format!("{:.4}", v)))
+                .collect::<Vec<_>>()
+                .join("  "),
+            None => format!("{:>12}", "-"),
+        };
+        lines.push(format!(
+            "{:width$}  {:>7}  {:>7}  {:>7}  {}",
+            s.sample,
+            s.records,
+            s.missing,
+            s.invalid,
+            quantiles,
+            width = width
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_samples_are_described() {
+        let input = "uniprot,sample,value\nQ00496,S2,1\nE0X97,S2,NA\nP0A6F5,S1,4\nQ00496,S2,3\nP0A9B2,S2,x\nQ00496,S1,2\n";
+        let stats = describe(
+            input.as_bytes(),
+            &InputFormat::TidyProt,
+            &ReadOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(stats[0].sample, "S2");
+        assert_eq!(
+            (stats[0].records, stats[0].missing, stats[0].invalid),
+            (4, 1, 1)
+        );
+        assert_eq!(stats[0].quantiles.unwrap().median, 2.0);
+        assert_eq!(
+            stats[1].quantiles,
+            Some(Quantiles {
+                min: 2.0,
+                q1: 2.5,
+                median: 3.0,
+                q3: 3.5,
+                max: 4.0
+            })
+        );
+        let counts = describe(
+            b"gene_id\tA\tB\nb0001\t10\t\nb0002\t20\t5\n",
+            &InputFormat::Counts,
+            &ReadOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(counts[1].missing, 1);
+        assert!(describe(b"Q00496,1\n", &InputFormat::Prot, &ReadOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_identifiers_are_counted() {
+        // a truncated export: E0X97 is missing from S2, and a header row in the data
+        let input =
+            "uniprot,sample,value\nQ00496,S1,1\nE0X97,S1,2\nuniprot,sample,value\nQ00496,S2,3\n";
+        let ids = identifiers(
+            input.as_bytes(),
+            &InputFormat::TidyProt,
+            &ReadOptions::default(),
+            1,
+        )
+        .unwrap();
+        assert_eq!((ids.distinct, ids.samples), (3, 3));
+        assert_eq!(
+            ids.top,
+            vec![Frequency {
+                id: "Q00496".to_string(),
+                rows: 2
+            }]
+        );
+        assert_eq!(ids.singletons, vec!["E0X97", "uniprot"]);
+        let multi = identifiers(
+            b"omics_type,id,sample,value\nprot,P0A6F5,S1,1\nmet,glc__D,S1,2\n",
+            &InputFormat::Multi,
+            &ReadOptions::default(),
+            5,
+        )
+        .unwrap();
+        assert_eq!(multi.top[0].id, "prot:P0A6F5");
+        assert!(multi.singletons.is_empty());
+    }
+}
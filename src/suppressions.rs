@@ -0,0 +1,85 @@
+use crate::report::unknown_code;
+use crate::validators::{IssueCode, ValidationIssue};
+use std::io;
+use std::path::Path;
+
+/// Suppression file read from the working directory if `--ignore-file` is not given.
+pub const DEFAULT_FILE: &str = ".omics_valid_ignore";
+
+/// Fingerprint of an accepted known issue: its code and its key, the
+/// offending value or, for the issues without one, the column, e.g.
+///
+/// ```text
+/// # legacy alias kept for the 2019 runs
+/// OV011 glc_D
+/// unknown-column batch_2019
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    pub code: IssueCode,
+    pub key: String,
+}
+
+impl Suppression {
+    pub fn matches(&self, issue: &ValidationIssue) -> bool {
+        self.code == issue.code && key(issue) == Some(self.key.as_str())
+    }
+}
+
+/// Key of an issue in the suppression file.
+pub fn key(issue: &ValidationIssue) -> Option<&str> {
+    issue.value.as_deref().or(issue.field.as_deref())
+}
+
+/// Load `path` or, if not given, the [`DEFAULT_FILE`] if it exists.
+pub fn load(path: Option<&Path>) -> io::Result<Vec<Suppression>> {
+    let path = match path {
+        Some(path) => path,
+        None if Path::new(DEFAULT_FILE).exists() => Path::new(DEFAULT_FILE),
+        None => return Ok(Vec::new()),
+    };
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+    parse(&text).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+/// A `CODE KEY` fingerprint per line, with blank lines and `#` comments
+/// ignored. The key is the rest of the line, so it may contain spaces.
+fn parse(text: &str) -> io::Result<Vec<Suppression>> {
+    let mut suppressions = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (code, key) = line.split_once(char::is_whitespace).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: expected CODE KEY, found {}", i + 1, line),
+            )
+        })?;
+        suppressions.push(Suppression {
+            code: IssueCode::lookup(code).ok_or_else(|| unknown_code(code))?,
+            key: key.trim().to_string(),
+        });
+    }
+    Ok(suppressions)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_suppressions_are_parsed() {
+        let suppressions =
+            parse("# legacy alias\nOV011 glc_D\n\nunknown-column batch 2019\n").unwrap();
+        assert_eq!(suppressions.len(), 2);
+        assert_eq!(suppressions[1].key, "batch 2019");
+        let issue = ValidationIssue::new(IssueCode::UnknownColumn).value("batch 2019");
+        assert!(suppressions[1].matches(&issue));
+        assert!(!suppressions[0].matches(&issue));
+        assert!(parse("OV031\n").is_err());
+        assert!(parse("OV999 glc_D\n").is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use crate::runner::InputFormat;
+use crate::validators::{IssueCode, LineError, ValidationIssue};
+use csv::ReaderBuilder;
+use std::collections::{HashMap, HashSet};
+
+/// Columns that identify a measurement with the sample, `None` for the
+/// formats that are not tidy.
+pub(crate) fn id_columns(format: &InputFormat) -> Option<&'static [&'static str]> {
+    match format {
+        InputFormat::TidyProt => Some(&["uniprot"]),
+        InputFormat::Met => Some(&["met_id"]),
+        InputFormat::Flux => Some(&["reaction_id"]),
+        InputFormat::EcUsage => Some(&["uniprot", "reaction_id"]),
+        // the identifiers of the multi format are unique per omics type
+        InputFormat::Multi => Some(&["omics_type", "id"]),
+        _ => None,
+    }
+}
+
+fn not_tidy(line: usize, field: &str, value: &str, detail: String) -> LineError {
+    LineError::error(
+        line,
+        ValidationIssue::new(IssueCode::NotTidy)
+            .field(field)
+            .value(value)
+            .detail(detail),
+    )
+}
+
+/// Check that a tidy file has one measurement per row: the identifier and the
+/// sample are a key of the rows, and no column is left over from a wide table,
+/// i.e., named with a number or after a sample. Checked with `--check-tidy`.
+pub fn check(content: &[u8], format: &InputFormat) -> Vec<LineError> {
+    match id_columns(format) {
+        Some(names) => check_with(content, format.delimiter(), names),
+        None => Vec::new(),
+    }
+}
+
+fn check_with(content: &[u8], delimiter: u8, names: &[&str]) -> Vec<LineError> {
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content);
+    let headers = match rdr.headers() {
+        Ok(headers) => headers.clone(),
+        Err(_) => return Vec::new(),
+    };
+    let position = |name: &str| headers.iter().position(|h| h == name);
+    let key: Option<Vec<usize>> = names.iter().map(|name| position(name)).collect();
+    let sample = position("sample");
+    let mut error_vec = Vec::new();
+    let mut samples = HashSet::new();
+    // line of the first row of each key
+    let mut first: HashMap<Vec<String>, usize> = HashMap::new();
+    // unreadable records are reported by the validation of the format
+    for (i, record) in rdr.records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(_) => continue,
+        };
+        let sample = match sample.and_then(|c| record.get(c)) {
+            Some(sample) => sample,
+            None => continue,
+        };
+        samples.insert(sample.to_string());
+        let mut fields: Vec<String> = match &key {
+            Some(key) => key
+                .iter()
+                .map(|&c| record.get(c).unwrap_or_default().to_string())
+                .collect(),
+            None => continue,
+        };
+        fields.push(sample.to_string());
+        if let Some(line) = first.get(&fields) {
+            error_vec.push(not_tidy(
+                i + 2,
+                "sample",
+                &fields.join(", "),
+                format!(
+                    "({}, sample) already on line {}: a tidy table has one row per identifier and \
+                     sample",
+                    names.join(", "),
+                    line
+                ),
+            ));
+        } else {
+            first.insert(fields, i + 2);
+        }
+    }
+    let mut header_errors: Vec<LineError> = headers
+        .iter()
+        .enumerate()
+        .filter_map(|(i, header)| {
+            let why = if header.trim().parse::<f64>().is_ok() {
+                "named with a number"
+            } else if samples.contains(header) {
+                "named after a sample"
+            } else {
+                return None;
+            };
+            Some(not_tidy(
+                1,
+                header,
+                header,
+                format!(
+                    "column {} is {}: the samples are spread over columns as in a wide table, \
+                     melt them into the sample and value columns",
+                    i + 1,
+                    why
+                ),
+            ))
+        })
+        .collect();
+    header_errors.extend(error_vec);
+    header_errors
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_untidy_files_are_explained() {
+        let input = "uniprot,sample,value,S2,2021\nQ00496,S1,1,,\nQ00496,S2,2,,\nQ00496,S1,3,,\n";
+        let error_vec = check_with(input.as_bytes(), b',', &["uniprot"]);
+        assert_eq!(
+            error_vec.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![1, 1, 4]
+        );
+        assert_eq!(error_vec[0].issues[0].field.as_deref(), Some("S2"));
+        assert_eq!(error_vec[1].issues[0].field.as_deref(), Some("2021"));
+        assert_eq!(error_vec[2].issues[0].value.as_deref(), Some("Q00496, S1"));
+        let tidy = "omics_type,id,sample,value\nprot,Q00496,S1,1\nmet,Q00496,S1,2\n";
+        assert!(check_with(tidy.as_bytes(), b',', &["omics_type", "id"]).is_empty());
+    }
+}
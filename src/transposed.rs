@@ -0,0 +1,121 @@
+use crate::models::Models;
+use crate::runner::InputFormat;
+use crate::uniprot;
+use crate::validators::{IssueCode, LineError, ValidationIssue};
+use csv::ReaderBuilder;
+use std::io::{self, BufRead};
+
+/// Lines read to detect a transposed matrix.
+const HEAD_LINES: usize = 20;
+
+/// Share of the fields of the first row that must be identifiers, and of the
+/// first column that may be, for a matrix to be taken as transposed.
+const THRESHOLD: f64 = 0.8;
+
+/// Read the first lines of a wide matrix (prot or counts), which are checked
+/// with [`check`] before the rows; nothing for the other formats.
+pub fn head<R: BufRead>(input: &mut R, format: &InputFormat) -> io::Result<Vec<u8>> {
+    let mut head = Vec::new();
+    if matches!(format, InputFormat::Prot | InputFormat::Counts) {
+        for _ in 0..HEAD_LINES {
+            if input.read_until(b'\n', &mut head)? == 0 {
+                break;
+            }
+        }
+    }
+    Ok(head)
+}
+
+/// Whether a field is an identifier of the rows of the format, `None` if it
+/// cannot be told: the gene ids of counts are only known with `--annotation`.
+fn is_id(format: &InputFormat, models: &Models, field: &str) -> Option<bool> {
+    match format {
+        InputFormat::Prot => Some(uniprot::parse(field.trim()).is_ok()),
+        InputFormat::Counts if models.has_annotation() => models.contains_gene(field.trim()),
+        _ => None,
+    }
+}
+
+fn share(ids: &[bool]) -> f64 {
+    ids.iter().filter(|&&id| id).count() as f64 / ids.len().max(1) as f64
+}
+
+/// A matrix with the samples in rows and the identifiers in columns: the
+/// first row is mostly identifiers (UniProt accessions for prot, genes of the
+/// annotation for counts) and the first column mostly not, e.g. sample names.
+/// It is reported once instead of an invalid identifier on every row.
+pub fn check(head: &[u8], format: &InputFormat, models: &Models) -> Option<LineError> {
+    let rows: Vec<csv::StringRecord> = ReaderBuilder::new()
+        .has_headers(false)
+        .delimiter(format.delimiter())
+        .flexible(true)
+        .from_reader(head)
+        .records()
+        // the last line may be cut, the unreadable ones are reported by the validation
+        .filter_map(Result::ok)
+        .collect();
+    let (first, rest) = rows.split_first()?;
+    let header: Vec<&str> = first.iter().skip(1).collect();
+    let column: Vec<&str> = rest.iter().filter_map(|row| row.get(0)).collect();
+    if header.len() < 2 || column.is_empty() {
+        return None;
+    }
+    let header_ids: Option<Vec<bool>> = header
+        .iter()
+        .map(|field| is_id(format, models, field))
+        .collect();
+    let column_ids: Option<Vec<bool>> = column
+        .iter()
+        .map(|field| is_id(format, models, field))
+        .collect();
+    if share(&header_ids?) < THRESHOLD || share(&column_ids?) > 1.0 - THRESHOLD {
+        return None;
+    }
+    let examples = |fields: &[&str]| {
+        fields
+            .iter()
+            .take(3)
+            .copied()
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    Some(LineError::error(
+        1,
+        ValidationIssue::new(IssueCode::TransposedMatrix)
+            .value(header[0])
+            .detail(format!(
+                "the first row has {} identifiers ({}, ...) and the first column values such as \
+                 {}: the samples are in rows and the identifiers in columns, transpose the matrix \
+                 so that each row is an identifier and each column a sample",
+                header.len(),
+                examples(&header),
+                examples(&column)
+            )),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_transposed_matrices_are_detected() {
+        let mut models = Models::load(&[], &[]).unwrap();
+        let transposed = b"sample,P0A6F5,Q00496,E0X9C7,P0A9B2\nSIM1,1,2,3,4\nSIM2,5,6,7,8\n";
+        let mut input = &transposed[..];
+        let head = head(&mut input, &InputFormat::Prot).unwrap();
+        let error = check(&head, &InputFormat::Prot, &models).unwrap();
+        assert_eq!(error.issues[0].code, IssueCode::TransposedMatrix);
+        assert_eq!(error.issues[0].value.as_deref(), Some("P0A6F5"));
+        let wide = b"Q00496,100001,21283\nQ7B2Q4,123.3444,0\nE0X9C7,10.2,21283\n";
+        assert!(check(wide, &InputFormat::Prot, &models).is_none());
+        // the genes of counts are only known with an annotation
+        let counts = b"sample\tb0001\tb0002\nglc_1\t10\t3\n";
+        assert!(check(counts, &InputFormat::Counts, &models).is_none());
+        models
+            .load_annotation(Path::new("tests/genes.gff3"))
+            .unwrap();
+        assert!(check(counts, &InputFormat::Counts, &models).is_some());
+    }
+}
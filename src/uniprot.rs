@@ -0,0 +1,256 @@
+use std::fmt;
+
+/// Pattern of the accessions accepted by [`parse`], exported by `schema-out`.
+pub(crate) const PATTERN: &str = r"^([OPQ][0-9][A-Z0-9]{3}[0-9]|[A-NR-Z][0-9]([A-Z][A-Z0-9]{2}[0-9]){1,2})(-[0-9]+)?(\.[0-9]+)?$";
+
+/// A UniProtKB accession, e.g. `P12345-2.3` for the version 3 of the sequence
+/// of the isoform 2 of the entry `P12345`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Accession<'a> {
+    /// accession of the entry, of 6 or 10 characters
+    pub entry: &'a str,
+    pub isoform: Option<u32>,
+    pub version: Option<u32>,
+}
+
+/// Why a text is not an accession, the detail of the invalid Uniprot ID issue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// length of the entry, neither 6 nor 10 characters, or 10 characters
+    /// starting with O, P or Q
+    Length(usize),
+    /// 1-based position of a character not allowed there
+    Character {
+        pos: usize,
+        found: char,
+        expected: &'static str,
+    },
+    /// isoform (`-`) or version (`.`) suffix that is not a number
+    Suffix(char),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Length(0) => write!(f, "empty accession"),
+            ParseError::Length(10) => write!(f, "O, P and Q start accessions of 6 characters"),
+            ParseError::Length(n) => write!(f, "{} characters, accessions have 6 or 10", n),
+            ParseError::Character {
+                pos,
+                found,
+                expected,
+            } => write!(f, "'{}' at position {}, expected {}", found, pos, expected),
+            ParseError::Suffix('-') => write!(f, "the isoform after '-' must be a number"),
+            ParseError::Suffix(_) => write!(f, "the version after '.' must be a number"),
+        }
+    }
+}
+
+/// Characters allowed at a position of an accession.
+#[derive(Debug, Clone, Copy)]
+enum Class {
+    Digit,
+    Letter,
+    Alphanumeric,
+}
+
+impl Class {
+    fn matches(self, c: u8) -> bool {
+        match self {
+            Class::Digit => c.is_ascii_digit(),
+            Class::Letter => c.is_ascii_uppercase(),
+            Class::Alphanumeric => c.is_ascii_digit() || c.is_ascii_uppercase(),
+        }
+    }
+
+    fn expected(self) -> &'static str {
+        match self {
+            Class::Digit => "a digit",
+            Class::Letter => "an uppercase letter",
+            Class::Alphanumeric => "a digit or an uppercase letter",
+        }
+    }
+}
+
+use Class::{Alphanumeric as X, Digit as D, Letter as L};
+
+/// Characters after the first one of the accessions starting with O, P or Q.
+const OPQ: [Class; 5] = [D, X, X, X, D];
+/// Characters after the first one of the other accessions, the last four
+/// repeated in the accessions of 10 characters.
+const OTHERS: [Class; 9] = [D, L, X, X, D, L, X, X, D];
+
+/// Number of a suffix, which must only have digits.
+fn suffix(text: &str, separator: char) -> Result<u32, ParseError> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(ParseError::Suffix(separator));
+    }
+    text.parse().map_err(|_| ParseError::Suffix(separator))
+}
+
+/// Parse an accession with the grammar of UniProtKB
+/// (<https://www.uniprot.org/help/accession_numbers>):
+/// `[OPQ][0-9][A-Z0-9]{3}[0-9]` or `[A-NR-Z][0-9]([A-Z][A-Z0-9]{2}[0-9]){1,2}`,
+/// followed by an optional isoform (`-2`) and sequence version (`.3`).
+pub fn parse(text: &str) -> Result<Accession<'_>, ParseError> {
+    let (rest, version) = match text.split_once('.') {
+        Some((rest, version)) => (rest, Some(suffix(version, '.')?)),
+        None => (text, None),
+    };
+    let (entry, isoform) = match rest.split_once('-') {
+        Some((entry, isoform)) => (entry, Some(suffix(isoform, '-')?)),
+        None => (rest, None),
+    };
+    if let Some((i, found)) = entry.chars().enumerate().find(|(_, c)| !c.is_ascii()) {
+        return Err(ParseError::Character {
+            pos: i + 1,
+            found,
+            expected: "an ASCII character",
+        });
+    }
+    let bytes = entry.as_bytes();
+    let first = match bytes.first() {
+        Some(&first) => first,
+        None => return Err(ParseError::Length(0)),
+    };
+    let classes: &[Class] = match first {
+        b'O' | b'P' | b'Q' if bytes.len() == 6 => &OPQ,
+        b'A'..=b'Z' if bytes.len() != 6 && bytes.len() != 10 => {
+            return Err(ParseError::Length(bytes.len()))
+        }
+        b'O' | b'P' | b'Q' => return Err(ParseError::Length(10)),
+        b'A'..=b'Z' => &OTHERS[..bytes.len() - 1],
+        _ => {
+            return Err(ParseError::Character {
+                pos: 1,
+                found: first as char,
+                expected: "an uppercase letter",
+            })
+        }
+    };
+    for (i, (&c, class)) in bytes[1..].iter().zip(classes).enumerate() {
+        if !class.matches(c) {
+            return Err(ParseError::Character {
+                pos: i + 2,
+                found: c as char,
+                expected: class.expected(),
+            });
+        }
+    }
+    Ok(Accession {
+        entry,
+        isoform,
+        version,
+    })
+}
+
+/// Accession of the entry of `text` (without isoform and version), `text`
+/// itself if it is not an accession.
+pub fn entry(text: &str) -> &str {
+    parse(text).map_or(text, |accession| accession.entry)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use regex::Regex;
+
+    #[test]
+    fn test_accessions_are_parsed() {
+        for entry in [
+            "P12345",
+            "Q9Y6K9",
+            "O00000",
+            "A0A023GPI8",
+            "E0X9C7",
+            "Z9Z9Z9",
+        ] {
+            assert_eq!(
+                parse(entry),
+                Ok(Accession {
+                    entry,
+                    isoform: None,
+                    version: None
+                })
+            );
+        }
+        assert_eq!(
+            parse("P12345-2.3"),
+            Ok(Accession {
+                entry: "P12345",
+                isoform: Some(2),
+                version: Some(3)
+            })
+        );
+        assert_eq!(parse("A0A023GPI8.1").unwrap().version, Some(1));
+        assert_eq!(parse("Q00496-10").unwrap().isoform, Some(10));
+        assert_eq!(entry("P12345-2"), "P12345");
+        assert_eq!(entry("CON__P12345"), "CON__P12345");
+    }
+
+    #[test]
+    fn test_invalid_accessions_are_explained() {
+        assert_eq!(parse(""), Err(ParseError::Length(0)));
+        assert_eq!(parse("E0X97"), Err(ParseError::Length(5)));
+        assert_eq!(parse("A0A023GPI"), Err(ParseError::Length(9)));
+        // O, P and Q only start short accessions
+        assert_eq!(parse("P0A023GPI8"), Err(ParseError::Length(10)));
+        assert_eq!(
+            parse("p12345"),
+            Err(ParseError::Character {
+                pos: 1,
+                found: 'p',
+                expected: "an uppercase letter"
+            })
+        );
+        // the third character of the A-N, R-Z accessions is a letter
+        assert_eq!(
+            parse("A01234"),
+            Err(ParseError::Character {
+                pos: 3,
+                found: '1',
+                expected: "an uppercase letter"
+            })
+        );
+        assert_eq!(
+            parse("P1234A"),
+            Err(ParseError::Character {
+                pos: 6,
+                found: 'A',
+                expected: "a digit"
+            })
+        );
+        assert!(parse("P12,45").is_err());
+        assert!(parse("P1234é").is_err());
+        assert_eq!(parse("P12345-"), Err(ParseError::Suffix('-')));
+        assert_eq!(parse("P12345-a"), Err(ParseError::Suffix('-')));
+        assert_eq!(parse("P12345.1.2"), Err(ParseError::Suffix('.')));
+        assert_eq!(parse("P12345-2-3"), Err(ParseError::Suffix('-')));
+        assert_eq!(
+            parse("E0X97").unwrap_err().to_string(),
+            "5 characters, accessions have 6 or 10"
+        );
+    }
+
+    #[test]
+    fn test_pattern_agrees_with_the_parser() {
+        let pattern = Regex::new(PATTERN).unwrap();
+        for text in [
+            "P12345",
+            "A0A023GPI8",
+            "P12345-2",
+            "P12345.3",
+            "P12345-2.3",
+            "E0X97",
+            "P0A023GPI8",
+            "A01234",
+            "P12,45",
+            "P12345-",
+            "P12345.1.2",
+            "p12345",
+            "Q00496 ",
+        ] {
+            assert_eq!(pattern.is_match(text), parse(text).is_ok(), "{}", text);
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::cache;
+use crate::remote::{self, NetworkOptions};
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+const API: &str = "https://rest.uniprot.org";
+/// Accessions sent in an ID-mapping job.
+const BATCH_SIZE: usize = 500;
+/// Least time between two requests to the API from any thread, to stay well
+/// below its rate limit.
+const MIN_INTERVAL: Duration = Duration::from_millis(200);
+/// Time between two polls of the status of a job.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Polls of a job before giving up on it.
+const MAX_POLLS: u32 = 120;
+/// File of the cache with a verdict (`1` if it exists) per accession.
+const CACHE_FILE: &str = "uniprot_verdicts.tsv";
+
+/// Accessions found by an ID-mapping job, or why the job failed.
+type BatchResult = Result<HashSet<String>, String>;
+
+/// Time of the last request to the API.
+static LAST_REQUEST: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
+
+/// Wait until a request can be sent without exceeding the rate of the API.
+fn throttle() {
+    let mut last = LAST_REQUEST.lock().unwrap();
+    if let Some(elapsed) = last.map(|last| last.elapsed()) {
+        if elapsed < MIN_INTERVAL {
+            std::thread::sleep(MIN_INTERVAL - elapsed);
+        }
+    }
+    *last = Some(Instant::now());
+}
+
+fn get_json(network: &NetworkOptions, url: &str) -> Result<Value, String> {
+    throttle();
+    let response = remote::request(network, "GET", url).map_err(|e| e.to_string())?;
+    serde_json::from_reader(response.into_reader()).map_err(|e| e.to_string())
+}
+
+/// Accessions of a batch found in UniProtKB by an ID-mapping job, which also
+/// maps the accessions merged into other entries.
+fn map_batch(network: &NetworkOptions, accessions: &[String]) -> BatchResult {
+    throttle();
+    let ids = accessions.join(",");
+    let response = remote::post_form(
+        network,
+        &format!("{}/idmapping/run", API),
+        &[
+            ("from", "UniProtKB_AC-ID"),
+            ("to", "UniProtKB"),
+            ("ids", &ids),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let job: Value = serde_json::from_reader(response.into_reader()).map_err(|e| e.to_string())?;
+    let id = job
+        .get("jobId")
+        .and_then(Value::as_str)
+        .ok_or("no job ID in the answer of the ID mapping")?;
+    debug!(
+        job = id,
+        accessions = accessions.len(),
+        "ID mapping submitted"
+    );
+    let mut polls = 0;
+    loop {
+        // redirected to the results once the job is finished
+        let status = get_json(network, &format!("{}/idmapping/status/{}", API, id))?;
+        match status.get("jobStatus").and_then(Value::as_str) {
+            None => break,
+            Some("NEW" | "QUEUED" | "RUNNING") if polls < MAX_POLLS => {
+                polls += 1;
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            Some(status) => return Err(format!("ID mapping job {}: {}", id, status)),
+        }
+    }
+    throttle();
+    let response = remote::request(
+        network,
+        "GET",
+        &format!(
+            "{}/idmapping/uniprotkb/results/stream/{}?fields=accession&format=tsv",
+            API, id
+        ),
+    )
+    .map_err(|e| e.to_string())?;
+    // `From` and `Entry` columns, the accessions not found are left out
+    let mut found = HashSet::new();
+    for line in BufReader::new(response.into_reader()).lines().skip(1) {
+        let line = line.map_err(|e| e.to_string())?;
+        if let Some(from) = line.split('\t').next() {
+            found.insert(from.to_string());
+        }
+    }
+    Ok(found)
+}
+
+fn read_cache(path: &Path) -> HashMap<String, bool> {
+    match File::open(path) {
+        Ok(file) => read_verdicts(file),
+        Err(_) => HashMap::new(),
+    }
+}
+
+/// Lines of an accession and its verdict, `1` if it exists.
+fn read_verdicts(input: impl Read) -> HashMap<String, bool> {
+    BufReader::new(input)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| {
+            let (accession, verdict) = line.split_once('\t')?;
+            Some((accession.to_string(), verdict == "1"))
+        })
+        .collect()
+}
+
+fn append_cache(path: &Path, verdicts: &[(String, bool)]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    write_verdicts(file, verdicts)?;
+    cache::evict(path)
+}
+
+fn write_verdicts(mut output: impl Write, verdicts: &[(String, bool)]) -> io::Result<()> {
+    for (accession, exists) in verdicts {
+        writeln!(output, "{}\t{}", accession, u8::from(*exists))?;
+    }
+    Ok(())
+}
+
+/// Whether each accession (of an entry, without isoform or version) exists in
+/// UniProtKB. The verdicts are cached on disk, the others are looked up by
+/// ID-mapping jobs of up to 500 accessions, `--remote-jobs` of them at the
+/// same time, and added to the cache.
+pub fn verdicts(
+    accessions: &[String],
+    network: &NetworkOptions,
+) -> io::Result<HashMap<String, bool>> {
+    let path = cache::dir()?.join(CACHE_FILE);
+    let mut verdicts = read_cache(&path);
+    let missing: Vec<String> = accessions
+        .iter()
+        .filter(|a| !verdicts.contains_key(*a))
+        .cloned()
+        .collect();
+    if missing.is_empty() {
+        return Ok(verdicts);
+    }
+    let batches: Vec<&[String]> = missing.chunks(BATCH_SIZE).collect();
+    info!(
+        accessions = missing.len(),
+        cached = accessions.len() - missing.len(),
+        "looking up the proteins in UniProtKB"
+    );
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<BatchResult>>> =
+        Mutex::new(batches.iter().map(|_| None).collect());
+    std::thread::scope(|scope| {
+        for _ in 0..network.jobs.clamp(1, batches.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let batch = match batches.get(i) {
+                    Some(batch) => batch,
+                    None => break,
+                };
+                let result = map_batch(network, batch);
+                results.lock().unwrap()[i] = Some(result);
+            });
+        }
+    });
+    let mut new = Vec::new();
+    let mut failure = None;
+    for (batch, result) in batches.iter().zip(results.into_inner().unwrap()) {
+        match result {
+            Some(Ok(found)) => new.extend(
+                batch
+                    .iter()
+                    .map(|accession| (accession.clone(), found.contains(accession))),
+            ),
+            Some(Err(e)) => failure = Some(e),
+            None => {}
+        }
+    }
+    // the verdicts of the batches that succeeded are kept for the next run
+    append_cache(&path, &new)?;
+    if let Some(e) = failure {
+        return Err(io::Error::other(format!("UniProt lookup failed: {}", e)));
+    }
+    verdicts.extend(new);
+    Ok(verdicts)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_verdicts_are_read_back() {
+        let mut cached = Vec::new();
+        write_verdicts(
+            &mut cached,
+            &[("P12345".to_string(), true), ("Q99999".to_string(), false)],
+        )
+        .unwrap();
+        // a line cut by an interrupted run is skipped
+        cached.extend(b"Q00496");
+        let verdicts = read_verdicts(cached.as_slice());
+        assert_eq!(verdicts.get("P12345"), Some(&true));
+        assert_eq!(verdicts.get("Q99999"), Some(&false));
+        assert_eq!(verdicts.len(), 2);
+    }
+}
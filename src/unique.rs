@@ -0,0 +1,132 @@
+use crate::validators::{IssueCode, LineError, ValidationIssue};
+use csv::{ReaderBuilder, StringRecord};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Composite keys whose values must be unique across the rows of a file, the
+/// `unique` setting of the configuration: a key, `unique = ["met_id",
+/// "sample", "time"]`, or several, `unique = [["met_id", "sample"], ["id"]]`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum UniqueKeys {
+    Key(Vec<String>),
+    Keys(Vec<Vec<String>>),
+}
+
+impl UniqueKeys {
+    pub fn keys(self) -> Vec<Vec<String>> {
+        match self {
+            UniqueKeys::Key(key) => vec![key],
+            UniqueKeys::Keys(keys) => keys,
+        }
+    }
+}
+
+/// Columns of a key, their positions in the header and the line of the first
+/// row of each of their values.
+type KeyIndex<'a> = (&'a [String], Vec<usize>, HashMap<Vec<String>, usize>);
+
+/// Keys whose columns are all in a header, with the line of the first row of
+/// each of their values; the keys of other formats are skipped.
+pub struct UniqueIndex<'a> {
+    keys: Vec<KeyIndex<'a>>,
+}
+
+impl<'a> UniqueIndex<'a> {
+    pub fn new(headers: &StringRecord, keys: &'a [Vec<String>]) -> Self {
+        let keys = keys
+            .iter()
+            .filter_map(|key| {
+                let positions = key
+                    .iter()
+                    .map(|column| headers.iter().position(|h| h == column))
+                    .collect::<Option<Vec<usize>>>()?;
+                Some((key.as_slice(), positions, HashMap::new()))
+            })
+            .collect();
+        UniqueIndex { keys }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Issues of a row repeating the key of an earlier row, with the line of
+    /// the first one. Rows whose key is empty are not checked.
+    pub fn check(&mut self, line: usize, record: &StringRecord) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        for (columns, positions, first) in self.keys.iter_mut() {
+            let values: Vec<String> = positions
+                .iter()
+                .map(|&p| record.get(p).unwrap_or_default().to_string())
+                .collect();
+            if values.iter().all(String::is_empty) {
+                continue;
+            }
+            match first.get(&values) {
+                Some(first) => issues.push(
+                    ValidationIssue::new(IssueCode::DuplicateKey)
+                        .field(columns.join(", "))
+                        .value(values.join(", "))
+                        .detail(format!("also on line {}", first)),
+                ),
+                None => {
+                    first.insert(values, line);
+                }
+            }
+        }
+        issues
+    }
+}
+
+/// Report every row repeating the key of an earlier row across the whole file,
+/// which has a header.
+pub fn check(content: &[u8], delimiter: u8, keys: &[Vec<String>]) -> Vec<LineError> {
+    let mut rdr = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(content);
+    let mut index = match rdr.headers() {
+        Ok(headers) => UniqueIndex::new(headers, keys),
+        Err(_) => return Vec::new(),
+    };
+    if index.is_empty() {
+        return Vec::new();
+    }
+    let mut error_vec = Vec::new();
+    // unreadable records are reported by the validation of the format
+    for (i, record) in rdr.records().enumerate() {
+        if let Ok(record) = record {
+            for issue in index.check(i + 2, &record) {
+                error_vec.push(LineError::error(i + 2, issue));
+            }
+        }
+    }
+    error_vec
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unique_keys_are_checked() {
+        let config: UniqueKeys = serde_json::from_str(r#"["met_id", "sample", "time"]"#).unwrap();
+        let keys = config.keys();
+        assert_eq!(keys.len(), 1);
+        let input = "met_id,sample,time,value\nglc__D,S1,0,1\nglc__D,S1,1,2\nglc__D,S1,0,3\n\
+                     pyr,S1,0,4\nglc__D,S1,0,5\n";
+        let error_vec = check(input.as_bytes(), b',', &keys);
+        assert_eq!(
+            error_vec.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![4, 6]
+        );
+        assert_eq!(
+            error_vec[0].issues[0].detail.as_deref(),
+            Some("also on line 2")
+        );
+        // the keys of other formats are skipped
+        let keys: UniqueKeys = serde_json::from_str(r#"[["uniprot", "sample"]]"#).unwrap();
+        assert!(check(input.as_bytes(), b',', &keys.keys()).is_empty());
+    }
+}
@@ -1,61 +1,553 @@
-use bio::io::fastq::Reader;
-use regex::Regex;
-use rust_sbml::ModelRaw;
+use crate::allowed::AllowedLists;
+use crate::bench::{Bench, Phase};
+use crate::counts::CountsOptions;
+use crate::deferred::{RemoteCheck, RemoteChecks};
+use crate::excel;
+use crate::fastq::{self, FastqError, FastqOptions, FastqStats, QualityEncoding};
+use crate::features::FeatureOptions;
+use crate::jsonl;
+use crate::missing::MissingOptions;
+use crate::models::Models;
+use crate::naming::Grammar;
+use crate::numbers;
+use crate::ontology::Ontologies;
+use crate::paths;
+use crate::proteomics::{self, DecoyPolicy, ProteomicsOptions};
+use crate::remote::{self, NetworkOptions, RemoteFile};
+use crate::replicates::ReplicateOptions;
+use crate::rewrite;
+use crate::scale::Scale;
+use crate::uniprot;
+use crate::wells::{self, PlateFormat};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::path::{Path, PathBuf};
 
 use csv::{ErrorKind, ReaderBuilder};
 
 use serde::Deserialize;
-use validator::{Validate, ValidateArgs, ValidationError, ValidationErrorsKind};
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator, IntoStaticStr};
+use validator::{Validate, ValidateArgs, ValidationError, ValidationErrors, ValidationErrorsKind};
 
-static RE_UNIPROT: once_cell::sync::Lazy<Regex> = once_cell::sync::Lazy::new(|| {
-    Regex::new(
-        r"^([A-N,R-Z][0-9]([A-Z][A-Z, 0-9][A-Z, 0-9][0-9]){1,2})|([O,P,Q][0-9][A-Z, 0-9][A-Z, 0-9][A-Z, 0-9][0-9])(\.\d+)?$"
-    )
-    .unwrap()
-});
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumString, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum Severity {
+    Error,
+    /// reported but does not make the validation fail
+    Warning,
+    /// accepted known issue of the suppression file, listed but not counted
+    Suppressed,
+}
+
+/// Stable identifier of the kind of issue, used for suppression and structured
+/// output; see [`crate::report`] for the human-readable messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumString, Display, IntoStaticStr, EnumIter)]
+#[strum(serialize_all = "kebab-case")]
+pub enum IssueCode {
+    /// the record could not be read
+    Parse,
+    UnknownColumn,
+    InvalidValue,
+    EmptyField,
+    EmptySample,
+    FractionalCount,
+    NegativeCount,
+    ColumnSumMismatch,
+    OutOfBounds,
+    InvalidUniprotId,
+    NotInModel,
+    UnknownUniprotId,
+    NotInAnnotation,
+    DecoyEntry,
+    ByteOrderMark,
+    NumericOverflow,
+    PrecisionLoss,
+    MissingFastq,
+    MalformedFastq,
+    InvalidFastqUrl,
+    UnreachableFastq,
+    EmptyFastq,
+    InterleavedFastq,
+    DuplicateReadIds,
+    LayoutMismatch,
+    PlatformMismatch,
+    InconsistentExperiment,
+    InconsistentFeature,
+    InvalidAdduct,
+    MassMismatch,
+    InvalidFormula,
+    MissingColumn,
+    MissingValue,
+    InvalidType,
+    ConstraintViolated,
+    DuplicateValue,
+    DuplicateKey,
+    ExcelSerialDate,
+    AmbiguousDate,
+    UnknownTerm,
+    ObsoleteTerm,
+    SizeMismatch,
+    HashMismatch,
+    InvalidWell,
+    Plugin,
+    TooFewReplicates,
+    MalformedSampleName,
+    SampleNotInAllLayers,
+    MissingRate,
+    Outlier,
+    InvalidDialect,
+    NotTidy,
+    NonPortablePath,
+    MixedLineEndings,
+    StrayCarriageReturn,
+    TransposedMatrix,
+    ExcelArtifact,
+    NotAllowed,
+    ForeignKeyViolation,
+    ScaleMismatch,
+    MissingBatch,
+    ConfoundedBatch,
+    WrongCompartment,
+    EnzymeNotInReaction,
+    Unphysiological,
+    NotAnExchange,
+}
+
+impl IssueCode {
+    /// Short identifier of the code, in the style of linters.
+    pub fn id(self) -> &'static str {
+        match self {
+            IssueCode::Parse => "OV001",
+            IssueCode::UnknownColumn => "OV002",
+            IssueCode::InvalidValue => "OV003",
+            IssueCode::EmptyField => "OV004",
+            IssueCode::EmptySample => "OV005",
+            IssueCode::FractionalCount => "OV006",
+            IssueCode::NegativeCount => "OV007",
+            IssueCode::ColumnSumMismatch => "OV008",
+            IssueCode::OutOfBounds => "OV009",
+            IssueCode::InvalidUniprotId => "OV010",
+            IssueCode::NotInModel => "OV011",
+            IssueCode::UnknownUniprotId => "OV012",
+            IssueCode::NotInAnnotation => "OV013",
+            IssueCode::DecoyEntry => "OV014",
+            IssueCode::ByteOrderMark => "OV015",
+            IssueCode::NumericOverflow => "OV016",
+            IssueCode::PrecisionLoss => "OV017",
+            IssueCode::MissingFastq => "OV020",
+            IssueCode::MalformedFastq => "OV021",
+            IssueCode::InvalidFastqUrl => "OV022",
+            IssueCode::UnreachableFastq => "OV023",
+            IssueCode::EmptyFastq => "OV024",
+            IssueCode::InterleavedFastq => "OV025",
+            IssueCode::DuplicateReadIds => "OV026",
+            IssueCode::LayoutMismatch => "OV027",
+            IssueCode::PlatformMismatch => "OV028",
+            IssueCode::InconsistentExperiment => "OV029",
+            IssueCode::InconsistentFeature => "OV030",
+            IssueCode::InvalidAdduct => "OV031",
+            IssueCode::MassMismatch => "OV032",
+            IssueCode::InvalidFormula => "OV033",
+            IssueCode::MissingColumn => "OV040",
+            IssueCode::MissingValue => "OV041",
+            IssueCode::InvalidType => "OV042",
+            IssueCode::ConstraintViolated => "OV043",
+            IssueCode::DuplicateValue => "OV044",
+            IssueCode::DuplicateKey => "OV045",
+            IssueCode::ExcelSerialDate => "OV046",
+            IssueCode::AmbiguousDate => "OV047",
+            IssueCode::UnknownTerm => "OV048",
+            IssueCode::ObsoleteTerm => "OV049",
+            IssueCode::SizeMismatch => "OV050",
+            IssueCode::HashMismatch => "OV051",
+            IssueCode::InvalidWell => "OV052",
+            IssueCode::Plugin => "OV060",
+            IssueCode::TooFewReplicates => "OV070",
+            IssueCode::MalformedSampleName => "OV071",
+            IssueCode::SampleNotInAllLayers => "OV072",
+            IssueCode::MissingRate => "OV073",
+            IssueCode::Outlier => "OV074",
+            IssueCode::InvalidDialect => "OV075",
+            IssueCode::NotTidy => "OV076",
+            IssueCode::NonPortablePath => "OV077",
+            IssueCode::MixedLineEndings => "OV078",
+            IssueCode::StrayCarriageReturn => "OV079",
+            IssueCode::TransposedMatrix => "OV080",
+            IssueCode::ExcelArtifact => "OV081",
+            IssueCode::NotAllowed => "OV082",
+            IssueCode::ForeignKeyViolation => "OV083",
+            IssueCode::ScaleMismatch => "OV084",
+            IssueCode::MissingBatch => "OV085",
+            IssueCode::ConfoundedBatch => "OV086",
+            IssueCode::WrongCompartment => "OV087",
+            IssueCode::EnzymeNotInReaction => "OV088",
+            IssueCode::Unphysiological => "OV089",
+            IssueCode::NotAnExchange => "OV090",
+        }
+    }
 
-#[derive(Debug)]
+    /// Find a code by its identifier (`OV020`) or its name (`missing-fastq`).
+    pub fn lookup(code: &str) -> Option<Self> {
+        IssueCode::iter().find(|c| c.id() == code || c.to_string() == code)
+    }
+}
+
+/// A single problem found in a record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub code: IssueCode,
+    /// column concerned, if any
+    pub field: Option<String>,
+    /// offending value, if any
+    pub value: Option<String>,
+    pub detail: Option<String>,
+}
+
+impl ValidationIssue {
+    pub fn new(code: IssueCode) -> Self {
+        ValidationIssue {
+            code,
+            field: None,
+            value: None,
+            detail: None,
+        }
+    }
+    pub fn field(mut self, field: impl Into<String>) -> Self {
+        self.field = Some(field.into());
+        self
+    }
+    pub fn value(mut self, value: impl Into<String>) -> Self {
+        self.value = Some(value.into());
+        self
+    }
+    pub fn detail(mut self, detail: impl fmt::Display) -> Self {
+        self.detail = Some(detail.to_string());
+        self
+    }
+}
+
+/// Issues found in a line of the input.
+#[derive(Debug, Clone)]
 pub struct LineError {
     pub line: usize,
-    pub msg: String,
+    pub issues: Vec<ValidationIssue>,
+    pub severity: Severity,
+}
+
+impl LineError {
+    pub fn error(line: usize, issue: ValidationIssue) -> Self {
+        LineError {
+            line,
+            issues: vec![issue],
+            severity: Severity::Error,
+        }
+    }
+
+    pub fn warning(line: usize, issue: ValidationIssue) -> Self {
+        LineError {
+            line,
+            issues: vec![issue],
+            severity: Severity::Warning,
+        }
+    }
+}
+
+/// Convert the errors of the validator crate into issues. The custom
+/// validations raise errors whose code is an [`IssueCode`]; the built-in ones
+/// (regex, length...) get the code given by `builtin_code` for the field.
+fn validation_issues(
+    errors: ValidationErrors,
+    builtin_code: fn(&str) -> IssueCode,
+) -> Vec<ValidationIssue> {
+    let param = |e: &ValidationError, name: &str| {
+        e.params
+            .get(name)
+            .map(|v| v.as_str().map_or_else(|| v.to_string(), String::from))
+    };
+    let mut issues: Vec<ValidationIssue> = errors
+        .into_errors()
+        .into_iter()
+        .flat_map(|(field, kind)| match kind {
+            ValidationErrorsKind::Field(errors) => errors
+                .iter()
+                .map(|e| ValidationIssue {
+                    code: e.code.parse().unwrap_or_else(|_| builtin_code(field)),
+                    field: (field != "__all__").then(|| field.to_string()),
+                    value: param(e, "value"),
+                    detail: param(e, "detail"),
+                })
+                .collect(),
+            _ => Vec::new(),
+        })
+        .collect();
+    // the errors come from a map, sort them for a deterministic output
+    issues.sort_by(|a, b| a.field.cmp(&b.field));
+    issues
+}
+
+/// Issue of a record that could not be read.
+pub(crate) fn parse_issue(e: &csv::Error) -> ValidationIssue {
+    match e.kind() {
+        // the line is reported on its own, records deserialized one by one
+        // have no position anyway
+        ErrorKind::Deserialize { ref err, .. } => {
+            ValidationIssue::new(IssueCode::Parse).detail(err)
+        }
+        _ => ValidationIssue::new(IssueCode::Parse).detail(e),
+    }
+}
+
+/// Which data rows of a file are validated.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum RowSelection {
+    #[default]
+    All,
+    /// only the first N rows
+    Head(usize),
+    /// N rows sampled uniformly
+    Sample(usize),
+}
+
+/// How a file is read before its records are validated.
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    pub rows: RowSelection,
+    /// ignore the columns that are not part of the format instead of reporting them
+    pub allow_extra_columns: bool,
+    /// header synonyms: alternative name -> expected field name
+    pub rename: HashMap<String, String>,
+    pub fastq: FastqOptions,
+    pub counts: CountsOptions,
+    pub proteomics: ProteomicsOptions,
+    pub features: FeatureOptions,
+    /// format of the plates of the `well` column
+    pub plate: PlateFormat,
+    /// ontologies of the columns checked by the table schemas
+    pub ontologies: Ontologies,
+    /// lists of the values allowed in some columns
+    pub allowed: AllowedLists,
+    /// read the values as 28-digit decimals instead of 64-bit floats
+    pub exact_decimal: bool,
+    /// check that the RNA sheets can be read by the modulome-workflow
+    pub imodulon: bool,
+    pub replicates: ReplicateOptions,
+    /// report the batches confounded with a condition
+    pub check_batches: bool,
+    /// naming convention of the sample names
+    pub naming: Option<Grammar>,
+    pub missing: MissingOptions,
+    /// robust z-score above which a value is reported, not checked if `None`
+    pub outlier_threshold: Option<f64>,
+    /// check that the tidy formats have one measurement per row
+    pub check_tidy: bool,
+    /// composite keys whose values must be unique across the rows
+    pub unique_keys: Vec<Vec<String>>,
+    /// scale the values of each format are declared on, by format name
+    pub scales: BTreeMap<String, Scale>,
+    /// rewriting of the identifiers before the validation
+    pub rewrite: Option<rewrite::Rules>,
+    /// timeouts and retries of the remote checks and the online lookups
+    pub network: NetworkOptions,
+    /// with `--output jsonl`, the issues written as they are found
+    pub stream: jsonl::Stream,
+    /// with `--bench`, the measurements of the validation
+    pub bench: Bench,
+}
+
+impl ReadOptions {
+    /// The options of a part of the input (archive member, file of a batch,
+    /// resource of a package), whose streamed issues are tagged with `part`.
+    pub(crate) fn part(&self, part: String) -> Cow<'_, ReadOptions> {
+        if !self.stream.enabled() {
+            return Cow::Borrowed(self);
+        }
+        Cow::Owned(ReadOptions {
+            stream: self.stream.part(part),
+            ..self.clone()
+        })
+    }
+
+    /// Replace the header synonyms by the expected field names.
+    pub(crate) fn rename_headers<R: std::io::Read>(&self, rdr: &mut csv::Reader<R>) {
+        if self.rename.is_empty() {
+            return;
+        }
+        if let Ok(headers) = rdr.headers() {
+            let renamed: csv::StringRecord = headers
+                .iter()
+                .map(|h| self.rename.get(h).map_or(h, String::as_str))
+                .collect();
+            rdr.set_headers(renamed);
+        }
+    }
+}
+
+/// Report the header columns that are not part of the format.
+pub(crate) fn unknown_columns(headers: &csv::StringRecord, columns: &[&str]) -> Option<LineError> {
+    let issues: Vec<ValidationIssue> = headers
+        .iter()
+        .filter(|h| !columns.contains(h))
+        .map(|h| ValidationIssue::new(IssueCode::UnknownColumn).value(h))
+        .collect();
+    (!issues.is_empty()).then_some(LineError {
+        line: 1,
+        issues,
+        severity: Severity::Error,
+    })
+}
+
+/// Errors and warnings of a line, if any.
+fn line_errors(
+    line: usize,
+    issues: Vec<ValidationIssue>,
+    warnings: Vec<ValidationIssue>,
+) -> Vec<LineError> {
+    [(issues, Severity::Error), (warnings, Severity::Warning)]
+        .into_iter()
+        .filter(|(issues, _)| !issues.is_empty())
+        .map(|(issues, severity)| LineError {
+            line,
+            issues,
+            severity,
+        })
+        .collect()
+}
+
+/// Fixed seed so that sampling the same file always validates the same rows.
+const SAMPLE_SEED: u64 = 0x0a11_da7a;
+
+impl RowSelection {
+    pub(crate) fn select<'a, T: 'a>(
+        self,
+        rows: impl Iterator<Item = (usize, T)> + 'a,
+    ) -> Box<dyn Iterator<Item = (usize, T)> + 'a> {
+        match self {
+            RowSelection::All => Box::new(rows),
+            RowSelection::Head(n) => Box::new(rows.take(n)),
+            RowSelection::Sample(n) => Box::new(reservoir_sample(rows, n).into_iter()),
+        }
+    }
+}
+
+/// Reservoir sampling (algorithm R) of `n` rows, returned in file order.
+fn reservoir_sample<T>(rows: impl Iterator<Item = (usize, T)>, n: usize) -> Vec<(usize, T)> {
+    let mut rng = StdRng::seed_from_u64(SAMPLE_SEED);
+    // `n` may be far more than the rows, the sample grows with the rows read
+    let mut sample = Vec::new();
+    for (seen, row) in rows.enumerate() {
+        if seen < n {
+            sample.push(row);
+        } else {
+            let j = rng.gen_range(0..=seen);
+            if j < n {
+                sample[j] = row;
+            }
+        }
+    }
+    sample.sort_by_key(|(i, _)| *i);
+    sample
 }
 
 pub trait OmicsValidator: Validate + for<'de> Deserialize<'de> {
     fn validate_omics<R: std::io::Read>(file: R) -> Vec<LineError> {
+        Self::validate_rows(file, &ReadOptions::default())
+    }
+    fn validate_rows<R: std::io::Read>(file: R, options: &ReadOptions) -> Vec<LineError> {
         let mut rdr = ReaderBuilder::new()
-            .flexible(Self::flexible())
+            .flexible(Self::flexible() || options.allow_extra_columns)
             .has_headers(Self::has_headers())
             .delimiter(Self::delimiter())
             .from_reader(file);
         let off = if Self::has_headers() { 2 } else { 1 };
-        rdr.deserialize()
-            .enumerate()
-            .filter_map(|(i, result): (usize, Result<Self, _>)| match result {
-                Ok(record) => match record.validate() {
-                    Err(e) => Some(LineError {
-                        line: i + off,
-                        msg: Self::handle_error(e.into_errors()),
-                    }),
-                    _ => None,
-                },
-                Err(e) => match *e.kind() {
-                    ErrorKind::Deserialize {
-                        pos: Some(ref _pos),
-                        ref err,
-                    } => Some(LineError {
-                        line: i + off,
-                        msg: format!("{}", err),
-                    }),
-                    _ => Some(LineError {
-                        line: i + off,
-                        msg: e.to_string(),
-                    }),
-                },
-            })
-            .collect()
+        if Self::has_headers() {
+            options.rename_headers(&mut rdr);
+        }
+        let header_error = match (Self::columns(), rdr.headers()) {
+            (Some(columns), Ok(headers)) if !options.allow_extra_columns => {
+                unknown_columns(headers, columns)
+            }
+            _ => None,
+        };
+        let headers = rdr.headers().ok().cloned().filter(|_| Self::has_headers());
+        let well = wells::column(headers.as_ref());
+        let id_column = excel::column(headers.as_ref());
+        let allowed = options.allowed.columns(headers.as_ref());
+        let numeric = numbers::columns(headers.as_ref(), Self::numeric_columns());
+        let mut remote_checks = RemoteChecks::default();
+        let row_errors = options
+            .rows
+            .select(options.bench.records(rdr.records()).enumerate())
+            .flat_map(|(i, result)| {
+                let record = match result {
+                    Ok(record) => record,
+                    Err(e) => return vec![LineError::error(i + off, parse_issue(&e))],
+                };
+                let mut issues = Vec::new();
+                let mut warnings = Vec::new();
+                match options.bench.time(Phase::Parsing, || {
+                    record.deserialize::<Self>(headers.as_ref())
+                }) {
+                    Ok(parsed) => {
+                        if let Err(e) = options.bench.time(Phase::Validation, || parsed.validate())
+                        {
+                            issues = validation_issues(e, Self::builtin_code);
+                        }
+                        issues.extend(
+                            options
+                                .bench
+                                .time(Phase::Validation, || parsed.issues(options)),
+                        );
+                        issues.sort_by(|a, b| a.field.cmp(&b.field));
+                        warnings = parsed.warnings(options);
+                        if issues.is_empty() {
+                            remote_checks.push(i + off, parsed.remote_checks(options));
+                        }
+                    }
+                    Err(e) => issues.push(parse_issue(&e)),
+                }
+                issues.extend(wells::issue(&record, well, options.plate));
+                excel::replace(&mut issues, excel::issue(&record, id_column.as_ref()));
+                issues.extend(options.allowed.issues(&record, &allowed));
+                let (errors, lost) = numbers::issues(
+                    &record,
+                    headers.as_ref(),
+                    numeric.as_deref(),
+                    options.exact_decimal,
+                );
+                issues.extend(errors);
+                warnings.extend(lost);
+                line_errors(i + off, issues, warnings)
+            });
+        // streamed as the rows are read with `--output jsonl`
+        let mut error_vec: Vec<LineError> = header_error
+            .into_iter()
+            .chain(row_errors)
+            .inspect(|e| options.stream.write(e))
+            .collect();
+        let failed = remote_checks.run(options.network.jobs);
+        if !failed.is_empty() {
+            options.stream.write_all(&failed);
+            error_vec.extend(failed);
+            error_vec.sort_by_key(|e| e.line);
+        }
+        error_vec
+    }
+    /// Checks of a record depending on the options of the run, on top of the
+    /// validations of its fields.
+    fn issues(&self, _options: &ReadOptions) -> Vec<ValidationIssue> {
+        Vec::new()
+    }
+    /// Non-fatal findings for a record, called after it has been validated.
+    fn warnings(&self, _options: &ReadOptions) -> Vec<ValidationIssue> {
+        Vec::new()
+    }
+    /// Checks of a valid record waiting on the network, run concurrently for
+    /// the whole file once its rows are read.
+    fn remote_checks(&self, _options: &ReadOptions) -> Vec<Box<dyn RemoteCheck>> {
+        Vec::new()
     }
     fn has_headers() -> bool {
         true
@@ -63,7 +555,18 @@ pub trait OmicsValidator: Validate + for<'de> Deserialize<'de> {
     fn flexible() -> bool {
         true
     }
-    fn handle_error(errors: HashMap<&'static str, ValidationErrorsKind>) -> String;
+    /// Columns of the format, the header may not contain others unless
+    /// extra columns are allowed. `None` if any column is accepted.
+    fn columns() -> Option<&'static [&'static str]> {
+        None
+    }
+    /// Columns whose values are checked for overflow and lost digits, see
+    /// [`numbers::check`]. Every column after the first without header.
+    fn numeric_columns() -> &'static [&'static str] {
+        &["value"]
+    }
+    /// Code of the issues raised by the built-in validations of `field`.
+    fn builtin_code(field: &str) -> IssueCode;
     fn delimiter() -> u8 {
         b','
     }
@@ -72,37 +575,82 @@ pub trait OmicsValidator: Validate + for<'de> Deserialize<'de> {
 pub trait OmicsModelValidator<'v, T: 'v>:
     ValidateArgs<'v, Args = &'v T> + for<'de> Deserialize<'de>
 {
-    fn validate_omics<R: std::io::Read>(file: R, args: &'v T) -> Vec<LineError> {
+    fn validate_rows<R: std::io::Read>(
+        file: R,
+        args: &'v T,
+        options: &ReadOptions,
+    ) -> Vec<LineError> {
         let mut rdr = ReaderBuilder::new()
-            .flexible(Self::flexible())
+            .flexible(Self::flexible() || options.allow_extra_columns)
             .has_headers(Self::has_headers())
             .from_reader(file);
 
         let off = if Self::has_headers() { 2 } else { 1 };
-        rdr.deserialize()
-            .enumerate()
-            .filter_map(|(i, result): (usize, Result<Self, _>)| match result {
-                Ok(record) => match record.validate_args(args) {
-                    Err(e) => Some(LineError {
-                        line: i + off,
-                        msg: Self::handle_error(e.into_errors()),
-                    }),
-                    _ => None,
-                },
-                Err(e) => match *e.kind() {
-                    ErrorKind::Deserialize {
-                        pos: Some(ref _pos),
-                        ref err,
-                    } => Some(LineError {
-                        line: i + off,
-                        msg: format!("{}", err),
-                    }),
-                    _ => Some(LineError {
-                        line: i + off,
-                        msg: e.to_string(),
-                    }),
-                },
-            })
+        if Self::has_headers() {
+            options.rename_headers(&mut rdr);
+        }
+        let header_error = match (Self::columns(), rdr.headers()) {
+            (Some(columns), Ok(headers)) if !options.allow_extra_columns => {
+                unknown_columns(headers, columns)
+            }
+            _ => None,
+        };
+        let headers = rdr.headers().ok().cloned().filter(|_| Self::has_headers());
+        let well = wells::column(headers.as_ref());
+        let id_column = excel::column(headers.as_ref());
+        let allowed = options.allowed.columns(headers.as_ref());
+        let numeric = numbers::columns(headers.as_ref(), Self::numeric_columns());
+        let row_errors = options
+            .rows
+            .select(options.bench.records(rdr.records()).enumerate())
+            .flat_map(|(i, result)| {
+                let record = match result {
+                    Ok(record) => record,
+                    Err(e) => return vec![LineError::error(i + off, parse_issue(&e))],
+                };
+                let mut issues = Vec::new();
+                let mut warnings = Vec::new();
+                match options.bench.time(Phase::Parsing, || {
+                    record.deserialize::<Self>(headers.as_ref())
+                }) {
+                    Ok(parsed) => {
+                        if let Err(e) = options
+                            .bench
+                            .time(Phase::Validation, || parsed.validate_args(args))
+                        {
+                            issues = validation_issues(e, Self::builtin_code);
+                        }
+                        let checked = options
+                            .bench
+                            .time(Phase::Validation, || parsed.issues(args, options));
+                        issues.retain(|issue| checked.iter().all(|c| c.field != issue.field));
+                        issues.extend(checked);
+                        issues.sort_by(|a, b| a.field.cmp(&b.field));
+                        if issues.is_empty() {
+                            issues = parsed.model_issues(args);
+                        }
+                        warnings = parsed.warnings();
+                    }
+                    Err(e) => issues.push(parse_issue(&e)),
+                }
+                issues.extend(wells::issue(&record, well, options.plate));
+                excel::replace(&mut issues, excel::issue(&record, id_column.as_ref()));
+                issues.extend(options.allowed.issues(&record, &allowed));
+                let (errors, lost) = numbers::issues(
+                    &record,
+                    headers.as_ref(),
+                    numeric.as_deref(),
+                    options.exact_decimal,
+                );
+                issues.extend(errors);
+                warnings.extend(lost);
+                line_errors(i + off, issues, warnings)
+            });
+        // streamed as the rows are read with `--output jsonl`
+        header_error
+            .into_iter()
+            .chain(row_errors)
+            .inspect(|e| options.stream.write(e))
             .collect()
     }
     fn has_headers() -> bool {
@@ -111,7 +659,31 @@ pub trait OmicsModelValidator<'v, T: 'v>:
     fn flexible() -> bool {
         true
     }
-    fn handle_error(errors: HashMap<&'static str, ValidationErrorsKind>) -> String;
+    /// Columns of the format, see [`OmicsValidator::columns`].
+    fn columns() -> Option<&'static [&'static str]> {
+        None
+    }
+    /// Numeric columns, see [`OmicsValidator::numeric_columns`].
+    fn numeric_columns() -> &'static [&'static str] {
+        &["value"]
+    }
+    /// Checks of a record depending on the options of the run, see
+    /// [`OmicsValidator::issues`]. They replace the issues of the built-in
+    /// validations of the same field.
+    fn issues(&self, _args: &'v T, _options: &ReadOptions) -> Vec<ValidationIssue> {
+        Vec::new()
+    }
+    /// Checks of a valid record against the models involving several of its
+    /// fields.
+    fn model_issues(&self, _args: &'v T) -> Vec<ValidationIssue> {
+        Vec::new()
+    }
+    /// Non-fatal findings for a record, see [`OmicsValidator::warnings`].
+    fn warnings(&self) -> Vec<ValidationIssue> {
+        Vec::new()
+    }
+    /// Code of the issues raised by the built-in validations of `field`.
+    fn builtin_code(field: &str) -> IssueCode;
 }
 
 /// Protein record without header in the form:
@@ -132,23 +704,101 @@ pub trait OmicsModelValidator<'v, T: 'v>:
 /// ```
 #[derive(Debug, Deserialize, Validate)]
 pub struct ProtRecord {
-    #[validate(regex(path = "RE_UNIPROT", message = "invalid Uniprot ID %s",))]
     uniprot: String,
     #[allow(dead_code)]
-    values: Vec<f32>,
+    values: Vec<f64>,
+}
+
+/// Check a Uniprot ID: contaminants and decoys are handled according to
+/// `--decoys`, the others must be well-formed and exist if a database was
+/// loaded with `--check-uniprot`.
+/// With `--allow-groups`, each member of a protein group is checked and the
+/// first malformed one is reported.
+fn validate_uniprot(uniprot: &str, options: &ProteomicsOptions) -> Option<ValidationIssue> {
+    let members = options.members(uniprot);
+    members.iter().enumerate().find_map(|(i, member)| {
+        let (code, detail) = check_accession(member, options).err()?;
+        let group =
+            (members.len() > 1).then(|| format!("member {} of the group {}", i + 1, uniprot));
+        let issue = ValidationIssue::new(code).field("uniprot").value(*member);
+        Some(
+            match [detail, group]
+                .into_iter()
+                .flatten()
+                .reduce(|a, b| a + ", " + &b)
+            {
+                Some(detail) => issue.detail(detail),
+                None => issue,
+            },
+        )
+    })
+}
+
+/// Code and detail of the issue of a single accession, if any.
+fn check_accession(
+    accession: &str,
+    options: &ProteomicsOptions,
+) -> Result<(), (IssueCode, Option<String>)> {
+    if let Some(prefix) = proteomics::decoy_prefix(accession) {
+        return match options.decoys {
+            DecoyPolicy::Error => Err((IssueCode::DecoyEntry, Some(format!("{} entry", prefix)))),
+            _ => Ok(()),
+        };
+    }
+    let accession = match uniprot::parse(accession) {
+        Ok(accession) => accession,
+        Err(e) => return Err((IssueCode::InvalidUniprotId, Some(e.to_string()))),
+    };
+    if options.isoforms == Some(false) && accession.isoform.is_some() {
+        Err((
+            IssueCode::InvalidUniprotId,
+            Some("isoforms are rejected by the strict profile".to_string()),
+        ))
+    } else if options
+        .accessions
+        .as_ref()
+        .is_some_and(|index| !index.uniprot_exists(accession.entry))
+    {
+        Err((IssueCode::UnknownUniprotId, None))
+    } else {
+        Ok(())
+    }
+}
+
+/// Contaminants and decoys reported as warnings with `--decoys warn`.
+fn decoy_warnings(uniprot: &str, options: &ProteomicsOptions) -> Vec<ValidationIssue> {
+    if options.decoys != DecoyPolicy::Warn {
+        return Vec::new();
+    }
+    options
+        .members(uniprot)
+        .into_iter()
+        .find_map(|member| Some((member, proteomics::decoy_prefix(member)?)))
+        .map(|(member, prefix)| {
+            ValidationIssue::new(IssueCode::DecoyEntry)
+                .field("uniprot")
+                .value(member)
+                .detail(format!("{} entry", prefix))
+        })
+        .into_iter()
+        .collect()
 }
 
 impl OmicsValidator for ProtRecord {
-    fn handle_error(errors: HashMap<&'static str, ValidationErrorsKind>) -> String {
-        if let Some(validator::ValidationErrorsKind::Field(v)) = errors.get("uniprot") {
-            format!(
-                "{} invalid Uniprot ID",
-                v[0].params.get("value").unwrap().as_str().unwrap()
-            )
-        } else {
-            String::from("Maybe wrong numbers?")
+    fn builtin_code(field: &str) -> IssueCode {
+        match field {
+            "uniprot" => IssueCode::InvalidUniprotId,
+            _ => IssueCode::InvalidValue,
         }
     }
+    fn issues(&self, options: &ReadOptions) -> Vec<ValidationIssue> {
+        validate_uniprot(&self.uniprot, &options.proteomics)
+            .into_iter()
+            .collect()
+    }
+    fn warnings(&self, options: &ReadOptions) -> Vec<ValidationIssue> {
+        decoy_warnings(&self.uniprot, &options.proteomics)
+    }
     fn has_headers() -> bool {
         false
     }
@@ -173,28 +823,34 @@ impl OmicsValidator for ProtRecord {
 /// ```
 #[derive(Debug, Deserialize, Validate)]
 pub struct TidyProtRecord {
-    #[validate(regex(path = "RE_UNIPROT", message = "invalid Uniprot ID %s",))]
     uniprot: String,
     #[validate(length(min = 1))]
     sample: String,
     #[allow(dead_code)]
-    value: f32,
+    value: f64,
 }
 
 impl OmicsValidator for TidyProtRecord {
-    fn handle_error(errors: HashMap<&'static str, ValidationErrorsKind>) -> String {
-        if let Some(validator::ValidationErrorsKind::Field(v)) = errors.get("uniprot") {
-            format!(
-                "{} invalid Uniprot ID",
-                v[0].params.get("value").unwrap().as_str().unwrap()
-            )
-        } else {
-            String::from("Empty sample?")
+    fn builtin_code(field: &str) -> IssueCode {
+        match field {
+            "uniprot" => IssueCode::InvalidUniprotId,
+            _ => IssueCode::EmptySample,
         }
     }
+    fn issues(&self, options: &ReadOptions) -> Vec<ValidationIssue> {
+        validate_uniprot(&self.uniprot, &options.proteomics)
+            .into_iter()
+            .collect()
+    }
+    fn warnings(&self, options: &ReadOptions) -> Vec<ValidationIssue> {
+        decoy_warnings(&self.uniprot, &options.proteomics)
+    }
     fn flexible() -> bool {
         false
     }
+    fn columns() -> Option<&'static [&'static str]> {
+        Some(&["uniprot", "sample", "value"])
+    }
 }
 
 /// Metabolite record in tidy form:
@@ -204,7 +860,8 @@ impl OmicsValidator for TidyProtRecord {
 /// BIGG_ID,SAMPLE_NAME,NUMBER_VALUE
 /// ```
 ///
-/// Identifiers that are not in the model will be reported.
+/// Identifiers that are not in any of the models will be reported, and those
+/// that are but not in the compartment of the optional `compartment` column.
 ///
 /// # Example
 ///
@@ -216,43 +873,643 @@ impl OmicsValidator for TidyProtRecord {
 /// ```
 #[derive(Debug, Deserialize, Validate)]
 pub struct TidyMetRecord {
-    #[validate(custom(function = "validate_model_identifier", arg = "&'v_a ModelRaw"))]
+    #[validate(custom(function = "validate_model_identifier", arg = "&'v_a Models"))]
     met_id: String,
     #[validate(length(min = 1))]
     sample: String,
     #[allow(dead_code)]
-    value: f32,
+    value: f64,
+    #[serde(default)]
+    compartment: Option<String>,
 }
 
-fn validate_model_identifier(met_id: &str, arg: &ModelRaw) -> Result<(), ValidationError> {
-    if arg
-        .list_of_species
-        .species
-        .iter()
-        .filter_map(|sp| sp.annotation.as_ref())
-        .flat_map(|annot| annot.into_iter().map(|rs| rs.split('/').last()))
-        .any(|id| id == Some(met_id))
-    {
+fn validate_model_identifier(met_id: &str, arg: &Models) -> Result<(), ValidationError> {
+    if arg.contains(met_id) {
         Ok(())
     } else {
-        Err(ValidationError::new("wrong id!"))
+        Err(ValidationError::new(IssueCode::NotInModel.into()))
+    }
+}
+
+impl<'a> OmicsModelValidator<'a, Models> for TidyMetRecord {
+    fn builtin_code(_field: &str) -> IssueCode {
+        IssueCode::EmptySample
+    }
+    fn flexible() -> bool {
+        false
+    }
+    fn columns() -> Option<&'static [&'static str]> {
+        Some(&["met_id", "sample", "value", "compartment"])
+    }
+    fn model_issues(&self, models: &Models) -> Vec<ValidationIssue> {
+        match self.compartment.as_deref() {
+            Some(compartment) => compartment_issue(&self.met_id, compartment, models)
+                .into_iter()
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// A metabolite of the models that none of their species has in
+/// `compartment`; not checked for the metabolites only in a universal
+/// namespace, nor without compartment.
+fn compartment_issue(met_id: &str, compartment: &str, models: &Models) -> Option<ValidationIssue> {
+    let compartments = models.compartments(met_id);
+    if compartment.is_empty() || compartments.is_empty() || compartments.contains(compartment) {
+        return None;
+    }
+    Some(
+        ValidationIssue::new(IssueCode::WrongCompartment)
+            .field("compartment")
+            .value(met_id)
+            .detail(format!(
+                "not in {}, only in {}",
+                compartment,
+                compartments.into_iter().collect::<Vec<_>>().join(", ")
+            )),
+    )
+}
+
+/// Flux record in tidy form, with the optional bounds (e.g. from flux
+/// variability analysis) or standard deviation (e.g. from flux sampling) of
+/// the value:
+///
+/// ```csv
+/// reaction_id,sample,value,lb,ub,sd
+/// REACTION_ID,SAMPLE_NAME,NUMBER_VALUE,NUMBER_VALUE,NUMBER_VALUE,NUMBER_VALUE
+/// ```
+///
+/// Empty reactions and samples, values outside their bounds and negative
+/// standard deviations will be reported.
+///
+/// # Example
+///
+/// ```csv
+/// reaction_id,sample,value,lb,ub,sd
+/// PGI,SIM1,4.86,4.2,5.5,0.3
+/// PFK,SIM1,7.48,,,0.2
+/// ```
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_flux_bounds"))]
+pub struct TidyFluxRecord {
+    #[validate(length(min = 1))]
+    reaction_id: String,
+    #[validate(length(min = 1))]
+    sample: String,
+    value: f64,
+    lb: Option<f64>,
+    ub: Option<f64>,
+    #[validate(range(min = 0.0))]
+    sd: Option<f64>,
+}
+
+fn validate_flux_bounds(record: &TidyFluxRecord) -> Result<(), ValidationError> {
+    let detail = match (record.lb, record.ub) {
+        (Some(lb), Some(ub)) if lb > ub => format!("lb {} > ub {}", lb, ub),
+        (Some(lb), _) if record.value < lb => format!("value {} < lb {}", record.value, lb),
+        (_, Some(ub)) if record.value > ub => format!("value {} > ub {}", record.value, ub),
+        _ => return Ok(()),
+    };
+    let mut err = ValidationError::new(IssueCode::OutOfBounds.into());
+    err.add_param(Cow::from("value"), &record.reaction_id);
+    err.add_param(Cow::from("detail"), &detail);
+    Err(err)
+}
+
+impl OmicsValidator for TidyFluxRecord {
+    fn builtin_code(field: &str) -> IssueCode {
+        match field {
+            "reaction_id" => IssueCode::EmptyField,
+            "sample" => IssueCode::EmptySample,
+            _ => IssueCode::InvalidValue,
+        }
+    }
+    fn columns() -> Option<&'static [&'static str]> {
+        Some(&["reaction_id", "sample", "value", "lb", "ub", "sd"])
+    }
+    fn numeric_columns() -> &'static [&'static str] {
+        &["value", "lb", "ub", "sd"]
     }
 }
 
-impl<'a> OmicsModelValidator<'a, ModelRaw> for TidyMetRecord {
-    fn handle_error(errors: HashMap<&'static str, ValidationErrorsKind>) -> String {
-        if let Some(validator::ValidationErrorsKind::Field(v)) = errors.get("met_id") {
+/// Protein usage of an enzyme-constrained model (ecModel), e.g. from ecFBA
+/// with GECKO:
+///
+/// ```csv
+/// uniprot,reaction_id,sample,usage
+/// UNIPROT_ID,REACTION_ID,SAMPLE_NAME,NUMBER_VALUE
+/// ```
+///
+/// Proteins without a `prot_` pseudo-metabolite in the models, reactions that
+/// do not consume the pseudo-metabolite of their protein and negative usages
+/// will be reported.
+///
+/// # Example
+///
+/// ```csv
+/// uniprot,reaction_id,sample,usage
+/// P0A6T1,PGI,SIM1,0.0021
+/// P0A796,PFKNo1,SIM1,0.0013
+/// ```
+#[derive(Debug, Deserialize, Validate)]
+pub struct EcUsageRecord {
+    #[validate(custom(function = "validate_enzyme", arg = "&'v_a Models"))]
+    uniprot: String,
+    #[validate(length(min = 1))]
+    reaction_id: String,
+    #[validate(length(min = 1))]
+    sample: String,
+    #[validate(range(min = 0.0))]
+    usage: f64,
+}
+
+fn validate_enzyme(uniprot: &str, arg: &Models) -> Result<(), ValidationError> {
+    if arg.enzyme_reactions(uniprot).is_some() {
+        Ok(())
+    } else {
+        let mut err = ValidationError::new(IssueCode::NotInModel.into());
+        err.add_param(Cow::from("detail"), &"no prot_ pseudo-metabolite");
+        Err(err)
+    }
+}
+
+impl<'a> OmicsModelValidator<'a, Models> for EcUsageRecord {
+    fn builtin_code(field: &str) -> IssueCode {
+        match field {
+            "reaction_id" => IssueCode::EmptyField,
+            "sample" => IssueCode::EmptySample,
+            _ => IssueCode::InvalidValue,
+        }
+    }
+    fn flexible() -> bool {
+        false
+    }
+    fn columns() -> Option<&'static [&'static str]> {
+        Some(&["uniprot", "reaction_id", "sample", "usage"])
+    }
+    fn numeric_columns() -> &'static [&'static str] {
+        &["usage"]
+    }
+    /// An invalid Uniprot ID, reported instead of its absence from the models.
+    fn issues(&self, _models: &Models, options: &ReadOptions) -> Vec<ValidationIssue> {
+        validate_uniprot(&self.uniprot, &options.proteomics)
+            .into_iter()
+            .collect()
+    }
+    /// A reaction that does not consume the pseudo-metabolite of the protein,
+    /// given with or without the `R_` prefix of the SBML ids.
+    fn model_issues(&self, models: &Models) -> Vec<ValidationIssue> {
+        let reactions = models.enzyme_reactions(&self.uniprot).unwrap_or_default();
+        let id = self.reaction_id.as_str();
+        if reactions.contains(id) || reactions.contains(format!("R_{}", id).as_str()) {
+            return Vec::new();
+        }
+        let detail = if reactions.is_empty() {
+            format!("no reaction consumes prot_{}", self.uniprot)
+        } else {
             format!(
-                "{} not in model!",
-                v[0].params.get("value").unwrap().as_str().unwrap()
+                "prot_{} is consumed by {}",
+                self.uniprot,
+                reactions.into_iter().collect::<Vec<_>>().join(", ")
             )
-        } else {
-            String::from("Empty sample?")
+        };
+        vec![ValidationIssue::new(IssueCode::EnzymeNotInReaction)
+            .field("reaction_id")
+            .value(id)
+            .detail(detail)]
+    }
+}
+
+/// Concentrations of the metabolites in cells, in mol/L: from about one
+/// molecule in a bacterial cell to the most abundant metabolites (glutamate).
+const PHYSIOLOGICAL_CONC: (f64, f64) = (1e-9, 0.1);
+/// Highest magnitude of a standard Gibbs free energy of reaction, in kJ/mol;
+/// larger ones are in J/mol, or the placeholders of unknown estimates.
+const DG0_MAX: f64 = 1000.0;
+
+/// Concentration bounds of the metabolites for thermodynamics-based flux
+/// analysis (TMFA), in mol/L:
+///
+/// ```csv
+/// met_id,compartment,conc_lb,conc_ub
+/// METABOLITE_IDENTIFIER,COMPARTMENT,NUMBER_VALUE,NUMBER_VALUE
+/// ```
+///
+/// Identifiers not in the models or not in their compartment, bounds that are
+/// not positive (their logarithm is used) and lower bounds greater than upper
+/// bounds will be reported; bounds outside the physiological range are warned
+/// about.
+///
+/// # Example
+///
+/// ```csv
+/// met_id,compartment,conc_lb,conc_ub
+/// glc__D,c,1e-06,0.02
+/// glc__D,e,1e-06,0.1
+/// ```
+#[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_conc_bounds"))]
+pub struct ThermoConcRecord {
+    #[validate(custom(function = "validate_model_identifier", arg = "&'v_a Models"))]
+    met_id: String,
+    #[validate(length(min = 1))]
+    compartment: String,
+    conc_lb: f64,
+    conc_ub: f64,
+}
+
+fn validate_conc_bounds(record: &ThermoConcRecord) -> Result<(), ValidationError> {
+    let (code, detail) = if record.conc_lb <= 0.0 {
+        (
+            IssueCode::InvalidValue,
+            format!("conc_lb {} is not positive", record.conc_lb),
+        )
+    } else if record.conc_lb > record.conc_ub {
+        (
+            IssueCode::OutOfBounds,
+            format!("conc_lb {} > conc_ub {}", record.conc_lb, record.conc_ub),
+        )
+    } else {
+        return Ok(());
+    };
+    let mut err = ValidationError::new(code.into());
+    err.add_param(Cow::from("value"), &record.met_id);
+    err.add_param(Cow::from("detail"), &detail);
+    Err(err)
+}
+
+impl<'a> OmicsModelValidator<'a, Models> for ThermoConcRecord {
+    fn builtin_code(field: &str) -> IssueCode {
+        match field {
+            "compartment" => IssueCode::EmptyField,
+            _ => IssueCode::InvalidValue,
+        }
+    }
+    fn flexible() -> bool {
+        false
+    }
+    fn columns() -> Option<&'static [&'static str]> {
+        Some(&["met_id", "compartment", "conc_lb", "conc_ub"])
+    }
+    fn numeric_columns() -> &'static [&'static str] {
+        &["conc_lb", "conc_ub"]
+    }
+    fn model_issues(&self, models: &Models) -> Vec<ValidationIssue> {
+        compartment_issue(&self.met_id, &self.compartment, models)
+            .into_iter()
+            .collect()
+    }
+    fn warnings(&self) -> Vec<ValidationIssue> {
+        let (min, max) = PHYSIOLOGICAL_CONC;
+        [("conc_lb", self.conc_lb), ("conc_ub", self.conc_ub)]
+            .into_iter()
+            // bounds that are not positive are errors
+            .filter(|(_, bound)| *bound > 0.0 && (*bound < min || *bound > max))
+            .map(|(field, bound)| {
+                ValidationIssue::new(IssueCode::Unphysiological)
+                    .field(field)
+                    .value(&self.met_id)
+                    .detail(format!(
+                        "{} {:e} mol/L, not between {:e} and {:e}",
+                        field, bound, min, max
+                    ))
+            })
+            .collect()
+    }
+}
+
+/// Standard Gibbs free energies of the reactions for thermodynamics-based
+/// flux analysis (TMFA), e.g. from eQuilibrator, in kJ/mol, with their
+/// optional uncertainty:
+///
+/// ```csv
+/// reaction_id,dg0,dg0_uncertainty
+/// REACTION_ID,NUMBER_VALUE,NUMBER_VALUE
+/// ```
+///
+/// Reactions not in the models (with or without the `R_` prefix) and negative
+/// uncertainties will be reported; energies and uncertainties beyond
+/// 1000 kJ/mol are warned about.
+///
+/// # Example
+///
+/// ```csv
+/// reaction_id,dg0,dg0_uncertainty
+/// PGI,2.6,1.0
+/// PFK,-15.6,1.5
+/// ```
+#[derive(Debug, Deserialize, Validate)]
+pub struct ThermoDgRecord {
+    #[validate(
+        length(min = 1),
+        custom(function = "validate_model_reaction", arg = "&'v_a Models")
+    )]
+    reaction_id: String,
+    dg0: f64,
+    #[validate(range(min = 0.0))]
+    dg0_uncertainty: Option<f64>,
+}
+
+fn validate_model_reaction(reaction_id: &str, arg: &Models) -> Result<(), ValidationError> {
+    if reaction_id.is_empty() || arg.contains_reaction(reaction_id) != Some(false) {
+        Ok(())
+    } else {
+        let mut err = ValidationError::new(IssueCode::NotInModel.into());
+        err.add_param(Cow::from("value"), &reaction_id);
+        Err(err)
+    }
+}
+
+impl<'a> OmicsModelValidator<'a, Models> for ThermoDgRecord {
+    fn builtin_code(field: &str) -> IssueCode {
+        match field {
+            "reaction_id" => IssueCode::EmptyField,
+            _ => IssueCode::InvalidValue,
         }
     }
     fn flexible() -> bool {
         false
     }
+    fn columns() -> Option<&'static [&'static str]> {
+        Some(&["reaction_id", "dg0", "dg0_uncertainty"])
+    }
+    fn numeric_columns() -> &'static [&'static str] {
+        &["dg0", "dg0_uncertainty"]
+    }
+    fn warnings(&self) -> Vec<ValidationIssue> {
+        [
+            ("dg0", Some(self.dg0)),
+            ("dg0_uncertainty", self.dg0_uncertainty),
+        ]
+        .into_iter()
+        .filter_map(|(field, value)| Some((field, value?)))
+        .filter(|(_, value)| value.abs() > DG0_MAX)
+        .map(|(field, value)| {
+            ValidationIssue::new(IssueCode::Unphysiological)
+                .field(field)
+                .value(&self.reaction_id)
+                .detail(format!(
+                    "{} {} kJ/mol, beyond {} kJ/mol",
+                    field, value, DG0_MAX
+                ))
+        })
+        .collect()
+    }
+}
+
+/// Units of the concentrations of a medium, uptake rates included.
+pub(crate) const MEDIUM_UNITS: [&str; 8] =
+    ["M", "mM", "uM", "µM", "nM", "g/L", "mg/L", "mmol/gDW/h"];
+
+/// Component of a growth medium, given by its exchange reaction or the
+/// metabolite it exchanges:
+///
+/// ```csv
+/// exchange_reaction_or_met_id,concentration,unit
+/// EXCHANGE_REACTION_OR_METABOLITE_IDENTIFIER,NUMBER_VALUE,UNIT
+/// ```
+///
+/// Identifiers that are not exchange reactions of the models nor their
+/// metabolites (a typo in `EX_o2_e` silently makes a simulation anaerobic),
+/// negative concentrations and unknown units will be reported.
+///
+/// # Example
+///
+/// ```csv
+/// exchange_reaction_or_met_id,concentration,unit
+/// EX_glc__D_e,10,mM
+/// EX_o2_e,20,mmol/gDW/h
+/// nh4_e,18.7,mM
+/// ```
+#[derive(Debug, Deserialize, Validate)]
+pub struct MediumRecord {
+    #[validate(custom(function = "validate_exchange", arg = "&'v_a Models"))]
+    exchange_reaction_or_met_id: String,
+    #[validate(range(min = 0.0))]
+    concentration: f64,
+    #[validate(custom = "validate_medium_unit")]
+    unit: String,
+}
+
+fn validate_exchange(id: &str, arg: &Models) -> Result<(), ValidationError> {
+    if arg.is_exchange(id) != Some(false) {
+        return Ok(());
+    }
+    let mut err = ValidationError::new(IssueCode::NotAnExchange.into());
+    err.add_param(Cow::from("value"), &id);
+    if arg.contains_reaction(id) == Some(true) {
+        err.add_param(
+            Cow::from("detail"),
+            &"a reaction of the model, but not an exchange",
+        );
+    }
+    Err(err)
+}
+
+fn validate_medium_unit(unit: &str) -> Result<(), ValidationError> {
+    if MEDIUM_UNITS.contains(&unit) {
+        return Ok(());
+    }
+    let mut err = ValidationError::new(IssueCode::InvalidValue.into());
+    err.add_param(Cow::from("value"), &unit);
+    err.add_param(
+        Cow::from("detail"),
+        &format!("expected one of {}", MEDIUM_UNITS.join(", ")),
+    );
+    Err(err)
+}
+
+impl<'a> OmicsModelValidator<'a, Models> for MediumRecord {
+    fn builtin_code(_field: &str) -> IssueCode {
+        IssueCode::InvalidValue
+    }
+    fn flexible() -> bool {
+        false
+    }
+    fn columns() -> Option<&'static [&'static str]> {
+        Some(&["exchange_reaction_or_met_id", "concentration", "unit"])
+    }
+    fn numeric_columns() -> &'static [&'static str] {
+        &["concentration"]
+    }
+}
+
+/// Controlled vocabulary of the modifications of a strain design: knock-out,
+/// overexpression and down-regulation.
+pub(crate) const MODIFICATIONS: [&str; 3] = ["KO", "OE", "down"];
+
+/// Modification of a gene or reaction of the model in a strain design, as
+/// tracked in design-build-test cycles:
+///
+/// ```csv
+/// strain,gene_or_reaction_id,modification
+/// STRAIN_NAME,GENE_OR_REACTION_IDENTIFIER,KO|OE|down
+/// ```
+///
+/// Identifiers that are neither gene products nor reactions of the models,
+/// modifications out of the vocabulary and empty strains will be reported.
+///
+/// # Example
+///
+/// ```csv
+/// strain,gene_or_reaction_id,modification
+/// CLAU_001,CAETHG_RS09090,KO
+/// CLAU_001,rxn00225_c0,down
+/// ```
+#[derive(Debug, Deserialize, Validate)]
+pub struct StrainDesignRecord {
+    #[validate(length(min = 1))]
+    strain: String,
+    #[validate(custom(function = "validate_design_target", arg = "&'v_a Models"))]
+    gene_or_reaction_id: String,
+    #[validate(custom = "validate_modification")]
+    modification: String,
+}
+
+fn validate_design_target(id: &str, arg: &Models) -> Result<(), ValidationError> {
+    let found = [arg.names_gene_product(id), arg.contains_reaction(id)];
+    // not checked against models without genes nor reactions
+    if found.contains(&Some(true)) || found == [None, None] {
+        return Ok(());
+    }
+    let mut err = ValidationError::new(IssueCode::NotInModel.into());
+    err.add_param(Cow::from("value"), &id);
+    err.add_param(
+        Cow::from("detail"),
+        &"neither a gene product nor a reaction",
+    );
+    Err(err)
+}
+
+fn validate_modification(modification: &str) -> Result<(), ValidationError> {
+    if MODIFICATIONS.contains(&modification) {
+        return Ok(());
+    }
+    let mut err = ValidationError::new(IssueCode::InvalidValue.into());
+    err.add_param(Cow::from("value"), &modification);
+    err.add_param(
+        Cow::from("detail"),
+        &format!("expected one of {}", MODIFICATIONS.join(", ")),
+    );
+    Err(err)
+}
+
+impl<'a> OmicsModelValidator<'a, Models> for StrainDesignRecord {
+    fn builtin_code(_field: &str) -> IssueCode {
+        IssueCode::EmptyField
+    }
+    fn flexible() -> bool {
+        false
+    }
+    fn columns() -> Option<&'static [&'static str]> {
+        Some(&["strain", "gene_or_reaction_id", "modification"])
+    }
+    fn numeric_columns() -> &'static [&'static str] {
+        &[]
+    }
+}
+
+/// Integrated multi-omics record in long form, each row being validated as
+/// the tidy record of its `omics_type`:
+///
+/// ```csv
+/// omics_type,id,sample,value
+/// prot|met|gene|flux,IDENTIFIER,SAMPLE_NAME,NUMBER_VALUE
+/// ```
+///
+/// `prot` identifiers must be Uniprot IDs and `met` identifiers must be in
+/// the models. `gene` and `flux` rows only need an identifier and a sample.
+///
+/// # Example
+///
+/// ```csv
+/// omics_type,id,sample,value
+/// prot,Q00496,SIM1,100001
+/// met,glc__D,SIM1,2
+/// gene,b0001,SIM1,12.5
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct MultiRecord {
+    omics_type: OmicsType,
+    id: String,
+    sample: String,
+    value: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OmicsType {
+    Prot,
+    Met,
+    Gene,
+    Flux,
+}
+
+impl<'v_a> ValidateArgs<'v_a> for MultiRecord {
+    type Args = &'v_a Models;
+
+    fn validate_args(&self, models: Self::Args) -> Result<(), ValidationErrors> {
+        match self.omics_type {
+            OmicsType::Prot => TidyProtRecord {
+                uniprot: self.id.clone(),
+                sample: self.sample.clone(),
+                value: self.value,
+            }
+            .validate(),
+            OmicsType::Met => TidyMetRecord {
+                met_id: self.id.clone(),
+                sample: self.sample.clone(),
+                value: self.value,
+                compartment: None,
+            }
+            .validate_args(models),
+            OmicsType::Gene | OmicsType::Flux => {
+                let mut errors = ValidationErrors::new();
+                if self.id.is_empty() {
+                    errors.add("id", ValidationError::new("length"));
+                } else if let (OmicsType::Gene, Some(false)) =
+                    (&self.omics_type, models.contains_gene(&self.id))
+                {
+                    let mut error = ValidationError::new(IssueCode::NotInAnnotation.into());
+                    error.add_param("value".into(), &self.id);
+                    errors.add("id", error);
+                }
+                if self.sample.is_empty() {
+                    errors.add("sample", ValidationError::new("length"));
+                }
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(errors)
+                }
+            }
+        }
+    }
+}
+
+impl<'a> OmicsModelValidator<'a, Models> for MultiRecord {
+    fn builtin_code(field: &str) -> IssueCode {
+        match field {
+            "uniprot" => IssueCode::InvalidUniprotId,
+            "sample" => IssueCode::EmptySample,
+            _ => IssueCode::EmptyField,
+        }
+    }
+    fn flexible() -> bool {
+        false
+    }
+    fn columns() -> Option<&'static [&'static str]> {
+        Some(&["omics_type", "id", "sample", "value"])
+    }
+    fn issues(&self, _models: &Models, options: &ReadOptions) -> Vec<ValidationIssue> {
+        match self.omics_type {
+            OmicsType::Prot => validate_uniprot(&self.id, &options.proteomics)
+                .into_iter()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
 }
 
 /// RNA files for iModulon. These are experiments from SRA or local files.
@@ -277,45 +1534,109 @@ impl<'a> OmicsModelValidator<'a, ModelRaw> for TidyMetRecord {
 #[validate(schema(function = "validate_rna_category"))]
 pub struct RnaRecord {
     #[validate(length(min = 1))]
-    experiment: String,
+    pub(crate) experiment: String,
     /// will be mathced with R1 and R2 if local
     library_layout: LibraryLayout,
-    /// we do not really care about the platform but it is nice to show usual values
-    #[allow(dead_code)]
+    /// cross-checked against the FASTQ files, if local
     platform: Platform,
     #[validate(length(min = 1))]
     run: Option<String>,
-    #[validate(custom(function = "validate_fastq"))]
-    r1: Option<PathBuf>,
-    #[validate(custom(function = "validate_fastq"))]
-    r2: Option<PathBuf>,
+    pub(crate) r1: Option<PathBuf>,
+    pub(crate) r2: Option<PathBuf>,
 }
 
 // Check that the fastq files are OK
 // TODO: it would be extra nice to check that the records correspond to the provided FASTA
-fn validate_fastq(fastq_path: &Path) -> Result<(), ValidationError> {
-    let reader = Reader::from_file(fastq_path)
-        .map_err(|_| ValidationError::new("Declared FASTQ path does not exist!"))?;
-    let records = reader.records();
-    for (i, result) in records.enumerate() {
-        result.map_err(|e| {
-            let mut err = ValidationError::new("Malformed FASTQ");
-            err.add_param(Cow::from("fastq"), &e.to_string());
-            err.add_param(Cow::from("pos"), &(i + 1));
-            err
-        })?;
+fn validate_fastq(fastq_path: &Path, options: &FastqOptions) -> Option<ValidationIssue> {
+    // remote files are requested by the remote checks of the record
+    let result = match fastq_path.to_str() {
+        Some(location) if remote::is_remote(location) => RemoteFile::parse(location)
+            .map(|_| ())
+            .map_err(FastqError::InvalidUrl),
+        _ => fastq::scan(&paths::local(fastq_path), options).map(|_| ()),
+    };
+    let (code, detail) = match result {
+        Ok(_) => return None,
+        Err(FastqError::Missing) => (IssueCode::MissingFastq, paths::missing_reason(fastq_path)),
+        Err(e) => fastq_issue(e),
+    };
+    let issue = ValidationIssue::new(code).value(fastq_path.display().to_string());
+    Some(match detail {
+        Some(detail) => issue.detail(detail),
+        None => issue,
+    })
+}
+
+/// Code and detail of the issue of a FASTQ file.
+fn fastq_issue(e: FastqError) -> (IssueCode, Option<String>) {
+    match e {
+        FastqError::Missing => (IssueCode::MissingFastq, None),
+        FastqError::Malformed {
+            pos,
+            msg,
+            problems,
+            aborted,
+        } => {
+            let mut detail = match pos {
+                Some(pos) => format!("{} in record {}", msg, pos),
+                None => format!("{} in the last records", msg),
+            };
+            if problems > 1 {
+                detail += &format!(" and {} more malformed records", problems - 1);
+            }
+            if aborted {
+                detail += &format!(
+                    "; scan stopped after {} problems, the rest of the file is not checked",
+                    problems
+                );
+            }
+            (IssueCode::MalformedFastq, Some(detail))
+        }
+        FastqError::InvalidUrl(detail) => (IssueCode::InvalidFastqUrl, Some(detail)),
+        FastqError::Unreachable(detail) => (IssueCode::UnreachableFastq, Some(detail)),
+        FastqError::Empty => (IssueCode::EmptyFastq, None),
+        FastqError::Interleaved => (IssueCode::InterleavedFastq, None),
+        FastqError::DuplicateIds { count, example } => (
+            IssueCode::DuplicateReadIds,
+            Some(format!(
+                "{} IDs appear more than once, e.g. {}; were lanes concatenated twice?",
+                count, example
+            )),
+        ),
+    }
+}
+
+/// Request of a remote FASTQ with `--check-remote`.
+struct RemoteFastq {
+    field: &'static str,
+    location: String,
+    network: NetworkOptions,
+}
+
+impl RemoteCheck for RemoteFastq {
+    fn key(&self) -> String {
+        self.location.clone()
+    }
+    fn run(&self) -> Option<ValidationIssue> {
+        let (code, detail) = fastq_issue(fastq::check_remote(&self.location, &self.network).err()?);
+        let issue = ValidationIssue::new(code)
+            .field(self.field)
+            .value(&self.location);
+        Some(match detail {
+            Some(detail) => issue.detail(detail),
+            None => issue,
+        })
     }
-    Ok(())
 }
 
 fn validate_rna_category(record: &RnaRecord) -> Result<(), ValidationError> {
     if record.run.is_none() {
         // we have local data
         return match (&record.library_layout, &record.r1, &record.r2) {
-        // return match (&record.library_layout, &record.r1, &record.r2) {
+            // return match (&record.library_layout, &record.r1, &record.r2) {
             (LibraryLayout::Paired, Some(_), Some(_)) => Ok(()),
             (LibraryLayout::Single, Some(_), None) => Ok(()),
-            _ => Err(ValidationError::new("R1 and R2 did not match the LibraryLayout! (assuming local data since field 'Run' is empty)")),
+            _ => Err(ValidationError::new(IssueCode::LayoutMismatch.into())),
         };
     }
     Ok(())
@@ -328,45 +1649,108 @@ pub enum LibraryLayout {
     Single,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Display)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
 pub enum Platform {
     Illumina,
     Bgiseq,
     AbiSolid,
     PacbioSmrt,
-    Other(String),
+    Other(#[allow(dead_code)] String),
+}
+
+/// Longest reads expected from short-read platforms (2x300 MiSeq with some slack).
+const SHORT_READ_MAX_LENGTH: usize = 1000;
+
+impl Platform {
+    /// Heuristically check that the reads of a FASTQ could have been produced by this platform.
+    fn inconsistency(&self, stats: &FastqStats) -> Option<String> {
+        match self {
+            Platform::PacbioSmrt if stats.max_length() <= SHORT_READ_MAX_LENGTH => Some(format!(
+                "{} declared but reads look like short reads (max length {}bp)",
+                self,
+                stats.max_length()
+            )),
+            Platform::Illumina | Platform::Bgiseq | Platform::AbiSolid
+                if stats.max_length() > SHORT_READ_MAX_LENGTH =>
+            {
+                Some(format!(
+                    "{} declared but reads look like long reads (max length {}bp)",
+                    self,
+                    stats.max_length()
+                ))
+            }
+            Platform::Bgiseq | Platform::AbiSolid | Platform::PacbioSmrt
+                if stats.quality_encoding() == QualityEncoding::Phred64 =>
+            {
+                Some(format!(
+                    "{} declared but qualities are Phred+64 encoded (old Illumina)",
+                    self
+                ))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl OmicsValidator for RnaRecord {
-    fn handle_error(errors: HashMap<&'static str, ValidationErrorsKind>) -> String {
-        let errors_vec: Vec<String> = errors
-            .iter()
-            .map(|(&k, val)| match val {
-                validator::ValidationErrorsKind::Field(v) if k != "__all__" => {
-                    if v[0].params.contains_key("fastq") {
-                        format!(
-                            "{} {} {} in record {}",
-                            v[0].code,
-                            v[0].params.get("value").unwrap().as_str().unwrap(),
-                            v[0].params.get("fastq").unwrap().as_str().unwrap(),
-                            v[0].params.get("pos").unwrap(),
-                        )
-                    } else {
-                        format!(
-                            "{} {}",
-                            v[0].params.get("value").unwrap().as_str().unwrap(),
-                            v[0].code,
-                        )
-                    }
-                }
-                validator::ValidationErrorsKind::Field(v) => {
-                    format!("Inconsistent experiment: {}", v[0].code,)
-                }
-                _ => "Empty experiment?".to_string(),
+    fn builtin_code(_field: &str) -> IssueCode {
+        IssueCode::EmptyField
+    }
+    fn issues(&self, options: &ReadOptions) -> Vec<ValidationIssue> {
+        [("r1", &self.r1), ("r2", &self.r2)]
+            .into_iter()
+            .filter_map(|(field, path)| {
+                Some(validate_fastq(path.as_deref()?, &options.fastq)?.field(field))
             })
+            .collect()
+    }
+    fn warnings(&self, _options: &ReadOptions) -> Vec<ValidationIssue> {
+        let paths: Vec<(&str, &Path)> = [("R1", &self.r1), ("R2", &self.r2)]
+            .into_iter()
+            .filter_map(|(field, path)| Some((field, path.as_deref()?)))
             .collect();
-        errors_vec.join(";\t")
+        let portability = paths.iter().filter_map(|(field, path)| {
+            Some(
+                ValidationIssue::new(IssueCode::NonPortablePath)
+                    .field(*field)
+                    .value(path.display().to_string())
+                    .detail(paths::portability(path)?),
+            )
+        });
+        let platform = paths.iter().filter_map(|(field, path)| {
+            let stats = fastq::stats(&paths::local(path))?;
+            let msg = self.platform.inconsistency(&stats)?;
+            Some(
+                ValidationIssue::new(IssueCode::PlatformMismatch)
+                    .field(*field)
+                    .value(path.display().to_string())
+                    .detail(msg),
+            )
+        });
+        portability.chain(platform).collect()
+    }
+    fn remote_checks(&self, options: &ReadOptions) -> Vec<Box<dyn RemoteCheck>> {
+        if !options.fastq.check_remote {
+            return Vec::new();
+        }
+        [("R1", &self.r1), ("R2", &self.r2)]
+            .into_iter()
+            .filter_map(|(field, path)| {
+                let location = path.as_deref()?.to_str()?;
+                remote::is_remote(location).then(|| {
+                    Box::new(RemoteFastq {
+                        field,
+                        location: location.to_string(),
+                        network: options.network,
+                    }) as Box<dyn RemoteCheck>
+                })
+            })
+            .collect()
+    }
+    fn numeric_columns() -> &'static [&'static str] {
+        &[]
     }
     fn flexible() -> bool {
         false
@@ -395,12 +1779,257 @@ mod test {
     #[test]
     fn test_validation_of_tidy_met_csv_works() {
         let file = fs::File::open("tests/met_tidy.csv").unwrap();
-        let model = ModelRaw::parse(include_str!("../tests/iCLAU786.xml")).unwrap();
-        assert_eq!(TidyMetRecord::validate_omics(file, &model).len(), 1);
+        let models = Models::load(&[PathBuf::from("tests/iCLAU786.xml")], &[]).unwrap();
+        assert_eq!(
+            TidyMetRecord::validate_rows(file, &models, &ReadOptions::default()).len(),
+            1
+        );
+    }
+    #[test]
+    fn test_metabolites_are_checked_in_their_compartment() {
+        let file = fs::File::open("tests/met_compartments.csv").unwrap();
+        let models = Models::load(&[PathBuf::from("tests/iCLAU786.xml")], &[]).unwrap();
+        let error_vec = TidyMetRecord::validate_rows(file, &models, &ReadOptions::default());
+        assert_eq!(
+            error_vec.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![5, 6]
+        );
+        assert_eq!(error_vec[0].issues[0].code, IssueCode::WrongCompartment);
+        assert_eq!(
+            error_vec[0].issues[0].detail.as_deref(),
+            Some("not in e, only in c")
+        );
+        assert_eq!(error_vec[1].issues[0].code, IssueCode::NotInModel);
+    }
+    #[test]
+    fn test_validation_of_multi_csv_works() {
+        let file = fs::File::open("tests/multi.csv").unwrap();
+        let models = Models::load(&[PathBuf::from("tests/iCLAU786.xml")], &[]).unwrap();
+        let error_vec = MultiRecord::validate_rows(file, &models, &ReadOptions::default());
+        assert_eq!(
+            error_vec.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![3, 5]
+        );
+    }
+    #[test]
+    fn test_validation_of_tidy_flux_csv_works() {
+        let file = fs::File::open("tests/flux_tidy.csv").unwrap();
+        let error_vec = TidyFluxRecord::validate_omics(file);
+        assert_eq!(
+            error_vec
+                .iter()
+                .map(|e| (e.line, e.issues[0].code))
+                .collect::<Vec<_>>(),
+            [
+                (4, IssueCode::OutOfBounds),
+                (6, IssueCode::OutOfBounds),
+                (7, IssueCode::InvalidValue)
+            ]
+        );
+    }
+    #[test]
+    fn test_protein_usages_are_checked_against_the_ec_model() {
+        let file = fs::File::open("tests/ec_usage.csv").unwrap();
+        let models = Models::load(&[PathBuf::from("tests/ec_model.xml")], &[]).unwrap();
+        let error_vec = EcUsageRecord::validate_rows(file, &models, &ReadOptions::default());
+        assert_eq!(
+            error_vec
+                .iter()
+                .map(|e| (e.line, e.issues[0].code))
+                .collect::<Vec<_>>(),
+            [
+                (4, IssueCode::EnzymeNotInReaction),
+                (5, IssueCode::EnzymeNotInReaction),
+                (6, IssueCode::NotInModel),
+                (7, IssueCode::InvalidValue)
+            ]
+        );
+        assert_eq!(
+            error_vec[0].issues[0].detail.as_deref(),
+            Some("prot_P06999 is consumed by R_PFKNo2")
+        );
+    }
+    #[test]
+    fn test_thermodynamics_are_checked_against_the_model() {
+        let file = fs::File::open("tests/thermo_conc.csv").unwrap();
+        let models = Models::load(&[PathBuf::from("tests/iCLAU786.xml")], &[]).unwrap();
+        let error_vec = ThermoConcRecord::validate_rows(file, &models, &ReadOptions::default());
+        assert_eq!(
+            error_vec
+                .iter()
+                .map(|e| (e.line, e.severity, e.issues[0].code))
+                .collect::<Vec<_>>(),
+            [
+                (3, Severity::Error, IssueCode::OutOfBounds),
+                (4, Severity::Error, IssueCode::WrongCompartment),
+                (5, Severity::Error, IssueCode::InvalidValue),
+                (6, Severity::Error, IssueCode::NotInModel),
+                (7, Severity::Warning, IssueCode::Unphysiological)
+            ]
+        );
+        assert_eq!(error_vec[4].issues.len(), 2);
+        let file = fs::File::open("tests/thermo_dg.csv").unwrap();
+        let models = Models::load(&[PathBuf::from("tests/e_coli_core_groups.xml")], &[]).unwrap();
+        let error_vec = ThermoDgRecord::validate_rows(file, &models, &ReadOptions::default());
+        assert_eq!(
+            error_vec
+                .iter()
+                .map(|e| (e.line, e.severity, e.issues[0].code))
+                .collect::<Vec<_>>(),
+            [
+                (4, Severity::Error, IssueCode::NotInModel),
+                (5, Severity::Warning, IssueCode::Unphysiological),
+                (6, Severity::Error, IssueCode::InvalidValue),
+                (7, Severity::Warning, IssueCode::Unphysiological)
+            ]
+        );
+        assert_eq!(
+            error_vec[1].issues[0].detail.as_deref(),
+            Some("dg0 6300 kJ/mol, beyond 1000 kJ/mol")
+        );
+    }
+    #[test]
+    fn test_media_are_checked_against_the_exchange_reactions() {
+        let file = fs::File::open("tests/medium.csv").unwrap();
+        let models = Models::load(&[PathBuf::from("tests/e_coli_core_groups.xml")], &[]).unwrap();
+        let error_vec = MediumRecord::validate_rows(file, &models, &ReadOptions::default());
+        assert_eq!(
+            error_vec
+                .iter()
+                .map(|e| (e.line, e.issues[0].code))
+                .collect::<Vec<_>>(),
+            [
+                (4, IssueCode::NotAnExchange),
+                (5, IssueCode::NotAnExchange),
+                (6, IssueCode::InvalidValue),
+                (7, IssueCode::InvalidValue)
+            ]
+        );
+        assert_eq!(error_vec[0].issues[0].value.as_deref(), Some("EX_02_e"));
+        assert!(error_vec[1].issues[0].detail.is_some());
+    }
+    #[test]
+    fn test_strain_designs_are_checked_against_the_model() {
+        let file = fs::File::open("tests/strain_designs.csv").unwrap();
+        let models = Models::load(&[PathBuf::from("tests/iCLAU786.xml")], &[]).unwrap();
+        let error_vec = StrainDesignRecord::validate_rows(file, &models, &ReadOptions::default());
+        assert_eq!(
+            error_vec
+                .iter()
+                .map(|e| (e.line, e.issues[0].code))
+                .collect::<Vec<_>>(),
+            [
+                (5, IssueCode::NotInModel),
+                (6, IssueCode::InvalidValue),
+                (7, IssueCode::EmptyField)
+            ]
+        );
+        // the genes of the designs are not counted in the summary
+        assert!(models.report().is_empty());
     }
     #[test]
     fn test_validation_of_rna_tsv_works() {
         let file = fs::File::open("tests/rna.tsv").unwrap();
         assert_eq!(RnaRecord::validate_omics(file).len(), 3);
     }
+    #[test]
+    fn test_validation_of_selected_rows() {
+        let file = fs::File::open("tests/uni.csv").unwrap();
+        let options = ReadOptions {
+            rows: RowSelection::Head(3),
+            ..Default::default()
+        };
+        assert_eq!(ProtRecord::validate_rows(file, &options).len(), 0);
+        let file = fs::File::open("tests/uni.csv").unwrap();
+        let options = ReadOptions {
+            rows: RowSelection::Sample(5),
+            ..Default::default()
+        };
+        assert_eq!(ProtRecord::validate_rows(file, &options).len(), 1);
+    }
+    #[test]
+    fn test_sample_larger_than_the_rows_keeps_every_row() {
+        let rows = (0..4).map(|i| (i, i * 10));
+        let sample = reservoir_sample(rows, usize::MAX);
+        assert_eq!(sample, vec![(0, 0), (1, 10), (2, 20), (3, 30)]);
+    }
+    #[test]
+    fn test_extra_columns_are_reported_unless_allowed() {
+        let file = fs::File::open("tests/uni_tidy_notes.csv").unwrap();
+        assert_eq!(TidyProtRecord::validate_omics(file).len(), 1);
+        let file = fs::File::open("tests/uni_tidy_notes.csv").unwrap();
+        let options = ReadOptions {
+            allow_extra_columns: true,
+            ..Default::default()
+        };
+        assert_eq!(TidyProtRecord::validate_rows(file, &options).len(), 0);
+    }
+    #[test]
+    fn test_header_synonyms_are_renamed() {
+        let input = "protein_id,sample,value\nQ00496,SIM1,100001\n";
+        assert_eq!(TidyProtRecord::validate_omics(input.as_bytes()).len(), 2);
+        let options = ReadOptions {
+            rename: HashMap::from([("protein_id".to_string(), "uniprot".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(
+            TidyProtRecord::validate_rows(input.as_bytes(), &options).len(),
+            0
+        );
+    }
+    #[test]
+    fn test_decoys_are_rejected_by_default() {
+        let input = "uniprot,sample,value\nCON__P02769,SIM1,1\nsp|P0A6F5|CH60_ECOLI,SIM1,2\n";
+        let error_vec = TidyProtRecord::validate_omics(input.as_bytes());
+        assert_eq!(error_vec.len(), 2);
+        assert!(error_vec
+            .iter()
+            .all(|e| e.issues[0].code == IssueCode::DecoyEntry));
+    }
+    #[test]
+    fn test_group_members_are_checked() {
+        let options = ProteomicsOptions {
+            allow_groups: true,
+            ..Default::default()
+        };
+        let members = options.members("P12345;E0X97");
+        assert_eq!(members, ["P12345", "E0X97"]);
+        assert!(check_accession(members[0], &options).is_ok());
+        let lenient = ProteomicsOptions {
+            isoforms: Some(true),
+            ..Default::default()
+        };
+        assert!(check_accession("P12345-2", &lenient).is_ok());
+        assert!(check_accession("P12345-2", &ProteomicsOptions::default()).is_ok());
+        let strict = ProteomicsOptions {
+            isoforms: Some(false),
+            ..Default::default()
+        };
+        assert!(check_accession("P12345-2", &strict).is_err());
+        assert_eq!(
+            check_accession(members[1], &options),
+            Err((
+                IssueCode::InvalidUniprotId,
+                Some("5 characters, accessions have 6 or 10".to_string())
+            ))
+        );
+    }
+    #[test]
+    fn test_wells_are_checked_if_present() {
+        let input = "uniprot,sample,value,well\nQ00496,SIM1,1,A1\nQ7B2Q4,SIM2,2,I13\n";
+        let options = ReadOptions {
+            allow_extra_columns: true,
+            ..Default::default()
+        };
+        let error_vec = TidyProtRecord::validate_rows(input.as_bytes(), &options);
+        assert_eq!(error_vec.len(), 1);
+        assert_eq!(error_vec[0].line, 3);
+        assert_eq!(error_vec[0].issues[0].code, IssueCode::InvalidWell);
+    }
+    #[test]
+    fn test_platform_inconsistent_with_reads_is_detected() {
+        let stats = fastq::scan(Path::new("tests/valid.fastq"), &FastqOptions::default()).unwrap();
+        assert!(Platform::Illumina.inconsistency(&stats).is_none());
+        assert!(Platform::PacbioSmrt.inconsistency(&stats).is_some());
+    }
 }
@@ -0,0 +1,141 @@
+use crate::validators::{IssueCode, ValidationIssue};
+use csv::StringRecord;
+use std::fmt;
+use std::str::FromStr;
+
+/// Column holding plate well coordinates, checked in any format that has it.
+const COLUMN: &str = "well";
+
+/// Format of the plates the samples come from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PlateFormat {
+    /// 8 rows (A-H) of 12 columns
+    #[default]
+    Wells96,
+    /// 16 rows (A-P) of 24 columns
+    Wells384,
+    /// 32 rows (A-AF) of 48 columns
+    Wells1536,
+}
+
+impl FromStr for PlateFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "96" => Ok(PlateFormat::Wells96),
+            "384" => Ok(PlateFormat::Wells384),
+            "1536" => Ok(PlateFormat::Wells1536),
+            _ => Err(format!(
+                "unknown plate format {}, expected 96, 384 or 1536",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PlateFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (rows, columns) = self.size();
+        write!(f, "{}-well plate", rows * columns)
+    }
+}
+
+impl PlateFormat {
+    /// Number of rows and columns.
+    fn size(self) -> (u32, u32) {
+        match self {
+            PlateFormat::Wells96 => (8, 12),
+            PlateFormat::Wells384 => (16, 24),
+            PlateFormat::Wells1536 => (32, 48),
+        }
+    }
+}
+
+/// Letters of the 1-based `row`: A to Z, then AA, AB...
+fn row_name(row: u32) -> String {
+    if row <= 26 {
+        char::from(b'A' + (row - 1) as u8).to_string()
+    } else {
+        format!(
+            "{}{}",
+            row_name((row - 1) / 26),
+            row_name((row - 1) % 26 + 1)
+        )
+    }
+}
+
+/// Row and column (1-based) of a well such as `B7` or `B07`.
+fn parse_well(well: &str) -> Option<(u32, u32)> {
+    let letters = well.bytes().take_while(u8::is_ascii_uppercase).count();
+    let (row, column) = well.split_at(letters);
+    if !(1..=2).contains(&letters)
+        || column.is_empty()
+        || !column.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let row = row
+        .bytes()
+        .fold(0, |row, b| row * 26 + u32::from(b - b'A' + 1));
+    Some((row, column.parse().ok()?))
+}
+
+/// Check that `well` is a well of `plate`.
+fn check(well: &str, plate: PlateFormat) -> Result<(), String> {
+    let (rows, columns) = plate.size();
+    let (row, column) = parse_well(well)
+        .ok_or_else(|| "expected a row letter and a column, e.g. A1".to_string())?;
+    if row > rows {
+        Err(format!("row outside A-{} of a {}", row_name(rows), plate))
+    } else if column == 0 || column > columns {
+        Err(format!("column outside 1-{} of a {}", columns, plate))
+    } else {
+        Ok(())
+    }
+}
+
+/// Position of the well column in the header, if any.
+pub fn column(headers: Option<&StringRecord>) -> Option<usize> {
+    headers?.iter().position(|h| h == COLUMN)
+}
+
+/// Issue of the well of a record, whose column is given by [`column`], on
+/// `plate`. Empty wells are not checked.
+pub fn issue(
+    record: &StringRecord,
+    column: Option<usize>,
+    plate: PlateFormat,
+) -> Option<ValidationIssue> {
+    let well = record.get(column?).filter(|w| !w.is_empty())?;
+    let detail = check(well, plate).err()?;
+    Some(
+        ValidationIssue::new(IssueCode::InvalidWell)
+            .field(COLUMN)
+            .value(well)
+            .detail(detail),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wells_are_checked_against_the_plate() {
+        assert!(check("A1", PlateFormat::Wells96).is_ok());
+        assert!(check("H12", PlateFormat::Wells96).is_ok());
+        assert!(check("B07", PlateFormat::Wells96).is_ok());
+        assert_eq!(
+            check("I13", PlateFormat::Wells96),
+            Err("row outside A-H of a 96-well plate".to_string())
+        );
+        assert!(check("A13", PlateFormat::Wells96).is_err());
+        assert!(check("I13", PlateFormat::Wells384).is_ok());
+        assert!(check("AF48", PlateFormat::Wells1536).is_ok());
+        assert!(check("AG1", PlateFormat::Wells1536).is_err());
+        for well in ["A0", "a1", "1A", "A", "A-1", ""] {
+            assert!(check(well, PlateFormat::Wells1536).is_err(), "{}", well);
+        }
+    }
+}